@@ -23,3 +23,25 @@ pub fn bench_synth_nested_borrow(c: &mut Criterion) {
         })
     });
 }
+pub fn bench_synth_nested_to_bytes(c: &mut Criterion) {
+    c.bench_function("synth_nested_to_value_then_bytes", |b| {
+        let placeholder: JP<async_graphql::Value> =
+            JP::init("{ posts { id title user { id name } } }", None);
+        let synth = placeholder.synth();
+        b.iter(|| {
+            let a: serde_json_borrow::Value = synth.synthesize().unwrap();
+            let bytes = serde_json::to_vec(&a).unwrap();
+            drop(bytes);
+        })
+    });
+    c.bench_function("synth_nested_to_bytes", |b| {
+        let placeholder: JP<async_graphql::Value> =
+            JP::init("{ posts { id title user { id name } } }", None);
+        let synth = placeholder.synth();
+        b.iter(|| {
+            let mut bytes = Vec::new();
+            synth.synthesize_to(&mut bytes).unwrap();
+            drop(bytes);
+        })
+    });
+}
@@ -13,6 +13,7 @@ use tailcall::core::config::Batch;
 use tailcall::core::http::{DataLoaderRequest, HttpDataLoader, Response};
 use tailcall::core::ir::model::IoId;
 use tailcall::core::runtime::TargetRuntime;
+use tailcall::core::secret::EnvSecretIO;
 use tailcall::core::{cache, EnvIO, FileIO, HttpIO};
 
 #[derive(Clone)]
@@ -82,6 +83,7 @@ pub fn benchmark_data_loader(c: &mut Criterion) {
                     http: client_clone.clone(),
                     http2_only: client_clone,
                     env: Arc::new(Env {}),
+                    secret: Arc::new(EnvSecretIO::new(Arc::new(Env {}))),
                     file: Arc::new(File {}),
                     cache: Arc::new(Cache {}),
                     extensions: Arc::new(vec![]),
@@ -22,6 +22,7 @@ fn all_benchmarks(c: &mut Criterion) {
     from_json_bench::benchmark_from_json_method(c);
     bench_synth::bench_synth_nested(c);
     bench_synth::bench_synth_nested_borrow(c);
+    bench_synth::bench_synth_nested_to_bytes(c);
 }
 
 criterion_group! {
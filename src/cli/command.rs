@@ -1,6 +1,7 @@
 use clap::{Parser, Subcommand};
 use strum_macros::Display;
 use tailcall_version::VERSION;
+use url::Url;
 
 const ABOUT: &str = r"
    __        _ __           ____
@@ -30,6 +31,11 @@ pub enum Command {
         /// production)
         #[arg(short, long, action = clap::ArgAction::Set, default_value_t = true)]
         verify_ssl: bool,
+
+        /// Fails if the linked resources don't match the hashes in
+        /// `tailcall.lock`, next to the config
+        #[arg(long)]
+        locked: bool,
     },
 
     /// Validate a composition spec
@@ -52,6 +58,11 @@ pub enum Command {
         /// production)
         #[arg(short, long, action = clap::ArgAction::Set, default_value_t = true)]
         verify_ssl: bool,
+
+        /// Writes the hashes of the linked resources to `tailcall.lock`, next
+        /// to the config
+        #[arg(long)]
+        lock: bool,
     },
 
     /// Initialize a new project
@@ -66,5 +77,68 @@ pub enum Command {
         /// Path of the configuration file
         #[arg(required = true)]
         file_path: String,
+
+        /// Base URL of the generated `@http` fields, overriding the one
+        /// inferred from the samples. The path of each sample is joined onto
+        /// it
+        #[arg(long)]
+        base_url: Option<Url>,
+
+        /// JSONPath of the value to infer the types from in the JSON samples,
+        /// e.g. `$.data` for samples wrapped in a `data` envelope
+        #[arg(long)]
+        root_path: Option<String>,
+
+        /// Directory to split the generated config into, with a file for
+        /// each operation type and one for the other types, all linked from
+        /// `main.graphql`
+        #[arg(long)]
+        output_dir: Option<String>,
+    },
+
+    /// Counts how many of the queries in a directory select each field
+    FieldUsage {
+        /// Path of the configuration file
+        #[arg(required = true)]
+        file_path: String,
+
+        /// Directory of the `.graphql` query files
+        #[arg(required = true)]
+        queries_dir: String,
+    },
+
+    /// Upgrades a config written with deprecated directives and prints it
+    Migrate {
+        /// Path of the legacy configuration file
+        #[arg(required = true)]
+        file_path: String,
+    },
+
+    /// Compares the schemas of two configs and reports each change as
+    /// breaking, dangerous or safe for the existing clients
+    DiffSchema {
+        /// Path of the configuration file of the current schema
+        #[arg(required = true)]
+        old_file_path: String,
+
+        /// Path of the configuration file of the new schema
+        #[arg(required = true)]
+        new_file_path: String,
+
+        /// Exits with an error when a change is breaking
+        #[arg(long)]
+        fail_on_breaking: bool,
+    },
+
+    /// Validates a query against the schema of a config, without executing
+    /// it
+    ValidateQuery {
+        /// Path of the configuration file
+        #[arg(required = true)]
+        file_path: String,
+
+        /// Path of the `.graphql` file of the query
+        #[arg(required = true)]
+        query_path: String,
     },
 }
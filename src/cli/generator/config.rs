@@ -45,6 +45,8 @@ pub struct PresetConfig {
     pub infer_type_names: Option<bool>,
     pub tree_shake: Option<bool>,
     pub unwrap_single_field_types: Option<bool>,
+    pub infer_relations: Option<bool>,
+    pub infer_nullability: Option<bool>,
 }
 #[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(transparent)]
@@ -79,6 +81,10 @@ pub enum Source<Status = UnResolved> {
         #[serde(skip_serializing_if = "Option::is_none")]
         is_mutation: Option<bool>,
         field_name: String,
+        /// Path of the endpoint of a sample read from a file, joined onto the
+        /// base url.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
     },
     #[serde(rename_all = "camelCase")]
     Proto {
@@ -148,6 +154,10 @@ impl ValidateFrom<PresetConfig> for Preset {
             preset = preset.tree_shake(tree_shake);
         }
 
+        if let Some(infer_relations) = config.infer_relations {
+            preset = preset.infer_relations(infer_relations);
+        }
+
         // TODO: The field names in trace should be inserted at compile time.
         Valid::succeed(preset)
             .and_then(|preset| {
@@ -206,7 +216,7 @@ impl Output<UnResolved> {
 impl Source<UnResolved> {
     pub fn resolve(self, parent_dir: Option<&Path>) -> anyhow::Result<Source<Resolved>> {
         match self {
-            Source::Curl { src, field_name, headers, body, method, is_mutation } => {
+            Source::Curl { src, field_name, headers, body, method, is_mutation, path } => {
                 let resolved_path = src.into_resolved(parent_dir);
                 Ok(Source::Curl {
                     src: resolved_path,
@@ -215,6 +225,7 @@ impl Source<UnResolved> {
                     body,
                     method,
                     is_mutation,
+                    path,
                 })
             }
             Source::Proto { src, url, proto_paths, connect_rpc } => {
@@ -329,6 +340,7 @@ mod tests {
                 field_name: "test".to_string(),
                 method: Some(Method::GET),
                 is_mutation: None,
+                path: None,
             },
         }]);
         let actual = serde_json::to_string_pretty(&config).unwrap();
@@ -342,6 +354,8 @@ mod tests {
             infer_type_names: None,
             merge_type: Some(2.0),
             unwrap_single_field_types: None,
+            infer_relations: None,
+            infer_nullability: None,
         };
 
         let transform_preset: Result<Preset, ValidationError<String>> =
@@ -356,6 +370,8 @@ mod tests {
             infer_type_names: Some(true),
             merge_type: Some(0.5),
             unwrap_single_field_types: None,
+            infer_relations: None,
+            infer_nullability: None,
         };
         let transform_preset: Preset = config_preset.validate_into().to_result().unwrap();
         let expected_preset = Preset::new()
@@ -436,7 +452,7 @@ mod tests {
             }}
         "#;
         let expected_error =
-            "unknown field `mergeTypes`, expected one of `mergeType`, `inferTypeNames`, `treeShake`, `unwrapSingleFieldTypes` at line 3 column 28";
+            "unknown field `mergeTypes`, expected one of `mergeType`, `inferTypeNames`, `treeShake`, `unwrapSingleFieldTypes`, `inferRelations`, `inferNullability` at line 3 column 28";
         assert_deserialization_error(json, expected_error);
     }
 
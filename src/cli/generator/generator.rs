@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -6,12 +7,13 @@ use http::header::{HeaderMap, HeaderName, HeaderValue};
 use inquire::Confirm;
 use pathdiff::diff_paths;
 use tailcall_valid::{ValidateInto, Validator};
+use url::Url;
 
 use super::config::{Config, LLMConfig, Resolved, Source};
 use super::source::ConfigSource;
 use crate::cli::llm::InferTypeName;
 use crate::core::config::transformer::{Preset, RenameTypes};
-use crate::core::config::{self, ConfigModule, ConfigReaderContext};
+use crate::core::config::{self, ConfigModule, ConfigReaderContext, Link, LinkType};
 use crate::core::generator::{Generator as ConfigGenerator, Input};
 use crate::core::proto_reader::ProtoReader;
 use crate::core::resource_reader::{Resource, ResourceReader};
@@ -24,11 +26,49 @@ pub struct Generator {
     /// path of config file.
     config_path: String,
     runtime: TargetRuntime,
+    /// base url that takes precedence over the one inferred from the samples.
+    base_url: Option<Url>,
+    /// path of the value to infer the types from in the json samples.
+    root_path: Vec<String>,
+    /// directory to split the generated config into, instead of the output
+    /// file.
+    output_dir: Option<String>,
 }
 
+/// Name of the root file of a split config, that links all the other files.
+const ROOT_FILE: &str = "main.graphql";
+
 impl Generator {
     pub fn new(config_path: &str, runtime: TargetRuntime) -> Self {
-        Self { config_path: config_path.to_string(), runtime }
+        Self {
+            config_path: config_path.to_string(),
+            runtime,
+            base_url: None,
+            root_path: Vec::new(),
+            output_dir: None,
+        }
+    }
+
+    /// Splits the generated config into multiple files in `output_dir`
+    /// instead of writing it to the output file.
+    pub fn with_output_dir(mut self, output_dir: Option<&str>) -> Self {
+        self.output_dir = output_dir.map(|dir| dir.to_string());
+        self
+    }
+
+    /// Sets the base url used for the generated `@http` fields.
+    pub fn with_base_url(mut self, base_url: Option<Url>) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Sets the JSONPath of the value to infer the types from in the json
+    /// samples.
+    pub fn with_root_path(mut self, root_path: Option<&str>) -> anyhow::Result<Self> {
+        if let Some(root_path) = root_path {
+            self.root_path = parse_root_path(root_path)?;
+        }
+        Ok(self)
     }
 
     /// Writes the configuration to the output file if allowed.
@@ -51,6 +91,27 @@ impl Generator {
         Ok(())
     }
 
+    /// Writes the configuration split into multiple files to `output_dir`.
+    async fn write_split(&self, config: &ConfigModule, output_dir: &str) -> anyhow::Result<()> {
+        fs::create_dir_all(output_dir)?;
+
+        for (file, config) in split_config(config.config()) {
+            let path = Path::new(output_dir).join(file);
+            let path = path.to_string_lossy();
+
+            if self.should_overwrite(&path)? {
+                self.runtime
+                    .file
+                    .write(&path, config.to_sdl().as_bytes())
+                    .await?;
+            }
+        }
+
+        tracing::info!("Config successfully generated at {output_dir}/{ROOT_FILE}");
+
+        Ok(())
+    }
+
     /// Checks if the output file already exists and prompts for overwrite
     /// confirmation.
     fn should_overwrite(&self, output_path: &str) -> anyhow::Result<bool> {
@@ -96,40 +157,67 @@ impl Generator {
 
         let reader = ResourceReader::cached(self.runtime.clone());
         let proto_reader = ProtoReader::init(reader.clone(), self.runtime.clone());
-        let output_dir = Path::new(&config.output.path.0)
-            .parent()
-            .unwrap_or(Path::new(""));
+        let output_dir = match &self.output_dir {
+            Some(output_dir) => Path::new(output_dir),
+            None => Path::new(&config.output.path.0)
+                .parent()
+                .unwrap_or(Path::new("")),
+        };
 
         for input in config.inputs {
             match input.source {
-                Source::Curl { src, field_name, headers, body, method, is_mutation } => {
-                    let url = src.0;
+                Source::Curl { src, field_name, headers, body, method, is_mutation, path } => {
+                    let src = src.0;
                     let req_body = body.unwrap_or_default();
                     let method = method.unwrap_or_default();
                     let is_mutation = is_mutation.unwrap_or_default();
 
-                    let request_method = method.clone().to_hyper();
-                    let mut request = reqwest::Request::new(request_method, url.parse()?);
-                    if !req_body.is_null() {
-                        request.body_mut().replace(req_body.to_string().into());
-                    }
-                    if let Some(headers_inner) = headers.as_btree_map() {
-                        let mut header_map = HeaderMap::new();
-                        for (key, value) in headers_inner {
-                            let header_name = HeaderName::try_from(key)?;
-                            let header_value = HeaderValue::try_from(value.to_string())?;
-                            header_map.insert(header_name, header_value);
+                    let (url, content) = match src.parse::<Url>() {
+                        Ok(url) => {
+                            let request_method = method.clone().to_hyper();
+                            let mut request = reqwest::Request::new(request_method, url.clone());
+                            if !req_body.is_null() {
+                                request.body_mut().replace(req_body.to_string().into());
+                            }
+                            if let Some(headers_inner) = headers.as_btree_map() {
+                                let mut header_map = HeaderMap::new();
+                                for (key, value) in headers_inner {
+                                    let header_name = HeaderName::try_from(key)?;
+                                    let header_value = HeaderValue::try_from(value.to_string())?;
+                                    header_map.insert(header_name, header_value);
+                                }
+                                *request.headers_mut() = header_map;
+                            }
+
+                            let resource: Resource = request.into();
+                            let response = reader.read_file(resource).await?;
+                            let url = match &self.base_url {
+                                Some(base_url) => rebase_url(base_url, &url),
+                                None => url,
+                            };
+                            (url, response.content)
                         }
-                        *request.headers_mut() = header_map;
-                    }
+                        // the sample is a saved response, so the url can only come from the
+                        // explicitly provided base url and the path of the input.
+                        Err(_) => {
+                            let base_url = self.base_url.as_ref().ok_or_else(|| {
+                                anyhow!("Sample '{src}' is not a url, provide one with --base-url")
+                            })?;
+                            let path = path.unwrap_or_default();
+                            let (path, query) = match path.split_once('?') {
+                                Some((path, query)) => (path, Some(query)),
+                                None => (path.as_str(), None),
+                            };
+                            let response = reader.read_file(src).await?;
+                            (join_path(base_url, path, query), response.content)
+                        }
+                    };
 
-                    let resource: Resource = request.into();
-                    let response = reader.read_file(resource).await?;
                     input_samples.push(Input::Json {
-                        url: url.parse()?,
+                        url,
                         method,
                         req_body,
-                        res_body: serde_json::from_str(&response.content)?,
+                        res_body: serde_json::from_str(&content)?,
                         field_name,
                         is_mutation,
                         headers: headers.into_btree_map(),
@@ -166,11 +254,14 @@ impl Generator {
 
         let llm = config.llm.clone();
         let preset = config.preset.clone().unwrap_or_default();
+        let infer_nullability = preset.infer_nullability.unwrap_or_default();
         let preset: Preset = preset.validate_into().to_result()?;
         let input_samples = self.resolve_io(config).await?;
         let infer_type_names = preset.infer_type_names;
         let mut config_gen = ConfigGenerator::default()
             .inputs(input_samples)
+            .root_path(self.root_path.clone())
+            .infer_nullability(infer_nullability)
             .transformers(vec![Box::new(preset)]);
 
         if let Some(query_name) = query_type {
@@ -191,11 +282,100 @@ impl Generator {
             }
         }
 
-        self.write(&config, &path).await?;
+        match &self.output_dir {
+            Some(output_dir) => self.write_split(&config, output_dir).await?,
+            None => self.write(&config, &path).await?,
+        }
         Ok(config)
     }
 }
 
+/// Splits the config into a root file, holding the schema and the runtime
+/// settings, that links a file for each operation type and one for all the
+/// other types.
+fn split_config(config: &config::Config) -> Vec<(&'static str, config::Config)> {
+    let mut types = config.types.clone();
+    let mut files = Vec::new();
+
+    for (file, type_name) in [
+        ("query.graphql", &config.schema.query),
+        ("mutation.graphql", &config.schema.mutation),
+    ] {
+        if let Some((name, ty)) = type_name.as_ref().and_then(|name| types.remove_entry(name)) {
+            let types = BTreeMap::from([(name, ty)]);
+            files.push((file, config::Config { types, ..Default::default() }));
+        }
+    }
+
+    files.push((
+        "types.graphql",
+        config::Config {
+            types,
+            enums: config.enums.clone(),
+            unions: config.unions.clone(),
+            ..Default::default()
+        },
+    ));
+
+    let mut root = config::Config {
+        types: Default::default(),
+        enums: Default::default(),
+        unions: Default::default(),
+        ..config.clone()
+    };
+    root.links.extend(files.iter().map(|(file, _)| Link {
+        src: file.to_string(),
+        type_of: LinkType::Config,
+        ..Default::default()
+    }));
+
+    std::iter::once((ROOT_FILE, root)).chain(files).collect()
+}
+
+/// Moves the sample `url` under `base_url`, keeping the path and query of the
+/// sample relative to the path of `base_url`. The two paths are joined with a
+/// single slash, whether or not `base_url` ends with one.
+fn rebase_url(base_url: &Url, url: &Url) -> Url {
+    join_path(base_url, url.path(), url.query())
+}
+
+/// Appends `path` to the path of `base_url` and sets the `query`.
+fn join_path(base_url: &Url, path: &str, query: Option<&str>) -> Url {
+    let mut joined = base_url.clone();
+    let path = format!(
+        "{}/{}",
+        base_url.path().trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+    joined.set_path(&path);
+    joined.set_query(query);
+    joined
+}
+
+/// Parses a JSONPath made of object keys and array indices, like `$.data` or
+/// `$.data.items[0]`, into its segments.
+fn parse_root_path(root_path: &str) -> anyhow::Result<Vec<String>> {
+    let path = root_path.strip_prefix('$').unwrap_or(root_path);
+    let segments = path
+        .replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_string())
+        .collect::<Vec<_>>();
+
+    if segments
+        .iter()
+        .any(|segment| segment.contains(['*', '?', '@']))
+    {
+        return Err(anyhow!(
+            "Root path '{root_path}' should only contain object keys and array indices"
+        ));
+    }
+
+    Ok(segments)
+}
+
 /// Checks if file or folder already exists or not.
 fn is_exists(path: &str) -> bool {
     fs::metadata(path).is_ok()
@@ -210,3 +390,180 @@ fn to_relative_path(from: &Path, to: &str) -> Option<String> {
     // Calculate the relative path from `from_path` to `to_path`
     diff_paths(to_path, from_path).map(|p| p.to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod test {
+    use url::Url;
+
+    use super::{parse_root_path, rebase_url, Generator};
+    use crate::core::config::reader::ConfigReader;
+    use crate::core::generator::Generator as ConfigGenerator;
+
+    #[test]
+    fn test_rebase_url() {
+        let base_url = Url::parse("https://staging.example.com:8080/api/").unwrap();
+        let url = Url::parse("http://localhost:3000/users/1?active=true").unwrap();
+
+        let actual = rebase_url(&base_url, &url);
+        let expected = "https://staging.example.com:8080/api/users/1?active=true";
+        assert_eq!(actual.as_str(), expected);
+    }
+
+    #[test]
+    fn test_rebase_url_path_prefix() {
+        let url = Url::parse("http://localhost:3000//users/1").unwrap();
+
+        for (base_url, expected) in [
+            (
+                "https://api.example.com/v2",
+                "https://api.example.com/v2/users/1",
+            ),
+            (
+                "https://api.example.com/v2/",
+                "https://api.example.com/v2/users/1",
+            ),
+            (
+                "https://api.example.com/v2//",
+                "https://api.example.com/v2/users/1",
+            ),
+            ("https://api.example.com", "https://api.example.com/users/1"),
+        ] {
+            let base_url = Url::parse(base_url).unwrap();
+            let actual = rebase_url(&base_url, &url);
+            assert_eq!(actual.as_str(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_with_root_path() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sample_path = dir.path().join("posts.json");
+        let sample = r#"{"data": {"posts": [{"id": 1, "title": "Hello"}]}}"#;
+        std::fs::write(&sample_path, sample)?;
+
+        let config_path = dir.path().join("gen.json");
+        let config = serde_json::json!({
+            "inputs": [{ "curl": { "src": "posts.json", "fieldName": "posts", "path": "/posts" } }],
+            "output": { "path": "./output.graphql" }
+        });
+        std::fs::write(&config_path, config.to_string())?;
+
+        let generator = Generator::new(
+            config_path.to_string_lossy().as_ref(),
+            crate::core::runtime::test::init(None),
+        )
+        .with_base_url(Some(Url::parse("https://api.example.com")?))
+        .with_root_path(Some("$.data.posts"))?;
+        let config = generator.read().await?;
+        let inputs = generator.resolve_io(config).await?;
+        let cfg_module = ConfigGenerator::default()
+            .inputs(inputs)
+            .root_path(generator.root_path.clone())
+            .generate(false)?;
+        let config = cfg_module.config();
+
+        let posts = &config.types["Query"].fields["posts"];
+        assert!(posts.type_of.is_list());
+        let post = &config.types[posts.type_of.name()];
+        assert_eq!(post.fields.keys().collect::<Vec<_>>(), vec!["id", "title"]);
+        assert!(config.to_sdl().contains(r#"select: "{{.data.posts}}""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_root_path() {
+        assert_eq!(parse_root_path("$.data").unwrap(), vec!["data"]);
+        assert_eq!(
+            parse_root_path("$.data.items[0]").unwrap(),
+            vec!["data", "items", "0"]
+        );
+        assert!(parse_root_path("$.data[*]").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generate_from_file_with_base_url() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sample_path = dir.path().join("user.json");
+        std::fs::write(&sample_path, r#"{"id": 1, "name": "Leanne Graham"}"#)?;
+
+        let config_path = dir.path().join("gen.json");
+        let config = serde_json::json!({
+            "inputs": [{ "curl": { "src": "user.json", "fieldName": "user", "path": "/users/1" } }],
+            "output": { "path": "./output.graphql" }
+        });
+        std::fs::write(&config_path, config.to_string())?;
+
+        let generator = Generator::new(
+            config_path.to_string_lossy().as_ref(),
+            crate::core::runtime::test::init(None),
+        )
+        .with_base_url(Some(Url::parse("https://api.example.com/v2/")?));
+        let config = generator.read().await?;
+        let inputs = generator.resolve_io(config).await?;
+        let cfg_module = ConfigGenerator::default().inputs(inputs).generate(false)?;
+
+        let sdl = cfg_module.config().to_sdl();
+        assert!(sdl.contains(r#"@http(url: "https://api.example.com/v2/users/{{.args.GEN__1}}")"#));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_generate_from_file_without_base_url() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("user.json"), r#"{"id": 1}"#)?;
+
+        let config_path = dir.path().join("gen.json");
+        let config = serde_json::json!({
+            "inputs": [{ "curl": { "src": "user.json", "fieldName": "user" } }],
+            "output": { "path": "./output.graphql" }
+        });
+        std::fs::write(&config_path, config.to_string())?;
+
+        let generator = Generator::new(
+            config_path.to_string_lossy().as_ref(),
+            crate::core::runtime::test::init(None),
+        );
+        let config = generator.read().await?;
+        assert!(generator.resolve_io(config).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_generate_to_output_dir() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("user.json"),
+            r#"{"id": 1, "name": "Leanne Graham", "address": {"city": "Gwenborough"}}"#,
+        )?;
+
+        let config_path = dir.path().join("gen.json");
+        let config = serde_json::json!({
+            "inputs": [{ "curl": { "src": "user.json", "fieldName": "user" } }],
+            "output": { "path": "./output.graphql" }
+        });
+        std::fs::write(&config_path, config.to_string())?;
+
+        let output_dir = dir.path().join("schema");
+        let output_dir = output_dir.to_string_lossy();
+        let runtime = crate::core::runtime::test::init(None);
+        let generated = Generator::new(config_path.to_string_lossy().as_ref(), runtime.clone())
+            .with_base_url(Some(Url::parse("https://api.example.com/users/1")?))
+            .with_output_dir(Some(&output_dir))
+            .generate()
+            .await?;
+
+        for file in ["main.graphql", "query.graphql", "types.graphql"] {
+            assert!(dir.path().join("schema").join(file).exists(), "{file}");
+        }
+
+        let config = ConfigReader::init(runtime)
+            .read(format!("{output_dir}/main.graphql"))
+            .await?;
+
+        assert_eq!(config.types, generated.types);
+        assert_eq!(config.enums, generated.enums);
+        assert_eq!(config.unions, generated.unions);
+        assert_eq!(config.schema, generated.schema);
+        Ok(())
+    }
+}
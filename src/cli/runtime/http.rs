@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
@@ -14,6 +15,7 @@ use opentelemetry_semantic_conventions::trace::{
 use reqwest::Client;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use tailcall_http_cache::HttpCacheManager;
+use tokio::sync::Semaphore;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use super::HttpIO;
@@ -72,6 +74,7 @@ pub struct NativeHttp {
     client: ClientWithMiddleware,
     http2_only: bool,
     enable_telemetry: bool,
+    streams: Option<Arc<Semaphore>>,
 }
 
 impl Default for NativeHttp {
@@ -80,6 +83,7 @@ impl Default for NativeHttp {
             client: ClientBuilder::new(Client::new()).build(),
             http2_only: false,
             enable_telemetry: false,
+            streams: None,
         }
     }
 }
@@ -88,7 +92,6 @@ impl NativeHttp {
     pub fn init(upstream: &Upstream, telemetry: &Telemetry) -> Self {
         let mut builder = Client::builder()
             .tcp_keepalive(Some(Duration::from_secs(upstream.tcp_keep_alive)))
-            .timeout(Duration::from_secs(upstream.timeout))
             .connect_timeout(Duration::from_secs(upstream.connect_timeout))
             .http2_keep_alive_interval(Some(Duration::from_secs(upstream.keep_alive_interval)))
             .http2_keep_alive_timeout(Duration::from_secs(upstream.keep_alive_timeout))
@@ -98,6 +101,11 @@ impl NativeHttp {
             .user_agent(upstream.user_agent.clone())
             .danger_accept_invalid_certs(!upstream.verify_ssl);
 
+        // A timeout of 0 disables it
+        if upstream.timeout > 0 {
+            builder = builder.timeout(Duration::from_secs(upstream.timeout));
+        }
+
         // Add Http2 Prior Knowledge
         if upstream.http2_only {
             builder = builder.http2_prior_knowledge();
@@ -105,10 +113,17 @@ impl NativeHttp {
 
         // Add Http Proxy
         if let Some(ref proxy) = upstream.proxy {
-            builder = builder.proxy(
-                reqwest::Proxy::http(proxy.url.clone())
-                    .expect("Failed to set proxy in http client"),
-            );
+            let mut reqwest_proxy =
+                reqwest::Proxy::all(proxy.url.clone()).expect("Failed to set proxy in http client");
+            if let Some(ref username) = proxy.username {
+                reqwest_proxy =
+                    reqwest_proxy.basic_auth(username, proxy.password.as_deref().unwrap_or(""));
+            }
+            if !proxy.no_proxy.is_empty() {
+                reqwest_proxy = reqwest_proxy
+                    .no_proxy(reqwest::NoProxy::from_string(&proxy.no_proxy.join(",")));
+            }
+            builder = builder.proxy(reqwest_proxy);
         }
 
         let mut client = ClientBuilder::new(builder.build().expect("Failed to build client"));
@@ -120,10 +135,19 @@ impl NativeHttp {
                 options: HttpCacheOptions::default(),
             }))
         }
+        // Cap the requests in flight, as each of them takes an HTTP/2 stream.
+        // The semaphore is shared by all the hosts and connections of the
+        // client.
+        let streams = upstream
+            .http2_max_concurrent_streams
+            .filter(|_| upstream.http2_only)
+            .map(|max| Arc::new(Semaphore::new(max)));
+
         Self {
             client: client.build(),
             http2_only: upstream.http2_only,
             enable_telemetry: telemetry.export.is_some(),
+            streams,
         }
     }
 }
@@ -148,6 +172,11 @@ impl HttpIO for NativeHttp {
             *request.version_mut() = reqwest::Version::HTTP_2;
         }
 
+        let _stream = match &self.streams {
+            Some(streams) => Some(streams.acquire().await?),
+            None => None,
+        };
+
         let mut req_counter = RequestCounter::new(self.enable_telemetry, &request);
 
         if self.enable_telemetry {
@@ -187,11 +216,16 @@ impl HttpIO for NativeHttp {
 
 #[cfg(test)]
 mod tests {
+    use reqwest::header::HeaderName;
     use reqwest::Method;
     use tokio;
 
     use super::*;
-    use crate::core::http::Response;
+    use crate::core::blueprint::Proxy;
+    use crate::core::http::{RequestContext, RequestTemplate, Response};
+    use crate::core::ir::{EmptyResolverContext, EvalContext};
+    use crate::core::mustache::Mustache;
+    use crate::core::tests::TestSecretIO;
 
     fn start_mock_server() -> httpmock::MockServer {
         httpmock::MockServer::start()
@@ -203,6 +237,21 @@ mod tests {
         result.unwrap()
     }
 
+    #[test]
+    fn test_http2_max_concurrent_streams() {
+        let upstream = Upstream::default()
+            .http2_only(true)
+            .http2_max_concurrent_streams(Some(2));
+        let native_http = NativeHttp::init(&upstream, &Default::default());
+        let permits = native_http
+            .streams
+            .map(|streams| streams.available_permits());
+        assert_eq!(permits, Some(2));
+
+        let native_http = NativeHttp::init(&upstream.http2_only(false), &Default::default());
+        assert!(native_http.streams.is_none());
+    }
+
     #[tokio::test]
     async fn test_native_http_get_request_without_cache() {
         let server = start_mock_server();
@@ -282,4 +331,99 @@ mod tests {
         let resp = make_request(&url1, &native_http).await;
         assert_eq!(resp.headers.get("x-cache-lookup").unwrap(), "MISS");
     }
+
+    #[tokio::test]
+    async fn test_native_http_routes_through_proxy() {
+        let proxy_server = start_mock_server();
+        let upstream_server = start_mock_server();
+
+        let proxied = proxy_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/hello")
+                .header("host", "upstream.invalid");
+            then.status(200).body("Proxied");
+        });
+        let direct = upstream_server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/hello");
+            then.status(200).body("Direct");
+        });
+
+        let upstream = Upstream {
+            proxy: Some(Proxy {
+                url: proxy_server.base_url(),
+                username: None,
+                password: None,
+                no_proxy: vec!["127.0.0.1".to_string()],
+            }),
+            ..Default::default()
+        };
+        let native_http = NativeHttp::init(&upstream, &Default::default());
+
+        let response = make_request("http://upstream.invalid/hello", &native_http).await;
+        assert_eq!(response.body, Bytes::from("Proxied"));
+
+        let request_url = format!("http://127.0.0.1:{}/hello", upstream_server.port());
+        let response = make_request(&request_url, &native_http).await;
+        assert_eq!(response.body, Bytes::from("Direct"));
+
+        proxied.assert_hits(1);
+        direct.assert_hits(1);
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_secret_header_is_redacted() {
+        let server = start_mock_server();
+        let upstream = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/users")
+                .header("authorization", "Bearer token-123");
+            then.status(200).body("Hello");
+        });
+
+        let mut req_ctx = RequestContext::default();
+        req_ctx.runtime.secret = Arc::new(TestSecretIO::from_iter([(
+            "UPSTREAM_TOKEN".to_string(),
+            "token-123".to_string(),
+        )]));
+        let ctx = EvalContext::new(&req_ctx, &EmptyResolverContext {});
+        let tmpl = RequestTemplate::new(&format!("{}/users", server.base_url()))
+            .unwrap()
+            .headers(vec![(
+                HeaderName::from_static("authorization"),
+                Mustache::parse("Bearer {{.secret.UPSTREAM_TOKEN}}"),
+            )]);
+        let request = tmpl.to_request(&ctx).unwrap().into_request();
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let native_http = NativeHttp::init(&Default::default(), &Default::default());
+        let response = native_http.execute(request).await.unwrap();
+        assert_eq!(response.body, Bytes::from("Hello"));
+        upstream.assert();
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains(r#""authorization": Sensitive"#));
+        assert!(!logs.contains("token-123"));
+    }
 }
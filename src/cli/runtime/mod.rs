@@ -12,6 +12,7 @@ use inquire::{Confirm, Select};
 use crate::core::blueprint::Blueprint;
 use crate::core::cache::InMemoryCache;
 use crate::core::runtime::TargetRuntime;
+use crate::core::secret::EnvSecretIO;
 use crate::core::worker::{Command, Event};
 use crate::core::{blueprint, EnvIO, FileIO, HttpIO, WorkerIO};
 
@@ -60,11 +61,21 @@ fn init_http(blueprint: &Blueprint) -> Arc<dyn HttpIO> {
 // Provides access to http in native rust environment
 fn init_http2_only(blueprint: &Blueprint) -> Arc<dyn HttpIO> {
     Arc::new(http::NativeHttp::init(
-        &blueprint.upstream.clone().http2_only(true),
+        &grpc_upstream(&blueprint.upstream),
         &blueprint.telemetry,
     ))
 }
 
+/// Settings of the HTTP/2 client used for gRPC, which takes its keep-alive
+/// pings from the gRPC specific ones.
+fn grpc_upstream(upstream: &blueprint::Upstream) -> blueprint::Upstream {
+    upstream
+        .clone()
+        .http2_only(true)
+        .keep_alive_interval(upstream.grpc_keepalive_interval)
+        .keep_alive_timeout(upstream.grpc_keepalive_timeout)
+}
+
 fn init_in_memory_cache<K: Hash + Eq, V: Clone>() -> InMemoryCache<K, V> {
     InMemoryCache::default()
 }
@@ -73,10 +84,13 @@ pub fn init(blueprint: &Blueprint) -> TargetRuntime {
     #[cfg(not(feature = "js"))]
     tracing::warn!("JS capabilities are disabled in this build");
 
+    let env = init_env();
+
     TargetRuntime {
         http: init_http(blueprint),
         http2_only: init_http2_only(blueprint),
-        env: init_env(),
+        env: env.clone(),
+        secret: Arc::new(EnvSecretIO::new(env)),
         file: init_file(),
         cache: Arc::new(init_in_memory_cache()),
         extensions: Arc::new(vec![]),
@@ -131,3 +145,22 @@ pub async fn create_directory(folder_path: &str) -> anyhow::Result<()> {
 pub fn select_prompt<T: std::fmt::Display>(message: &str, options: Vec<T>) -> anyhow::Result<T> {
     Ok(Select::new(message, options).prompt()?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grpc_upstream_keepalive() {
+        let upstream = blueprint::Upstream::default()
+            .grpc_keepalive_interval(10)
+            .grpc_keepalive_timeout(5);
+        let grpc = grpc_upstream(&upstream);
+
+        assert!(grpc.http2_only);
+        assert_eq!(grpc.keep_alive_interval, 10);
+        assert_eq!(grpc.keep_alive_timeout, 5);
+        assert!(!upstream.http2_only);
+        assert_eq!(upstream.keep_alive_interval, 60);
+    }
+}
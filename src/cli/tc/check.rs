@@ -1,6 +1,9 @@
+use std::path::Path;
+
 use anyhow::Result;
+use tailcall_valid::Validator;
 
-use super::helpers::{display_schema, log_endpoint_set};
+use super::helpers::{display_schema, lockfile_path, log_endpoint_set};
 use crate::cli::fmt::Fmt;
 use crate::core::blueprint::Blueprint;
 use crate::core::config::reader::ConfigReader;
@@ -11,14 +14,19 @@ pub(super) struct CheckParams {
     pub(super) file_paths: Vec<String>,
     pub(super) n_plus_one_queries: bool,
     pub(super) schema: bool,
+    pub(super) lock: bool,
     pub(super) runtime: TargetRuntime,
 }
 
 pub(super) async fn check_command(params: CheckParams, config_reader: &ConfigReader) -> Result<()> {
-    let CheckParams { file_paths, n_plus_one_queries, schema, runtime } = params;
+    let CheckParams { file_paths, n_plus_one_queries, schema, lock, runtime } = params;
 
     let config_module = (config_reader.read_all(&file_paths)).await?;
     log_endpoint_set(&config_module.extensions().endpoint_set);
+    config_module
+        .validate_links()
+        .to_result()
+        .map_err(Errata::from)?;
     let blueprint = Blueprint::try_from(&config_module).map_err(Errata::from);
 
     match blueprint {
@@ -30,8 +38,16 @@ pub(super) async fn check_command(params: CheckParams, config_reader: &ConfigRea
                 .extensions()
                 .endpoint_set
                 .clone()
-                .into_checked(&blueprint, runtime)
+                .into_checked(&blueprint, runtime.clone())
                 .await?;
+            if lock {
+                let path = lockfile_path(&file_paths);
+                let dir = Path::new(&path).parent().unwrap_or(Path::new(""));
+                let lockfile = config_module.extensions().lockfile.relative_to(dir);
+                let lockfile = lockfile.to_json()?;
+                runtime.file.write(&path, lockfile.as_bytes()).await?;
+                tracing::info!("Lockfile {} ... ok", path);
+            }
             if schema {
                 display_schema(&blueprint);
             }
@@ -0,0 +1,53 @@
+use anyhow::Result;
+
+use crate::cli::fmt::Fmt;
+use crate::core::blueprint::{diff_schema, Blueprint, Criticality};
+use crate::core::config::reader::ConfigReader;
+use crate::core::Errata;
+
+pub(super) async fn diff_schema_command(
+    old_file_path: &str,
+    new_file_path: &str,
+    fail_on_breaking: bool,
+    config_reader: &ConfigReader,
+) -> Result<()> {
+    let old = config_reader.read(old_file_path).await?;
+    let old = Blueprint::try_from(&old).map_err(Errata::from)?;
+    let new = config_reader.read(new_file_path).await?;
+    let new = Blueprint::try_from(&new).map_err(Errata::from)?;
+
+    let changes = diff_schema(&old, &new);
+    if changes.is_empty() {
+        Fmt::display("No changes".to_string());
+        return Ok(());
+    }
+
+    let mut breaking = 0;
+    for criticality in [
+        Criticality::Breaking,
+        Criticality::Dangerous,
+        Criticality::Safe,
+    ] {
+        let changes = changes
+            .iter()
+            .filter(|change| change.criticality == criticality)
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            continue;
+        }
+        if criticality == Criticality::Breaking {
+            breaking = changes.len();
+        }
+
+        Fmt::display(Fmt::heading(&format!("{} changes:", criticality)));
+        for change in changes {
+            Fmt::display(format!("  {}: {}", change.path, change.message));
+        }
+    }
+
+    if fail_on_breaking && breaking > 0 {
+        return Err(Errata::new(&format!("Found {breaking} breaking change(s)")).into());
+    }
+
+    Ok(())
+}
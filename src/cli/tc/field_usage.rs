@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::cli::fmt::Fmt;
+use crate::core::blueprint::{Blueprint, Definition};
+use crate::core::config::reader::ConfigReader;
+use crate::core::jit::{Builder, FieldUsage};
+use crate::core::Errata;
+
+pub(super) async fn field_usage_command(
+    file_path: &str,
+    queries_dir: &str,
+    config_reader: &ConfigReader,
+) -> Result<()> {
+    let config_module = config_reader.read(file_path).await?;
+    let blueprint = Blueprint::try_from(&config_module).map_err(Errata::from)?;
+    let usage = FieldUsage::default();
+
+    let mut paths = std::fs::read_dir(queries_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+
+    for path in paths.iter().filter(|path| is_query_file(path)) {
+        let query = std::fs::read_to_string(path)?;
+        let result = async_graphql::parser::parse_query(&query)
+            .map_err(anyhow::Error::from)
+            .and_then(|document| {
+                Builder::new(&blueprint, &document)
+                    .with_field_usage(&usage)
+                    .build(None)
+                    .map_err(anyhow::Error::from)
+            });
+
+        if let Err(err) = result {
+            tracing::warn!("Skipping {}: {}", path.display(), err);
+        }
+    }
+
+    Fmt::display(Fmt::heading("Field usage:\n"));
+    for definition in blueprint.definitions.iter() {
+        if let Definition::Object(def) = definition {
+            for field in def.fields.iter() {
+                let count = usage.get(&def.name, &field.name);
+                Fmt::display(format!("{}.{}: {}", def.name, field.name, count));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_query_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("graphql" | "gql")
+    )
+}
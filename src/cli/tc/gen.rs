@@ -1,10 +1,20 @@
 use anyhow::Result;
+use url::Url;
 
 use crate::cli::generator::Generator;
 use crate::core::runtime::TargetRuntime;
 
-pub(super) async fn gen_command(file_path: &str, runtime: TargetRuntime) -> Result<()> {
+pub(super) async fn gen_command(
+    file_path: &str,
+    base_url: Option<Url>,
+    root_path: Option<String>,
+    output_dir: Option<String>,
+    runtime: TargetRuntime,
+) -> Result<()> {
     Generator::new(file_path, runtime.clone())
+        .with_base_url(base_url)
+        .with_root_path(root_path.as_deref())?
+        .with_output_dir(output_dir.as_deref())
         .generate()
         .await?;
     Ok(())
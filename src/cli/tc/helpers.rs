@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use lazy_static::lazy_static;
 
 use crate::cli::fmt::Fmt;
@@ -9,11 +11,24 @@ use crate::core::rest::{EndpointSet, Unchecked};
 pub const TAILCALL_RC: &str = ".tailcallrc.graphql";
 pub const GRAPHQL_RC: &str = ".graphqlrc.yml";
 pub const TAILCALL_RC_SCHEMA: &str = ".tailcallrc.schema.json";
+pub const TAILCALL_LOCK: &str = "tailcall.lock";
 
 lazy_static! {
     pub static ref TRACKER: tailcall_tracker::Tracker = tailcall_tracker::Tracker::default();
 }
 
+/// Returns the path of the lockfile, next to the first of the config files.
+/// It's in the working directory when that config is read over HTTP.
+pub(super) fn lockfile_path(file_paths: &[String]) -> String {
+    match file_paths.first() {
+        Some(path) if !path.starts_with("http") => Path::new(path)
+            .with_file_name(TAILCALL_LOCK)
+            .to_string_lossy()
+            .to_string(),
+        _ => TAILCALL_LOCK.to_string(),
+    }
+}
+
 pub(super) fn log_endpoint_set(endpoint_set: &EndpointSet<Unchecked>) {
     let mut endpoints = endpoint_set.get_endpoints().clone();
     endpoints.sort_by(|a, b| {
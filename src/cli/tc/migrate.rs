@@ -0,0 +1,195 @@
+use std::path::Path;
+
+use anyhow::Result;
+use async_graphql::parser::types::{ConstDirective, TypeKind, TypeSystemDefinition};
+use async_graphql::{Name, Positioned};
+use async_graphql_value::ConstValue;
+use tokio::io::AsyncWriteExt;
+
+use crate::cli::fmt::Fmt;
+use crate::core::config::{Config, Link, LinkType};
+use crate::core::document;
+
+/// Result of migrating a legacy config.
+pub struct Migration {
+    pub config: Config,
+    /// Functions extracted from the `@unsafe` scripts, if any.
+    pub script: Option<String>,
+}
+
+pub(super) async fn migrate_command(file_path: &str) -> Result<()> {
+    let path = Path::new(file_path);
+    let script_path = path.with_extension("js");
+    let script_name = script_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("migrated.js");
+
+    let sdl = tokio::fs::read_to_string(path).await?;
+    let migration = migrate(&sdl, script_name)?;
+
+    if let Some(script) = migration.script {
+        write_new(&script_path, &script).await?;
+        tracing::info!("Scripts extracted to: {}", script_path.display());
+    }
+
+    Fmt::display(migration.config.to_sdl());
+
+    Ok(())
+}
+
+/// Writes the extracted scripts into a new file, refusing to overwrite an
+/// existing one.
+async fn write_new(path: &Path, content: &str) -> Result<()> {
+    let file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .await;
+    let mut file = match file {
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+            anyhow::bail!(
+                "Can't extract the scripts to {}, the file already exists",
+                path.display()
+            )
+        }
+        file => file?,
+    };
+    file.write_all(content.as_bytes()).await?;
+
+    Ok(())
+}
+
+/// Rewrites the deprecated directives of a config into their current
+/// equivalents:
+/// - `@const(data: ...)` becomes `@expr(body: ...)`
+/// - `@unsafe(script: ...)` becomes `@js(name: ...)` and the script is moved
+///   into a function of a linked script named `script_name`
+///
+/// Deprecated `@server` arguments such as `enableJIT` are dropped.
+pub fn migrate(sdl: &str, script_name: &str) -> Result<Migration> {
+    let mut doc = async_graphql::parser::parse_schema(sdl)?;
+    let mut functions = Vec::new();
+
+    for definition in doc.definitions.iter_mut() {
+        let TypeSystemDefinition::Type(type_def) = definition else {
+            continue;
+        };
+        let type_name = type_def.node.name.node.to_string();
+        let fields = match &mut type_def.node.kind {
+            TypeKind::Object(object) => &mut object.fields,
+            TypeKind::Interface(interface) => &mut interface.fields,
+            _ => continue,
+        };
+
+        for field in fields.iter_mut() {
+            let function_name = format!("{}_{}", type_name, field.node.name.node);
+            for directive in field.node.directives.iter_mut() {
+                migrate_const(&mut directive.node);
+                if let Some(function) = migrate_unsafe(&mut directive.node, &function_name) {
+                    functions.push(function);
+                }
+            }
+        }
+    }
+
+    let mut config = Config::from_sdl(&document::print(doc)).to_result()?;
+    let script = if functions.is_empty() {
+        None
+    } else {
+        config.links.push(Link {
+            src: script_name.to_string(),
+            type_of: LinkType::Script,
+            ..Default::default()
+        });
+        Some(functions.join("\n"))
+    };
+
+    Ok(Migration { config, script })
+}
+
+fn migrate_const(directive: &mut ConstDirective) {
+    if directive.name.node != "const" {
+        return;
+    }
+
+    directive.name.node = Name::new("expr");
+    for (name, _) in directive.arguments.iter_mut() {
+        if name.node == "data" {
+            name.node = Name::new("body");
+        }
+    }
+}
+
+fn migrate_unsafe(directive: &mut ConstDirective, function_name: &str) -> Option<String> {
+    if directive.name.node != "unsafe" {
+        return None;
+    }
+
+    let script = directive
+        .get_argument("script")
+        .and_then(|script| match &script.node {
+            ConstValue::String(script) => Some(script.clone()),
+            _ => None,
+        })?;
+
+    let pos = directive.name.pos;
+    directive.name.node = Name::new("js");
+    directive.arguments = vec![(
+        Positioned::new(Name::new("name"), pos),
+        Positioned::new(ConstValue::String(function_name.to_string()), pos),
+    )];
+
+    Some(format!(
+        "function {}(ctx) {{\n  {}\n}}\n",
+        function_name,
+        script.trim()
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{migrate, write_new};
+
+    #[test]
+    fn test_migrate_unsafe() {
+        let sdl = r#"
+            schema @server(enableJIT: true) {
+              query: Query
+            }
+
+            type Query {
+              greeting: String @const(data: "hello")
+              user: User @http(url: "http://jsonplaceholder.typicode.com/users/1")
+            }
+
+            type User {
+              id: Int
+              name: String @unsafe(script: "return ctx.value.name.toUpperCase();")
+            }
+        "#;
+
+        let migration = migrate(sdl, "legacy.js").unwrap();
+
+        insta::assert_snapshot!(migration.config.to_sdl());
+        assert_eq!(
+            migration.script.unwrap(),
+            "function User_name(ctx) {\n  return ctx.value.name.toUpperCase();\n}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keep_existing_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.js");
+        std::fs::write(&path, "function existing() {}").unwrap();
+
+        let actual = write_new(&path, "function User_name(ctx) {}").await;
+
+        assert!(actual.is_err());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "function existing() {}"
+        );
+    }
+}
@@ -1,7 +1,11 @@
 mod check;
+mod diff_schema;
+mod field_usage;
 mod gen;
 mod helpers;
 mod init;
+mod migrate;
 pub mod run;
 mod start;
+mod validate_query;
 mod validate_rc;
@@ -1,14 +1,17 @@
+use std::path::Path;
+
 use anyhow::Result;
 use clap::Parser;
 use dotenvy::dotenv;
 
-use super::helpers::TRACKER;
+use super::helpers::{lockfile_path, TRACKER};
 use super::validate_rc::validate_rc_config_files;
-use super::{check, gen, init, start};
+use super::{check, diff_schema, field_usage, gen, init, migrate, start, validate_query};
 use crate::cli::command::{Cli, Command};
 use crate::cli::{self, update_checker};
 use crate::core::blueprint::Blueprint;
 use crate::core::config::reader::ConfigReader;
+use crate::core::config::Lockfile;
 use crate::core::runtime::TargetRuntime;
 
 pub async fn run() -> Result<()> {
@@ -41,16 +44,24 @@ fn get_runtime_and_config_reader(verify_ssl: bool) -> (TargetRuntime, ConfigRead
 
 async fn run_command(cli: Cli) -> Result<()> {
     match cli.command {
-        Command::Start { file_paths, verify_ssl } => {
+        Command::Start { file_paths, verify_ssl, locked } => {
             let (runtime, config_reader) = get_runtime_and_config_reader(verify_ssl);
-            validate_rc_config_files(runtime, &file_paths).await;
+            validate_rc_config_files(runtime.clone(), &file_paths).await;
+            let config_reader = if locked {
+                let path = lockfile_path(&file_paths);
+                let dir = Path::new(&path).parent().unwrap_or(Path::new(""));
+                let lockfile = Lockfile::from_json(&runtime.file.read(&path).await?)?;
+                config_reader.locked(lockfile.resolve(dir))
+            } else {
+                config_reader
+            };
             start::start_command(file_paths, &config_reader).await?;
         }
-        Command::Check { file_paths, n_plus_one_queries, schema, verify_ssl } => {
+        Command::Check { file_paths, n_plus_one_queries, schema, verify_ssl, lock } => {
             let (runtime, config_reader) = get_runtime_and_config_reader(verify_ssl);
             validate_rc_config_files(runtime.clone(), &file_paths).await;
             check::check_command(
-                check::CheckParams { file_paths, n_plus_one_queries, schema, runtime },
+                check::CheckParams { file_paths, n_plus_one_queries, schema, lock, runtime },
                 &config_reader,
             )
             .await?;
@@ -59,9 +70,30 @@ async fn run_command(cli: Cli) -> Result<()> {
             let (runtime, _) = get_runtime_and_config_reader(true);
             init::init_command(runtime, &folder_path).await?;
         }
-        Command::Gen { file_path } => {
+        Command::Gen { file_path, base_url, root_path, output_dir } => {
             let (runtime, _) = get_runtime_and_config_reader(true);
-            gen::gen_command(&file_path, runtime).await?;
+            gen::gen_command(&file_path, base_url, root_path, output_dir, runtime).await?;
+        }
+        Command::FieldUsage { file_path, queries_dir } => {
+            let (_, config_reader) = get_runtime_and_config_reader(true);
+            field_usage::field_usage_command(&file_path, &queries_dir, &config_reader).await?;
+        }
+        Command::Migrate { file_path } => {
+            migrate::migrate_command(&file_path).await?;
+        }
+        Command::DiffSchema { old_file_path, new_file_path, fail_on_breaking } => {
+            let (_, config_reader) = get_runtime_and_config_reader(true);
+            diff_schema::diff_schema_command(
+                &old_file_path,
+                &new_file_path,
+                fail_on_breaking,
+                &config_reader,
+            )
+            .await?;
+        }
+        Command::ValidateQuery { file_path, query_path } => {
+            let (_, config_reader) = get_runtime_and_config_reader(true);
+            validate_query::validate_query_command(&file_path, &query_path, &config_reader).await?;
         }
     }
     Ok(())
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute};
+use async_graphql::{Response, ServerError, Value};
+
+use crate::cli::fmt::Fmt;
+use crate::core::blueprint::{Blueprint, SchemaModifiers};
+use crate::core::config::reader::ConfigReader;
+use crate::core::schema_extension::SchemaExtension;
+use crate::core::Errata;
+
+pub(super) async fn validate_query_command(
+    file_path: &str,
+    query_path: &str,
+    config_reader: &ConfigReader,
+) -> Result<()> {
+    let config_module = config_reader.read(file_path).await?;
+    let blueprint = Blueprint::try_from(&config_module).map_err(Errata::from)?;
+    let query = tokio::fs::read_to_string(query_path).await?;
+
+    let errors = validate_query(&blueprint, &query).await;
+    if errors.is_empty() {
+        Fmt::display("ok".to_string());
+        return Ok(());
+    }
+
+    let causes = errors
+        .iter()
+        .map(|error| {
+            Errata::new(&error.message).trace(
+                error
+                    .locations
+                    .iter()
+                    .map(|pos| format!("{}:{}", pos.line, pos.column))
+                    .collect(),
+            )
+        })
+        .collect();
+    Err(Errata::new(&format!("Invalid query {query_path}"))
+        .caused_by(causes)
+        .into())
+}
+
+/// Validates the query against the schema of the blueprint without executing
+/// it, so that no upstream is ever called.
+async fn validate_query(blueprint: &Blueprint, query: &str) -> Vec<ServerError> {
+    let modifiers = SchemaModifiers::default()
+        .extensions(Arc::new(vec![SchemaExtension::new(SkipExecution)]))
+        .with_no_resolver();
+    let schema = blueprint.to_schema_with(modifiers);
+
+    schema
+        .execute(async_graphql::Request::new(query))
+        .await
+        .errors
+}
+
+/// Stops the requests right after their validation.
+struct SkipExecution;
+
+impl ExtensionFactory for SkipExecution {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(SkipExecution)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for SkipExecution {
+    async fn execute(
+        &self,
+        _: &ExtensionContext<'_>,
+        _: Option<&str>,
+        _: NextExecute<'_>,
+    ) -> Response {
+        Response::new(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::Pos;
+    use pretty_assertions::assert_eq;
+    use tailcall_valid::Validator;
+
+    use super::*;
+    use crate::core::config::Config;
+
+    const CONFIG: &str = r#"
+        schema @server @upstream {
+          query: Query
+        }
+
+        type Query {
+          user(id: Int!): User @http(url: "http://jsonplaceholder.typicode.com/users/{{.args.id}}")
+        }
+
+        type User {
+          id: Int!
+          name: String!
+        }
+    "#;
+
+    fn blueprint() -> Blueprint {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        Blueprint::try_from(&config.into()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_valid_query() {
+        let query = r#"
+            query ($id: Int!) { user(id: $id) { ...user } }
+            fragment user on User { id name }
+        "#;
+        let errors = validate_query(&blueprint(), query).await;
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_field() {
+        let query = "query {\n  user(id: 1) {\n    id\n    email\n  }\n}";
+        let errors = validate_query(&blueprint(), query).await;
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.message.as_str(), error.locations.clone()))
+                .collect::<Vec<_>>(),
+            vec![(
+                r#"Unknown field "email" on type "User"."#,
+                vec![Pos { line: 4, column: 5 }]
+            )]
+        );
+    }
+}
@@ -46,12 +46,31 @@ impl AppContext {
             if let Definition::Object(def) = def {
                 for field in &mut def.fields {
                     let upstream_batch = &blueprint.upstream.batch;
+                    let field_name = field.name.clone();
                     field.map_expr(|expr| {
                         expr.modify(&mut |expr| match expr {
                             IR::IO(io) => match io {
                                 IO::Http {
-                                    req_template, group_by, is_list, dedupe, hook, ..
+                                    req_template,
+                                    group_by,
+                                    is_list,
+                                    dedupe,
+                                    hook,
+                                    skip_if,
+                                    ok_status,
+                                    ..
                                 } => {
+                                    for name in req_template.root_url.env_vars() {
+                                        if runtime.env.get(name).is_none() {
+                                            tracing::warn!(
+                                                "Environment variable '{}' used in the url of '{}.{}' is not set",
+                                                name,
+                                                def.name,
+                                                field_name
+                                            );
+                                        }
+                                    }
+
                                     let is_list = *is_list;
                                     let dedupe = *dedupe;
                                     let data_loader = HttpDataLoader::new(
@@ -68,6 +87,8 @@ impl AppContext {
                                         hook: hook.clone(),
                                         is_list,
                                         dedupe,
+                                        skip_if: skip_if.clone(),
+                                        ok_status: ok_status.clone(),
                                     }));
 
                                     http_data_loaders.push(data_loader);
@@ -123,6 +144,19 @@ impl AppContext {
                                     Some(IR::IO(IO::Js { name: method.clone() }))
                                 }
                             },
+                            IR::Dynamic(value) => {
+                                for name in value.env_vars() {
+                                    if runtime.env.get(name).is_none() {
+                                        tracing::warn!(
+                                            "Environment variable '{}' used by '{}.{}' is not set",
+                                            name,
+                                            def.name,
+                                            field_name
+                                        );
+                                    }
+                                }
+                                None
+                            }
                             _ => None,
                         })
                     });
@@ -133,6 +167,9 @@ impl AppContext {
         let schema = blueprint
             .to_schema_with(SchemaModifiers::default().extensions(runtime.extensions.clone()));
 
+        let dedupe_handler =
+            DedupeResult::new(false).with_max_pending(blueprint.server.dedupe_max_pending);
+
         AppContext {
             schema,
             runtime,
@@ -142,7 +179,7 @@ impl AppContext {
             grpc_data_loaders: Arc::new(grpc_data_loaders),
             endpoints,
 
-            dedupe_handler: Arc::new(DedupeResult::new(false)),
+            dedupe_handler: Arc::new(dedupe_handler),
             dedupe_operation_handler: DedupeResult::new(false),
             operation_plans: DashMap::new(),
             const_execution_cache: DashMap::default(),
@@ -153,3 +190,48 @@ impl AppContext {
         self.schema.execute(request).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use serde_json::json;
+    use tailcall_valid::Validator;
+
+    use super::AppContext;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::http::RequestContext;
+    use crate::core::jit::{ConstValueExecutor, Request};
+    use crate::core::rest::EndpointSet;
+    use crate::core::tests::TestEnvIO;
+
+    #[tokio::test]
+    async fn test_expr_env() {
+        let sdl = r#"
+            schema @server @upstream {
+              query: Query
+            }
+
+            type Query {
+              region: String @expr(body: "{{.env.REGION}}")
+            }
+        "#;
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        let mut runtime = crate::cli::runtime::init(&blueprint);
+        runtime.env = Arc::new(TestEnvIO::from_iter([(
+            "REGION".to_string(),
+            "eu-west-1".to_string(),
+        )]));
+        let app_ctx = Arc::new(AppContext::new(blueprint, runtime, EndpointSet::default()));
+        let req_ctx = RequestContext::from(app_ctx.as_ref());
+
+        let request = Request::new("{ region }");
+        let executor = ConstValueExecutor::try_new(&request, &app_ctx).unwrap();
+        let response = executor.execute(&app_ctx, &req_ctx, request).await;
+        let actual: serde_json::Value = serde_json::from_slice(response.body.as_slice()).unwrap();
+
+        assert_eq!(actual, json!({"data": {"region": "eu-west-1"}}));
+    }
+}
@@ -3,7 +3,7 @@ use std::hash::{Hash, Hasher};
 
 use anyhow::Result;
 use async_graphql::parser::types::{ExecutableDocument, OperationType};
-use async_graphql::{BatchResponse, Executor, Value};
+use async_graphql::{BatchResponse, Executor, ServerError, Value};
 use http::header::{HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
 use http::{Response, StatusCode};
 use hyper::Body;
@@ -12,6 +12,7 @@ use serde::{Deserialize, Serialize};
 use tailcall_hasher::TailcallHasher;
 
 use super::jit::{BatchResponse as JITBatchResponse, JITExecutor};
+use super::rest::{Envelope, JsonApi};
 
 #[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub struct OperationId(u64);
@@ -27,6 +28,12 @@ pub trait GraphQLRequestLike: Hash + Send {
 
     fn parse_query(&mut self) -> Option<&ExecutableDocument>;
 
+    /// Takes the request out when it holds a single operation, giving `self`
+    /// back otherwise.
+    fn into_single(self) -> std::result::Result<async_graphql::Request, Self>
+    where
+        Self: Sized;
+
     fn is_query(&mut self) -> bool {
         self.parse_query()
             .map(|a| {
@@ -39,6 +46,17 @@ pub trait GraphQLRequestLike: Hash + Send {
             .unwrap_or(false)
     }
 
+    fn is_mutation(&mut self) -> bool {
+        self.parse_query()
+            .map(|document| {
+                document
+                    .operations
+                    .iter()
+                    .any(|(_, operation)| operation.node.ty == OperationType::Mutation)
+            })
+            .unwrap_or(false)
+    }
+
     fn operation_id(&self, headers: &HeaderMap) -> OperationId {
         let mut hasher = TailcallHasher::default();
         let state = &mut hasher;
@@ -93,6 +111,13 @@ impl GraphQLRequestLike for GraphQLBatchRequest {
     fn parse_query(&mut self) -> Option<&ExecutableDocument> {
         None
     }
+
+    fn into_single(self) -> std::result::Result<async_graphql::Request, Self> {
+        match self.0 {
+            async_graphql::BatchRequest::Single(request) => Ok(request),
+            batch => Err(Self(batch)),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -132,6 +157,10 @@ impl GraphQLRequestLike for GraphQLRequest {
     fn parse_query(&mut self) -> Option<&ExecutableDocument> {
         self.0.parsed_query().ok()
     }
+
+    fn into_single(self) -> std::result::Result<async_graphql::Request, Self> {
+        Ok(self.0)
+    }
 }
 
 // TODO: drop this type since we can use jit::response?
@@ -166,7 +195,10 @@ impl From<GraphQLQuery> for GraphQLRequest {
     }
 }
 
-#[derive(Debug)]
+/// A request sent in the query string of a GET request, as in
+/// `?query=...&variables=...&operationName=...`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GraphQLQuery {
     query: String,
     operation_name: Option<String>,
@@ -187,6 +219,42 @@ impl GraphQLQuery {
 static APPLICATION_JSON: Lazy<HeaderValue> =
     Lazy::new(|| HeaderValue::from_static("application/json"));
 
+static APPLICATION_JSON_API: Lazy<HeaderValue> =
+    Lazy::new(|| HeaderValue::from_static("application/vnd.api+json"));
+
+static APPLICATION_PROBLEM_JSON: Lazy<HeaderValue> =
+    Lazy::new(|| HeaderValue::from_static("application/problem+json"));
+
+/// The HTTP status of the category of `error`. Errors raised before the
+/// execution, such as the parsing and the validation of the request, have no
+/// path and are the client's fault. Errors of the resolvers are server errors,
+/// unless they carry the code of a failed gRPC call.
+fn error_status(error: &ServerError) -> StatusCode {
+    let grpc_code = error
+        .extensions
+        .as_ref()
+        .and_then(|extensions| extensions.get("grpcCode"))
+        .and_then(|code| match code {
+            Value::Number(code) => code.as_i64(),
+            _ => None,
+        });
+
+    match grpc_code {
+        Some(3 | 9 | 11) => StatusCode::BAD_REQUEST,
+        Some(4) => StatusCode::GATEWAY_TIMEOUT,
+        Some(5) => StatusCode::NOT_FOUND,
+        Some(6 | 10) => StatusCode::CONFLICT,
+        Some(7) => StatusCode::FORBIDDEN,
+        Some(8) => StatusCode::TOO_MANY_REQUESTS,
+        Some(12) => StatusCode::NOT_IMPLEMENTED,
+        Some(14) => StatusCode::SERVICE_UNAVAILABLE,
+        Some(16) => StatusCode::UNAUTHORIZED,
+        Some(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        None if error.path.is_empty() => StatusCode::BAD_REQUEST,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 impl GraphQLResponse {
     fn build_response(&self, status: StatusCode, body: Body) -> Result<Response<Body>> {
         let mut response = Response::builder()
@@ -248,6 +316,116 @@ impl GraphQLResponse {
         }
     }
 
+    /// Transforms a plain `GraphQLResponse` into a `Response<Body>` whose body
+    /// wraps the flattened data and the errors in the keys of the `envelope`,
+    /// as in `{"data": {"name": "John"}, "errors": []}`.
+    pub fn into_rest_envelope_response(self, envelope: &Envelope) -> Result<Response<Body>> {
+        let status = if self.0.is_ok() {
+            StatusCode::OK
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        };
+
+        let (data, errors, meta) = match self.0 {
+            BatchResponse::Single(ref res) => (
+                serde_json::to_value(Self::flatten_response(&res.data))?,
+                serde_json::to_value(&res.errors)?,
+                serde_json::to_value(&res.extensions)?,
+            ),
+            BatchResponse::Batch(ref list) => (
+                serde_json::to_value(
+                    list.iter()
+                        .map(|res| Self::flatten_response(&res.data))
+                        .collect::<Vec<_>>(),
+                )?,
+                serde_json::to_value(list.iter().flat_map(|res| &res.errors).collect::<Vec<_>>())?,
+                serde_json::to_value(list.iter().map(|res| &res.extensions).collect::<Vec<_>>())?,
+            ),
+        };
+
+        let mut body = serde_json::Map::new();
+        body.insert(envelope.data.clone(), data);
+        body.insert(envelope.errors.clone(), errors);
+        if let Some(key) = &envelope.meta {
+            body.insert(key.clone(), meta);
+        }
+
+        self.build_response(status, Body::from(serde_json::to_string(&body)?))
+    }
+
+    /// Transforms a plain `GraphQLResponse` into a `Response<Body>` whose body
+    /// is a JSON:API document of the flattened data. The errors are returned
+    /// as JSON:API error objects.
+    pub fn into_json_api_response(self, json_api: &JsonApi) -> Result<Response<Body>> {
+        let (status, body) = if self.0.is_ok() {
+            let data = match self.0 {
+                BatchResponse::Single(ref res) => {
+                    serde_json::to_value(Self::flatten_response(&res.data))?
+                }
+                BatchResponse::Batch(ref list) => serde_json::to_value(
+                    list.iter()
+                        .map(|res| Self::flatten_response(&res.data))
+                        .collect::<Vec<_>>(),
+                )?,
+            };
+            (StatusCode::OK, json_api.to_document(data))
+        } else {
+            let errors = match self.0 {
+                BatchResponse::Single(ref res) => res.errors.iter().collect::<Vec<_>>(),
+                BatchResponse::Batch(ref list) => list.iter().flat_map(|res| &res.errors).collect(),
+            };
+            let errors = errors
+                .into_iter()
+                .map(|error| serde_json::json!({ "status": "500", "detail": error.message }))
+                .collect::<Vec<_>>();
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                serde_json::json!({ "errors": errors }),
+            )
+        };
+
+        let mut response =
+            self.build_response(status, Body::from(serde_json::to_string(&body)?))?;
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, APPLICATION_JSON_API.clone());
+        Ok(response)
+    }
+
+    /// Transforms a `GraphQLResponse` with errors into a RFC 7807 problem
+    /// details `Response<Body>`, for clients that accept
+    /// `application/problem+json`. The problem describes the first error, the
+    /// others are listed in its `errors` member. `instance` is the path of the
+    /// request.
+    pub fn into_problem_details_response(self, instance: &str) -> Result<Response<Body>> {
+        let errors = match self.0 {
+            BatchResponse::Single(ref res) => res.errors.iter().collect::<Vec<_>>(),
+            BatchResponse::Batch(ref list) => list.iter().flat_map(|res| &res.errors).collect(),
+        };
+        let Some(error) = errors.first() else {
+            return self.into_rest_response();
+        };
+
+        let status = error_status(error);
+        let mut body = serde_json::json!({
+            "type": "about:blank",
+            "title": status.canonical_reason().unwrap_or_default(),
+            "status": status.as_u16(),
+            "detail": error.message,
+            "instance": instance,
+        });
+        if errors.len() > 1 {
+            body["errors"] = serde_json::to_value(&errors)?;
+        }
+
+        let mut response =
+            self.build_response(status, Body::from(serde_json::to_string(&body)?))?;
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, APPLICATION_PROBLEM_JSON.clone());
+        Ok(response)
+    }
+
     /// Sets the `cache_control` for a given `GraphQLResponse`.
     ///
     /// The function modifies the `GraphQLResponse` to set the `cache_control`
@@ -506,6 +684,147 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_to_rest_envelope_response() {
+        let user = IndexMap::from([(Name::new("name"), Value::String("John".to_string()))]);
+        let data = IndexMap::from([(Name::new("user"), Value::Object(user))]);
+
+        let response = GraphQLResponse(BatchResponse::Single(Response::new(Value::Object(data))));
+        let rest_response = response
+            .into_rest_envelope_response(&Envelope::default())
+            .unwrap();
+
+        assert_eq!(rest_response.status(), StatusCode::OK);
+        assert_eq!(
+            hyper::body::to_bytes(rest_response.into_body())
+                .await
+                .unwrap()
+                .to_vec(),
+            json!({ "data": { "name": "John" }, "errors": [] })
+                .to_string()
+                .as_bytes()
+                .to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_to_rest_envelope_response_with_error() {
+        let mut response: Response = Default::default();
+        response.errors = vec![ServerError::new("Some error", None)];
+        let response = GraphQLResponse(BatchResponse::Single(response));
+        let envelope = Envelope {
+            data: "result".to_string(),
+            errors: "problems".to_string(),
+            meta: Some("meta".to_string()),
+        };
+        let rest_response = response.into_rest_envelope_response(&envelope).unwrap();
+
+        assert_eq!(rest_response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            hyper::body::to_bytes(rest_response.into_body())
+                .await
+                .unwrap()
+                .to_vec(),
+            json!({
+                "result": null,
+                "problems": [{ "message": "Some error" }],
+                "meta": {}
+            })
+            .to_string()
+            .as_bytes()
+            .to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_to_json_api_response() {
+        let data = json!({
+            "post": {
+                "__typename": "Post",
+                "id": 1,
+                "title": "Hello",
+                "author": {"__typename": "User", "id": 2, "name": "John"}
+            }
+        });
+        let response = Response::new(Value::from_json(data).unwrap());
+        let response = GraphQLResponse(BatchResponse::Single(response));
+        let rest_response = response
+            .into_json_api_response(&JsonApi::default())
+            .unwrap();
+
+        assert_eq!(rest_response.status(), StatusCode::OK);
+        assert_eq!(
+            rest_response.headers()["content-type"],
+            "application/vnd.api+json"
+        );
+        assert_eq!(
+            hyper::body::to_bytes(rest_response.into_body())
+                .await
+                .unwrap()
+                .to_vec(),
+            json!({
+                "data": {
+                    "type": "Post",
+                    "id": "1",
+                    "attributes": { "title": "Hello" },
+                    "relationships": { "author": { "data": { "type": "User", "id": "2" } } }
+                },
+                "included": [{ "type": "User", "id": "2", "attributes": { "name": "John" } }]
+            })
+            .to_string()
+            .as_bytes()
+            .to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_to_problem_details_response() {
+        let mut response: Response = Default::default();
+        response.errors = vec![ServerError::new(
+            r#"Invalid value for argument "id", expected type "Int""#,
+            Some(async_graphql::Pos { line: 1, column: 10 }),
+        )];
+        let response = GraphQLResponse(BatchResponse::Single(response));
+        let rest_response = response
+            .into_problem_details_response("/api/users/abc")
+            .unwrap();
+
+        assert_eq!(rest_response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            rest_response.headers()["content-type"],
+            "application/problem+json"
+        );
+        assert_eq!(
+            hyper::body::to_bytes(rest_response.into_body())
+                .await
+                .unwrap()
+                .to_vec(),
+            json!({
+                "type": "about:blank",
+                "title": "Bad Request",
+                "status": 400,
+                "detail": r#"Invalid value for argument "id", expected type "Int""#,
+                "instance": "/api/users/abc"
+            })
+            .to_string()
+            .as_bytes()
+            .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_error_status() {
+        let mut error = ServerError::new("Some error", None);
+        error.path = vec![async_graphql::PathSegment::Field("user".to_string())];
+        assert_eq!(error_status(&error), StatusCode::INTERNAL_SERVER_ERROR);
+
+        error
+            .extensions
+            .get_or_insert_with(Default::default)
+            .set("grpcCode", 5);
+        assert_eq!(error_status(&error), StatusCode::NOT_FOUND);
+    }
+
     #[test]
     fn to_value() {
         assert_eq!(CacheControl { public: true, max_age: 0 }.value(), None);
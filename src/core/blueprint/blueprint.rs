@@ -10,6 +10,7 @@ use super::directive::Directive;
 use super::telemetry::Telemetry;
 use super::{GlobalTimeout, Index};
 use crate::core::blueprint::{Server, Upstream};
+use crate::core::config::Deprecated;
 use crate::core::ir::model::IR;
 use crate::core::schema_extension::SchemaExtension;
 use crate::core::{scalar, Type};
@@ -128,6 +129,10 @@ pub struct FieldDefinition {
     pub directives: Vec<Directive>,
     pub description: Option<String>,
     pub default_value: Option<serde_json::Value>,
+    pub deprecated: Option<Deprecated>,
+    /// Hidden from the schema, the field is only resolved when a sibling reads
+    /// its value.
+    pub internal: bool,
 }
 
 impl FieldDefinition {
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use async_graphql_value::ConstValue;
 use directive::Directive;
@@ -8,8 +8,12 @@ use tailcall_valid::{Valid, Validator};
 use union_resolver::update_union_resolver;
 
 use crate::core::blueprint::*;
-use crate::core::config::{Config, Enum, Field, GraphQLOperationType, Protected, Union};
+use crate::core::config::{
+    Config, ConfigModule, Enum, Field, GraphQLOperationType, Protected, Resolver, ResolverSet,
+    Union,
+};
 use crate::core::directive::DirectiveCodec;
+use crate::core::document::print_directive;
 use crate::core::ir::model::{Cache, IR};
 use crate::core::try_fold::TryFold;
 use crate::core::{config, scalar, Type};
@@ -46,6 +50,7 @@ pub fn to_input_object_type_definition(
         fields: definition
             .fields
             .iter()
+            .filter(|field| !field.internal)
             .map(|field| InputFieldDefinition {
                 name: field.name.clone(),
                 description: field.description.clone(),
@@ -264,15 +269,70 @@ fn to_object_type_definition(
     type_of: &config::Type,
     config_module: &ConfigModule,
 ) -> Valid<Definition, BlueprintError> {
-    to_fields(name, type_of, config_module).map(|fields| {
-        Definition::Object(ObjectTypeDefinition {
-            name: name.to_string(),
-            description: type_of.doc.clone(),
-            fields,
-            implements: type_of.implements.clone(),
-            directives: to_directives(&type_of.directives),
+    to_fields(name, type_of, config_module)
+        .and_then(|fields| validate_field_dependencies(&fields).map(|_| fields))
+        .map(|fields| {
+            Definition::Object(ObjectTypeDefinition {
+                name: name.to_string(),
+                description: type_of.doc.clone(),
+                fields,
+                implements: type_of.implements.clone(),
+                directives: to_directives(&type_of.directives),
+            })
         })
-    })
+}
+
+/// Fails if the resolvers of the fields read each other's resolved values,
+/// since neither of them could be executed first.
+fn validate_field_dependencies(fields: &[FieldDefinition]) -> Valid<(), BlueprintError> {
+    let dependencies = fields
+        .iter()
+        .map(|field| {
+            let names = field
+                .resolver
+                .iter()
+                .flat_map(|ir| ir.parent_fields())
+                .filter(|name| {
+                    *name != field.name
+                        && fields
+                            .iter()
+                            .any(|other| other.name == *name && other.resolver.is_some())
+                })
+                .collect::<Vec<_>>();
+            (field.name.as_str(), names)
+        })
+        .collect::<HashMap<_, _>>();
+
+    for field in fields {
+        if let Some(cycle) = find_cycle(&field.name, &dependencies, &mut vec![]) {
+            return Valid::fail(BlueprintError::CyclicFieldDependency(cycle.join(" -> ")))
+                .trace(&field.name);
+        }
+    }
+
+    Valid::succeed(())
+}
+
+fn find_cycle<'a>(
+    name: &'a str,
+    dependencies: &HashMap<&'a str, Vec<&'a str>>,
+    path: &mut Vec<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if let Some(pos) = path.iter().position(|visited| *visited == name) {
+        let mut cycle = path[pos..].to_vec();
+        cycle.push(name);
+        return Some(cycle);
+    }
+
+    path.push(name);
+    for dependency in dependencies.get(name).into_iter().flatten() {
+        if let Some(cycle) = find_cycle(dependency, dependencies, path) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+
+    None
 }
 
 fn update_args<'a>() -> TryFold<
@@ -300,6 +360,8 @@ fn update_args<'a>() -> TryFold<
                 directives: to_directives(&field.directives),
                 resolver: None,
                 default_value: field.default_value.clone(),
+                deprecated: field.deprecated.clone(),
+                internal: false,
             })
         },
     )
@@ -372,8 +434,12 @@ pub fn update_cache_resolvers<'a>() -> TryFold<
 > {
     TryFold::<(&ConfigModule, &Field, &config::Type, &str), FieldDefinition, BlueprintError>::new(
         move |(_config, field, typ, _name), mut b_field| {
-            if let Some(config::Cache { max_age }) = field.cache.as_ref().or(typ.cache.as_ref()) {
-                b_field.map_expr(|expression| Cache::wrap(*max_age, expression))
+            if let Some(config::Cache { max_age, stale_while_revalidate }) =
+                field.cache.as_ref().or(typ.cache.as_ref())
+            {
+                b_field.map_expr(|expression| {
+                    Cache::wrap(*max_age, *stale_while_revalidate, expression)
+                })
             }
 
             Valid::succeed(b_field)
@@ -381,15 +447,77 @@ pub fn update_cache_resolvers<'a>() -> TryFold<
     )
 }
 
-fn validate_field_type_exist(config: &Config, field: &Field) -> Valid<(), BlueprintError> {
-    let field_type = field.type_of.name();
-    if !scalar::Scalar::is_predefined(field_type) && !config.contains(field_type) {
-        Valid::fail(BlueprintError::UndeclaredTypeFound(field_type.clone()))
+fn validate_type_exist(config: &Config, type_name: &String) -> Valid<(), BlueprintError> {
+    if !scalar::Scalar::is_predefined(type_name) && !config.contains(type_name) {
+        Valid::fail(BlueprintError::UndeclaredTypeFound(type_name.clone()))
     } else {
         Valid::succeed(())
     }
 }
 
+/// Checks that the types of every field and argument, and the members of
+/// every union, are either declared in the config or predefined scalars.
+pub fn validate_type_references(config: &Config) -> Valid<(), BlueprintError> {
+    Valid::from_iter(config.types.iter(), |(type_name, type_of)| {
+        Valid::from_iter(
+            type_of
+                .fields
+                .iter()
+                .filter(|(_, field)| !field.is_omitted() || field.is_internal()),
+            |(field_name, field)| {
+                validate_type_exist(config, field.type_of.name())
+                    .and(
+                        Valid::from_iter(field.args.iter(), |(arg_name, arg)| {
+                            validate_type_exist(config, arg.type_of.name()).trace(arg_name)
+                        })
+                        .unit(),
+                    )
+                    .trace(field_name)
+            },
+        )
+        .trace(type_name)
+    })
+    .fuse(Valid::from_iter(
+        config.unions.iter(),
+        |(union_name, union_)| {
+            Valid::from_iter(union_.types.iter(), |member| {
+                validate_type_exist(config, member)
+            })
+            .trace(union_name)
+        },
+    ))
+    .unit()
+}
+
+/// Fails for the fields that merged documents, or a type and its extension,
+/// define with different resolvers, since the merge would silently resolve
+/// them with both.
+pub fn validate_resolver_conflicts(config_module: &ConfigModule) -> Valid<(), BlueprintError> {
+    let print = |resolvers: &ResolverSet, source: &Option<String>| {
+        let directives = resolvers
+            .0
+            .iter()
+            .filter_map(Resolver::to_directive)
+            .map(|directive| format!("`{}`", print_directive(&directive)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match source {
+            Some(source) => format!("{directives} in {source}"),
+            None => directives,
+        }
+    };
+
+    Valid::from_iter(config_module.conflicts(), |conflict| {
+        Valid::<(), _>::fail(BlueprintError::ConflictingResolvers(
+            print(&conflict.left, &conflict.left_source),
+            print(&conflict.right, &conflict.right_source),
+        ))
+        .trace(&conflict.field_name)
+        .trace(&conflict.type_name)
+    })
+    .unit()
+}
+
 fn to_fields(
     object_name: &str,
     type_of: &config::Type,
@@ -405,7 +533,7 @@ fn to_fields(
     } else {
         GraphQLOperationType::Query
     };
-    // Process fields that are not marked as `omit`
+    // Process fields that are not marked as `omit`, unless they are internal
 
     // collect the parent auth ids
     let parent_auth_ids = type_of.protected.as_ref().and_then(|p| p.id.as_ref());
@@ -430,17 +558,17 @@ fn to_fields(
         type_of
             .fields
             .iter()
-            .filter(|(_, field)| !field.is_omitted()),
+            .filter(|(_, field)| !field.is_omitted() || field.is_internal()),
         |(name, field)| {
-            let mut result =
-                validate_field_type_exist(config_module, field).and(to_field_definition(
-                    field,
-                    &operation_type,
-                    object_name,
-                    config_module,
-                    type_of,
-                    name,
-                ));
+            let mut result = to_field_definition(
+                field,
+                &operation_type,
+                object_name,
+                config_module,
+                type_of,
+                name,
+            )
+            .map(|definition| definition.internal(field.is_internal()));
 
             if fields_with_different_auth_ids.contains(name) || parent_auth_ids.is_none() {
                 // if the field has a different auth id than the parent type or parent has no
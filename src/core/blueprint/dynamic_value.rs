@@ -88,6 +88,32 @@ impl<A> DynamicValue<A> {
             _ => true,
         }
     }
+
+    /// Returns the names of the environment variables referenced by the value.
+    pub fn env_vars(&self) -> Vec<&str> {
+        match self {
+            DynamicValue::Mustache(m) => m.env_vars(),
+            DynamicValue::Object(obj) => obj.values().flat_map(|v| v.env_vars()).collect(),
+            DynamicValue::Array(arr) => arr.iter().flat_map(|v| v.env_vars()).collect(),
+            DynamicValue::Value(_) => vec![],
+        }
+    }
+
+    /// Returns the names of the fields of the parent value referenced by the
+    /// value.
+    pub fn parent_fields(&self) -> Vec<&str> {
+        match self {
+            DynamicValue::Mustache(m) => m
+                .expression_segments()
+                .into_iter()
+                .filter(|parts| parts.len() > 1 && parts[0] == "value")
+                .map(|parts| parts[1].as_str())
+                .collect(),
+            DynamicValue::Object(obj) => obj.values().flat_map(|v| v.parent_fields()).collect(),
+            DynamicValue::Array(arr) => arr.iter().flat_map(|v| v.parent_fields()).collect(),
+            DynamicValue::Value(_) => vec![],
+        }
+    }
 }
 
 impl TryFrom<&Value> for DynamicValue<ConstValue> {
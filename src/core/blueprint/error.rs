@@ -58,6 +58,27 @@ pub enum BlueprintError {
     #[error("batchKey requires either body or query parameters")]
     BatchKeyRequiresEitherBodyOrQuery,
 
+    #[error("batchKey on a GET request requires a query parameter with a dynamic value")]
+    BatchKeyRequiresDynamicQuery,
+
+    #[error("GET requests can't have a body, use a method like POST or PUT instead")]
+    GetRequestWithBody,
+
+    #[error("batchKey and batchKeys can't be used together")]
+    BatchKeyAndBatchKeysConflict,
+
+    #[error("body and bodyArgs can't be used together")]
+    BodyAndBodyArgsConflict,
+
+    #[error("batchKeys is only supported for GET requests")]
+    BatchKeysRequiresGet,
+
+    #[error("batchKeys requires one query parameter referencing the value for each key")]
+    BatchKeysQueryMismatch,
+
+    #[error("batchKeys paths must only differ in their last segment")]
+    BatchKeysPathMismatch,
+
     #[error("script is required")]
     ScriptIsRequired,
 
@@ -133,6 +154,12 @@ pub enum BlueprintError {
     #[error("var '{0}' is not set in the server config")]
     VarNotSetInServerConfig(String),
 
+    #[error("header '{0}' is not in the upstream allowedHeaders")]
+    HeaderNotAllowed(String),
+
+    #[error("secret '{0}' can only be used in headers")]
+    SecretOutsideHeaders(String),
+
     #[error("unknown template directive '{0}'")]
     UnknownTemplateDirective(String),
 
@@ -163,6 +190,24 @@ pub enum BlueprintError {
     #[error("Invalid CORS configuration: Cannot combine `Access-Control-Allow-Credentials: true` with `{0}: *`")]
     InvalidCORSConfiguration(String),
 
+    #[error("Invalid proxy url '{0}': {1}")]
+    InvalidProxyUrl(String, String),
+
+    #[error("`http2MaxConcurrentStreams` must be greater than 0")]
+    ZeroHttp2MaxConcurrentStreams,
+
+    #[error("Service '{0}' is not defined in the upstream services")]
+    UnknownService(String),
+
+    #[error("Either `url` or `service` must be set")]
+    UrlOrServiceRequired,
+
+    #[error("Cyclic dependency between the fields {0}")]
+    CyclicFieldDependency(String),
+
+    #[error("Field is defined more than once with different resolvers: {0} and {1}")]
+    ConflictingResolvers(String, String),
+
     #[error("{0}")]
     Cause(String),
 
@@ -119,16 +119,19 @@ impl TryFrom<&ConfigModule> for Blueprint {
     type Error = ValidationError<crate::core::blueprint::BlueprintError>;
 
     fn try_from(config_module: &ConfigModule) -> Result<Self, Self::Error> {
-        config_blueprint()
-            .try_fold(
-                // Apply required transformers to the configuration
-                &config_module
-                    .to_owned()
-                    .transform(Required)
-                    .to_result()
-                    .map_err(BlueprintError::from_validation_string)?,
-                Blueprint::default(),
-            )
+        // Apply required transformers to the configuration
+        let config_module = config_module
+            .to_owned()
+            .resolve_type_extensions()
+            .and_then(|config_module| config_module.transform(Required))
+            .to_result()
+            .map_err(BlueprintError::from_validation_string)?;
+
+        // Fail early on undeclared types, before they surface as errors from
+        // the resolvers or the schema that refer to them
+        validate_type_references(&config_module)
+            .fuse(validate_resolver_conflicts(&config_module))
+            .and_then(|_| config_blueprint().try_fold(&config_module, Blueprint::default()))
             .and_then(|blueprint| {
                 let schema_builder = SchemaBuilder::from(&blueprint);
                 match schema_builder.finish() {
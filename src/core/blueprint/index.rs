@@ -31,6 +31,11 @@ impl QueryField {
             QueryField::InputField(_) => None,
         }
     }
+    /// Checks if the field is hidden from the schema, see
+    /// [FieldDefinition::internal].
+    pub fn is_internal(&self) -> bool {
+        matches!(self, QueryField::Field((field, _)) if field.internal)
+    }
 }
 
 impl Index {
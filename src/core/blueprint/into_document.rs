@@ -10,6 +10,7 @@ use tailcall_valid::Validator;
 use super::blueprint;
 use super::directive::{to_const_directive, Directive};
 use crate::core::blueprint::{Blueprint, Definition};
+use crate::core::directive::DirectiveCodec;
 use crate::core::pos;
 
 fn to_directives(directives: &[Directive]) -> Vec<Positioned<ConstDirective>> {
@@ -44,16 +45,22 @@ fn to_args(args: &[blueprint::InputFieldDefinition]) -> Vec<Positioned<InputValu
 fn to_fields(fields: &[blueprint::FieldDefinition]) -> Vec<Positioned<FieldDefinition>> {
     fields
         .iter()
+        .filter(|field| !field.internal)
         .map(|field| {
             let of_type = &field.of_type;
             let arguments = to_args(&field.args);
 
+            let directives = to_directives(&field.directives)
+                .into_iter()
+                .chain(field.deprecated.as_ref().map(|d| pos(d.to_directive())))
+                .collect();
+
             pos(FieldDefinition {
                 description: None,
                 name: pos(Name::new(&field.name)),
                 arguments,
                 ty: pos(of_type.into()),
-                directives: to_directives(&field.directives),
+                directives,
             })
         })
         .collect()
@@ -55,7 +55,7 @@ fn to_type(def: &Definition) -> dynamic::Type {
     match def {
         Definition::Object(def) => {
             let mut object = dynamic::Object::new(def.name.clone());
-            for field in def.fields.iter() {
+            for field in def.fields.iter().filter(|field| !field.internal) {
                 let field = field.clone();
                 let type_ref = TypeRef::from(&field.of_type);
                 let field_name = &field.name.clone();
@@ -118,6 +118,9 @@ fn to_type(def: &Definition) -> dynamic::Type {
                 if let Some(description) = &field.description {
                     dyn_schema_field = dyn_schema_field.description(description);
                 }
+                if let Some(deprecated) = &field.deprecated {
+                    dyn_schema_field = dyn_schema_field.deprecation(deprecated.reason.as_deref());
+                }
                 for arg in field.args.iter() {
                     dyn_schema_field = dyn_schema_field.argument(set_default_value(
                         dynamic::InputValue::new(arg.name.clone(), TypeRef::from(&arg.of_type)),
@@ -137,7 +140,7 @@ fn to_type(def: &Definition) -> dynamic::Type {
         }
         Definition::Interface(def) => {
             let mut interface = dynamic::Interface::new(def.name.clone());
-            for field in def.fields.iter() {
+            for field in def.fields.iter().filter(|field| !field.internal) {
                 interface = interface.field(dynamic::InterfaceField::new(
                     field.name.clone(),
                     TypeRef::from(&field.of_type),
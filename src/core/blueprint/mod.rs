@@ -15,6 +15,7 @@ mod links;
 mod mustache;
 mod operators;
 mod schema;
+mod schema_diff;
 mod server;
 pub mod telemetry;
 mod template_validation;
@@ -33,6 +34,7 @@ pub use index::*;
 pub use links::*;
 pub use operators::*;
 pub use schema::*;
+pub use schema_diff::*;
 pub use server::*;
 pub use timeout::GlobalTimeout;
 pub use upstream::*;
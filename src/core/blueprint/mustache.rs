@@ -1,35 +1,90 @@
+use std::collections::BTreeSet;
+
 use tailcall_valid::{Valid, Validator};
 
 use super::{BlueprintError, FieldDefinition};
-use crate::core::config::{self, Config};
+use crate::core::config::{self, Config, ConfigModule};
 use crate::core::directive::DirectiveCodec;
 use crate::core::ir::model::{IO, IR};
+use crate::core::mustache::Segment;
 use crate::core::scalar;
 
 struct MustachePartsValidator<'a> {
     type_of: &'a config::Type,
     config: &'a Config,
     field: &'a FieldDefinition,
+    allowed_headers: BTreeSet<String>,
 }
 
 impl<'a> MustachePartsValidator<'a> {
-    fn new(type_of: &'a config::Type, config: &'a Config, field: &'a FieldDefinition) -> Self {
-        Self { type_of, config, field }
+    fn new(
+        type_of: &'a config::Type,
+        config: &'a Config,
+        field: &'a FieldDefinition,
+        allowed_headers: BTreeSet<String>,
+    ) -> Self {
+        Self { type_of, config, field, allowed_headers }
+    }
+
+    /// Only headers from the upstream allowlist are forwarded to the
+    /// resolvers, so referencing any other header is a misconfiguration.
+    fn validate_header(&self, parts: &[String]) -> Valid<(), BlueprintError> {
+        match parts {
+            [head, name, ..] if head == "headers" => {
+                if self
+                    .allowed_headers
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(name))
+                {
+                    Valid::succeed(())
+                } else {
+                    Valid::fail(BlueprintError::HeaderNotAllowed(name.to_string()))
+                }
+            }
+            _ => Valid::succeed(()),
+        }
+    }
+
+    /// Secrets are only rendered into headers, whose values are hidden from
+    /// logs unlike urls and bodies.
+    fn validate_secret(&self, parts: &[String]) -> Valid<(), BlueprintError> {
+        match parts {
+            [head, name, ..] if head == "secret" => {
+                Valid::fail(BlueprintError::SecretOutsideHeaders(name.to_string()))
+            }
+            _ => Valid::succeed(()),
+        }
+    }
+
+    /// Validates the template of a header value, which unlike other templates
+    /// can reference secrets.
+    fn validate_header_value(&self, parts: &[String]) -> Valid<(), BlueprintError> {
+        match parts.first() {
+            Some(head) if head == "secret" => Valid::succeed(()),
+            _ => self.validate(parts, true),
+        }
     }
 
     fn validate_type(&self, parts: &[String], is_query: bool) -> Result<(), BlueprintError> {
+        // a `*` maps over the items of a list, so the parts after it are the
+        // fields of the items
+        let parts = parts
+            .iter()
+            .map(String::as_str)
+            .filter(|part| *part != "*")
+            .collect::<Vec<_>>();
         let mut len = parts.len();
         let mut type_of = self.type_of;
-        for item in parts {
+        for item in parts.iter().copied() {
             let field = type_of.fields.get(item).ok_or_else(|| {
                 BlueprintError::NoValueFound(parts[0..parts.len() - len + 1].join("."))
             })?;
             let val_type = &field.type_of;
 
             if !is_query && val_type.is_nullable() {
-                return Err(BlueprintError::ValueIsNullableType(item.clone()));
+                return Err(BlueprintError::ValueIsNullableType(item.to_string()));
             } else if len == 1 && !scalar::Scalar::is_predefined(val_type.name()) {
-                return Err(BlueprintError::ValueIsNotOfScalarType(item.clone()));
+                return Err(BlueprintError::ValueIsNotOfScalarType(item.to_string()));
             } else if len == 1 {
                 break;
             }
@@ -61,6 +116,11 @@ impl<'a> MustachePartsValidator<'a> {
                 // all items on parts except the first one
                 let tail = &parts[1..];
 
+                // a wildcard selects a list, which only a query can take
+                if !is_query && tail.iter().any(|part| part == "*") {
+                    return Valid::fail(BlueprintError::CantUseListTypeHere(tail.join(".")));
+                }
+
                 if let Err(e) = self.validate_type(tail, is_query) {
                     return Valid::fail(e);
                 }
@@ -89,10 +149,12 @@ impl<'a> MustachePartsValidator<'a> {
                     return Valid::fail(BlueprintError::VarNotSetInServerConfig(tail.to_string()));
                 }
             }
-            "headers" | "env" => {
-                // "headers" and "env" refers to values known at runtime, which
-                // we can't validate here
+            "headers" => return self.validate_header(parts),
+            "env" => {
+                // "env" refers to values known at runtime, which we can't
+                // validate here
             }
+            "secret" => return self.validate_secret(parts),
             _ => {
                 return Valid::fail(BlueprintError::UnknownTemplateDirective(head.to_string()));
             }
@@ -101,14 +163,51 @@ impl<'a> MustachePartsValidator<'a> {
         Valid::succeed(())
     }
 
+    /// Validates an expression of the path that joins a list with a `join`
+    /// filter, which unlike the rest of the path can use a list argument or a
+    /// wildcard.
+    fn validate_joined(&self, parts: &[String]) -> Valid<(), BlueprintError> {
+        if let [head, tail @ ..] = parts {
+            if head == "value" && tail.iter().any(|part| part == "*") {
+                return match self.validate_type(tail, false) {
+                    Ok(()) => Valid::succeed(()),
+                    Err(e) => Valid::fail(e),
+                };
+            }
+        }
+
+        let list_arg = match parts {
+            [head, tail] if head == "args" => self
+                .field
+                .args
+                .iter()
+                .find(|arg| arg.name == *tail && arg.of_type.is_list()),
+            _ => None,
+        };
+
+        match list_arg {
+            Some(arg) if arg.default_value.is_none() && arg.of_type.is_nullable() => {
+                Valid::fail(BlueprintError::ArgumentIsNullableType(arg.name.clone()))
+            }
+            Some(_) => Valid::succeed(()),
+            None => self.validate(parts, false),
+        }
+    }
+
     fn validate_resolver(&self, resolver: &IR) -> Valid<(), BlueprintError> {
         match resolver {
             IR::Merge(resolvers) => {
                 Valid::from_iter(resolvers, |resolver| self.validate_resolver(resolver)).unit()
             }
+            IR::Rename(_, resolver) | IR::Values(resolver) => self.validate_resolver(resolver),
             IR::IO(IO::Http { req_template, .. }) => {
-                Valid::from_iter(req_template.root_url.expression_segments(), |parts| {
-                    self.validate(parts, false).trace("path")
+                let path = req_template.root_url.segments().iter().enumerate();
+                Valid::from_iter(path, |(i, segment)| match segment {
+                    Segment::Expression(parts) if req_template.path_joins.contains_key(&i) => {
+                        self.validate_joined(parts).trace("path")
+                    }
+                    Segment::Expression(parts) => self.validate(parts, false).trace("path"),
+                    Segment::Literal(_) => Valid::succeed(()),
                 })
                 .and(Valid::from_iter(req_template.query.clone(), |query| {
                     let mustache = &query.value;
@@ -117,13 +216,31 @@ impl<'a> MustachePartsValidator<'a> {
                         self.validate(parts, true).trace("query")
                     })
                 }))
+                .and(Valid::from_iter(
+                    req_template.headers.iter(),
+                    |(_, mustache)| {
+                        Valid::from_iter(mustache.expression_segments(), |parts| {
+                            self.validate_header(parts).trace("headers")
+                        })
+                    },
+                ))
+                .and(Valid::from_iter(
+                    req_template.body_path.iter(),
+                    |mustache| {
+                        Valid::from_iter(mustache.expression_segments(), |parts| {
+                            self.validate_header(parts)
+                                .and(self.validate_secret(parts))
+                                .trace("body")
+                        })
+                    },
+                ))
                 .unit()
                 .trace(config::Http::trace_name().as_str())
             }
             IR::IO(IO::GraphQL { req_template, .. }) => {
                 Valid::from_iter(req_template.headers.clone(), |(_, mustache)| {
                     Valid::from_iter(mustache.expression_segments(), |parts| {
-                        self.validate(parts, true).trace("headers")
+                        self.validate_header_value(parts).trace("headers")
                     })
                 })
                 .and_then(|_| {
@@ -147,7 +264,7 @@ impl<'a> MustachePartsValidator<'a> {
                 .and(
                     Valid::from_iter(req_template.headers.clone(), |(_, mustache)| {
                         Valid::from_iter(mustache.expression_segments(), |parts| {
-                            self.validate(parts, true).trace("headers")
+                            self.validate_header_value(parts).trace("headers")
                         })
                     })
                     .unit(),
@@ -179,7 +296,7 @@ impl FieldDefinition {
     pub fn validate_field(
         &self,
         type_of: &config::Type,
-        config: &Config,
+        config_module: &ConfigModule,
     ) -> Valid<(), BlueprintError> {
         // XXX we could use `Mustache`'s `render` method with a mock
         // struct implementing the `PathString` trait encapsulating `validation_map`
@@ -187,7 +304,12 @@ impl FieldDefinition {
         // type if it doesn't exist, so we wouldn't be able to get enough
         // context from that method alone
         // So we must duplicate some of that logic here :(
-        let parts_validator = MustachePartsValidator::new(type_of, config, self);
+        let parts_validator = MustachePartsValidator::new(
+            type_of,
+            config_module,
+            self,
+            config_module.allowed_headers(),
+        );
 
         match &self.resolver {
             Some(resolver) => parts_validator.validate_resolver(resolver),
@@ -198,6 +320,8 @@ impl FieldDefinition {
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeSet;
+
     use tailcall_valid::Validator;
 
     use super::MustachePartsValidator;
@@ -236,6 +360,8 @@ mod test {
             directives: vec![],
             description: None,
             default_value: None,
+            deprecated: None,
+            internal: false,
         };
 
         (config, fld)
@@ -245,8 +371,12 @@ mod test {
     fn test_allow_list_arguments_for_query_type() {
         let (config, field_def) = initialize_test_config_and_field();
 
-        let parts_validator =
-            MustachePartsValidator::new(config.types.get("T1").unwrap(), &config, &field_def);
+        let parts_validator = MustachePartsValidator::new(
+            config.types.get("T1").unwrap(),
+            &config,
+            &field_def,
+            Default::default(),
+        );
         let validation_result =
             parts_validator.validate(&["args".to_string(), "q".to_string()], true);
 
@@ -257,11 +387,115 @@ mod test {
     fn test_should_not_allow_list_arguments_for_path_variable() {
         let (config, field_def) = initialize_test_config_and_field();
 
-        let parts_validator =
-            MustachePartsValidator::new(config.types.get("T1").unwrap(), &config, &field_def);
+        let parts_validator = MustachePartsValidator::new(
+            config.types.get("T1").unwrap(),
+            &config,
+            &field_def,
+            Default::default(),
+        );
         let validation_result =
             parts_validator.validate(&["args".to_string(), "q".to_string()], false);
 
         assert!(validation_result.to_result().is_err())
     }
+
+    #[test]
+    fn test_headers_must_be_allowed() {
+        let (config, field_def) = initialize_test_config_and_field();
+        let allowed_headers = BTreeSet::from(["X-Api-Key".to_string()]);
+
+        let parts_validator = MustachePartsValidator::new(
+            config.types.get("T1").unwrap(),
+            &config,
+            &field_def,
+            allowed_headers,
+        );
+
+        let allowed =
+            parts_validator.validate(&["headers".to_string(), "x-api-key".to_string()], true);
+        assert!(allowed.is_succeed());
+
+        let not_allowed =
+            parts_validator.validate(&["headers".to_string(), "cookie".to_string()], true);
+        assert!(not_allowed.to_result().is_err());
+    }
+
+    #[test]
+    fn test_secrets_only_in_headers() {
+        let (config, field_def) = initialize_test_config_and_field();
+
+        let parts_validator = MustachePartsValidator::new(
+            config.types.get("T1").unwrap(),
+            &config,
+            &field_def,
+            Default::default(),
+        );
+        let secret = ["secret".to_string(), "API_KEY".to_string()];
+
+        let header = parts_validator.validate_header_value(&secret);
+        assert!(header.is_succeed());
+
+        let query = parts_validator.validate(&secret, true);
+        assert!(query.to_result().is_err());
+
+        let path = parts_validator.validate(&secret, false);
+        assert!(path.to_result().is_err());
+    }
+
+    #[test]
+    fn test_allow_list_arguments_for_joined_path() {
+        let (config, mut field_def) = initialize_test_config_and_field();
+        let q = ["args".to_string(), "q".to_string()];
+
+        let parts_validator = MustachePartsValidator::new(
+            config.types.get("T1").unwrap(),
+            &config,
+            &field_def,
+            Default::default(),
+        );
+        // the nullable list still can't be rendered into the path
+        assert!(parts_validator.validate_joined(&q).to_result().is_err());
+
+        field_def.args[0].of_type = field_def.args[0].of_type.clone().into_required();
+        let parts_validator = MustachePartsValidator::new(
+            config.types.get("T1").unwrap(),
+            &config,
+            &field_def,
+            Default::default(),
+        );
+        assert!(parts_validator.validate_joined(&q).is_succeed());
+        assert!(parts_validator.validate(&q, false).to_result().is_err());
+    }
+
+    #[test]
+    fn test_wildcard_value_path() {
+        let (mut config, field_def) = initialize_test_config_and_field();
+        config.types.get_mut("T1").unwrap().fields.insert(
+            "items".to_owned(),
+            Field {
+                type_of: Type::from("T2".to_owned()).into_list(),
+                ..Default::default()
+            },
+        );
+        let mut t2_type = config::Type::default();
+        t2_type.fields.insert(
+            "id".to_owned(),
+            Field { type_of: Type::from("Int".to_owned()), ..Default::default() },
+        );
+        config.types.insert("T2".to_string(), t2_type);
+
+        let parts_validator = MustachePartsValidator::new(
+            config.types.get("T1").unwrap(),
+            &config,
+            &field_def,
+            Default::default(),
+        );
+        let parts = ["value", "items", "*", "id"].map(String::from);
+        assert!(parts_validator.validate(&parts, true).is_succeed());
+        // the selected list can't be rendered into the path
+        assert!(parts_validator.validate(&parts, false).to_result().is_err());
+
+        let parts = ["value", "items", "*", "name"].map(String::from);
+        assert!(parts_validator.validate(&parts, true).to_result().is_err());
+    }
 }
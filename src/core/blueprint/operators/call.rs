@@ -12,7 +12,9 @@ pub fn compile_call(
     operation_type: &GraphQLOperationType,
     object_name: &str,
 ) -> Valid<IR, BlueprintError> {
-    Valid::from_iter(call.steps.iter(), |step| {
+    let is_composed = call.steps.len() > 1;
+
+    Valid::from_iter(call.steps.iter().enumerate(), |(index, step)| {
         get_field_and_field_name(step, config_module).and_then(|(field, field_name, type_of)| {
             let args = step.args.iter();
 
@@ -68,6 +70,11 @@ pub fn compile_call(
                     b_field.map_expr(|expr| args_expr.clone().pipe(expr));
                 }
 
+                // point the errors of a composed call to the step that failed.
+                if is_composed {
+                    b_field.map_expr(|expr| IR::Step(index, Box::new(expr)));
+                }
+
                 b_field
             })
         })
@@ -129,3 +136,124 @@ fn get_field_and_field_name<'a>(
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_graphql_value::ConstValue;
+    use hyper::body::Bytes;
+    use serde_json::json;
+    use tailcall_valid::Validator;
+
+    use crate::core::blueprint::{Blueprint, Definition};
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::http::{RequestContext, Response};
+    use crate::core::ir::model::IR;
+    use crate::core::ir::{EmptyResolverContext, Error, EvalContext};
+    use crate::core::HttpIO;
+
+    const CONFIG: &str = r#"
+        schema { query: Query }
+
+        type Query {
+          user(id: Int!): User @http(url: "http://localhost/users/{{.args.id}}")
+          company(id: Int!): Company @http(url: "http://localhost/companies/{{.args.id}}")
+          userCompany(id: Int!): Company
+            @call(
+              steps: [
+                {query: "user", args: {id: "{{.args.id}}"}}
+                {query: "company", args: {id: "{{.args.companyId}}"}}
+              ]
+            )
+        }
+
+        type User {
+          id: Int
+          companyId: Int
+        }
+
+        type Company {
+          id: Int
+          name: String
+        }
+    "#;
+
+    /// Serves users and companies, recording the paths it was called with.
+    #[derive(Default)]
+    struct DirectoryHttp(Mutex<Vec<String>>);
+
+    #[async_trait::async_trait]
+    impl HttpIO for DirectoryHttp {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let path = request.url().path().to_string();
+            self.0.lock().unwrap().push(path.clone());
+
+            let response = match path.as_str() {
+                "/users/1" => http::Response::builder().body(r#"{"id": 1, "companyId": 7}"#),
+                "/users/2" => http::Response::builder().body(r#"{"id": 2, "companyId": 8}"#),
+                "/companies/7" => {
+                    http::Response::builder().body(r#"{"id": 7, "name": "Tailcall"}"#)
+                }
+                _ => http::Response::builder().status(404).body("{}"),
+            }?;
+
+            Response::from_reqwest(reqwest::Response::from(response).error_for_status()?).await
+        }
+    }
+
+    fn user_company() -> IR {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+
+        blueprint
+            .definitions
+            .into_iter()
+            .find_map(|def| match def {
+                Definition::Object(def) if def.name == "Query" => def
+                    .fields
+                    .into_iter()
+                    .find(|field| field.name == "userCompany")
+                    .and_then(|field| field.resolver),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    async fn eval_user_company(id: i32) -> (Result<ConstValue, Error>, Vec<String>) {
+        let http = Arc::new(DirectoryHttp::default());
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = http.clone();
+        let req_ctx = RequestContext::new(runtime);
+        let res_ctx = EmptyResolverContext {};
+        let args = ConstValue::from_json(json!({ "id": id })).unwrap();
+
+        let actual = user_company()
+            .eval(&mut EvalContext::new(&req_ctx, &res_ctx).with_args(args))
+            .await;
+        let paths = http.0.lock().unwrap().clone();
+
+        (actual, paths)
+    }
+
+    #[tokio::test]
+    async fn test_steps_use_previous_output() {
+        let (actual, paths) = eval_user_company(1).await;
+
+        let expected = ConstValue::from_json(json!({ "id": 7, "name": "Tailcall" })).unwrap();
+        assert_eq!(actual.unwrap(), expected);
+        assert_eq!(paths, vec!["/users/1", "/companies/7"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_step_index() {
+        let (actual, paths) = eval_user_company(2).await;
+
+        let actual = actual.unwrap_err();
+        assert!(matches!(actual, Error::Step { index: 1, .. }));
+        assert!(actual
+            .to_string()
+            .starts_with("@call failed at steps[1]: IOException"));
+        assert_eq!(paths, vec!["/users/2", "/companies/8"]);
+    }
+}
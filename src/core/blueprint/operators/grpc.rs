@@ -5,19 +5,19 @@ use prost_reflect::FieldDescriptor;
 use tailcall_valid::{Valid, ValidationError, Validator};
 
 use super::apply_select;
-use crate::core::blueprint::BlueprintError;
+use crate::core::blueprint::{to_service_url, BlueprintError};
 use crate::core::config::group_by::GroupBy;
 use crate::core::config::{Config, ConfigModule, Field, GraphQLOperationType, Grpc};
 use crate::core::grpc::protobuf::{ProtobufOperation, ProtobufSet};
-use crate::core::grpc::request_template::RequestTemplate;
+use crate::core::grpc::request_template::{MessageSchemas, RequestTemplate};
 use crate::core::helpers;
 use crate::core::ir::model::{IO, IR};
 use crate::core::json::JsonSchema;
 use crate::core::mustache::Mustache;
 use crate::core::worker_hooks::WorkerHooks;
 
-fn to_url(grpc: &Grpc, method: &GrpcMethod) -> Valid<Mustache, String> {
-    Valid::succeed(grpc.url.as_str()).and_then(|base_url| {
+fn to_url(url: &str, method: &GrpcMethod) -> Valid<Mustache, String> {
+    Valid::succeed(url).and_then(|base_url| {
         let mut base_url = base_url.trim_end_matches('/').to_owned();
         base_url.push('/');
         base_url.push_str(format!("{}.{}", method.package, method.service).as_str());
@@ -179,6 +179,24 @@ impl TryFrom<&str> for GrpcMethod {
     }
 }
 
+/// Creates the schemas of the input and output messages of the operation when
+/// the `@grpc` directive asks to validate them.
+fn to_message_schemas(
+    grpc: &Grpc,
+    operation: &ProtobufOperation,
+) -> Valid<Option<MessageSchemas>, BlueprintError> {
+    if !grpc.validate {
+        return Valid::succeed(None);
+    }
+
+    match JsonSchema::try_from(&operation.input_type).and_then(|input| {
+        Ok(MessageSchemas { input, output: JsonSchema::try_from(&operation.output_type)? })
+    }) {
+        Ok(schemas) => Valid::succeed(Some(schemas)),
+        Err(e) => Valid::from_validation_err(BlueprintError::from_validation_string(e)),
+    }
+}
+
 pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, BlueprintError> {
     let config_module = inputs.config_module;
     let operation_type = inputs.operation_type;
@@ -187,8 +205,15 @@ pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, BlueprintError> {
     let validate_with_schema = inputs.validate_with_schema;
     let dedupe = grpc.dedupe.unwrap_or_default();
 
-    Valid::from(GrpcMethod::try_from(grpc.method.as_str()))
-        .and_then(|method| {
+    Valid::<(), BlueprintError>::fail(BlueprintError::UrlOrServiceRequired)
+        .when(|| grpc.url.is_empty() && grpc.service.is_none())
+        .and(Valid::from(GrpcMethod::try_from(grpc.method.as_str())))
+        .zip(to_service_url(
+            &config_module.upstream,
+            grpc.service.as_deref(),
+            &grpc.url,
+        ))
+        .and_then(|(method, url)| {
             let file_descriptor_set = config_module.extensions().get_file_descriptor_set();
 
             if file_descriptor_set.file.is_empty() {
@@ -196,7 +221,7 @@ pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, BlueprintError> {
             }
 
             match to_operation(&method, file_descriptor_set)
-                .fuse(to_url(grpc, &method))
+                .fuse(to_url(&url, &method))
                 .fuse(helpers::headers::to_mustache_headers(&grpc.headers))
                 .fuse(helpers::body::to_body(grpc.body.as_ref()))
                 .to_result()
@@ -216,15 +241,19 @@ pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, BlueprintError> {
             } else {
                 Valid::succeed(())
             };
-            validation.map(|_| (url, headers, operation, body))
+            validation
+                .and(to_message_schemas(grpc, &operation))
+                .map(|schemas| (url, headers, operation, body, schemas))
         })
-        .map(|(url, headers, operation, body)| {
+        .map(|(url, headers, operation, body, schemas)| {
             let req_template = RequestTemplate {
                 url,
                 headers,
                 operation,
                 body,
                 operation_type: operation_type.clone(),
+                schemas,
+                protocol: grpc.protocol.clone(),
             };
             let on_response = grpc.on_response_body.clone();
             let hook = WorkerHooks::try_new(None, on_response).ok();
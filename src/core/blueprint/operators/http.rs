@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use tailcall_valid::{Valid, Validator};
 use template_validation::validate_argument;
 
@@ -7,6 +9,7 @@ use crate::core::config::Field;
 use crate::core::endpoint::Endpoint;
 use crate::core::http::{Method, RequestTemplate};
 use crate::core::ir::model::{IO, IR};
+use crate::core::ir::Rename;
 use crate::core::worker_hooks::WorkerHooks;
 use crate::core::{config, helpers, Mustache};
 
@@ -25,7 +28,7 @@ pub fn compile_http(
     Valid::<(), BlueprintError>::fail(BlueprintError::IncorrectBatchingUsage)
         .when(|| {
             (config_module.upstream.get_delay() < 1 || config_module.upstream.get_max_size() < 1)
-                && !http.batch_key.is_empty()
+                && (!http.batch_key.is_empty() || !http.batch_keys.is_empty())
         })
         .and(
             Valid::from_iter(http.query.iter(), |query| {
@@ -40,7 +43,49 @@ pub fn compile_http(
                     !http.batch_key.is_empty() && (http.body.is_none() && http.query.is_empty())
                 }),
         )
-        .and(Valid::succeed(http.url.as_str()))
+        .and(
+            Valid::<(), BlueprintError>::fail(BlueprintError::GetRequestWithBody)
+                .when(|| http.method == Method::GET && http.body.is_some())
+                .trace("body"),
+        )
+        .and(
+            Valid::<(), BlueprintError>::fail(BlueprintError::BatchKeyRequiresDynamicQuery)
+                .when(|| {
+                    !http.batch_key.is_empty()
+                        && http.method == Method::GET
+                        && !http.query.is_empty()
+                        && http
+                            .query
+                            .iter()
+                            .all(|q| Mustache::parse(&q.value).is_const())
+                })
+                .trace("query"),
+        )
+        .and(validate_batch_keys(http).trace("batchKeys"))
+        .and(validate_args(&http.body_args, field).trace("bodyArgs"))
+        .and(validate_args(&http.query_args, field).trace("queryArgs"))
+        .and(
+            Valid::<(), BlueprintError>::fail(BlueprintError::BodyAndBodyArgsConflict)
+                .when(|| http.body.is_some() && !http.body_args.is_empty())
+                .trace("bodyArgs"),
+        )
+        .and(
+            Valid::<(), BlueprintError>::fail(BlueprintError::GetRequestWithBody)
+                .when(|| http.method == Method::GET && !http.body_args.is_empty())
+                .trace("bodyArgs"),
+        )
+        .and(
+            Valid::from_iter(http.skip_if.iter(), |skip_if| {
+                validate_argument(config_module, Mustache::parse(skip_if.as_str()), field)
+            })
+            .unit()
+            .trace("skipIf"),
+        )
+        .and(to_service_url(
+            &config_module.upstream,
+            http.service.as_deref(),
+            &http.url,
+        ))
         .zip(mustache_headers)
         .and_then(|(base_url, headers)| {
             let query = http
@@ -54,6 +99,11 @@ pub fn compile_http(
                         key_value.skip_empty.unwrap_or_default(),
                     )
                 })
+                .chain(
+                    http.query_args
+                        .iter()
+                        .map(|name| (name.clone(), format!("{{{{.args.{}}}}}", name), true)),
+                )
                 .collect();
 
             match RequestTemplate::try_from(
@@ -63,8 +113,16 @@ pub fn compile_http(
                     .body(http.body.clone())
                     .encoding(http.encoding.clone()),
             )
-            .map(|req_tmpl| req_tmpl.headers(headers))
-            {
+            .map(|req_tmpl| {
+                // The field settings take precedence over the upstream ones, the
+                // upstream timeout is already applied by the client.
+                req_tmpl
+                    .headers(headers)
+                    .body_args(http.body_args.clone())
+                    .timeout(http.timeout.map(Duration::from_secs))
+                    .retry(http.retry.unwrap_or(config_module.upstream.get_retry()))
+                    .idempotency_key(http.idempotency_key.as_deref().map(Mustache::parse))
+            }) {
                 Ok(data) => Valid::succeed(data),
                 Err(e) => Valid::fail(BlueprintError::Error(e)),
             }
@@ -93,15 +151,32 @@ pub fn compile_http(
                 .or(config_module.upstream.on_request.clone());
             let on_response_body = http.on_response_body.clone();
             let hook = WorkerHooks::try_new(on_request, on_response_body).ok();
+            let skip_if = http.skip_if.as_deref().map(Mustache::parse);
+            let ok_status = http.ok_status.clone();
+
+            let io = if !http.batch_keys.is_empty() {
+                let group_by = GroupBy::composite(
+                    http.batch_keys
+                        .iter()
+                        .zip(value_query_keys(http))
+                        .map(|(path, key)| GroupBy::new(path.clone(), Some(key)))
+                        .collect(),
+                );
 
-            let io = if !http.batch_key.is_empty() {
+                IR::IO(IO::Http {
+                    req_template,
+                    group_by,
+                    dl_id: None,
+                    is_list,
+                    dedupe,
+                    hook,
+                    skip_if,
+                    ok_status,
+                })
+            } else if !http.batch_key.is_empty() {
                 // Find a query parameter that contains a reference to the {{.value}} key
                 let key = if http.method == Method::GET {
-                    http.query.iter().find_map(|q| {
-                        Mustache::parse(&q.value)
-                            .expression_contains("value")
-                            .then(|| q.key.clone())
-                    })
+                    value_query_keys(http).next()
                 } else {
                     None
                 };
@@ -113,6 +188,8 @@ pub fn compile_http(
                     is_list,
                     dedupe,
                     hook,
+                    skip_if,
+                    ok_status,
                 })
             } else {
                 IR::IO(IO::Http {
@@ -122,13 +199,76 @@ pub fn compile_http(
                     is_list,
                     dedupe,
                     hook,
+                    skip_if,
+                    ok_status,
                 })
             };
+            let io = match http.list_from {
+                Some(config::ListFrom::Values) => IR::Values(Box::new(io)),
+                None => io,
+            };
+            let io = if http.rename.is_empty() {
+                io
+            } else {
+                let rename =
+                    Rename::new(http.rename.clone(), http.rename_nested.unwrap_or_default());
+                IR::Rename(rename, Box::new(io))
+            };
             (io, &http.select)
         })
         .and_then(apply_select)
 }
 
+/// Returns the query parameters that reference the `{{.value}}`, in order.
+fn value_query_keys(http: &config::Http) -> impl Iterator<Item = String> + '_ {
+    http.query.iter().filter_map(|q| {
+        Mustache::parse(&q.value)
+            .expression_contains("value")
+            .then(|| q.key.clone())
+    })
+}
+
+fn validate_batch_keys(http: &config::Http) -> Valid<(), BlueprintError> {
+    if http.batch_keys.is_empty() {
+        return Valid::succeed(());
+    }
+
+    let prefixes = http
+        .batch_keys
+        .iter()
+        .map(|path| path.split_last().map(|(_, prefix)| prefix))
+        .collect::<Vec<_>>();
+
+    Valid::<(), BlueprintError>::fail(BlueprintError::BatchKeyAndBatchKeysConflict)
+        .when(|| !http.batch_key.is_empty())
+        .and(
+            Valid::<(), BlueprintError>::fail(BlueprintError::BatchKeysRequiresGet)
+                .when(|| http.method != Method::GET || http.body.is_some()),
+        )
+        .and(
+            Valid::<(), BlueprintError>::fail(BlueprintError::BatchKeysQueryMismatch)
+                .when(|| value_query_keys(http).count() != http.batch_keys.len()),
+        )
+        .and(
+            Valid::<(), BlueprintError>::fail(BlueprintError::BatchKeysPathMismatch).when(|| {
+                prefixes.contains(&None) || prefixes.windows(2).any(|pair| pair[0] != pair[1])
+            }),
+        )
+}
+
+/// Checks that every name of `bodyArgs` or `queryArgs` is an argument of the
+/// field.
+fn validate_args(names: &[String], field: &Field) -> Valid<(), BlueprintError> {
+    Valid::from_iter(names.iter(), |name| {
+        if field.args.contains_key(name) {
+            Valid::succeed(())
+        } else {
+            Valid::fail(BlueprintError::ArgumentNotFound(name.clone()))
+        }
+    })
+    .unit()
+}
+
 /// Count the number of dynamic expressions in the JSON value.
 fn count_dynamic_paths(json: &serde_json::Value) -> usize {
     let mut count = 0;
@@ -155,9 +295,123 @@ fn count_dynamic_paths(json: &serde_json::Value) -> usize {
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
     use serde_json::json;
 
     use super::*;
+    use crate::core::config::{Arg, Batch, Config, ConfigModule, URLQuery, Upstream};
+
+    fn compile(http: config::Http) -> Valid<(), BlueprintError> {
+        let config = Config {
+            upstream: Upstream {
+                batch: Some(Batch { delay: 1, ..Default::default() }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let field = Field::default();
+
+        compile_http(&ConfigModule::from(config), &http, &field).unit()
+    }
+
+    fn compile_template(upstream: Upstream, http: config::Http) -> RequestTemplate {
+        let config = Config { upstream, ..Default::default() };
+        let ir = compile_http(&ConfigModule::from(config), &http, &Field::default())
+            .to_result()
+            .unwrap();
+
+        match ir {
+            IR::IO(IO::Http { req_template, .. }) => req_template,
+            ir => panic!("Expected an http IO, got {ir:?}"),
+        }
+    }
+
+    /// A field with the `title`, `userId` and `draft` arguments, to route
+    /// between the body and the query.
+    fn post_field() -> Field {
+        let args = ["title", "userId", "draft"]
+            .into_iter()
+            .map(|name| (name.to_string(), Arg::default()))
+            .collect();
+        Field { args, ..Default::default() }
+    }
+
+    fn http(timeout: Option<u64>, retry: Option<usize>) -> config::Http {
+        config::Http {
+            url: "http://localhost/users".to_string(),
+            timeout,
+            retry,
+            ..Default::default()
+        }
+    }
+
+    fn upstream() -> Upstream {
+        Upstream { timeout: Some(10), retry: Some(3), ..Default::default() }
+    }
+
+    #[test]
+    fn test_timeout_and_retry_inherit_upstream() {
+        let template = compile_template(upstream(), http(None, None));
+
+        assert_eq!(template.timeout, None);
+        assert_eq!(template.retry, 3);
+    }
+
+    #[test]
+    fn test_timeout_and_retry_override_upstream() {
+        let template = compile_template(upstream(), http(Some(2), Some(1)));
+
+        assert_eq!(template.timeout, Some(Duration::from_secs(2)));
+        assert_eq!(template.retry, 1);
+    }
+
+    #[test]
+    fn test_timeout_and_retry_disabled() {
+        let template = compile_template(upstream(), http(Some(0), Some(0)));
+
+        assert_eq!(template.timeout, Some(Duration::ZERO));
+        assert_eq!(template.retry, 0);
+    }
+
+    #[test]
+    fn test_services() {
+        let upstream = Upstream {
+            services: BTreeMap::from([
+                ("users".to_string(), "http://users.local/".to_string()),
+                ("orders".to_string(), "http://orders.local/v1".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let http = |service: &str, url: &str| config::Http {
+            url: url.to_string(),
+            service: Some(service.to_string()),
+            ..Default::default()
+        };
+
+        let users = compile_template(upstream.clone(), http("users", "/users"));
+        let orders = compile_template(upstream, http("orders", "orders/{{.args.id}}"));
+
+        assert_eq!(users.endpoint.path, "http://users.local/users");
+        assert_eq!(
+            orders.endpoint.path,
+            "http://orders.local/v1/orders/{{.args.id}}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_service() {
+        let http = config::Http {
+            url: "/users".to_string(),
+            service: Some("users".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compile(http),
+            Valid::fail(BlueprintError::UnknownService("users".to_string())).trace("service")
+        );
+    }
 
     #[test]
     fn test_extract_expression_keys_from_nested_objects() {
@@ -181,4 +435,94 @@ mod test {
         let keys = count_dynamic_paths(&json);
         assert_eq!(keys, 1);
     }
+
+    #[test]
+    fn test_get_with_body() {
+        let http = config::Http {
+            url: "http://localhost/users".to_string(),
+            body: Some(json!({"name": "{{.args.name}}"})),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compile(http),
+            Valid::fail(BlueprintError::GetRequestWithBody).trace("body")
+        );
+    }
+
+    #[test]
+    fn test_batch_key_without_placeholder() {
+        let http = config::Http {
+            url: "http://localhost/users".to_string(),
+            query: vec![URLQuery {
+                key: "id".to_string(),
+                value: "1".to_string(),
+                ..Default::default()
+            }],
+            batch_key: vec!["id".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compile(http),
+            Valid::fail(BlueprintError::BatchKeyRequiresDynamicQuery).trace("query")
+        );
+    }
+
+    #[test]
+    fn test_body_and_query_args() {
+        let http = config::Http {
+            url: "http://localhost/posts".to_string(),
+            method: Method::POST,
+            body_args: vec!["title".to_string(), "userId".to_string()],
+            query_args: vec!["draft".to_string()],
+            ..Default::default()
+        };
+        let ir = compile_http(&ConfigModule::default(), &http, &post_field())
+            .to_result()
+            .unwrap();
+        let IR::IO(IO::Http { req_template, .. }) = ir else {
+            panic!("Expected an http IO, got {ir:?}");
+        };
+
+        assert_eq!(req_template.body_args, vec!["title", "userId"]);
+        let query = req_template
+            .query
+            .iter()
+            .map(|query| (query.key.as_str(), query.value.clone(), query.skip_empty))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            query,
+            vec![("draft", Mustache::parse("{{.args.draft}}"), true)]
+        );
+    }
+
+    #[test]
+    fn test_unknown_query_arg() {
+        let http = config::Http {
+            url: "http://localhost/posts".to_string(),
+            query_args: vec!["published".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compile_http(&ConfigModule::default(), &http, &post_field()).unit(),
+            Valid::fail(BlueprintError::ArgumentNotFound("published".to_string()))
+                .trace("queryArgs")
+        );
+    }
+
+    #[test]
+    fn test_get_with_body_args() {
+        let http = config::Http {
+            url: "http://localhost/posts".to_string(),
+            body_args: vec!["title".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compile_http(&ConfigModule::default(), &http, &post_field()).unit(),
+            Valid::fail(BlueprintError::GetRequestWithBody).trace("bodyArgs")
+        );
+    }
 }
@@ -0,0 +1,416 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use super::{Blueprint, Definition, FieldDefinition, InputFieldDefinition};
+use crate::core::Type;
+
+/// How a change of the schema affects the clients of the previous schema.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Criticality {
+    /// Queries that were valid fail, or get responses they can't read.
+    Breaking,
+    /// Queries keep working, but may behave differently, e.g. an enum value
+    /// that clients don't handle can be returned.
+    Dangerous,
+    /// Clients aren't affected.
+    Safe,
+}
+
+impl Display for Criticality {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Criticality::Breaking => "breaking",
+            Criticality::Dangerous => "dangerous",
+            Criticality::Safe => "safe",
+        })
+    }
+}
+
+/// A change between two schemas, at the `path` of the type, field or argument
+/// it's about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaChange {
+    pub criticality: Criticality,
+    pub path: String,
+    pub message: String,
+}
+
+impl SchemaChange {
+    fn new(criticality: Criticality, path: impl ToString, message: impl ToString) -> Self {
+        Self {
+            criticality,
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Lists the changes from the schema of `old` to the schema of `new`, with
+/// their criticality following the rules of `graphql-js`'s
+/// `findBreakingChanges` and `findDangerousChanges`. The changes are ordered
+/// by the name of the type they are about.
+pub fn diff_schema(old: &Blueprint, new: &Blueprint) -> Vec<SchemaChange> {
+    let old_types = definitions(old);
+    let new_types = definitions(new);
+    let mut changes = Vec::new();
+
+    let names = old_types
+        .keys()
+        .chain(new_types.keys())
+        .collect::<BTreeSet<_>>();
+    for name in names {
+        match (old_types.get(name), new_types.get(name)) {
+            (Some(_), None) => changes.push(SchemaChange::new(
+                Criticality::Breaking,
+                name,
+                "Type was removed",
+            )),
+            (None, Some(_)) => {
+                changes.push(SchemaChange::new(Criticality::Safe, name, "Type was added"))
+            }
+            (Some(old), Some(new)) => diff_definition(old, new, &mut changes),
+            (None, None) => {}
+        }
+    }
+
+    changes
+}
+
+fn definitions(blueprint: &Blueprint) -> BTreeMap<&str, &Definition> {
+    blueprint
+        .definitions
+        .iter()
+        .map(|definition| (definition.name(), definition))
+        .collect()
+}
+
+fn kind(definition: &Definition) -> &'static str {
+    match definition {
+        Definition::Interface(_) => "interface",
+        Definition::Object(_) => "object",
+        Definition::InputObject(_) => "input object",
+        Definition::Scalar(_) => "scalar",
+        Definition::Enum(_) => "enum",
+        Definition::Union(_) => "union",
+    }
+}
+
+fn diff_definition(old: &Definition, new: &Definition, changes: &mut Vec<SchemaChange>) {
+    let name = old.name();
+    match (old, new) {
+        (Definition::Object(old), Definition::Object(new)) => {
+            diff_fields(name, &old.fields, &new.fields, changes);
+            diff_members(name, "Interface", &old.implements, &new.implements, changes);
+        }
+        (Definition::Interface(old), Definition::Interface(new)) => {
+            diff_fields(name, &old.fields, &new.fields, changes);
+            diff_members(name, "Interface", &old.implements, &new.implements, changes);
+        }
+        (Definition::InputObject(old), Definition::InputObject(new)) => {
+            diff_input_fields(name, "Input field", &old.fields, &new.fields, changes)
+        }
+        (Definition::Enum(old), Definition::Enum(new)) => {
+            let old_values: BTreeSet<_> = old.enum_values.iter().map(|value| &value.name).collect();
+            let new_values: BTreeSet<_> = new.enum_values.iter().map(|value| &value.name).collect();
+            diff_members(name, "Enum value", &old_values, &new_values, changes);
+        }
+        (Definition::Union(old), Definition::Union(new)) => {
+            diff_members(name, "Member", &old.types, &new.types, changes)
+        }
+        (Definition::Scalar(_), Definition::Scalar(_)) => {}
+        (old, new) => changes.push(SchemaChange::new(
+            Criticality::Breaking,
+            name,
+            format!("Type changed from {} to {}", kind(old), kind(new)),
+        )),
+    }
+}
+
+/// Diffs the sets of interfaces, enum values or union members of a type.
+/// Clients break when one is removed, and may not handle the added ones.
+fn diff_members<T: Ord + Display>(
+    type_name: &str,
+    label: &str,
+    old: &BTreeSet<T>,
+    new: &BTreeSet<T>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for member in old.difference(new) {
+        changes.push(SchemaChange::new(
+            Criticality::Breaking,
+            format!("{type_name}.{member}"),
+            format!("{label} was removed"),
+        ));
+    }
+    for member in new.difference(old) {
+        changes.push(SchemaChange::new(
+            Criticality::Dangerous,
+            format!("{type_name}.{member}"),
+            format!("{label} was added"),
+        ));
+    }
+}
+
+fn diff_fields<'a>(
+    type_name: &str,
+    old: &'a [FieldDefinition],
+    new: &'a [FieldDefinition],
+    changes: &mut Vec<SchemaChange>,
+) {
+    let visible = |fields: &'a [FieldDefinition]| -> Vec<&'a FieldDefinition> {
+        fields.iter().filter(|field| !field.internal).collect()
+    };
+    let (old, new) = (visible(old), visible(new));
+
+    for old_field in old.iter() {
+        let path = format!("{type_name}.{}", old_field.name);
+        let Some(new_field) = new.iter().find(|field| field.name == old_field.name) else {
+            changes.push(SchemaChange::new(
+                Criticality::Breaking,
+                path,
+                "Field was removed",
+            ));
+            continue;
+        };
+
+        if !is_safe_output_change(&old_field.of_type, &new_field.of_type) {
+            changes.push(SchemaChange::new(
+                Criticality::Breaking,
+                &path,
+                format!(
+                    "Field type changed from {:?} to {:?}",
+                    old_field.of_type, new_field.of_type
+                ),
+            ));
+        }
+        diff_input_fields(&path, "Argument", &old_field.args, &new_field.args, changes);
+    }
+
+    for new_field in new.iter() {
+        if !old.iter().any(|field| field.name == new_field.name) {
+            changes.push(SchemaChange::new(
+                Criticality::Safe,
+                format!("{type_name}.{}", new_field.name),
+                "Field was added",
+            ));
+        }
+    }
+}
+
+/// Diffs the arguments of a field or the fields of an input object, which are
+/// both written by the clients.
+fn diff_input_fields(
+    parent: &str,
+    label: &str,
+    old: &[InputFieldDefinition],
+    new: &[InputFieldDefinition],
+    changes: &mut Vec<SchemaChange>,
+) {
+    for old_field in old.iter() {
+        let path = format!("{parent}.{}", old_field.name);
+        let Some(new_field) = new.iter().find(|field| field.name == old_field.name) else {
+            changes.push(SchemaChange::new(
+                Criticality::Breaking,
+                path,
+                format!("{label} was removed"),
+            ));
+            continue;
+        };
+
+        if !is_safe_input_change(&old_field.of_type, &new_field.of_type) {
+            changes.push(SchemaChange::new(
+                Criticality::Breaking,
+                &path,
+                format!(
+                    "{label} type changed from {:?} to {:?}",
+                    old_field.of_type, new_field.of_type
+                ),
+            ));
+        }
+        if old_field.default_value.is_some() && old_field.default_value != new_field.default_value {
+            changes.push(SchemaChange::new(
+                Criticality::Dangerous,
+                &path,
+                format!("{label} default value changed"),
+            ));
+        }
+    }
+
+    for new_field in new.iter() {
+        if old.iter().any(|field| field.name == new_field.name) {
+            continue;
+        }
+        let path = format!("{parent}.{}", new_field.name);
+        if !new_field.of_type.is_nullable() && new_field.default_value.is_none() {
+            changes.push(SchemaChange::new(
+                Criticality::Breaking,
+                path,
+                format!("Required {} was added", label.to_lowercase()),
+            ));
+        } else {
+            changes.push(SchemaChange::new(
+                Criticality::Dangerous,
+                path,
+                format!("Optional {} was added", label.to_lowercase()),
+            ));
+        }
+    }
+}
+
+/// Clients can still read an output type that was made non-null, but not one
+/// that became nullable or changed its shape.
+fn is_safe_output_change(old: &Type, new: &Type) -> bool {
+    if !old.is_nullable() && new.is_nullable() {
+        return false;
+    }
+
+    match (old, new) {
+        (Type::Named { name: old, .. }, Type::Named { name: new, .. }) => old == new,
+        (Type::List { of_type: old, .. }, Type::List { of_type: new, .. }) => {
+            is_safe_output_change(old, new)
+        }
+        _ => false,
+    }
+}
+
+/// Clients can still write an input type that was made nullable, but not one
+/// that became non-null or changed its shape.
+fn is_safe_input_change(old: &Type, new: &Type) -> bool {
+    if old.is_nullable() && !new.is_nullable() {
+        return false;
+    }
+
+    match (old, new) {
+        (Type::Named { name: old, .. }, Type::Named { name: new, .. }) => old == new,
+        (Type::List { of_type: old, .. }, Type::List { of_type: new, .. }) => {
+            is_safe_input_change(old, new)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tailcall_valid::Validator;
+
+    use super::*;
+    use crate::core::config::{Config, ConfigModule};
+
+    fn blueprint(sdl: &str) -> Blueprint {
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        Blueprint::try_from(&ConfigModule::from(config)).unwrap()
+    }
+
+    fn schema(user: &str) -> Blueprint {
+        blueprint(&format!(
+            r#"
+            schema {{
+              query: Query
+            }}
+
+            type Query {{
+              user(id: Int!): User @http(url: "http://jsonplaceholder.typicode.com/users/{{{{.args.id}}}}")
+            }}
+
+            type User {{
+              {user}
+            }}
+            "#
+        ))
+    }
+
+    #[test]
+    fn test_removed_field() {
+        let old = schema("id: Int!\nname: String");
+        let new = schema("id: Int!");
+
+        assert_eq!(
+            diff_schema(&old, &new),
+            vec![SchemaChange::new(
+                Criticality::Breaking,
+                "User.name",
+                "Field was removed"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_added_optional_field() {
+        let old = schema("id: Int!");
+        let new = schema("id: Int!\nemail: String");
+
+        assert_eq!(
+            diff_schema(&old, &new),
+            vec![SchemaChange::new(
+                Criticality::Safe,
+                "User.email",
+                "Field was added"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_type_changes() {
+        let old = schema("id: Int!\nname: String\nemail: String!");
+        let new = schema("id: Int\nname: String!\nemail: [String]!");
+
+        assert_eq!(
+            diff_schema(&old, &new),
+            vec![
+                SchemaChange::new(
+                    Criticality::Breaking,
+                    "User.email",
+                    "Field type changed from String! to [String]!"
+                ),
+                SchemaChange::new(
+                    Criticality::Breaking,
+                    "User.id",
+                    "Field type changed from Int! to Int"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_added_required_argument() {
+        let old = blueprint(
+            r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              users: [Int] @http(url: "http://jsonplaceholder.typicode.com/users")
+            }
+            "#,
+        );
+        let new = blueprint(
+            r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              users(page: Int!, limit: Int): [Int] @http(url: "http://jsonplaceholder.typicode.com/users")
+            }
+            "#,
+        );
+
+        assert_eq!(
+            diff_schema(&old, &new),
+            vec![
+                SchemaChange::new(
+                    Criticality::Breaking,
+                    "Query.users.page",
+                    "Required argument was added"
+                ),
+                SchemaChange::new(
+                    Criticality::Dangerous,
+                    "Query.users.limit",
+                    "Optional argument was added"
+                ),
+            ]
+        );
+    }
+}
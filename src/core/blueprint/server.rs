@@ -5,15 +5,22 @@ use std::time::Duration;
 
 use derive_setters::Setters;
 use http::header::{HeaderMap, HeaderName, HeaderValue};
+use rand::Rng;
 use rustls_pki_types::CertificateDer;
 use tailcall_valid::{Valid, ValidationError, Validator};
+use url::Url;
 
 use super::BlueprintError;
 use crate::core::blueprint::Cors;
-use crate::core::config::{self, ConfigModule, HttpVersion, PrivateKey, Routes};
+use crate::core::config::{
+    self, ConfigModule, HttpVersion, PrivateKey, ProtectedDenialMode, Routes, ScalarCoercion,
+    UnknownFields,
+};
+use crate::core::ir::model::{IO, IR};
 
 #[derive(Clone, Debug, Setters)]
 pub struct Server {
+    pub access_log: Option<AccessLog>,
     pub enable_apollo_tracing: bool,
     pub enable_cache_control_header: bool,
     pub enable_set_cookie_header: bool,
@@ -34,6 +41,118 @@ pub struct Server {
     pub cors: Option<Cors>,
     pub experimental_headers: HashSet<HeaderName>,
     pub routes: Routes,
+    pub complexity: Complexity,
+    pub readiness: Option<Readiness>,
+    pub lenient_dates: bool,
+    pub dedupe_ttl: Option<Duration>,
+    pub dedupe_max_pending: Option<usize>,
+    pub max_deadline: Option<Duration>,
+    pub max_errors: Option<usize>,
+    pub max_field_nodes: Option<usize>,
+    pub max_response_bytes: Option<usize>,
+    pub max_upstream_calls: Option<usize>,
+    pub omit_nulls: bool,
+    pub protected_denial_mode: ProtectedDenialMode,
+    pub scalar_coercion: ScalarCoercion,
+    pub unknown_fields: UnknownFields,
+    pub unwrap_root: bool,
+}
+
+/// The logging of a sample of the GraphQL requests.
+#[derive(Clone, Debug)]
+pub struct AccessLog {
+    pub sample_rate: u8,
+    pub redact_variables: BTreeSet<String>,
+}
+
+impl AccessLog {
+    /// Decides whether the current request is part of the logged sample.
+    pub fn sample(&self) -> bool {
+        self.sample_rate >= 100 || rand::thread_rng().gen_range(0..100) < self.sample_rate
+    }
+}
+
+impl From<&config::AccessLog> for AccessLog {
+    fn from(access_log: &config::AccessLog) -> Self {
+        Self {
+            sample_rate: access_log.sample_rate.unwrap_or(100),
+            redact_variables: access_log.redact_variables.iter().cloned().collect(),
+        }
+    }
+}
+
+/// The upstreams pinged by the readiness endpoint.
+#[derive(Clone, Debug)]
+pub struct Readiness {
+    pub urls: Vec<Url>,
+    pub timeout: Duration,
+}
+
+/// Limits the complexity of the queries, where every field costs by the kind
+/// of its resolver.
+#[derive(Clone, Debug)]
+pub struct Complexity {
+    pub max: Option<usize>,
+    pub scalar: usize,
+    pub http: usize,
+    pub grpc: usize,
+    pub graphql: usize,
+}
+
+impl Complexity {
+    /// Returns the cost of a field resolved with `ir`, that is the cost of its
+    /// most expensive resolver.
+    pub fn cost(&self, ir: Option<&IR>) -> usize {
+        ir.map_or(self.scalar, |ir| self.ir_cost(ir))
+    }
+
+    fn ir_cost(&self, ir: &IR) -> usize {
+        match ir {
+            IR::IO(io) => self.io_cost(io),
+            IR::Cache(cache) => self.io_cost(&cache.io),
+            IR::Dynamic(_) | IR::ContextPath(_) | IR::Service(_) => self.scalar,
+            IR::Path(ir, _)
+            | IR::Protect(_, ir)
+            | IR::Discriminate(_, ir)
+            | IR::Rename(_, ir)
+            | IR::Values(ir)
+            | IR::Step(_, ir) => self.ir_cost(ir),
+            IR::Map(map) => self.ir_cost(&map.input),
+            IR::Pipe(first, second) => self.ir_cost(first).max(self.ir_cost(second)),
+            IR::Merge(irs) => irs
+                .iter()
+                .map(|ir| self.ir_cost(ir))
+                .max()
+                .unwrap_or(self.scalar),
+            IR::Entity(irs) => irs
+                .values()
+                .map(|ir| self.ir_cost(ir))
+                .max()
+                .unwrap_or(self.scalar),
+        }
+    }
+
+    fn io_cost(&self, io: &IO) -> usize {
+        match io {
+            IO::Http { .. } => self.http,
+            IO::Grpc { .. } => self.grpc,
+            IO::GraphQL { .. } => self.graphql,
+            IO::Js { .. } => self.scalar,
+        }
+    }
+}
+
+impl From<&config::Server> for Complexity {
+    fn from(server: &config::Server) -> Self {
+        let cost = server.get_complexity_cost();
+        Self {
+            max: server.get_max_complexity(),
+            scalar: cost.scalar.unwrap_or(1),
+            http: cost.http.unwrap_or(10),
+            grpc: cost.grpc.unwrap_or(10),
+            graphql: cost.graphql.unwrap_or(10),
+        }
+    }
 }
 
 /// Mimic of mini_v8::Script that's wasm compatible
@@ -115,14 +234,25 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
             .fuse(handle_experimental_headers(
                 (config_server).get_experimental_headers(),
             ))
-            .fuse(validate_cors(
-                config_server
-                    .headers
-                    .as_ref()
-                    .and_then(|headers| headers.get_cors()),
-            ))
+            .fuse(
+                validate_cors(
+                    config_server
+                        .headers
+                        .as_ref()
+                        .and_then(|headers| headers.get_cors()),
+                )
+                .zip(to_readiness(&config_server)),
+            )
             .map(
-                |(hostname, http, response_headers, script, experimental_headers, cors)| Server {
+                |(
+                    hostname,
+                    http,
+                    response_headers,
+                    script,
+                    experimental_headers,
+                    (cors, readiness),
+                )| Server {
+                    access_log: config_server.get_access_log().map(AccessLog::from),
                     enable_apollo_tracing: (config_server).enable_apollo_tracing(),
                     enable_cache_control_header: (config_server).enable_cache_control(),
                     enable_set_cookie_header: (config_server).enable_set_cookies(),
@@ -143,6 +273,21 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
                     script,
                     cors,
                     routes: config_server.get_routes(),
+                    complexity: Complexity::from(&config_server),
+                    readiness,
+                    lenient_dates: config_server.get_lenient_dates(),
+                    dedupe_ttl: config_server.get_dedupe_ttl(),
+                    dedupe_max_pending: config_server.get_dedupe_max_pending(),
+                    max_deadline: config_server.get_max_deadline(),
+                    max_errors: config_server.get_max_errors(),
+                    max_field_nodes: config_server.get_max_field_nodes(),
+                    max_response_bytes: config_server.get_max_response_bytes(),
+                    max_upstream_calls: config_server.get_max_upstream_calls(),
+                    omit_nulls: config_server.get_omit_nulls(),
+                    protected_denial_mode: config_server.get_protected_denial_mode(),
+                    scalar_coercion: config_server.get_scalar_coercion(),
+                    unknown_fields: config_server.get_unknown_fields(),
+                    unwrap_root: config_server.get_unwrap_root(),
                 },
             )
             .to_result()
@@ -176,6 +321,29 @@ fn validate_cors(cors: Option<config::cors::Cors>) -> Valid<Option<Cors>, Bluepr
         .trace("schema")
 }
 
+fn to_readiness(server: &config::Server) -> Valid<Option<Readiness>, BlueprintError> {
+    server.readiness_checks.as_ref().map_or_else(
+        || Valid::succeed(None),
+        |checks| {
+            Valid::from_iter(checks.urls.iter(), |url| {
+                Valid::from(
+                    Url::parse(url).map_err(|e| ValidationError::new(BlueprintError::UrlParse(e))),
+                )
+                .trace(url)
+            })
+            .map(|urls| {
+                Some(Readiness {
+                    urls,
+                    timeout: Duration::from_millis(checks.timeout.unwrap_or(1000)),
+                })
+            })
+            .trace("readinessChecks")
+            .trace("@server")
+            .trace("schema")
+        },
+    )
+}
+
 fn validate_hostname(hostname: String) -> Valid<IpAddr, BlueprintError> {
     if hostname == "localhost" {
         Valid::succeed(IpAddr::from([127, 0, 0, 1]))
@@ -236,11 +404,39 @@ fn handle_experimental_headers(
 
 #[cfg(test)]
 mod tests {
-    use crate::core::config::ConfigModule;
+    use tailcall_valid::Validator;
+
+    use crate::core::config::{Config, ConfigModule};
 
     #[test]
     fn test_try_from_default() {
         let actual = super::Server::try_from(ConfigModule::default());
         assert!(actual.is_ok())
     }
+
+    fn with_custom_headers(custom: &str) -> ConfigModule {
+        let sdl = format!("schema @server(headers: {{custom: {custom}}}) {{ query: Query }}");
+        ConfigModule::from(Config::from_sdl(&sdl).to_result().unwrap())
+    }
+
+    #[test]
+    fn test_response_headers() {
+        let config = with_custom_headers(
+            r#"[{key: "x-frame-options", value: "DENY"}, {key: "server", value: "tailcall"}]"#,
+        );
+
+        let server = super::Server::try_from(config).unwrap();
+
+        let headers = &server.response_headers;
+        assert_eq!(headers.get("x-frame-options").unwrap(), "DENY");
+        assert_eq!(headers.get("server").unwrap(), "tailcall");
+    }
+
+    #[test]
+    fn test_invalid_response_headers() {
+        let config = with_custom_headers(r#"[{key: "invalid header", value: "value"}]"#);
+
+        let actual = super::Server::try_from(config);
+        assert!(actual.is_err())
+    }
 }
@@ -1,14 +1,17 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use derive_setters::Setters;
 use tailcall_valid::{Valid, ValidationError, Validator};
 
 use super::BlueprintError;
-use crate::core::config::{self, Batch, ConfigModule};
+use crate::core::config::{self, Batch, BatchFallback, ConfigModule};
 
 #[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
 pub struct Proxy {
     pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub no_proxy: Vec<String>,
 }
 
 #[derive(PartialEq, Eq, Clone, Debug, Setters, schemars::JsonSchema)]
@@ -18,14 +21,20 @@ pub struct Upstream {
     pub keep_alive_interval: u64,
     pub keep_alive_timeout: u64,
     pub keep_alive_while_idle: bool,
+    pub grpc_keepalive_interval: u64,
+    pub grpc_keepalive_timeout: u64,
+    pub http2_max_concurrent_streams: Option<usize>,
     pub proxy: Option<Proxy>,
     pub connect_timeout: u64,
     pub timeout: u64,
+    pub retry: usize,
+    pub services: BTreeMap<String, String>,
     pub tcp_keep_alive: u64,
     pub user_agent: String,
     pub allowed_headers: BTreeSet<String>,
     pub http_cache: u64,
     pub batch: Option<Batch>,
+    pub batch_fallback: BatchFallback,
     pub http2_only: bool,
     pub on_request: Option<String>,
     pub verify_ssl: bool,
@@ -56,29 +65,31 @@ impl TryFrom<&ConfigModule> for Upstream {
     fn try_from(config_module: &ConfigModule) -> Result<Self, Self::Error> {
         let config_upstream = config_module.upstream.clone();
 
-        let mut allowed_headers = config_upstream.get_allowed_headers();
-
-        if config_module.extensions().has_auth() {
-            // force add auth specific headers to use it to make actual validation
-            allowed_headers.insert(http::header::AUTHORIZATION.to_string());
-        }
+        let allowed_headers = config_module.allowed_headers();
 
         get_batch(&config_upstream)
             .fuse(get_proxy(&config_upstream))
-            .map(|(batch, proxy)| Upstream {
+            .fuse(get_http2_max_concurrent_streams(&config_upstream))
+            .map(|(batch, proxy, http2_max_concurrent_streams)| Upstream {
                 pool_idle_timeout: (config_upstream).get_pool_idle_timeout(),
                 pool_max_idle_per_host: (config_upstream).get_pool_max_idle_per_host(),
                 keep_alive_interval: (config_upstream).get_keep_alive_interval(),
                 keep_alive_timeout: (config_upstream).get_keep_alive_timeout(),
                 keep_alive_while_idle: (config_upstream).get_keep_alive_while_idle(),
+                grpc_keepalive_interval: (config_upstream).get_grpc_keepalive_interval(),
+                grpc_keepalive_timeout: (config_upstream).get_grpc_keepalive_timeout(),
+                http2_max_concurrent_streams,
                 proxy,
                 connect_timeout: (config_upstream).get_connect_timeout(),
                 timeout: (config_upstream).get_timeout(),
+                retry: (config_upstream).get_retry(),
+                services: config_upstream.services.clone(),
                 tcp_keep_alive: (config_upstream).get_tcp_keep_alive(),
                 user_agent: (config_upstream).get_user_agent(),
                 allowed_headers,
                 http_cache: (config_upstream).get_http_cache_size(),
                 batch,
+                batch_fallback: (config_upstream).get_batch_fallback(),
                 http2_only: (config_upstream).get_http_2_only(),
                 on_request: (config_upstream).get_on_request(),
                 verify_ssl: (config_upstream).get_verify_ssl(),
@@ -87,6 +98,28 @@ impl TryFrom<&ConfigModule> for Upstream {
     }
 }
 
+/// Resolves the `url` of a directive against the base URL of its `service`,
+/// when it refers to one of the upstream services.
+pub fn to_service_url(
+    upstream: &config::Upstream,
+    service: Option<&str>,
+    url: &str,
+) -> Valid<String, BlueprintError> {
+    let Some(service) = service else {
+        return Valid::succeed(url.to_string());
+    };
+
+    match upstream.get_service_url(service) {
+        Some(base_url) if url.is_empty() => Valid::succeed(base_url.to_string()),
+        Some(base_url) => Valid::succeed(format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            url.trim_start_matches('/')
+        )),
+        None => Valid::fail(BlueprintError::UnknownService(service.to_string())).trace("service"),
+    }
+}
+
 fn get_batch(upstream: &config::Upstream) -> Valid<Option<Batch>, BlueprintError> {
     upstream.batch.as_ref().map_or_else(
         || Valid::succeed(None),
@@ -101,9 +134,36 @@ fn get_batch(upstream: &config::Upstream) -> Valid<Option<Batch>, BlueprintError
 }
 
 fn get_proxy(upstream: &config::Upstream) -> Valid<Option<Proxy>, BlueprintError> {
-    if let Some(ref proxy) = upstream.proxy {
-        Valid::succeed(Some(Proxy { url: proxy.url.clone() }))
-    } else {
-        Valid::succeed(None)
+    let Some(ref proxy) = upstream.proxy else {
+        return Valid::succeed(None);
+    };
+
+    let error = match url::Url::parse(&proxy.url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => None,
+        Ok(url) => Some(format!("unsupported scheme '{}'", url.scheme())),
+        Err(e) => Some(e.to_string()),
+    };
+
+    match error {
+        Some(error) => Valid::fail(BlueprintError::InvalidProxyUrl(proxy.url.clone(), error))
+            .trace("proxy")
+            .trace("upstream"),
+        None => Valid::succeed(Some(Proxy {
+            url: proxy.url.clone(),
+            username: proxy.username.clone(),
+            password: proxy.password.clone(),
+            no_proxy: proxy.no_proxy.clone(),
+        })),
+    }
+}
+
+fn get_http2_max_concurrent_streams(
+    upstream: &config::Upstream,
+) -> Valid<Option<usize>, BlueprintError> {
+    match upstream.get_http2_max_concurrent_streams() {
+        Some(0) => Valid::fail(BlueprintError::ZeroHttp2MaxConcurrentStreams)
+            .trace("http2MaxConcurrentStreams")
+            .trace("upstream"),
+        max => Valid::succeed(max),
     }
 }
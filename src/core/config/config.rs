@@ -16,8 +16,8 @@ use tailcall_valid::{Valid, Validator};
 use super::directive::Directive;
 use super::from_document::from_document;
 use super::{
-    AddField, Alias, Cache, Call, Discriminate, Expr, GraphQL, Grpc, Http, Link, Modify, Omit,
-    Protected, ResolverSet, Server, Telemetry, Upstream, JS,
+    AddField, Alias, Cache, Call, Deprecated, Discriminate, Expr, GraphQL, Grpc, Http, Link,
+    Modify, Omit, Protected, ResolverSet, Server, Telemetry, Upstream, JS,
 };
 use crate::core::config::npo::QueryPath;
 use crate::core::config::source::Source;
@@ -99,6 +99,13 @@ pub struct Config {
 
     /// Enable [opentelemetry](https://opentelemetry.io) support
     pub telemetry: Telemetry,
+
+    ///
+    /// Types declared with `extend type` whose base definition isn't part of
+    /// the same document. They're folded into `types` once all the files are
+    /// merged.
+    #[setters(skip)]
+    pub type_extensions: BTreeMap<String, Type>,
 }
 
 ///
@@ -216,6 +223,10 @@ pub struct Field {
     /// Used to overwrite the default discrimination strategy
     pub discriminate: Option<Discriminate>,
 
+    ///
+    /// Marks the field as deprecated, with an optional reason
+    pub deprecated: Option<Deprecated>,
+
     ///
     /// Resolver for the field
     pub resolvers: ResolverSet,
@@ -269,6 +280,14 @@ impl Field {
                 .and_then(|m| m.omit)
                 .unwrap_or_default()
     }
+
+    /// Checks if the field is omitted but still resolved for its siblings.
+    pub fn is_internal(&self) -> bool {
+        self.omit
+            .as_ref()
+            .and_then(|omit| omit.internal)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -682,6 +701,7 @@ pub enum Encoding {
     #[default]
     ApplicationJson,
     ApplicationXWwwFormUrlencoded,
+    MultipartFormData,
 }
 
 #[cfg(test)]
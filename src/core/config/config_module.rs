@@ -6,21 +6,47 @@ use prost_reflect::prost_types::{FileDescriptorProto, FileDescriptorSet};
 use rustls_pki_types::{CertificateDer, PrivateKeyDer};
 use tailcall_valid::{Valid, Validator};
 
-use crate::core::config::Config;
+use crate::core::config::{Config, Lockfile, Type};
 use crate::core::macros::MergeRight;
 use crate::core::merge_right::MergeRight;
 use crate::core::proto_reader::ProtoMetadata;
 use crate::core::rest::{EndpointSet, Unchecked};
 use crate::core::Transform;
 
+mod links;
 mod merge;
 
+pub use merge::ResolverConflict;
+
 /// A wrapper on top of Config that contains all the resolved extensions and
 /// computed values.
 #[derive(Clone, Debug, Default)]
 pub struct ConfigModule {
     extensions: Extensions,
     cache: Cache,
+    conflicts: Vec<ResolverConflict>,
+    sources: Sources,
+}
+
+/// The files the fields with a resolver were read from, keyed by the name of
+/// the type and the name of the field, so that conflicts can name them.
+#[derive(Clone, Debug, Default)]
+struct Sources {
+    types: BTreeMap<(String, String), String>,
+    type_extensions: BTreeMap<(String, String), String>,
+}
+
+impl Sources {
+    /// Keeps the file of the first definition of each field.
+    fn merge(mut self, other: Sources) -> Self {
+        for (key, source) in other.types {
+            self.types.entry(key).or_insert(source);
+        }
+        for (key, source) in other.type_extensions {
+            self.type_extensions.entry(key).or_insert(source);
+        }
+        self
+    }
 }
 
 /// A cache that store resolved input, output and interface types so that it's
@@ -50,7 +76,44 @@ impl From<Config> for Cache {
 
 impl ConfigModule {
     pub fn new(config: Config, extensions: Extensions) -> Self {
-        ConfigModule { cache: Cache::from(config), extensions }
+        ConfigModule {
+            cache: Cache::from(config),
+            extensions,
+            conflicts: Vec::new(),
+            sources: Sources::default(),
+        }
+    }
+
+    /// Records `path` as the file of the fields with a resolver, and of the
+    /// conflicts, that don't have one yet.
+    pub fn with_source(mut self, path: &str) -> Self {
+        let fields = |types: &BTreeMap<String, Type>| {
+            types
+                .iter()
+                .flat_map(|(type_name, ty)| {
+                    ty.fields
+                        .iter()
+                        .filter(|(_, field)| field.resolvers.has_resolver())
+                        .map(move |(field_name, _)| (type_name.clone(), field_name.clone()))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for key in fields(&self.cache.config.types) {
+            self.sources.types.entry(key).or_insert(path.to_string());
+        }
+        for key in fields(&self.cache.config.type_extensions) {
+            self.sources
+                .type_extensions
+                .entry(key)
+                .or_insert(path.to_string());
+        }
+        for conflict in self.conflicts.iter_mut() {
+            conflict.left_source.get_or_insert(path.to_string());
+            conflict.right_source.get_or_insert(path.to_string());
+        }
+
+        self
     }
 
     pub fn set_extensions(mut self, extensions: Extensions) -> Self {
@@ -63,6 +126,51 @@ impl ConfigModule {
         self
     }
 
+    /// Folds the pending `extend type` blocks into their base definitions.
+    /// Must be called once all the documents are merged, since the base
+    /// definition may live in any of them.
+    pub fn resolve_type_extensions(self) -> Valid<Self, String> {
+        let mut config = self.cache.config;
+        let type_extensions = std::mem::take(&mut config.type_extensions);
+
+        Valid::from_iter(type_extensions, |(name, extension)| {
+            if config.types.contains_key(&name) {
+                Valid::succeed((name, extension))
+            } else {
+                Valid::fail(format!(
+                    "Cannot extend type '{name}' because it is not defined"
+                ))
+                .trace(&name)
+            }
+        })
+        .map(|extensions| {
+            let mut conflicts = self.conflicts;
+            let mut sources = self.sources;
+            for (name, extension) in extensions {
+                if let Some(tpe) = config.types.remove(&name) {
+                    conflicts.extend(
+                        ResolverConflict::find(&name, &tpe, &extension)
+                            .into_iter()
+                            .map(|conflict| {
+                                conflict.with_sources(&sources.types, &sources.type_extensions)
+                            }),
+                    );
+                    config.types.insert(name, tpe.merge_right(extension));
+                }
+            }
+            // the fields of the extensions now belong to their types
+            for (key, source) in std::mem::take(&mut sources.type_extensions) {
+                sources.types.entry(key).or_insert(source);
+            }
+
+            ConfigModule {
+                conflicts,
+                sources,
+                ..ConfigModule::new(config, self.extensions)
+            }
+        })
+    }
+
     pub fn config(&self) -> &Config {
         &self.cache.config
     }
@@ -71,6 +179,18 @@ impl ConfigModule {
         &self.extensions
     }
 
+    /// Returns the request headers that are available to the resolvers.
+    pub fn allowed_headers(&self) -> BTreeSet<String> {
+        let mut allowed_headers = self.upstream.get_allowed_headers();
+
+        if self.extensions.has_auth() {
+            // force add auth specific headers to use it to make actual validation
+            allowed_headers.insert(http::header::AUTHORIZATION.to_string());
+        }
+
+        allowed_headers
+    }
+
     pub fn input_types(&self) -> &HashSet<String> {
         &self.cache.input_types
     }
@@ -83,10 +203,21 @@ impl ConfigModule {
         &self.cache.interfaces_types_map
     }
 
+    /// Returns the fields defined more than once with different resolvers.
+    pub fn conflicts(&self) -> &[ResolverConflict] {
+        &self.conflicts
+    }
+
     pub fn transform<T: Transform<Value = Config>>(self, transformer: T) -> Valid<Self, T::Error> {
+        let conflicts = self.conflicts;
+        let sources = self.sources;
         transformer
             .transform(self.cache.config)
-            .map(|config| ConfigModule::new(config, self.extensions))
+            .map(|config| ConfigModule {
+                conflicts,
+                sources,
+                ..ConfigModule::new(config, self.extensions)
+            })
     }
 }
 
@@ -132,6 +263,10 @@ pub struct Extensions {
     /// Contains the file descriptor set resolved from the links to proto files
     pub grpc_file_descriptors: HashMap<String, FileDescriptorProto>,
 
+    /// Contains the services defined by the proto files of each link, keyed by
+    /// the src of the link
+    pub proto_services: BTreeMap<String, BTreeSet<String>>,
+
     /// Contains the contents of the JS file
     pub script: Option<String>,
 
@@ -147,10 +282,22 @@ pub struct Extensions {
     pub htpasswd: Vec<Content<String>>,
 
     pub jwks: Vec<Content<JwkSet>>,
+
+    /// Contains the content hashes of the linked resources
+    pub lockfile: Lockfile,
 }
 
 impl Extensions {
-    pub fn add_proto(&mut self, metadata: ProtoMetadata) {
+    pub fn add_proto(&mut self, src: &str, metadata: ProtoMetadata) {
+        let services = self.proto_services.entry(src.to_string()).or_default();
+        for file in metadata.descriptor_set.file.iter() {
+            services.extend(
+                file.service
+                    .iter()
+                    .map(|service| format!("{}.{}", file.package(), service.name())),
+            );
+        }
+
         for file in metadata.descriptor_set.file {
             self.grpc_file_descriptors
                 .insert(file.name().to_string(), file);
@@ -0,0 +1,161 @@
+use std::collections::BTreeSet;
+
+use tailcall_valid::{Valid, Validator};
+
+use crate::core::config::{ConfigModule, LinkType, Resolver};
+
+impl ConfigModule {
+    /// Checks that every link to proto files defines a service called by a
+    /// `@grpc` resolver, and that every `@grpc` resolver calls a service
+    /// defined by a linked proto file.
+    pub fn validate_links(&self) -> Valid<(), String> {
+        let calls = self
+            .types
+            .iter()
+            .flat_map(|(type_name, type_of)| {
+                let type_calls = type_of
+                    .resolvers
+                    .iter()
+                    .map(move |resolver| (type_name.clone(), resolver));
+                let field_calls = type_of.fields.iter().flat_map(move |(field_name, field)| {
+                    field
+                        .resolvers
+                        .iter()
+                        .map(move |resolver| (format!("{type_name}.{field_name}"), resolver))
+                });
+                type_calls.chain(field_calls)
+            })
+            .filter_map(|(trace, resolver)| match resolver {
+                Resolver::Grpc(grpc) => grpc
+                    .method
+                    .rsplit_once('.')
+                    .map(|(service, _)| (trace, service.to_string())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let used = calls
+            .iter()
+            .map(|(_, service)| service.as_str())
+            .collect::<BTreeSet<_>>();
+        let defined = self
+            .extensions()
+            .proto_services
+            .values()
+            .flatten()
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>();
+
+        let orphaned = Valid::from_iter(self.links.iter().enumerate(), |(pos, link)| {
+            if !matches!(link.type_of, LinkType::Protobuf | LinkType::Grpc) {
+                return Valid::succeed(());
+            }
+
+            let services = self.extensions().proto_services.get(&link.src);
+            if services.is_some_and(|services| services.iter().any(|s| used.contains(s.as_str()))) {
+                Valid::succeed(())
+            } else {
+                Valid::fail(format!(
+                    "Link '{}' is not used by any @grpc resolver",
+                    link.src
+                ))
+                .trace(&pos.to_string())
+            }
+        })
+        .trace("@link")
+        .trace("schema");
+
+        let dangling = Valid::from_iter(calls.iter(), |(trace, service)| {
+            if defined.contains(service.as_str()) {
+                Valid::succeed(())
+            } else {
+                Valid::fail(format!(
+                    "Service '{service}' is not defined by any linked proto file"
+                ))
+                .trace("@grpc")
+                .trace(trace)
+            }
+        });
+
+        orphaned.fuse(dangling).unit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use tailcall_valid::Validator;
+
+    use crate::core::config::{Config, ConfigModule, Extensions};
+
+    fn config_module(sdl: &str) -> ConfigModule {
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        let extensions = Extensions {
+            proto_services: BTreeMap::from([
+                (
+                    "news.proto".to_string(),
+                    BTreeSet::from(["news.NewsService".to_string()]),
+                ),
+                (
+                    "users.proto".to_string(),
+                    BTreeSet::from(["users.UserService".to_string()]),
+                ),
+            ]),
+            ..Default::default()
+        };
+
+        ConfigModule::new(config, extensions)
+    }
+
+    #[test]
+    fn test_links_used() {
+        let config_module = config_module(
+            r#"
+            schema @link(src: "news.proto", type: Protobuf) { query: Query }
+            type Query { news: [String] @grpc(method: "news.NewsService.GetAllNews") }
+            "#,
+        );
+
+        assert!(config_module.validate_links().is_succeed());
+    }
+
+    #[test]
+    fn test_unused_link() {
+        let config_module = config_module(
+            r#"
+            schema
+              @link(src: "news.proto", type: Protobuf)
+              @link(src: "users.proto", type: Protobuf) {
+              query: Query
+            }
+            type Query { news: [String] @grpc(method: "news.NewsService.GetAllNews") }
+            "#,
+        );
+
+        let error = config_module.validate_links().to_result().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Validation Error\n• Link 'users.proto' is not used by any @grpc resolver [schema, @link, 1]\n"
+        );
+    }
+
+    #[test]
+    fn test_dangling_link_reference() {
+        let config_module = config_module(
+            r#"
+            schema @link(src: "news.proto", type: Protobuf) { query: Query }
+            type Query {
+              news: [String] @grpc(method: "news.NewsService.GetAllNews")
+              posts: [String] @grpc(method: "posts.PostService.GetPosts")
+            }
+            "#,
+        );
+
+        let error = config_module.validate_links().to_result().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Validation Error\n• Service 'posts.PostService' is not defined by any linked proto file [Query.posts, @grpc]\n"
+        );
+    }
+}
@@ -3,8 +3,8 @@ use std::collections::BTreeMap;
 use indexmap::IndexMap;
 use tailcall_valid::{Valid, Validator};
 
-use super::{Cache, ConfigModule};
-use crate::core::config::{Arg, Config, Enum, Field, RootSchema, Type};
+use super::{Cache, ConfigModule, Sources};
+use crate::core::config::{Arg, Config, Enum, Field, ResolverSet, RootSchema, Type};
 use crate::core::merge_right::MergeRight;
 use crate::core::variance::{Contravariant, Covariant, Invariant};
 use crate::core::{self};
@@ -297,6 +297,10 @@ impl Invariant for Cache {
                 types,
                 enums,
                 unions: self.config.unions.merge_right(other.config.unions),
+                type_extensions: self
+                    .config
+                    .type_extensions
+                    .merge_right(other.config.type_extensions),
                 schema,
                 ..self.config
             };
@@ -311,11 +315,96 @@ impl Invariant for Cache {
     }
 }
 
+/// A field that is defined twice with different resolvers, either by two
+/// merged documents or by a type and its extension. The merge keeps both
+/// resolvers, so the conflict is recorded to be reported by the blueprint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolverConflict {
+    pub type_name: String,
+    pub field_name: String,
+    pub left: ResolverSet,
+    pub right: ResolverSet,
+    /// The file of the first definition, if known.
+    pub left_source: Option<String>,
+    /// The file of the second definition, if known.
+    pub right_source: Option<String>,
+}
+
+impl ResolverConflict {
+    /// Finds the fields that two definitions of the same type resolve
+    /// differently.
+    pub fn find(type_name: &str, left: &Type, right: &Type) -> Vec<Self> {
+        right
+            .fields
+            .iter()
+            .filter_map(|(field_name, right)| {
+                let left = left.fields.get(field_name)?;
+                let conflicts = left.resolvers.has_resolver()
+                    && right.resolvers.has_resolver()
+                    && left.resolvers != right.resolvers;
+
+                conflicts.then(|| Self {
+                    type_name: type_name.to_string(),
+                    field_name: field_name.clone(),
+                    left: left.resolvers.clone(),
+                    right: right.resolvers.clone(),
+                    left_source: None,
+                    right_source: None,
+                })
+            })
+            .collect()
+    }
+
+    fn find_all(
+        left: &BTreeMap<String, Type>,
+        right: &BTreeMap<String, Type>,
+        left_sources: &BTreeMap<(String, String), String>,
+        right_sources: &BTreeMap<(String, String), String>,
+    ) -> Vec<Self> {
+        right
+            .iter()
+            .filter_map(|(name, right)| Some(Self::find(name, left.get(name)?, right)))
+            .flatten()
+            .map(|conflict| conflict.with_sources(left_sources, right_sources))
+            .collect()
+    }
+
+    /// Looks up the files of both definitions of the field.
+    pub(super) fn with_sources(
+        self,
+        left: &BTreeMap<(String, String), String>,
+        right: &BTreeMap<(String, String), String>,
+    ) -> Self {
+        let key = (self.type_name.clone(), self.field_name.clone());
+        Self {
+            left_source: left.get(&key).cloned(),
+            right_source: right.get(&key).cloned(),
+            ..self
+        }
+    }
+}
+
 impl Invariant for ConfigModule {
     fn unify(self, other: Self) -> Valid<Self, String> {
+        let mut conflicts = self.conflicts.merge_right(other.conflicts);
+        conflicts.extend(ResolverConflict::find_all(
+            &self.cache.config.types,
+            &other.cache.config.types,
+            &self.sources.types,
+            &other.sources.types,
+        ));
+        conflicts.extend(ResolverConflict::find_all(
+            &self.cache.config.type_extensions,
+            &other.cache.config.type_extensions,
+            &self.sources.type_extensions,
+            &other.sources.type_extensions,
+        ));
+
         self.cache.unify(other.cache).map(|cache| Self {
             cache,
             extensions: self.extensions.merge_right(other.extensions),
+            conflicts,
+            sources: self.sources.merge(other.sources),
         })
     }
 }
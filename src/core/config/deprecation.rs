@@ -0,0 +1,102 @@
+use std::fmt::{self, Display};
+
+use async_graphql::parser::types::{ServiceDocument, TypeKind, TypeSystemDefinition};
+use async_graphql::Pos;
+
+/// Directives that are no longer supported, along with the ones replacing
+/// them. `tc migrate` rewrites a config to use the replacements.
+const DEPRECATED_DIRECTIVES: &[(&str, &str)] = &[("const", "expr"), ("unsafe", "js")];
+
+/// A deprecated directive used on a field of the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deprecation {
+    /// Name of the deprecated directive.
+    pub directive: &'static str,
+    /// Name of the directive to use instead.
+    pub replacement: &'static str,
+    /// The field using the directive, as `Type.field`.
+    pub field: String,
+    /// Position of the directive in the config.
+    pub pos: Pos,
+}
+
+impl Display for Deprecation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "@{} on {} at {}:{} is deprecated, use @{} instead or run `tc migrate`",
+            self.directive, self.field, self.pos.line, self.pos.column, self.replacement
+        )
+    }
+}
+
+/// Finds the deprecated directives used on the fields of `doc`.
+pub fn deprecations(doc: &ServiceDocument) -> Vec<Deprecation> {
+    let mut deprecations = Vec::new();
+
+    for definition in &doc.definitions {
+        let TypeSystemDefinition::Type(type_def) = definition else {
+            continue;
+        };
+        let fields = match &type_def.node.kind {
+            TypeKind::Object(object) => &object.fields,
+            TypeKind::Interface(interface) => &interface.fields,
+            _ => continue,
+        };
+
+        for field in fields {
+            for directive in &field.node.directives {
+                let name = directive.node.name.node.as_str();
+                let Some((directive_name, replacement)) = DEPRECATED_DIRECTIVES
+                    .iter()
+                    .find(|(deprecated, _)| *deprecated == name)
+                else {
+                    continue;
+                };
+                deprecations.push(Deprecation {
+                    directive: directive_name,
+                    replacement,
+                    field: format!("{}.{}", type_def.node.name.node, field.node.name.node),
+                    pos: directive.pos,
+                });
+            }
+        }
+    }
+
+    deprecations
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::Pos;
+
+    use super::{deprecations, Deprecation};
+
+    #[test]
+    fn test_unsafe_deprecation() {
+        let sdl = r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              greeting: String @unsafe(script: "return 'hello'")
+              user: String @http(url: "http://jsonplaceholder.typicode.com/users/1")
+            }
+        "#;
+        let doc = async_graphql::parser::parse_schema(sdl).unwrap();
+        let actual = deprecations(&doc);
+
+        let expected = vec![Deprecation {
+            directive: "unsafe",
+            replacement: "js",
+            field: "Query.greeting".to_string(),
+            pos: Pos { line: 7, column: 32 },
+        }];
+        assert_eq!(actual, expected);
+        assert_eq!(
+            actual[0].to_string(),
+            "@unsafe on Query.greeting at 7:32 is deprecated, use @js instead or run `tc migrate`"
+        );
+    }
+}
@@ -24,4 +24,9 @@ pub struct Cache {
     /// Specifies the duration, in milliseconds, of how long the value has to be
     /// stored in the cache.
     pub max_age: NonZeroU64,
+
+    /// Specifies the duration, in milliseconds, for which an expired value is
+    /// still served while it is refreshed in the background.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_while_revalidate: Option<NonZeroU64>,
 }
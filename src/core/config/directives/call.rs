@@ -42,7 +42,8 @@ pub struct Step {
 pub struct Call {
     /// Steps are composed together to form a call.
     /// If you have multiple steps, the output of the previous step is passed as
-    /// input to the next step.
+    /// input to the next step, whose `args` read it through `{{.args}}`. A
+    /// step that fails is reported with its index in `steps`.
     pub steps: Vec<Step>,
     #[serde(default, skip_serializing_if = "is_default")]
     /// Enables deduplication of IO operations to enhance performance.
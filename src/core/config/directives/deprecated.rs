@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::is_default;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+/// The built-in `@deprecated` directive, marking a field as deprecated. It's
+/// kept on the field in the generated SDL and exposed by introspection.
+pub struct Deprecated {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Explains why the field is deprecated and what to use instead.
+    pub reason: Option<String>,
+}
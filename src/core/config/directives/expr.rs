@@ -17,7 +17,9 @@ use tailcall_macros::{DirectiveDefinition, InputDefinition};
 #[serde(deny_unknown_fields)]
 /// The `@expr` operators allows you to specify an expression that can evaluate
 /// to a value. The expression can be a static value or built form a Mustache
-/// template. schema.
+/// template. schema. Sibling fields with their own resolver that are read
+/// through `{{.value.<field>}}` are resolved before the expression when they
+/// are selected.
 pub struct Expr {
     pub body: Value,
 }
@@ -30,9 +30,16 @@ use crate::core::is_default;
 /// In this scenario, the GraphQL server will make a gRPC request to the gRPC
 /// endpoint specified when the `users` field is queried.
 pub struct Grpc {
-    /// This refers to URL of the API.
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// This refers to URL of the API. It can be left out when a `service` is
+    /// given.
     pub url: String,
     #[serde(default, skip_serializing_if = "is_default")]
+    /// The logical name of the upstream service that is called, e.g.
+    /// `"news"`. It's resolved to its base URL from the upstream `services`,
+    /// and `url`, if any, is appended to it.
+    pub service: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
     /// This refers to the arguments of your gRPC call. You can pass it as a
     /// static object or use Mustache template for dynamic parameters. These
     /// parameters will be added in the body in `protobuf` format.
@@ -58,6 +65,12 @@ pub struct Grpc {
     /// nonce-based APIs.
     pub dedupe: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The protocol the upstream speaks. It can be `grpc`, `grpc-web` or
+    /// `connect`. `grpc-web` and `connect` upstreams are called over HTTP/1.1.
+    /// @default `grpc`.
+    pub protocol: GrpcProtocol,
+
     /// You can use `select` with mustache syntax to re-construct the directives
     /// response to the desired format. This is useful when data are deeply
     /// nested or want to keep specific fields only from the response.
@@ -74,4 +87,22 @@ pub struct Grpc {
     /// body before it's sent back to the client.
     #[serde(rename = "onResponseBody", default, skip_serializing_if = "is_default")]
     pub on_response_body: Option<String>,
+
+    /// Validates the request body against the input message of the method
+    /// before sending it, and the response against the output message after
+    /// receiving it.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub validate: bool,
+}
+
+/// The protocol a `@grpc` upstream is called with.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, Default, schemars::JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum GrpcProtocol {
+    #[default]
+    Grpc,
+    GrpcWeb,
+    Connect,
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tailcall_macros::{DirectiveDefinition, InputDefinition};
@@ -34,9 +36,20 @@ pub struct Http {
     /// request interception handler.
     pub on_request: Option<String>,
 
-    /// This refers to URL of the API.
+    /// This refers to URL of the API. A list can be joined into the path with
+    /// the `join` filter, e.g. `/tags/{{.args.tags | join:,}}`. The whole URL,
+    /// host included, can be templated, e.g. `{{.env.USERS_URL}}/users` or
+    /// `http://{{.headers.x-tenant}}.example.com`, as long as the headers are
+    /// in the upstream `allowedHeaders`. A header rendered into the host has to
+    /// be a DNS label. With a `service`, it's the path that is appended to the
+    /// base URL of the service, e.g. `/users`.
     pub url: String,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The logical name of the upstream service that is called, e.g.
+    /// `"users"`. It's resolved to its base URL from the upstream `services`.
+    pub service: Option<String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The body of the API call. It's used for methods like POST or PUT that
     /// send data to the server. You can pass it as a static object or use a
@@ -44,22 +57,43 @@ pub struct Http {
     /// variables.
     pub body: Option<Value>,
 
+    #[serde(rename = "bodyArgs", default, skip_serializing_if = "is_default")]
+    /// The arguments sent as the keys of a JSON object body, for example
+    /// `["title", "content"]`. It's a shorthand for a `body` listing each
+    /// argument, that keeps the types of their values.
+    pub body_args: Vec<String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The `encoding` parameter specifies the encoding of the request body. It
-    /// can be `ApplicationJson` or `ApplicationXWwwFormUrlEncoded`. @default
-    /// `ApplicationJson`.
+    /// can be `ApplicationJson`, `ApplicationXWwwFormUrlEncoded` or
+    /// `MultipartFormData`. @default `ApplicationJson`.
     pub encoding: Encoding,
 
     #[serde(rename = "batchKey", default, skip_serializing_if = "is_default")]
     /// The `batchKey` dictates the path Tailcall will follow to group the returned items from the batch request. For more details please refer out [n + 1 guide](https://tailcall.run/docs/guides/n+1#solving-using-batching).
     pub batch_key: Vec<String>,
 
+    #[serde(rename = "batchKeys", default, skip_serializing_if = "is_default")]
+    /// The `batchKeys` groups the returned items by a composite key, built
+    /// from an ordered list of paths. Every path is paired, in order, with a
+    /// query parameter that references `{{.value}}`.
+    pub batch_keys: Vec<Vec<String>>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The `headers` parameter allows you to customize the headers of the HTTP
     /// request made by the `@http` operator. It is used by specifying a
     /// key-value map of header names and their values.
     pub headers: Vec<KeyValue>,
 
+    #[serde(rename = "idempotencyKey", default, skip_serializing_if = "is_default")]
+    /// Sends an `Idempotency-Key` header so that the API can recognize the
+    /// retries of a request, for example `"{{.args.requestId}}"`. A key
+    /// without expressions, such as `"auto"`, is replaced by a hash of the
+    /// method, url, headers and body of the request, so that the same request
+    /// always gets the same key, even when it's deduplicated or batched. The
+    /// key stays the same across the `retry` attempts of a request.
+    pub idempotency_key: Option<String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// Schema of the input of the API call. It is automatically inferred in
     /// most cases.
@@ -83,6 +117,12 @@ pub struct Http {
     /// first parameter referencing a field in the current value using mustache
     /// syntax is automatically selected as the batching parameter.
     pub query: Vec<URLQuery>,
+
+    #[serde(rename = "queryArgs", default, skip_serializing_if = "is_default")]
+    /// The arguments sent as query parameters named after them, for example
+    /// `["draft"]`. The arguments that aren't provided are left out.
+    pub query_args: Vec<String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// Enables deduplication of IO operations to enhance performance.
     ///
@@ -108,4 +148,53 @@ pub struct Http {
     /// body before it's sent back to the client.
     #[serde(rename = "onResponseBody", default, skip_serializing_if = "is_default")]
     pub on_response_body: Option<String>,
+
+    #[serde(rename = "skipIf", default, skip_serializing_if = "is_default")]
+    /// Mustache template that gates the upstream call. When the rendered
+    /// value is empty, `null` or `false` the request is not made and the field
+    /// resolves to `null`. For example `"{{.args.id}}"` only calls the API
+    /// when the `id` argument is provided.
+    pub skip_if: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Renames the keys of the objects in the response, from the upstream
+    /// name to the GraphQL field name, for example `{user_name: "name"}`.
+    /// The keys are renamed before `select` is applied.
+    pub rename: BTreeMap<String, String>,
+
+    #[serde(rename = "renameNested", default, skip_serializing_if = "is_default")]
+    /// Applies `rename` to the nested objects of the response as well.
+    /// @default `false`.
+    pub rename_nested: Option<bool>,
+
+    #[serde(rename = "listFrom", default, skip_serializing_if = "is_default")]
+    /// Builds the list of a list field from a response that is a keyed
+    /// object, such as `{"1": {...}, "2": {...}}`. With `values` the list is
+    /// made of the values of the object, in order. Arrays are left as is.
+    pub list_from: Option<ListFrom>,
+
+    #[serde(rename = "okStatus", default, skip_serializing_if = "is_default")]
+    /// The status codes that are expected from the API. A listed non-2xx
+    /// status, such as a `404` for a missing resource, resolves the field to
+    /// `null` instead of an error. Any other non-2xx status is still an error.
+    pub ok_status: Vec<u16>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The time in seconds to wait for the API to respond. It overrides the
+    /// `timeout` of `@upstream` for this field, and `0` disables the timeout.
+    pub timeout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The number of times a request that fails with a network error or a 5xx
+    /// status is retried. Only requests with an idempotent method, or an
+    /// `idempotencyKey`, are retried. It overrides the `retry` of `@upstream`
+    /// for this field, and `0` turns the retries off.
+    pub retry: Option<usize>,
+}
+
+/// How a list is read from an object response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ListFrom {
+    Values,
 }
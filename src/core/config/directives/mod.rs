@@ -2,6 +2,7 @@ mod add_field;
 mod alias;
 mod cache;
 mod call;
+mod deprecated;
 mod discriminate;
 mod expr;
 mod federation;
@@ -21,6 +22,7 @@ pub use add_field::*;
 pub use alias::*;
 pub use cache::*;
 pub use call::*;
+pub use deprecated::*;
 pub use discriminate::*;
 pub use expr::*;
 pub use federation::*;
@@ -1,11 +1,14 @@
 use serde::{Deserialize, Serialize};
 use tailcall_macros::{DirectiveDefinition, MergeRight};
 
+use crate::core::is_default;
+
 #[derive(
     Serialize,
     Deserialize,
     Clone,
     Debug,
+    Default,
     PartialEq,
     Eq,
     schemars::JsonSchema,
@@ -15,4 +18,9 @@ use tailcall_macros::{DirectiveDefinition, MergeRight};
 #[directive_definition(locations = "FieldDefinition")]
 #[serde(deny_unknown_fields)]
 /// Used to omit a field from public consumption.
-pub struct Omit {}
+pub struct Omit {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Keeps the resolver of the omitted field, so that the sibling fields can
+    /// still read its resolved value through `{{.value.<field>}}`.
+    pub internal: Option<bool>,
+}
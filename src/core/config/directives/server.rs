@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 use derive_getters::Getters;
 use schemars::JsonSchema;
@@ -34,6 +35,12 @@ pub struct Server {
     #[schemars(skip)]
     pub enable_jit: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `accessLog` emits a structured log event for the GraphQL requests, with
+    /// their operation name, variables, duration, status, error count and
+    /// response size.
+    pub access_log: Option<AccessLog>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `apolloTracing` exposes GraphQL query performance data, including
     /// execution time of queries and individual resolvers.
@@ -45,6 +52,23 @@ pub struct Server {
     /// debugging. Use judiciously. @default `false`.
     pub batch_requests: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `complexityCost` sets the cost of a field by the kind of its resolver,
+    /// when computing the complexity of a query against `maxComplexity`.
+    pub complexity_cost: Option<ComplexityCost>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `dedupeTtl` is the time in milliseconds the results of the upstream
+    /// calls made by a request are reused by its other fields. They're reused
+    /// for the whole request when not set.
+    pub dedupe_ttl: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `dedupeMaxPending` caps the deduplicated upstream calls in progress at
+    /// once across requests. New calls beyond it fail instead of being made,
+    /// while calls identical to one in progress still wait for its result.
+    pub dedupe_max_pending: Option<usize>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `headers` contains key-value pairs that are included as default headers
     /// in server responses, allowing for consistent header management across
@@ -66,11 +90,58 @@ pub struct Server {
     /// and operations. @default `true`.
     pub introspection: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `lenientDates` accepts the `Date` and `DateTime` values of the upstream
+    /// written in other common formats than RFC 3339, such as RFC 2822 or unix
+    /// timestamps, and normalizes them to RFC 3339. Other values are still
+    /// rejected. @default `false`.
+    pub lenient_dates: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxComplexity` rejects the queries whose complexity, the sum of the
+    /// costs of their fields, exceeds the limit.
+    pub max_complexity: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxDeadline` is the time in milliseconds a request is allowed to take.
+    /// The time left is sent as the `grpc-timeout` of the `@grpc` calls made
+    /// by the request. Requests can shorten it with a `grpc-timeout` header.
+    pub max_deadline: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxErrors` caps the `errors` of a response. The first errors are kept
+    /// and the rest is replaced by a single error counting them.
+    pub max_errors: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxFieldNodes` rejects the queries selecting more fields than the
+    /// limit, counting every aliased selection of a field separately.
+    pub max_field_nodes: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxResponseBytes` caps the size in bytes of a serialized response.
+    /// The responses growing past it are replaced by an error, before they
+    /// are fully written.
+    pub max_response_bytes: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxUpstreamCalls` caps the calls to `@http`, `@grpc` and `@graphQL`
+    /// upstreams a single request can make. The fields resolved once the limit
+    /// is reached fail without calling the upstream.
+    pub max_upstream_calls: Option<usize>,
+
     /// `enableFederation` enables functionality to Tailcall server to act
     /// as a federation subgraph.
     #[serde(default, skip_serializing_if = "is_default")]
     pub enable_federation: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `omitNulls` leaves the nullable fields that resolve to `null` out of
+    /// the response, instead of responding with a `null` value for them. Lets
+    /// clients tell an absent field apart from an explicit `null`. @default
+    /// `false`.
+    pub omit_nulls: Option<bool>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `pipelineFlush` allows to control flushing behavior of the server
     /// pipeline.
@@ -80,17 +151,39 @@ pub struct Server {
     /// `port` sets the Tailcall running port. @default `8000`.
     pub port: Option<u16>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `protectedDenialMode` decides what happens when a query selects a
+    /// `@protected` field the request isn't authorized for. With `error` the
+    /// whole query fails, with `null` the denied fields resolve to `null`
+    /// with a field error while the rest of the query resolves normally.
+    /// @default `error`.
+    pub protected_denial_mode: Option<ProtectedDenialMode>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `queryValidation` checks incoming GraphQL queries against the schema,
     /// preventing errors from invalid queries. Can be disabled for performance.
     /// @default `false`.
     pub query_validation: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `readinessChecks` lists the upstreams that must be reachable for the
+    /// server to be ready. The `/readyz` endpoint pings them and responds with
+    /// `503` if any of them is down.
+    pub readiness_checks: Option<ReadinessChecks>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `responseValidation` Tailcall automatically validates responses from
     /// upstream services using inferred schema. @default `false`.
     pub response_validation: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `scalarCoercion` decides what happens to the upstream values that
+    /// aren't valid for their scalar. With `strict` the field fails, and with
+    /// `lenient` the values that can be converted without losing information,
+    /// such as the numeric string `"42"` of an `Int32`, are coerced with a
+    /// warning. Values that can't be coerced still fail. @default `strict`.
+    pub scalar_coercion: Option<ScalarCoercion>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// A link to an external JS file that listens on every HTTP request
     /// response event.
@@ -100,6 +193,20 @@ pub struct Server {
     /// `showcase` enables the /showcase/graphql endpoint.
     pub showcase: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `unknownFields` decides what happens to the keys of an upstream object
+    /// that aren't fields of its type in the schema. With `drop` they are
+    /// left out of the response, with `error` the field fails, and with
+    /// `passthrough` every object type gets a `_unknownFields: JSON` field
+    /// that clients can select to get them as-is. @default `drop`.
+    pub unknown_fields: Option<UnknownFields>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `unwrapRoot` responds with the bare value of the root field as the
+    /// `data`, instead of an object keyed by its name, when a query selects
+    /// exactly one root field. @default `false`.
+    pub unwrap_root: Option<bool>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     #[merge_right(merge_right_fn = "merge_right_vars")]
     /// This configuration defines local variables for server operations. Useful
@@ -169,6 +276,54 @@ pub struct ScriptOptions {
     pub timeout: Option<u64>,
 }
 
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// The logging of the GraphQL requests handled by the server.
+pub struct AccessLog {
+    /// Logs the requests when `true`. @default `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Percentage of the requests that are logged, from `0` to `100`.
+    /// @default `100`.
+    pub sample_rate: Option<u8>,
+    /// Names of the variables whose values are masked in the logs, at any
+    /// depth of the variables.
+    #[serde(default)]
+    pub redact_variables: Vec<String>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// The upstreams that are pinged to check that the server is ready.
+pub struct ReadinessChecks {
+    /// Base URLs of the upstreams, each must respond successfully to a `GET`.
+    pub urls: Vec<String>,
+    /// Time in milliseconds to wait for each upstream. @default `1000`.
+    pub timeout: Option<u64>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// The cost of a field in the complexity of a query, by the kind of its
+/// resolver.
+pub struct ComplexityCost {
+    /// Cost of the fields that don't call an upstream. @default `1`.
+    pub scalar: Option<usize>,
+    /// Cost of the fields resolved with `@http`. @default `10`.
+    pub http: Option<usize>,
+    /// Cost of the fields resolved with `@grpc`. @default `10`.
+    pub grpc: Option<usize>,
+    /// Cost of the fields resolved with `@graphQL`. @default `10`.
+    #[serde(rename = "graphQL")]
+    pub graphql: Option<usize>,
+}
+
 #[derive(
     Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Default, schemars::JsonSchema, MergeRight,
 )]
@@ -178,6 +333,50 @@ pub enum HttpVersion {
     HTTP2,
 }
 
+#[derive(
+    Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// How a query is answered when it selects `@protected` fields the request
+/// isn't authorized for.
+pub enum ProtectedDenialMode {
+    /// The whole query fails with the authentication error.
+    #[default]
+    Error,
+    /// The denied fields resolve to `null` with a field error.
+    Null,
+}
+
+#[derive(
+    Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// How the upstream values that aren't valid for their scalar are handled.
+pub enum ScalarCoercion {
+    /// The field fails with an error.
+    #[default]
+    Strict,
+    /// The values are coerced when possible, the others fail.
+    Lenient,
+}
+
+#[derive(
+    Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// How the keys of an upstream object that aren't fields of its type are
+/// handled in the response.
+pub enum UnknownFields {
+    /// The keys are left out of the response.
+    #[default]
+    Drop,
+    /// The field holding the object fails with an error naming the key.
+    Error,
+    /// The keys are exposed by a `_unknownFields: JSON` field added to every
+    /// object type.
+    Passthrough,
+}
+
 impl Server {
     pub fn enable_apollo_tracing(&self) -> bool {
         self.apollo_tracing.unwrap_or(false)
@@ -215,6 +414,38 @@ impl Server {
     pub fn enable_query_validation(&self) -> bool {
         self.query_validation.unwrap_or(false)
     }
+    pub fn get_max_complexity(&self) -> Option<usize> {
+        self.max_complexity
+    }
+    pub fn get_dedupe_ttl(&self) -> Option<Duration> {
+        self.dedupe_ttl.map(Duration::from_millis)
+    }
+    pub fn get_dedupe_max_pending(&self) -> Option<usize> {
+        self.dedupe_max_pending
+    }
+    pub fn get_max_deadline(&self) -> Option<Duration> {
+        self.max_deadline.map(Duration::from_millis)
+    }
+    pub fn get_max_errors(&self) -> Option<usize> {
+        self.max_errors
+    }
+    pub fn get_max_field_nodes(&self) -> Option<usize> {
+        self.max_field_nodes
+    }
+    pub fn get_max_response_bytes(&self) -> Option<usize> {
+        self.max_response_bytes
+    }
+    pub fn get_max_upstream_calls(&self) -> Option<usize> {
+        self.max_upstream_calls
+    }
+    pub fn get_complexity_cost(&self) -> ComplexityCost {
+        self.complexity_cost.clone().unwrap_or_default()
+    }
+    pub fn get_access_log(&self) -> Option<&AccessLog> {
+        self.access_log
+            .as_ref()
+            .filter(|access_log| access_log.enabled)
+    }
     pub fn enable_batch_requests(&self) -> bool {
         self.batch_requests.unwrap_or(false)
     }
@@ -261,10 +492,34 @@ impl Server {
         self.pipeline_flush.unwrap_or(true)
     }
 
+    pub fn get_protected_denial_mode(&self) -> ProtectedDenialMode {
+        self.protected_denial_mode.clone().unwrap_or_default()
+    }
+
+    pub fn get_unknown_fields(&self) -> UnknownFields {
+        self.unknown_fields.clone().unwrap_or_default()
+    }
+
     pub fn get_routes(&self) -> Routes {
         self.routes.clone().unwrap_or_default()
     }
 
+    pub fn get_scalar_coercion(&self) -> ScalarCoercion {
+        self.scalar_coercion.clone().unwrap_or_default()
+    }
+
+    pub fn get_lenient_dates(&self) -> bool {
+        self.lenient_dates.unwrap_or(false)
+    }
+
+    pub fn get_unwrap_root(&self) -> bool {
+        self.unwrap_root.unwrap_or(false)
+    }
+
+    pub fn get_omit_nulls(&self) -> bool {
+        self.omit_nulls.unwrap_or(false)
+    }
+
     pub fn get_enable_federation(&self) -> bool {
         self.enable_federation.unwrap_or(false)
     }
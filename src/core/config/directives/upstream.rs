@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
@@ -29,9 +29,48 @@ impl Default for Batch {
     }
 }
 
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    schemars::JsonSchema,
+    MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// What happens to the requests of a batch when the batched request fails.
+pub enum BatchFallback {
+    /// All the requests of the batch fail with the error of the batched
+    /// request.
+    #[default]
+    Fail,
+    /// Every request of the batch is retried individually, so that it fails
+    /// only if its own request fails.
+    Individual,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase")]
 pub struct Proxy {
+    /// The URL of the proxy server, e.g. `http://proxy.internal:3128`.
     pub url: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The username used to authenticate with the proxy server.
+    pub username: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The password used to authenticate with the proxy server.
+    pub password: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Hosts that bypass the proxy, in the same format as the `NO_PROXY`
+    /// environment variable, e.g. `localhost`, `.internal` or `10.0.0.0/8`.
+    pub no_proxy: Vec<String>,
 }
 
 #[derive(
@@ -73,15 +112,43 @@ pub struct Upstream {
     /// the batch).
     pub batch: Option<Batch>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `batchFallback` sets what happens to the requests of a batch when the
+    /// batched request fails: `fail` returns the error to all of them, while
+    /// `individual` retries each of them individually. @default `fail`.
+    pub batch_fallback: Option<BatchFallback>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The time in seconds that the connection will wait for a response before
     /// timing out.
     pub connect_timeout: Option<u64>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The time in seconds between the HTTP/2 keep-alive pings sent on the
+    /// connections to gRPC upstreams. Pings are only sent while requests are
+    /// in flight unless `keepAliveWhileIdle` is set, and they don't keep a
+    /// connection in the pool: it's still closed after `poolIdleTimeout`
+    /// without requests. @default `keepAliveInterval`.
+    pub grpc_keepalive_interval: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The time in seconds a gRPC connection waits for the acknowledgement of
+    /// a keep-alive ping before it's closed. @default `keepAliveTimeout`.
+    pub grpc_keepalive_timeout: Option<u64>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// Providing httpCache size enables Tailcall's HTTP caching, adhering to the [HTTP Caching RFC](https://tools.ietf.org/html/rfc7234), to enhance performance by minimizing redundant data fetches. Defaults to `0` if unspecified.
     pub http_cache: Option<u64>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The maximum number of requests in flight at once on the HTTP/2 only
+    /// clients, like the one used for gRPC, each of them taking a stream.
+    /// Further requests wait for one to complete. Unlike the HTTP/2 setting,
+    /// the limit is shared by all the requests of the client, whatever their
+    /// host or connection. It must be greater than `0`, and is unlimited if
+    /// not set.
+    pub http2_max_concurrent_streams: Option<usize>,
+
     #[setters(strip_option)]
     #[serde(rename = "http2Only", default, skip_serializing_if = "is_default")]
     /// The `http2Only` setting allows you to specify whether the client should
@@ -121,6 +188,20 @@ pub struct Upstream {
     /// enabling custom routing and security policies.
     pub proxy: Option<Proxy>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The number of times a request that fails with a network error or a 5xx
+    /// status is retried before the error is returned. Only requests with an
+    /// idempotent method, or an `Idempotency-Key`, are retried. It can be
+    /// overridden per field with the `retry` of `@http`. @default `0`.
+    pub retry: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maps the logical names of the upstream services to their base URLs,
+    /// for example `{users: "https://users.example.com"}`. `@http` and
+    /// `@grpc` refer to these names with their `service` argument, so that
+    /// moving to another environment only changes this map.
+    pub services: BTreeMap<String, String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The time in seconds between each TCP keep-alive message sent to maintain
     /// the connection.
@@ -128,7 +209,8 @@ pub struct Upstream {
 
     #[serde(default, skip_serializing_if = "is_default")]
     /// The maximum time in seconds that the connection will wait for a
-    /// response.
+    /// response. `0` disables the timeout and `@http` can override it per
+    /// field.
     pub timeout: Option<u64>,
 
     #[serde(default, skip_serializing_if = "is_default")]
@@ -165,12 +247,26 @@ impl Upstream {
     pub fn get_keep_alive_while_idle(&self) -> bool {
         self.keep_alive_while_idle.unwrap_or(false)
     }
+    pub fn get_grpc_keepalive_interval(&self) -> u64 {
+        self.grpc_keepalive_interval
+            .unwrap_or(self.get_keep_alive_interval())
+    }
+    pub fn get_grpc_keepalive_timeout(&self) -> u64 {
+        self.grpc_keepalive_timeout
+            .unwrap_or(self.get_keep_alive_timeout())
+    }
+    pub fn get_http2_max_concurrent_streams(&self) -> Option<usize> {
+        self.http2_max_concurrent_streams
+    }
     pub fn get_connect_timeout(&self) -> u64 {
         self.connect_timeout.unwrap_or(60)
     }
     pub fn get_timeout(&self) -> u64 {
         self.timeout.unwrap_or(60)
     }
+    pub fn get_retry(&self) -> usize {
+        self.retry.unwrap_or(0)
+    }
     pub fn get_tcp_keep_alive(&self) -> u64 {
         self.tcp_keep_alive.unwrap_or(5)
     }
@@ -194,6 +290,9 @@ impl Upstream {
             .as_ref()
             .map_or(DEFAULT_MAX_SIZE, |b| b.max_size.unwrap_or(DEFAULT_MAX_SIZE))
     }
+    pub fn get_batch_fallback(&self) -> BatchFallback {
+        self.batch_fallback.unwrap_or_default()
+    }
     pub fn get_http_2_only(&self) -> bool {
         self.http2_only.unwrap_or(false)
     }
@@ -204,6 +303,9 @@ impl Upstream {
     pub fn get_verify_ssl(&self) -> bool {
         self.verify_ssl.unwrap_or(true)
     }
+    pub fn get_service_url(&self, service: &str) -> Option<&str> {
+        self.services.get(service).map(String::as_str)
+    }
 }
 
 #[cfg(test)]
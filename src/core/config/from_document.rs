@@ -11,13 +11,17 @@ use async_graphql_value::ConstValue;
 use indexmap::IndexMap;
 use tailcall_valid::{Valid, ValidationError, Validator};
 
+use super::deprecation::deprecations;
 use super::directive::{to_directive, Directive};
-use super::{Alias, Discriminate, Resolver, RuntimeConfig, Telemetry, FEDERATION_DIRECTIVES};
+use super::{
+    Alias, Deprecated, Discriminate, Resolver, RuntimeConfig, Telemetry, FEDERATION_DIRECTIVES,
+};
 use crate::core::config::{
     self, Cache, Config, Enum, Link, Modify, Omit, Protected, RootSchema, Server, Union, Upstream,
     Variant,
 };
 use crate::core::directive::DirectiveCodec;
+use crate::core::merge_right::MergeRight;
 
 const DEFAULT_SCHEMA_DEFINITION: &SchemaDefinition = &SchemaDefinition {
     extend: false,
@@ -28,6 +32,17 @@ const DEFAULT_SCHEMA_DEFINITION: &SchemaDefinition = &SchemaDefinition {
 };
 
 pub fn from_document(doc: ServiceDocument) -> Valid<Config, String> {
+    for deprecation in deprecations(&doc) {
+        tracing::warn!(
+            directive = deprecation.directive,
+            replacement = deprecation.replacement,
+            field = %deprecation.field,
+            line = deprecation.pos.line,
+            column = deprecation.pos.column,
+            "{deprecation}"
+        );
+    }
+
     let type_definitions: Vec<_> = doc
         .definitions
         .iter()
@@ -40,8 +55,9 @@ pub fn from_document(doc: ServiceDocument) -> Valid<Config, String> {
     let types = to_types(&type_definitions);
     let unions = to_union_types(&type_definitions);
     let enums = to_enum_types(&type_definitions);
-    let schema = schema_definition(&doc).map(to_root_schema);
+    let schema = schema_definition(&doc).map(|sd| to_root_schema(&sd));
     schema_definition(&doc).and_then(|sd| {
+        let sd = &sd;
         server(sd)
             .fuse(upstream(sd))
             .fuse(types)
@@ -51,9 +67,25 @@ pub fn from_document(doc: ServiceDocument) -> Valid<Config, String> {
             .fuse(links(sd))
             .fuse(telemetry(sd))
             .map(
-                |(server, upstream, types, unions, enums, schema, links, telemetry)| {
+                |(
+                    server,
+                    upstream,
+                    (types, type_extensions),
+                    unions,
+                    enums,
+                    schema,
+                    links,
+                    telemetry,
+                )| {
                     let runtime_config = RuntimeConfig { server, upstream, links, telemetry };
-                    let config = Config { types, unions, enums, schema, ..Default::default() };
+                    let config = Config {
+                        types,
+                        type_extensions,
+                        unions,
+                        enums,
+                        schema,
+                        ..Default::default()
+                    };
 
                     config.with_runtime_config(runtime_config)
                 },
@@ -61,24 +93,46 @@ pub fn from_document(doc: ServiceDocument) -> Valid<Config, String> {
     })
 }
 
-fn schema_definition(doc: &ServiceDocument) -> Valid<&SchemaDefinition, String> {
-    doc.definitions
+/// Returns the schema definition of the document with the directives and
+/// operation types of every `extend schema` block merged into it.
+fn schema_definition(doc: &ServiceDocument) -> Valid<SchemaDefinition, String> {
+    let (extensions, definitions): (Vec<_>, Vec<_>) = doc
+        .definitions
         .iter()
-        .find_map(|def| match def {
+        .filter_map(|def| match def {
             TypeSystemDefinition::Schema(schema_definition) => Some(&schema_definition.node),
             _ => None,
         })
-        .map_or_else(|| Valid::succeed(DEFAULT_SCHEMA_DEFINITION), Valid::succeed)
+        .partition(|sd| sd.extend);
+
+    let base = definitions
+        .first()
+        .copied()
+        .unwrap_or(DEFAULT_SCHEMA_DEFINITION)
+        .clone();
+
+    Valid::succeed(extensions.into_iter().fold(base, |mut base, extension| {
+        base.directives.extend(extension.directives.iter().cloned());
+        base.query = base.query.or_else(|| extension.query.clone());
+        base.mutation = base.mutation.or_else(|| extension.mutation.clone());
+        base.subscription = base.subscription.or_else(|| extension.subscription.clone());
+        base.extend = false;
+        base
+    }))
 }
 
-fn process_schema_directives<T: DirectiveCodec + Default>(
+fn process_schema_directives<T: DirectiveCodec + Default + MergeRight>(
     schema_definition: &SchemaDefinition,
     directive_name: &str,
 ) -> Valid<T, String> {
     let mut res = Valid::succeed(T::default());
     for directive in schema_definition.directives.iter() {
         if directive.node.name.node.as_ref() == directive_name {
-            res = T::from_directive(&directive.node);
+            // Repeated directives come from `extend schema` blocks and are
+            // merged into the ones defined before them.
+            res = res
+                .zip(T::from_directive(&directive.node))
+                .map(|(this, that)| this.merge_right(that));
         }
     }
     res
@@ -119,10 +173,15 @@ fn links(schema_definition: &SchemaDefinition) -> Valid<Vec<Link>, String> {
 }
 
 fn telemetry(schema_definition: &SchemaDefinition) -> Valid<Telemetry, String> {
-    process_schema_directives(
+    process_schema_multiple_directives(
         schema_definition,
         super::Telemetry::directive_name().as_str(),
     )
+    .map(|telemetry: Vec<Telemetry>| {
+        telemetry
+            .into_iter()
+            .fold(Telemetry::default(), Telemetry::merge_right)
+    })
 }
 
 fn to_root_schema(schema_definition: &SchemaDefinition) -> RootSchema {
@@ -138,10 +197,22 @@ fn to_root_schema(schema_definition: &SchemaDefinition) -> RootSchema {
 fn pos_name_to_string(pos: &Positioned<Name>) -> String {
     pos.node.to_string()
 }
+/// Converts the type definitions into config types. Types declared with
+/// `extend type` are merged into their base definition; extensions whose base
+/// isn't part of this document are returned separately so that they can be
+/// resolved once all the documents are merged.
+#[allow(clippy::type_complexity)]
 fn to_types(
     type_definitions: &Vec<&Positioned<TypeDefinition>>,
-) -> Valid<BTreeMap<String, config::Type>, String> {
+) -> Valid<
+    (
+        BTreeMap<String, config::Type>,
+        BTreeMap<String, config::Type>,
+    ),
+    String,
+> {
     Valid::from_iter(type_definitions, |type_definition| {
+        let extend = type_definition.node.extend;
         let type_name = pos_name_to_string(&type_definition.node.name);
         match type_definition.node.kind.clone() {
             TypeKind::Object(object_type) => to_object_type(
@@ -169,13 +240,32 @@ fn to_types(
             TypeKind::Union(_) => Valid::none(),
             TypeKind::Scalar => Valid::succeed(Some(to_scalar_type())),
         }
-        .map(|option| (type_name, option))
+        .map(|option| (type_name, extend, option))
     })
     .map(|vec| {
-        BTreeMap::from_iter(
-            vec.into_iter()
-                .filter_map(|(name, option)| option.map(|tpe| (name, tpe))),
-        )
+        let (extensions, definitions): (Vec<_>, Vec<_>) = vec
+            .into_iter()
+            .filter_map(|(name, extend, option)| option.map(|tpe| (name, extend, tpe)))
+            .partition(|(_, extend, _)| *extend);
+
+        let mut types =
+            BTreeMap::from_iter(definitions.into_iter().map(|(name, _, tpe)| (name, tpe)));
+        let mut type_extensions = BTreeMap::<String, config::Type>::new();
+
+        for (name, _, extension) in extensions {
+            let target = if types.contains_key(&name) {
+                &mut types
+            } else {
+                &mut type_extensions
+            };
+            let tpe = match target.remove(&name) {
+                Some(tpe) => tpe.merge_right(extension),
+                None => extension,
+            };
+            target.insert(name, tpe);
+        }
+
+        (types, type_extensions)
     })
 }
 fn to_scalar_type() -> config::Type {
@@ -330,7 +420,10 @@ where
         .fuse(Omit::from_directives(directives.iter()))
         .fuse(Modify::from_directives(directives.iter()))
         .fuse(Protected::from_directives(directives.iter()))
-        .fuse(Discriminate::from_directives(directives.iter()))
+        .fuse(
+            Discriminate::from_directives(directives.iter())
+                .zip(Deprecated::from_directives(directives.iter())),
+        )
         .fuse(default_value)
         .fuse(to_federation_directives(directives))
         .map(
@@ -340,7 +433,7 @@ where
                 omit,
                 modify,
                 protected,
-                discriminate,
+                (discriminate, deprecated),
                 default_value,
                 directives,
             )| config::Field {
@@ -352,6 +445,7 @@ where
                 cache,
                 protected,
                 discriminate,
+                deprecated,
                 default_value,
                 resolvers,
                 directives,
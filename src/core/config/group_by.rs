@@ -9,11 +9,32 @@ pub struct GroupBy {
     path: Vec<String>,
     #[serde(default, skip_serializing_if = "is_default")]
     key: Option<String>,
+    /// Remaining parts of a composite key, in order.
+    #[serde(default, skip_serializing_if = "is_default")]
+    composite: Vec<GroupBy>,
 }
 
 impl GroupBy {
     pub fn new(path: Vec<String>, key: Option<String>) -> Self {
-        Self { path, key }
+        Self { path, key, composite: Vec::new() }
+    }
+
+    /// Creates a group by that identifies items by all the given parts
+    /// together. Returns `None` when no parts are given.
+    pub fn composite(parts: Vec<GroupBy>) -> Option<Self> {
+        let mut parts = parts.into_iter();
+        let mut head = parts.next()?;
+        head.composite = parts.collect();
+        Some(head)
+    }
+
+    pub fn is_composite(&self) -> bool {
+        !self.composite.is_empty()
+    }
+
+    /// Returns all the parts of the key, starting with itself.
+    pub fn parts(&self) -> impl Iterator<Item = &GroupBy> {
+        std::iter::once(self).chain(self.composite.iter())
     }
 
     pub fn path(&self) -> Vec<String> {
@@ -40,6 +61,6 @@ const ID: &str = "id";
 
 impl Default for GroupBy {
     fn default() -> Self {
-        Self { path: vec![ID.to_string()], key: None }
+        Self { path: vec![ID.to_string()], key: None, composite: Vec::new() }
     }
 }
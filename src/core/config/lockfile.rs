@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use path_clean::PathClean;
+use pathdiff::diff_paths;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::core::merge_right::MergeRight;
+
+/// Content hashes of the resources linked from a configuration with `@link`,
+/// keyed by their absolute path or URL. Checking the hashes against a saved
+/// lockfile makes sure that a build uses the same linked content every time. A
+/// saved lockfile keys the local paths relative to its own directory, so that
+/// it can be used from any working directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub links: BTreeMap<String, String>,
+}
+
+impl Lockfile {
+    /// Returns the SHA-256 digest of `content` as hex.
+    pub fn hash(content: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(content))
+    }
+
+    /// Records the hash of the content linked at `path`.
+    pub fn insert(&mut self, path: &str, content: &[u8]) {
+        self.links.insert(key(path), Self::hash(content));
+    }
+
+    /// Fails if the content linked at `path` doesn't match the locked hash.
+    pub fn verify(&self, path: &str, content: &[u8]) -> anyhow::Result<()> {
+        match self.links.get(&key(path)) {
+            Some(hash) if *hash == Self::hash(content) => Ok(()),
+            Some(_) => Err(anyhow::anyhow!(
+                "Linked resource {path} has changed since the lockfile was created"
+            )),
+            None => Err(anyhow::anyhow!(
+                "Linked resource {path} is missing from the lockfile"
+            )),
+        }
+    }
+
+    /// Keys the local paths relative to `dir`, the directory the lockfile is
+    /// saved in, instead of the working directory.
+    pub fn relative_to(&self, dir: &Path) -> Self {
+        let dir = absolute(dir);
+        let links = self
+            .links
+            .iter()
+            .map(|(path, hash)| {
+                let path = match Url::parse(path) {
+                    Ok(_) => path.clone(),
+                    Err(_) => diff_paths(path, &dir)
+                        .map(|path| path.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.clone()),
+                };
+                (path, hash.clone())
+            })
+            .collect();
+        Self { links }
+    }
+
+    /// Resolves the local paths of a lockfile saved in `dir`, undoing
+    /// [`Lockfile::relative_to`].
+    pub fn resolve(self, dir: &Path) -> Self {
+        let links = self
+            .links
+            .into_iter()
+            .map(|(path, hash)| {
+                let path = match Url::parse(&path) {
+                    Ok(_) => path,
+                    Err(_) => key(&dir.join(path).to_string_lossy()),
+                };
+                (path, hash)
+            })
+            .collect();
+        Self { links }
+    }
+
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Returns the absolute and normalized form of a local path.
+fn absolute(path: &Path) -> PathBuf {
+    match std::env::current_dir() {
+        Ok(cwd) if path.is_relative() => cwd.join(path).clean(),
+        _ => path.clean(),
+    }
+}
+
+/// Keys a local path by its absolute form so that a file is keyed the same
+/// way however it's linked. URLs are kept as they are.
+fn key(path: &str) -> String {
+    match Url::parse(path) {
+        Ok(_) => path.to_string(),
+        Err(_) => absolute(Path::new(path)).to_string_lossy().to_string(),
+    }
+}
+
+impl MergeRight for Lockfile {
+    fn merge_right(mut self, other: Self) -> Self {
+        self.links.extend(other.links);
+        self
+    }
+}
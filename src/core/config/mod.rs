@@ -1,9 +1,11 @@
 pub use apollo::*;
 pub use config::*;
 pub use config_module::*;
+pub use deprecation::Deprecation;
 pub use directive::Directive;
 pub use directives::*;
 pub use key_values::*;
+pub use lockfile::Lockfile;
 pub use npo::QueryPath;
 pub use reader_context::*;
 pub use resolver::*;
@@ -13,6 +15,7 @@ mod apollo;
 mod config;
 mod config_module;
 pub mod cors;
+mod deprecation;
 mod directive;
 pub mod directives;
 mod from_document;
@@ -20,6 +23,7 @@ pub mod group_by;
 mod headers;
 mod into_document;
 mod key_values;
+mod lockfile;
 mod npo;
 pub mod reader;
 pub mod reader_context;
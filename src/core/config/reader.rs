@@ -1,20 +1,33 @@
+use std::hash::{Hash, Hasher};
 use std::path::Path;
+use std::time::Duration;
 
+use futures_timer::Delay;
 use futures_util::future::join_all;
+use futures_util::{Future, Stream};
 use rustls_pemfile;
 use rustls_pki_types::{
     CertificateDer, PrivateKeyDer, PrivatePkcs1KeyDer, PrivatePkcs8KeyDer, PrivateSec1KeyDer,
 };
+use tailcall_hasher::TailcallHasher;
 use tailcall_valid::{Valid, ValidationError, Validator};
 use url::Url;
 
-use super::{ConfigModule, Content, Link, LinkType, PrivateKey};
+use super::{ConfigModule, Content, Link, LinkType, Lockfile, PrivateKey};
 use crate::core::config::{Config, ConfigReaderContext, Source};
 use crate::core::proto_reader::ProtoReader;
-use crate::core::resource_reader::{Cached, Resource, ResourceReader};
+use crate::core::resource_reader::{Cached, FileRead, Resource, ResourceReader};
 use crate::core::rest::EndpointSet;
 use crate::core::runtime::TargetRuntime;
 use crate::core::variance::Invariant;
+use crate::core::FileIO;
+
+/// How often the watched files are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long the watched files have to stay unchanged before they're read
+/// again, so that a burst of writes triggers a single reload.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
 
 /// Reads the configuration from a file or from an HTTP URL and resolves all
 /// linked extensions to create a ConfigModule.
@@ -22,6 +35,7 @@ pub struct ConfigReader {
     runtime: TargetRuntime,
     resource_reader: ResourceReader<Cached>,
     proto_reader: ProtoReader,
+    lockfile: Option<Lockfile>,
 }
 
 impl ConfigReader {
@@ -31,9 +45,27 @@ impl ConfigReader {
             runtime: runtime.clone(),
             resource_reader: resource_reader.clone(),
             proto_reader: ProtoReader::init(resource_reader, runtime),
+            lockfile: None,
         }
     }
 
+    /// Fails reading configs whose linked resources don't match the hashes in
+    /// `lockfile`.
+    pub fn locked(mut self, lockfile: Lockfile) -> Self {
+        self.lockfile = Some(lockfile);
+        self
+    }
+
+    /// Records the hash of a linked resource, checking it against the lockfile
+    /// if the reader is locked.
+    fn lock(&self, lockfile: &mut Lockfile, path: &str, content: &str) -> anyhow::Result<()> {
+        if let Some(locked) = &self.lockfile {
+            locked.verify(path, content.as_bytes())?;
+        }
+        lockfile.insert(path, content.as_bytes());
+        Ok(())
+    }
+
     /// Reads the links in a Config and fill the content
     async fn ext_links(
         &self,
@@ -62,55 +94,81 @@ impl ConfigReader {
         let mut extensions = config_module.extensions().clone();
         let mut config_module = Valid::succeed(config_module);
 
+        // Reads the linked files concurrently, they are still applied in the
+        // order of the links below. Reflected services have no file to read, and
+        // proto files are read along with their imports.
+        let paths = links
+            .iter()
+            .filter(|link| !matches!(link.type_of, LinkType::Grpc | LinkType::Protobuf))
+            .map(|link| Self::resolve_path(&link.src, parent_dir))
+            .collect::<Vec<_>>();
+        let mut sources = self.resource_reader.read_files(&paths).await?.into_iter();
+
         for link in links.iter() {
             let path = Self::resolve_path(&link.src, parent_dir);
 
+            // Reflected services aren't locked, as there's no file to hash
+            if link.type_of == LinkType::Grpc {
+                let meta = self
+                    .proto_reader
+                    .fetch(link.src.as_str(), link.headers.clone())
+                    .await?;
+
+                for m in meta {
+                    extensions.add_proto(&link.src, m);
+                }
+                continue;
+            }
+
+            // The proto file and the files it imports are locked as they were
+            // parsed
+            if link.type_of == LinkType::Protobuf {
+                let proto_paths = link.proto_paths.as_ref().map(|paths| {
+                    paths
+                        .iter()
+                        .map(|p| Self::resolve_path(p, parent_dir))
+                        .collect::<Vec<_>>()
+                });
+                let meta = self.proto_reader.read(path, proto_paths.as_deref()).await?;
+                for (path, content) in meta.sources.iter() {
+                    self.lock(&mut extensions.lockfile, path, content)?;
+                }
+                extensions.add_proto(&link.src, meta);
+                continue;
+            }
+
+            let source = sources
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("Failed to read {}", path))?;
+            self.lock(&mut extensions.lockfile, &source.path, &source.content)?;
+
             match link.type_of {
                 LinkType::Config => {
-                    let source = self
-                        .resource_reader
-                        .read_file(path)
-                        .await?
-                        .render(&reader_ctx);
-                    let content = source.content;
-                    let config = Config::from_source(Source::detect(&source.path)?, &content)?;
+                    let source = source.render(&reader_ctx);
+                    let config = parse_config(&source)?;
                     config_module = config_module.and_then(|config_module| {
-                        config_module.unify(ConfigModule::from(config.clone()))
-                    });
-                }
-                LinkType::Protobuf => {
-                    let proto_paths = link.proto_paths.as_ref().map(|paths| {
-                        paths
-                            .iter()
-                            .map(|p| Self::resolve_path(p, parent_dir))
-                            .collect::<Vec<_>>()
+                        config_module
+                            .unify(ConfigModule::from(config.clone()).with_source(&source.path))
                     });
-                    let meta = self.proto_reader.read(path, proto_paths.as_deref()).await?;
-                    extensions.add_proto(meta);
                 }
                 LinkType::Script => {
-                    let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
                     extensions.script = Some(content);
                 }
                 LinkType::Cert => {
-                    let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
                     extensions.cert.extend(self.load_cert(content).await?);
                 }
                 LinkType::Key => {
-                    let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
                     extensions.keys = self.load_private_key(content).await?
                 }
                 LinkType::Operation => {
-                    let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
 
                     extensions.endpoint_set = EndpointSet::try_new(&content)?;
                 }
                 LinkType::Htpasswd => {
-                    let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
 
                     extensions
@@ -118,7 +176,6 @@ impl ConfigReader {
                         .push(Content { id: link.id.clone(), content });
                 }
                 LinkType::Jwks => {
-                    let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
 
                     let de = &mut serde_json::Deserializer::from_str(&content);
@@ -128,16 +185,7 @@ impl ConfigReader {
                         content: serde_path_to_error::deserialize(de)?,
                     })
                 }
-                LinkType::Grpc => {
-                    let meta = self
-                        .proto_reader
-                        .fetch(link.src.as_str(), link.headers.clone())
-                        .await?;
-
-                    for m in meta {
-                        extensions.add_proto(m);
-                    }
-                }
+                LinkType::Grpc | LinkType::Protobuf => {}
             }
         }
 
@@ -199,15 +247,11 @@ impl ConfigReader {
             .collect::<Vec<_>>();
 
         let mut config_modules = join_all(files.iter().map(|file| async {
-            let source = Source::detect(&file.path)?;
-            let schema = &file.content;
-
             // Create initial config module
-            self.resolve(
-                Config::from_source(source, schema)?,
-                Path::new(&file.path).parent(),
-            )
-            .await
+            let config_module = self
+                .resolve(parse_config(file)?, Path::new(&file.path).parent())
+                .await?;
+            anyhow::Ok(config_module.with_source(&file.path))
         }))
         .await
         .into_iter();
@@ -225,7 +269,53 @@ impl ConfigReader {
             })
         });
 
-        Ok(config_module.to_result()?)
+        Ok(config_module
+            .and_then(|config_module| config_module.resolve_type_extensions())
+            .to_result()?)
+    }
+
+    /// Watches the local `files` and emits the config merged from all of them
+    /// every time one of them changes. A reload that fails is emitted as an
+    /// error and the files keep being watched. Files are read from scratch on
+    /// every reload, bypassing the cache of the reader.
+    pub fn watch(
+        &self,
+        files: &[String],
+    ) -> impl Future<Output = impl Stream<Item = anyhow::Result<ConfigModule>>> {
+        let runtime = self.runtime.clone();
+        let lockfile = self.lockfile.clone();
+        let files = files.to_vec();
+
+        async move {
+            // the contents the changes are compared to are read before the
+            // stream is returned, so that no change made after is missed
+            let state = fingerprint(runtime.file.as_ref(), &files).await;
+
+            futures_util::stream::unfold(state, move |mut state| {
+                let runtime = runtime.clone();
+                let lockfile = lockfile.clone();
+                let files = files.clone();
+                async move {
+                    loop {
+                        Delay::new(WATCH_INTERVAL).await;
+                        if fingerprint(runtime.file.as_ref(), &files).await != state {
+                            break;
+                        }
+                    }
+                    loop {
+                        Delay::new(WATCH_DEBOUNCE).await;
+                        let current = fingerprint(runtime.file.as_ref(), &files).await;
+                        if current == state {
+                            break;
+                        }
+                        state = current;
+                    }
+
+                    let reader = ConfigReader { lockfile, ..ConfigReader::init(runtime) };
+                    Some((reader.read_all(&files).await, state))
+                }
+            })
+        }
     }
 
     /// Resolves all the links in a Config to create a ConfigModule
@@ -262,6 +352,35 @@ impl ConfigReader {
     }
 }
 
+/// Parses the config read from `file`, tracing parse errors with the path so
+/// that it's clear which of the files is broken. The format is detected from
+/// the content type the file was served with, falling back to its extension.
+fn parse_config(file: &FileRead) -> anyhow::Result<Config> {
+    let path = file.path.as_str();
+    let source = match file
+        .content_type
+        .as_deref()
+        .and_then(Source::detect_content_type)
+    {
+        Some(source) => source,
+        None => Source::detect(path)?,
+    };
+    Config::from_source(source, &file.content)
+        .map_err(|e| to_validation_error(e).trace(path).into())
+}
+
+/// Hashes of the contents of the `files`, `None` for the ones that can't be
+/// read.
+async fn fingerprint(file_io: &dyn FileIO, files: &[String]) -> Vec<Option<u64>> {
+    join_all(files.iter().map(|file| async move {
+        let content = file_io.read(file).await.ok()?;
+        let mut hasher = TailcallHasher::default();
+        content.hash(&mut hasher);
+        Some(hasher.finish())
+    }))
+    .await
+}
+
 fn to_validation_error(error: anyhow::Error) -> ValidationError<String> {
     match error.downcast::<ValidationError<String>>() {
         Ok(err) => err,
@@ -275,8 +394,9 @@ mod reader_tests {
 
     use pretty_assertions::assert_eq;
 
+    use crate::core::blueprint::Blueprint;
     use crate::core::config::reader::ConfigReader;
-    use crate::core::config::{Config, Type};
+    use crate::core::config::{Config, Link, LinkType, Type};
 
     fn start_mock_server() -> httpmock::MockServer {
         httpmock::MockServer::start()
@@ -325,6 +445,137 @@ mod reader_tests {
         bar_mock.assert();
     }
 
+    #[cfg(feature = "object-storage")]
+    #[tokio::test]
+    async fn test_s3_uri() {
+        use std::borrow::Cow;
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        use crate::core::EnvIO;
+
+        struct Env(HashMap<String, String>);
+
+        impl EnvIO for Env {
+            fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+                self.0.get(key).map(Cow::from)
+            }
+        }
+
+        let server = start_mock_server();
+        let mut cfg = Config::default();
+        cfg.schema.query = Some("Query".to_string());
+        cfg = cfg.types([("Query", Type::default())].to_vec());
+
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/configs/prod/main.graphql")
+                .header_exists("authorization")
+                .header_exists("x-amz-date");
+            then.status(200).body(cfg.to_sdl());
+        });
+
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.env = Arc::new(Env(HashMap::from([
+            ("AWS_ENDPOINT_URL".to_string(), server.base_url()),
+            ("AWS_ACCESS_KEY_ID".to_string(), "access-key".to_string()),
+            (
+                "AWS_SECRET_ACCESS_KEY".to_string(),
+                "secret-key".to_string(),
+            ),
+        ])));
+
+        let cr = ConfigReader::init(runtime);
+        let c = cr
+            .read_all(&["s3://configs/prod/main.graphql"])
+            .await
+            .unwrap();
+
+        assert_eq!(c.schema.query, Some("Query".to_string()));
+        assert!(c.types.contains_key("Query"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_links_read_concurrently() {
+        let runtime = crate::core::runtime::test::init(None);
+        let server = start_mock_server();
+        let delay = std::time::Duration::from_millis(500);
+
+        // The slowest link comes first but the last script still wins
+        let first_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/first.js");
+            then.status(200).body("first").delay(delay);
+        });
+        let second_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/second.js");
+            then.status(200).body("second").delay(delay / 2);
+        });
+
+        let port = server.port();
+        let mut cfg = Config::default();
+        cfg.links = ["first.js", "second.js"]
+            .iter()
+            .map(|file| Link {
+                src: format!("http://localhost:{port}/{file}"),
+                type_of: LinkType::Script,
+                ..Default::default()
+            })
+            .collect();
+        let root_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/root.graphql");
+            then.status(200).body(cfg.to_sdl());
+        });
+
+        let reader = ConfigReader::init(runtime);
+        let start = std::time::Instant::now();
+        let config = reader
+            .read(format!("http://localhost:{port}/root.graphql"))
+            .await
+            .unwrap();
+
+        assert!(start.elapsed() < delay + delay / 2, "{:?}", start.elapsed());
+        assert_eq!(config.extensions().script, Some("second".to_string()));
+        root_mock.assert();
+        first_mock.assert();
+        second_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_failed_link_contains_path() {
+        let runtime = crate::core::runtime::test::init(None);
+        let server = start_mock_server();
+
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/found.js");
+            then.status(200).body("found");
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/missing.js");
+            then.status(404);
+        });
+
+        let port = server.port();
+        let missing = format!("http://localhost:{port}/missing.js");
+        let mut cfg = Config::default();
+        cfg.links = [format!("http://localhost:{port}/found.js"), missing.clone()]
+            .into_iter()
+            .map(|src| Link { src, type_of: LinkType::Script, ..Default::default() })
+            .collect();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/root.graphql");
+            then.status(200).body(cfg.to_sdl());
+        });
+
+        let reader = ConfigReader::init(runtime);
+        let error = reader
+            .read(format!("http://localhost:{port}/root.graphql"))
+            .await
+            .unwrap_err();
+
+        assert!(format!("{error:#}").contains(&missing), "{error:#}");
+    }
+
     #[tokio::test]
     async fn test_local_files() {
         let runtime = crate::core::runtime::test::init(None);
@@ -372,6 +623,306 @@ mod reader_tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_parse_error_contains_path() {
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.graphql");
+        let sdl = "schema {\n  query: Query\n}\n\ntype Query {\n  user User\n}\n";
+        std::fs::write(&path, sdl).unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let reader = ConfigReader::init(runtime);
+        let error = reader.read(path.clone()).await.unwrap_err().to_string();
+
+        assert!(error.contains(&path), "{error}");
+        assert!(error.contains("--> 6:"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_graphql_extension_aliases() {
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let sdl = std::fs::read_to_string("examples/jsonplaceholder.graphql").unwrap();
+        let reader = ConfigReader::init(runtime);
+
+        let path = dir.path().join("schema.graphql");
+        std::fs::write(&path, &sdl).unwrap();
+        let expected = reader
+            .read(path.to_string_lossy().to_string())
+            .await
+            .unwrap();
+
+        for name in ["schema.gql", "schema.graphqls"] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, &sdl).unwrap();
+            let actual = reader
+                .read(path.to_string_lossy().to_string())
+                .await
+                .unwrap();
+
+            assert_eq!(actual.to_sdl(), expected.to_sdl());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_type_extensions_across_files() {
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let reader = ConfigReader::init(runtime);
+
+        let base = dir.path().join("base.graphql");
+        std::fs::write(
+            &base,
+            r#"
+            schema @server(port: 8000) { query: Query }
+            type Query { user: String @expr(body: "user") }
+            "#,
+        )
+        .unwrap();
+        let extension = dir.path().join("extension.graphql");
+        std::fs::write(
+            &extension,
+            r#"extend type Query { post: String @expr(body: "post") }"#,
+        )
+        .unwrap();
+
+        let files = [base, extension].map(|path| path.to_string_lossy().to_string());
+        let config = reader.read_all(&files).await.unwrap();
+
+        assert!(config.type_extensions.is_empty());
+        assert_eq!(
+            config.types["Query"].fields.keys().collect::<Vec<_>>(),
+            ["post", "user"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_resolvers_across_files() {
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let reader = ConfigReader::init(runtime);
+
+        let users = dir.path().join("users.graphql");
+        std::fs::write(
+            &users,
+            r#"
+            schema { query: Query }
+            type Query { user: String @expr(body: "user") }
+            "#,
+        )
+        .unwrap();
+        let accounts = dir.path().join("accounts.graphql");
+        std::fs::write(
+            &accounts,
+            r#"
+            schema { query: Query }
+            type Query { user: String @http(url: "http://localhost/user") }
+            "#,
+        )
+        .unwrap();
+
+        let files = [users, accounts].map(|path| path.to_string_lossy().to_string());
+        let config = reader.read_all(&files).await.unwrap();
+        let error = Blueprint::try_from(&config).unwrap_err().to_string();
+
+        let expected = format!(
+            "Field is defined more than once with different resolvers: `@expr(body: \"user\")` \
+             in {} and `@http(url: \"http://localhost/user\")` in {} [Query, user]",
+            files[0], files[1]
+        );
+        assert!(error.contains(&expected));
+    }
+
+    #[tokio::test]
+    async fn test_type_extension_without_base() {
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let reader = ConfigReader::init(runtime);
+
+        let path = dir.path().join("schema.graphql");
+        std::fs::write(
+            &path,
+            r#"
+            schema { query: Query }
+            type Query { user: String @expr(body: "user") }
+            extend type Post { title: String }
+            "#,
+        )
+        .unwrap();
+
+        let error = reader
+            .read(path.to_string_lossy().to_string())
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Validation Error\n• Cannot extend type 'Post' because it is not defined [Post]\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_locked_links() {
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("script.js");
+        let config = dir.path().join("config.graphql");
+        let sdl = r#"
+            schema @link(type: Script, src: "script.js") {
+              query: Query
+            }
+
+            type Query {
+              hello: String @expr(body: "world")
+            }
+        "#;
+        std::fs::write(&script, "function onRequest() {}").unwrap();
+        std::fs::write(&config, sdl).unwrap();
+        let config = config.to_string_lossy().to_string();
+
+        let lockfile = ConfigReader::init(runtime.clone())
+            .read(config.clone())
+            .await
+            .unwrap()
+            .extensions()
+            .lockfile
+            .clone();
+        assert_eq!(lockfile.links.len(), 1);
+
+        let reader = ConfigReader::init(runtime.clone()).locked(lockfile.clone());
+        assert!(reader.read(config.clone()).await.is_ok());
+
+        std::fs::write(&script, "function onRequest() { return 1 }").unwrap();
+        let reader = ConfigReader::init(runtime).locked(lockfile);
+        let error = reader.read(config).await.unwrap_err().to_string();
+        assert!(error.contains("has changed"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_locked_proto_imports() {
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let news = dir.path().join("news.proto");
+        let common = dir.path().join("common.proto");
+        let config = dir.path().join("config.graphql");
+        let sdl = r#"
+            schema @link(type: Protobuf, src: "news.proto") {
+              query: Query
+            }
+
+            type Query {
+              hello: String @expr(body: "world")
+            }
+        "#;
+        std::fs::write(
+            &news,
+            r#"syntax = "proto3"; package news; import "common.proto"; message News { common.Id id = 1; }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &common,
+            r#"syntax = "proto3"; package common; message Id { int32 id = 1; }"#,
+        )
+        .unwrap();
+        std::fs::write(&config, sdl).unwrap();
+        let config = config.to_string_lossy().to_string();
+
+        let lockfile = ConfigReader::init(runtime.clone())
+            .read(config.clone())
+            .await
+            .unwrap()
+            .extensions()
+            .lockfile
+            .clone();
+        assert_eq!(lockfile.links.len(), 2);
+
+        std::fs::write(
+            &common,
+            r#"syntax = "proto3"; package common; message Id { int64 id = 1; }"#,
+        )
+        .unwrap();
+        let reader = ConfigReader::init(runtime).locked(lockfile);
+        let error = reader.read(config).await.unwrap_err().to_string();
+        assert!(error.contains("has changed"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_locked_links_from_another_dir() {
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let configs = dir.path().join("configs");
+        let shared = dir.path().join("shared");
+        std::fs::create_dir_all(&configs).unwrap();
+        std::fs::create_dir_all(&shared).unwrap();
+        let config = configs.join("config.graphql");
+        let sdl = r#"
+            schema @link(type: Script, src: "../shared/script.js") {
+              query: Query
+            }
+
+            type Query {
+              hello: String @expr(body: "world")
+            }
+        "#;
+        std::fs::write(shared.join("script.js"), "function onRequest() {}").unwrap();
+        std::fs::write(&config, sdl).unwrap();
+
+        // locked with the absolute path of the config
+        let lockfile = ConfigReader::init(runtime.clone())
+            .read(config.to_string_lossy().to_string())
+            .await
+            .unwrap()
+            .extensions()
+            .lockfile
+            .relative_to(&configs);
+        let paths = lockfile.links.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(paths, vec!["../shared/script.js".to_string()]);
+
+        // read with a path relative to the working directory
+        let cwd = std::env::current_dir().unwrap();
+        let config = pathdiff::diff_paths(&config, cwd).unwrap();
+        let lockfile = lockfile.resolve(config.parent().unwrap());
+        let reader = ConfigReader::init(runtime).locked(lockfile);
+        assert!(reader
+            .read(config.to_string_lossy().to_string())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_watch() {
+        use futures_util::StreamExt;
+
+        let runtime = crate::core::runtime::test::init(None);
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.graphql");
+        std::fs::write(
+            &config,
+            "schema { query: Query } type Query { foo: String }",
+        )
+        .unwrap();
+        let config = config.to_string_lossy().to_string();
+
+        let stream = ConfigReader::init(runtime).watch(&[config.clone()]).await;
+        futures_util::pin_mut!(stream);
+        let timeout = std::time::Duration::from_secs(5);
+
+        std::fs::write(&config, "schema { query: Query } type Query {").unwrap();
+        let reloaded = tokio::time::timeout(timeout, stream.next()).await.unwrap();
+        assert!(reloaded.unwrap().is_err());
+
+        std::fs::write(
+            &config,
+            "schema { query: Query } type Query { foo: String, bar: Int }",
+        )
+        .unwrap();
+        let reloaded = tokio::time::timeout(timeout, stream.next()).await.unwrap();
+        let fields = &reloaded.unwrap().unwrap().types["Query"].fields;
+        assert!(fields.contains_key("foo"));
+        assert!(fields.contains_key("bar"));
+    }
+
     #[test]
     fn test_relative_path() {
         let path_dir = Path::new("abc/xyz");
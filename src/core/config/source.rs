@@ -5,7 +5,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Clone, Default, Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum Source {
     Json,
@@ -43,7 +43,7 @@ impl std::str::FromStr for Source {
         match s.to_lowercase().as_str() {
             "json" => Ok(Source::Json),
             "yml" | "yaml" => Ok(Source::Yml),
-            "graphql" | "gql" => Ok(Source::GraphQL),
+            "graphql" | "gql" | "graphqls" => Ok(Source::GraphQL),
             _ => Err(SourceError::UnsupportedFileFormat(s.to_string())),
         }
     }
@@ -67,4 +67,49 @@ impl Source {
             .map(Source::from_str)
             .ok_or(SourceError::InvalidPath(name.to_string()))?
     }
+
+    /// Detect the config format from the `Content-Type` it was served with.
+    /// Parameters such as the charset are ignored, and the `+json` and `+yaml`
+    /// suffixes are recognized.
+    pub fn detect_content_type(content_type: &str) -> Option<Source> {
+        let media_type = content_type.split(';').next()?.trim().to_lowercase();
+        let (_, subtype) = media_type.split_once('/')?;
+        let syntax = subtype
+            .rsplit_once('+')
+            .map_or(subtype, |(_, suffix)| suffix);
+
+        match syntax {
+            "json" => Some(Source::Json),
+            "yaml" | "x-yaml" => Some(Source::Yml),
+            "graphql" => Some(Source::GraphQL),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Source;
+
+    #[test]
+    fn test_detect_content_type() {
+        let detect = Source::detect_content_type;
+
+        assert_eq!(detect("application/json"), Some(Source::Json));
+        assert_eq!(
+            detect("application/json; charset=utf-8"),
+            Some(Source::Json)
+        );
+        assert_eq!(detect("Application/JSON;charset=UTF-8"), Some(Source::Json));
+        assert_eq!(
+            detect("application/graphql-response+json"),
+            Some(Source::Json)
+        );
+        assert_eq!(detect("application/yaml"), Some(Source::Yml));
+        assert_eq!(detect("application/vnd.tailcall+yaml"), Some(Source::Yml));
+        assert_eq!(detect("text/x-yaml; charset=utf-8"), Some(Source::Yml));
+        assert_eq!(detect("application/graphql"), Some(Source::GraphQL));
+        assert_eq!(detect("text/plain"), None);
+        assert_eq!(detect("json"), None);
+    }
 }
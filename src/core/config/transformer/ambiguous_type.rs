@@ -250,7 +250,11 @@ mod tests {
 
         let cfg_module = Generator::default()
             .inputs(vec![Input::Proto {
-                metadata: ProtoMetadata { descriptor_set: set, path: news_proto.to_string() },
+                metadata: ProtoMetadata {
+                    descriptor_set: set,
+                    path: news_proto.to_string(),
+                    sources: Default::default(),
+                },
                 url,
                 connect_rpc: None,
             }])
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use tailcall_valid::Valid;
+
+use super::RenameTypes;
+use crate::core::config::Config;
+use crate::core::Transform;
+
+/// Merges the object types that are identical in everything but their name,
+/// rewriting the references to the merged types. The merged type keeps the
+/// first of its names in lexicographic order, unless one of them is preferred.
+#[derive(Default)]
+pub struct DedupeTypes {
+    /// names to keep over the others, in order of preference.
+    preferred_names: Vec<String>,
+}
+
+impl DedupeTypes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the first of `names` found among the names of the merged types.
+    pub fn prefer<I: IntoIterator<Item = S>, S: ToString>(mut self, names: I) -> Self {
+        self.preferred_names = names.into_iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    fn pick_name<'a>(&self, names: &'a [&'a String]) -> &'a String {
+        self.preferred_names
+            .iter()
+            .find_map(|preferred| names.iter().find(|name| **name == preferred))
+            .or(names.iter().min())
+            .copied()
+            .unwrap()
+    }
+}
+
+impl Transform for DedupeTypes {
+    type Value = Config;
+    type Error = String;
+
+    fn transform(&self, config: Self::Value) -> Valid<Self::Value, Self::Error> {
+        let input_types = config.input_types();
+        let interfaces = config
+            .types
+            .values()
+            .flat_map(|ty| ty.implements.iter())
+            .collect::<BTreeSet<_>>();
+        let is_mergeable =
+            |name: &String| !config.is_root_operation_type(name) && !interfaces.contains(name);
+
+        // groups of identical types, input and output types are never merged.
+        let mut groups: Vec<Vec<&String>> = vec![];
+        for (name, ty) in config.types.iter().filter(|(name, _)| is_mergeable(name)) {
+            let group = groups.iter_mut().find(|group| {
+                let other = group[0];
+                config.types.get(other) == Some(ty)
+                    && input_types.contains(other) == input_types.contains(name)
+            });
+
+            match group {
+                Some(group) => group.push(name),
+                None => groups.push(vec![name]),
+            }
+        }
+
+        let renames = groups
+            .iter()
+            .filter(|group| group.len() > 1)
+            .flat_map(|group| {
+                let kept = self.pick_name(group);
+                group
+                    .iter()
+                    .filter(move |name| **name != kept)
+                    .map(move |name| (name.to_string(), kept.to_string()))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        if renames.is_empty() {
+            return Valid::succeed(config);
+        }
+
+        RenameTypes::new(renames.iter()).transform(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailcall_valid::Validator;
+
+    use super::DedupeTypes;
+    use crate::core::config::Config;
+    use crate::core::transform::Transform;
+
+    const CONFIG: &str = r#"
+        schema {
+          query: Query
+        }
+
+        type Query {
+          user: User @http(url: "http://jsonplaceholder.typicode.com/users/1")
+        }
+
+        type User {
+          address: Address
+          location: Location
+          office: Office
+        }
+
+        type Address {
+          city: String
+          street: String
+        }
+
+        type Location {
+          city: String
+          street: String
+        }
+
+        type Office {
+          city: String
+          street: String
+          zip: String
+        }
+    "#;
+
+    #[test]
+    fn test_dedupe_types() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let config = DedupeTypes::new().transform(config).to_result().unwrap();
+
+        insta::assert_snapshot!(config.to_sdl());
+    }
+
+    #[test]
+    fn test_dedupe_types_preferred_name() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let config = DedupeTypes::new()
+            .prefer(["Location"])
+            .transform(config)
+            .to_result()
+            .unwrap();
+
+        assert!(config.types.contains_key("Location"));
+        assert!(!config.types.contains_key("Address"));
+        assert!(config.types.contains_key("Office"));
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use tailcall_valid::Valid;
+
+use crate::core::config::{Config, Field, Type};
+use crate::core::Transform;
+
+/// Lifts the fields shared by several object types into an interface that the
+/// types implement. The interface is built around the field shared by the most
+/// types, and holds every field that all of these types define identically.
+/// Fields with a resolver are never lifted.
+pub struct ExtractInterface {
+    /// name of the extracted interface.
+    name: String,
+    /// minimum number of types that must share the fields.
+    min_types: usize,
+}
+
+impl ExtractInterface {
+    pub fn new(name: impl ToString) -> Self {
+        Self { name: name.to_string(), min_types: 2 }
+    }
+
+    /// Only extracts the interface when at least `min_types` types share its
+    /// fields.
+    pub fn min_types(mut self, min_types: usize) -> Self {
+        self.min_types = min_types;
+        self
+    }
+}
+
+impl Transform for ExtractInterface {
+    type Value = Config;
+    type Error = String;
+
+    fn transform(&self, mut config: Self::Value) -> Valid<Self::Value, Self::Error> {
+        if config.types.contains_key(&self.name) {
+            return Valid::fail(format!("Type {} already exists", self.name));
+        }
+
+        let input_types = config.input_types();
+        let interfaces = config
+            .types
+            .values()
+            .flat_map(|ty| ty.implements.iter())
+            .collect::<BTreeSet<_>>();
+        let candidates = config
+            .types
+            .iter()
+            .filter(|(name, _)| {
+                !config.is_root_operation_type(name)
+                    && !input_types.contains(*name)
+                    && !interfaces.contains(name)
+            })
+            .collect::<Vec<_>>();
+
+        // the types sharing each field, keyed by its name and definition.
+        let mut shared: Vec<((&String, &Field), Vec<&String>)> = vec![];
+        for (type_name, ty) in candidates.iter().copied() {
+            for field in ty.fields.iter().filter(|(_, field)| !field.has_resolver()) {
+                match shared.iter_mut().find(|(other, _)| *other == field) {
+                    Some((_, types)) => types.push(type_name),
+                    None => shared.push((field, vec![type_name])),
+                }
+            }
+        }
+
+        // ties are broken by the name of the field for a stable output.
+        let types = shared
+            .iter()
+            .filter(|(_, types)| types.len() >= self.min_types)
+            .max_by(|(a, a_types), (b, b_types)| {
+                a_types.len().cmp(&b_types.len()).then(b.0.cmp(a.0))
+            })
+            .map(|(_, types)| {
+                types
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect::<Vec<_>>()
+            });
+
+        let Some(types) = types else {
+            return Valid::succeed(config);
+        };
+
+        let fields = shared
+            .iter()
+            .filter(|(_, sharing)| types.iter().all(|name| sharing.contains(&name)))
+            .map(|((name, field), _)| (name.to_string(), (*field).clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        for name in types.iter() {
+            if let Some(ty) = config.types.get_mut(name) {
+                ty.implements.insert(self.name.clone());
+            }
+        }
+        config
+            .types
+            .insert(self.name.clone(), Type { fields, ..Default::default() });
+
+        Valid::succeed(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailcall_valid::Validator;
+
+    use super::ExtractInterface;
+    use crate::core::config::Config;
+    use crate::core::transform::Transform;
+
+    const CONFIG: &str = r#"
+        schema {
+          query: Query
+        }
+
+        type Query {
+          users: [User] @http(url: "http://jsonplaceholder.typicode.com/users")
+          posts: [Post] @http(url: "http://jsonplaceholder.typicode.com/posts")
+          comments: [Comment] @http(url: "http://jsonplaceholder.typicode.com/comments")
+        }
+
+        type User {
+          id: Int!
+          createdAt: String
+          name: String
+        }
+
+        type Post {
+          id: Int!
+          createdAt: String
+          title: String
+          user: User @http(url: "http://jsonplaceholder.typicode.com/users/{{.value.userId}}")
+        }
+
+        type Comment {
+          id: Int!
+          createdAt: String
+          body: String
+        }
+    "#;
+
+    #[test]
+    fn test_extract_interface() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let config = ExtractInterface::new("Node")
+            .transform(config)
+            .to_result()
+            .unwrap();
+
+        insta::assert_snapshot!(config.to_sdl());
+    }
+
+    #[test]
+    fn test_extract_interface_min_types() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let config = ExtractInterface::new("Node")
+            .min_types(4)
+            .transform(config)
+            .to_result()
+            .unwrap();
+
+        assert!(!config.types.contains_key("Node"));
+        assert!(config.types.values().all(|ty| ty.implements.is_empty()));
+    }
+}
@@ -73,7 +73,7 @@ impl Transform for FlattenSingleField {
                     &mut visited_types,
                 ) {
                     if path.len() > 1 {
-                        field.omit = Some(Omit {});
+                        field.omit = Some(Omit::default());
                         ty.added_fields
                             .push(AddField { name: field_name.to_owned(), path });
                     }
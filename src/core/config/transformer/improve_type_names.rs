@@ -76,42 +76,50 @@ impl<'a> CandidateGeneration<'a> {
     /// This method iterates over the configuration and collects candidate type
     /// names for each type.
     fn generate(mut self) -> CandidateConvergence<'a> {
-        for (type_name, type_info) in self.config.types.iter() {
+        let config = self.config;
+        for (type_name, type_info) in config.types.iter() {
+            // in order to infer the types correctly, always prioritize the non-operation
+            // types but final selection will still depend upon the frequency.
+            let priority = match config.is_root_operation_type(type_name) {
+                true => 0,
+                false => 1,
+            };
+
             for (field_name, field_info) in type_info.fields.iter() {
-                if self.config.is_scalar(field_info.type_of.name())
-                    || field_name.starts_with(PREFIX)
-                {
-                    // If field type is scalar or auto generated then ignore type name inference.
+                if field_name.starts_with(PREFIX) {
+                    // If field is auto generated then ignore type name inference.
                     continue;
                 }
 
-                let inner_map = self
-                    .candidates
-                    .entry(field_info.type_of.name().to_owned())
-                    .or_default();
-
-                let singularized_candidate = pluralizer::pluralize(field_name, 1, false);
+                // input types inferred from request bodies are named after the field that
+                // accepts them, eg. `createPost(input: T1)` suggests `CreatePostInput`.
+                for arg in field_info.args.values() {
+                    if !config.is_scalar(arg.type_of.name()) {
+                        self.insert(arg.type_of.name(), format!("{}Input", field_name), priority);
+                    }
+                }
 
-                if let Some(key_val) = inner_map.get_mut(&singularized_candidate) {
-                    key_val.frequency += 1
-                } else {
-                    // in order to infer the types correctly, always prioritize the non-operation
-                    // types but final selection will still depend upon the
-                    // frequency.
-                    let priority = match self.config.is_root_operation_type(type_name) {
-                        true => 0,
-                        false => 1,
-                    };
-
-                    inner_map.insert(
-                        singularized_candidate,
-                        CandidateStats { frequency: 1, priority },
-                    );
+                if config.is_scalar(field_info.type_of.name()) {
+                    // If field type is scalar then ignore type name inference.
+                    continue;
                 }
+
+                let singularized_candidate = pluralizer::pluralize(field_name, 1, false);
+                self.insert(field_info.type_of.name(), singularized_candidate, priority);
             }
         }
         CandidateConvergence::new(self)
     }
+
+    fn insert(&mut self, type_name: &str, candidate: String, priority: u8) {
+        let inner_map = self.candidates.entry(type_name.to_owned()).or_default();
+
+        if let Some(key_val) = inner_map.get_mut(&candidate) {
+            key_val.frequency += 1
+        } else {
+            inner_map.insert(candidate, CandidateStats { frequency: 1, priority });
+        }
+    }
 }
 
 #[derive(Default)]
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use tailcall_valid::Valid;
+
+use crate::core::config::{Config, GraphQLOperationType};
+use crate::core::transform::Transform;
+
+/// Infers the nullability of the fields from the samples the types were
+/// generated from. A field is required if it's present and non-null in every
+/// sample it was observed in, and nullable otherwise.
+pub struct InferNullability<'a> {
+    /// The response of an operation field along with the operation and the
+    /// name of the field it was sampled from.
+    samples: Vec<(GraphQLOperationType, &'a str, &'a Value)>,
+}
+
+impl<'a> InferNullability<'a> {
+    pub fn new(samples: Vec<(GraphQLOperationType, &'a str, &'a Value)>) -> Self {
+        Self { samples }
+    }
+}
+
+/// Records for every field of the type whether it's present and non-null in
+/// the value, then descends into the values of the fields.
+fn observe(
+    config: &Config,
+    type_name: &str,
+    value: &Value,
+    observed: &mut BTreeMap<(String, String), bool>,
+) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                observe(config, type_name, value, observed);
+            }
+        }
+        Value::Object(object) => {
+            let Some(type_of) = config.types.get(type_name) else {
+                return;
+            };
+
+            for (field_name, field) in &type_of.fields {
+                let value = object.get(field_name).filter(|value| !value.is_null());
+                let required = observed
+                    .entry((type_name.to_owned(), field_name.to_owned()))
+                    .or_insert(true);
+                *required &= value.is_some();
+
+                if let Some(value) = value {
+                    observe(config, field.type_of.name(), value, observed);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Transform for InferNullability<'_> {
+    type Value = Config;
+    type Error = String;
+
+    fn transform(&self, mut config: Self::Value) -> Valid<Self::Value, Self::Error> {
+        let mut observed = BTreeMap::new();
+
+        for (operation_type, field_name, value) in &self.samples {
+            let operation = match operation_type {
+                GraphQLOperationType::Query => config.schema.query.as_ref(),
+                GraphQLOperationType::Mutation => config.schema.mutation.as_ref(),
+            };
+            let type_name = operation
+                .and_then(|operation| config.types.get(operation))
+                .and_then(|operation| operation.fields.get(*field_name))
+                .map(|field| field.type_of.name().to_owned());
+
+            if let Some(type_name) = type_name {
+                observe(&config, &type_name, value, &mut observed);
+            }
+        }
+
+        for ((type_name, field_name), required) in observed {
+            if let Some(field) = config
+                .types
+                .get_mut(&type_name)
+                .and_then(|type_of| type_of.fields.get_mut(&field_name))
+            {
+                let type_of = std::mem::take(&mut field.type_of);
+                field.type_of = if required {
+                    type_of.into_required()
+                } else {
+                    type_of.into_nullable()
+                };
+            }
+        }
+
+        Valid::succeed(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use tailcall_valid::Validator;
+
+    use super::InferNullability;
+    use crate::core::config::{Config, GraphQLOperationType};
+    use crate::core::transform::Transform;
+    use crate::core::Type;
+
+    #[test]
+    fn test_nested_field_null_in_one_sample() {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query }
+            type Query { users: [User] @http(url: "http://example.com/users") }
+            type User { id: Int, name: String, address: Address }
+            type Address { city: String, zip: String }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        let first = json!([{ "id": 1, "name": "foo", "address": { "city": "A", "zip": "1" } }]);
+        let second = json!([{ "id": 2, "name": "bar", "address": { "city": "B", "zip": null } }]);
+        let samples = vec![
+            (GraphQLOperationType::Query, "users", &first),
+            (GraphQLOperationType::Query, "users", &second),
+        ];
+
+        let config = InferNullability::new(samples)
+            .transform(config)
+            .to_result()
+            .unwrap();
+
+        let type_of = |type_name: &str, field_name: &str| {
+            config.types[type_name].fields[field_name].type_of.clone()
+        };
+        let named = |name: &str| Type::from(name.to_string());
+
+        assert_eq!(type_of("User", "id"), named("Int").into_required());
+        assert_eq!(type_of("User", "address"), named("Address").into_required());
+        assert_eq!(type_of("Address", "city"), named("String").into_required());
+        assert_eq!(type_of("Address", "zip"), named("String"));
+    }
+}
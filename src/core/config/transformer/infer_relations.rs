@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, HashSet};
+
+use convert_case::{Case, Casing};
+use tailcall_valid::Valid;
+
+use crate::core::config::{Batch, Config, Field, Http, Resolver, URLQuery};
+use crate::core::http::Method;
+use crate::core::transform::Transform;
+
+/// Links the types that reference each other by id. A field like `userId` on
+/// `Post` gets a sibling `user` field, resolved with a batched call to the
+/// query that lists all the `User`s.
+#[derive(Default)]
+pub struct InferRelations;
+
+/// Finds the http resolvers of the query fields that list all the values of a
+/// type, without any argument.
+fn list_resolvers(config: &Config, query: &str) -> BTreeMap<String, Http> {
+    let Some(query) = config.types.get(query) else {
+        return BTreeMap::new();
+    };
+
+    query
+        .fields
+        .values()
+        .filter(|field| field.type_of.is_list() && field.args.is_empty())
+        .filter_map(|field| match field.resolvers.first() {
+            Some(Resolver::Http(http))
+                if http.method == Method::GET
+                    && http.query.is_empty()
+                    && http.batch_key.is_empty()
+                    && http.batch_keys.is_empty() =>
+            {
+                Some((field.type_of.name().to_owned(), http.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn relation(field_name: &str, type_name: &str, http: &Http) -> Field {
+    let http = Http {
+        query: vec![URLQuery {
+            key: "id".to_string(),
+            value: format!("{{{{.value.{}}}}}", field_name),
+            skip_empty: None,
+        }],
+        batch_key: vec!["id".to_string()],
+        ..http.clone()
+    };
+
+    Field {
+        type_of: type_name.to_string().into(),
+        resolvers: Resolver::Http(http).into(),
+        ..Default::default()
+    }
+}
+
+impl Transform for InferRelations {
+    type Value = Config;
+    type Error = String;
+
+    fn transform(&self, mut config: Self::Value) -> Valid<Self::Value, Self::Error> {
+        let Some(query) = config.schema.query.clone() else {
+            return Valid::succeed(config);
+        };
+
+        let list_resolvers = list_resolvers(&config, &query);
+        let identified_types = config
+            .types
+            .iter()
+            .filter(|(_, ty)| ty.fields.contains_key("id"))
+            .map(|(name, _)| name.to_owned())
+            .collect::<HashSet<_>>();
+        let input_types = config.input_types();
+        let mut has_relations = false;
+
+        for (type_name, ty) in config.types.iter_mut() {
+            if type_name == &query || input_types.contains(type_name) {
+                continue;
+            }
+
+            let relations = ty
+                .fields
+                .keys()
+                .filter_map(|field_name| {
+                    let name = field_name
+                        .strip_suffix("Id")
+                        .filter(|name| !name.is_empty())?;
+                    let target = name.to_case(Case::Pascal);
+
+                    if ty.fields.contains_key(name) || !identified_types.contains(&target) {
+                        return None;
+                    }
+
+                    let http = list_resolvers.get(&target)?;
+                    Some((name.to_string(), relation(field_name, &target, http)))
+                })
+                .collect::<Vec<_>>();
+
+            has_relations |= !relations.is_empty();
+            ty.fields.extend(relations);
+        }
+
+        if has_relations && config.upstream.batch.is_none() {
+            config.upstream.batch = Some(Batch::default());
+        }
+
+        Valid::succeed(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailcall_valid::Validator;
+
+    use super::InferRelations;
+    use crate::core::config::{Config, Resolver};
+    use crate::core::transform::Transform;
+
+    const CONFIG: &str = r#"
+        schema @server @upstream {
+          query: Query
+        }
+
+        type Query {
+          posts: [Post] @http(url: "http://jsonplaceholder.typicode.com/posts")
+          users: [User] @http(url: "http://jsonplaceholder.typicode.com/users")
+        }
+
+        type Post {
+          id: Int
+          title: String
+          userId: Int
+          authorId: Int
+        }
+
+        type User {
+          id: Int
+          name: String
+        }
+    "#;
+
+    #[test]
+    fn test_infer_relations() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let config = InferRelations.transform(config).to_result().unwrap();
+
+        let post = config.types.get("Post").unwrap();
+        let user = post.fields.get("user").unwrap();
+        let Some(Resolver::Http(http)) = user.resolvers.first() else {
+            panic!("expected an @http resolver on Post.user");
+        };
+
+        assert_eq!(user.type_of.name(), "User");
+        assert_eq!(http.url, "http://jsonplaceholder.typicode.com/users");
+        assert_eq!(http.batch_key, vec!["id".to_string()]);
+        assert_eq!(http.query[0].key, "id");
+        assert_eq!(http.query[0].value, "{{.value.userId}}");
+        assert!(config.upstream.batch.is_some());
+
+        // There's no type for authors
+        assert!(!post.fields.contains_key("author"));
+    }
+}
@@ -1,23 +1,35 @@
 mod ambiguous_type;
+mod dedupe_types;
+mod extract_interface;
 mod flatten_single_field;
 mod improve_type_names;
+mod infer_nullability;
+mod infer_relations;
 mod merge_types;
 mod nested_unions;
 mod preset;
 mod rename_types;
 mod required;
 mod subgraph;
+mod suggest_cache;
 mod tree_shake;
 mod union_input_type;
+mod unknown_fields;
 
 pub use ambiguous_type::{AmbiguousType, Resolution};
+pub use dedupe_types::DedupeTypes;
+pub use extract_interface::ExtractInterface;
 pub use flatten_single_field::FlattenSingleField;
 pub use improve_type_names::ImproveTypeNames;
+pub use infer_nullability::InferNullability;
+pub use infer_relations::InferRelations;
 pub use merge_types::TypeMerger;
 pub use nested_unions::NestedUnions;
 pub use preset::Preset;
 pub use rename_types::RenameTypes;
 pub use required::Required;
 pub use subgraph::Subgraph;
+pub use suggest_cache::SuggestCache;
 pub use tree_shake::TreeShake;
 pub use union_input_type::UnionInputType;
+pub use unknown_fields::{PassthroughUnknownFields, UNKNOWN_FIELDS_FIELD_NAME};
@@ -11,6 +11,7 @@ pub struct Preset {
     pub tree_shake: bool,
     pub infer_type_names: bool,
     pub unwrap_single_field_types: bool,
+    pub infer_relations: bool,
 }
 
 impl Preset {
@@ -20,6 +21,7 @@ impl Preset {
             tree_shake: false,
             infer_type_names: true,
             unwrap_single_field_types: true,
+            infer_relations: false,
         }
     }
 }
@@ -38,6 +40,7 @@ impl Transform for Preset {
             )
             .pipe(super::FlattenSingleField.when(self.unwrap_single_field_types))
             .pipe(super::ImproveTypeNames.when(self.infer_type_names))
+            .pipe(super::InferRelations.when(self.infer_relations))
             .transform(config)
     }
 }
@@ -49,6 +52,7 @@ impl Default for Preset {
             infer_type_names: true,
             tree_shake: true,
             unwrap_single_field_types: false,
+            infer_relations: false,
         }
     }
 }
@@ -16,6 +16,7 @@ impl Transform for Required {
             .pipe(super::NestedUnions)
             .pipe(super::UnionInputType)
             .pipe(super::AmbiguousType::default())
+            .pipe(super::PassthroughUnknownFields)
             .transform(config)
     }
 }
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroU64;
+use std::time::Duration;
+
+use tailcall_valid::Valid;
+
+use crate::core::config::{Cache, Config};
+use crate::core::transform::Transform;
+
+/// How many times its latency a response is kept in the cache.
+const MAX_AGE_FACTOR: u32 = 100;
+
+/// Bounds of the `maxAge`, in milliseconds, given to the cached fields.
+const MIN_MAX_AGE: u64 = 1_000;
+const MAX_MAX_AGE: u64 = 3_600_000;
+
+/// Adds `@cache` to the fields whose observed latency reaches the threshold,
+/// so that their responses are served from the cache. The slower a field, the
+/// longer it's cached. Fields of the mutation and the subscription, fields and
+/// types marked `@protected`, fields without a resolver and fields that are
+/// already cached are left untouched.
+pub struct SuggestCache {
+    /// The observed latency of the fields, keyed by type and field name.
+    latencies: BTreeMap<(String, String), Duration>,
+    /// Fields faster than this aren't cached.
+    threshold: Duration,
+}
+
+impl SuggestCache {
+    pub fn new(latencies: BTreeMap<(String, String), Duration>, threshold: Duration) -> Self {
+        Self { latencies, threshold }
+    }
+}
+
+/// The `maxAge` given to a field answering in `latency`.
+fn max_age(latency: Duration) -> NonZeroU64 {
+    let max_age = (latency * MAX_AGE_FACTOR).as_millis() as u64;
+    NonZeroU64::new(max_age.clamp(MIN_MAX_AGE, MAX_MAX_AGE)).unwrap_or(NonZeroU64::MIN)
+}
+
+impl Transform for SuggestCache {
+    type Value = Config;
+    type Error = String;
+
+    fn transform(&self, mut config: Self::Value) -> Valid<Self::Value, Self::Error> {
+        let operations = [&config.schema.mutation, &config.schema.subscription]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for ((type_name, field_name), latency) in &self.latencies {
+            if *latency < self.threshold || operations.contains(type_name) {
+                continue;
+            }
+            let Some(type_of) = config.types.get_mut(type_name) else {
+                continue;
+            };
+            if type_of.protected.is_some() || type_of.cache.is_some() {
+                continue;
+            }
+            let Some(field) = type_of.fields.get_mut(field_name) else {
+                continue;
+            };
+            if field.has_resolver() && field.protected.is_none() && field.cache.is_none() {
+                field.cache =
+                    Some(Cache { max_age: max_age(*latency), stale_while_revalidate: None });
+            }
+        }
+
+        Valid::succeed(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    use tailcall_valid::Validator;
+
+    use super::SuggestCache;
+    use crate::core::config::Config;
+    use crate::core::transform::Transform;
+
+    #[test]
+    fn test_suggest_cache() {
+        let config = Config::from_sdl(
+            r#"
+            schema { query: Query, mutation: Mutation }
+            type Query {
+                posts: [Post] @http(url: "http://example.com/posts")
+                users: [User] @http(url: "http://example.com/users")
+                secret: String @http(url: "http://example.com/secret") @protected
+            }
+            type Mutation {
+                createPost: Post @http(url: "http://example.com/posts")
+            }
+            type Post { id: Int, title: String }
+            type User { id: Int, name: String }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+
+        let latencies = [
+            ("Query", "posts", 800),
+            ("Query", "users", 20),
+            ("Query", "secret", 900),
+            ("Mutation", "createPost", 1200),
+        ]
+        .into_iter()
+        .map(|(type_name, field_name, millis)| {
+            (
+                (type_name.to_string(), field_name.to_string()),
+                Duration::from_millis(millis),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+        let config = SuggestCache::new(latencies, Duration::from_millis(100))
+            .transform(config)
+            .to_result()
+            .unwrap();
+
+        insta::assert_snapshot!(config.to_sdl());
+    }
+}
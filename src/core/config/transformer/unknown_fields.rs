@@ -0,0 +1,126 @@
+use tailcall_valid::Valid;
+
+use crate::core::config::{Config, Field, UnknownFields};
+use crate::core::Transform;
+
+pub const UNKNOWN_FIELDS_FIELD_NAME: &str = "_unknownFields";
+
+/// Adds the `_unknownFields: JSON` field to every output object type when the
+/// keys of upstream objects that aren't fields of their type are passed
+/// through, so that clients can select them.
+pub struct PassthroughUnknownFields;
+
+impl Transform for PassthroughUnknownFields {
+    type Value = Config;
+    type Error = String;
+
+    fn transform(&self, mut config: Self::Value) -> Valid<Self::Value, Self::Error> {
+        if config.server.get_unknown_fields() != UnknownFields::Passthrough {
+            return Valid::succeed(config);
+        }
+
+        let input_types = config.input_types();
+        let mut output_types = config.output_types();
+        // the implementations of an interface have to expose its fields too
+        for (type_name, ty) in config.types.iter() {
+            if ty.implements.iter().any(|name| output_types.contains(name)) {
+                output_types.insert(type_name.clone());
+            }
+        }
+
+        for (type_name, ty) in config.types.iter_mut() {
+            if !output_types.contains(type_name)
+                || input_types.contains(type_name)
+                || ty.scalar()
+                || config.schema.query.as_ref() == Some(type_name)
+                || config.schema.mutation.as_ref() == Some(type_name)
+                || config.schema.subscription.as_ref() == Some(type_name)
+            {
+                continue;
+            }
+
+            ty.fields
+                .entry(UNKNOWN_FIELDS_FIELD_NAME.to_string())
+                .or_insert_with(|| Field {
+                    type_of: "JSON".to_string().into(),
+                    doc: Some(
+                        "The keys of the upstream object that aren't fields of the type."
+                            .to_string(),
+                    ),
+                    ..Default::default()
+                });
+        }
+
+        Valid::succeed(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tailcall_valid::Validator;
+
+    use super::{PassthroughUnknownFields, UNKNOWN_FIELDS_FIELD_NAME};
+    use crate::core::config::Config;
+    use crate::core::transform::Transform;
+
+    const SDL: &str = r#"
+        schema @server(unknownFields: passthrough) {
+          query: Query
+        }
+
+        type Query {
+          users(filter: Filter): [User] @http(url: "http://localhost/users")
+        }
+
+        input Filter {
+          name: String
+        }
+
+        interface Node {
+          id: Int
+        }
+
+        type User implements Node {
+          id: Int
+          name: String
+          node: Node
+        }
+
+        type Post implements Node {
+          id: Int
+        }
+    "#;
+
+    fn has_unknown_fields(config: &Config, type_name: &str) -> bool {
+        config.types[type_name]
+            .fields
+            .contains_key(UNKNOWN_FIELDS_FIELD_NAME)
+    }
+
+    #[test]
+    fn test_passthrough() {
+        let config = Config::from_sdl(SDL).to_result().unwrap();
+        let config = PassthroughUnknownFields
+            .transform(config)
+            .to_result()
+            .unwrap();
+
+        assert!(has_unknown_fields(&config, "User"));
+        assert!(has_unknown_fields(&config, "Node"));
+        assert!(has_unknown_fields(&config, "Post"));
+        assert!(!has_unknown_fields(&config, "Query"));
+        assert!(!has_unknown_fields(&config, "Filter"));
+    }
+
+    #[test]
+    fn test_drop() {
+        let sdl = SDL.replace("@server(unknownFields: passthrough)", "");
+        let config = Config::from_sdl(&sdl).to_result().unwrap();
+        let config = PassthroughUnknownFields
+            .transform(config)
+            .to_result()
+            .unwrap();
+
+        assert!(!has_unknown_fields(&config, "User"));
+    }
+}
@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
 
 use futures_util::Future;
 use tokio::sync::broadcast;
+use tokio::time::Instant;
 
 pub trait Key: Send + Sync + Eq + Hash + Clone {}
 impl<A: Send + Sync + Eq + Hash + Clone> Key for A {}
@@ -20,18 +23,37 @@ pub struct Dedupe<Key, Value> {
     size: usize,
     /// When enabled allows the operations to be cached forever.
     persist: bool,
+    /// How long the persisted results are kept, forever when not set.
+    ttl: Option<Duration>,
+    /// How many operations can be in progress at once, unbounded when not set.
+    max_pending: Option<usize>,
+    /// Number of operations in progress.
+    pending: Arc<AtomicUsize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DedupeError {
+    #[error("Too many pending operations, at most {0} can be in progress")]
+    TooManyPending(usize),
 }
 
 /// Represents the current state of the operation.
 enum State<Value> {
-    /// Means that the operation has been executed and the result is stored.
-    Ready(Value),
+    /// Means that the operation has been executed and the result is stored,
+    /// until the instant it expires at if any.
+    Ready(Value, Option<Instant>),
 
     /// Means that the operation is in progress and the result can be sent via
     /// the stored sender whenever it's available in the future.
     Pending(Weak<broadcast::Sender<Value>>),
 }
 
+impl<Value> State<Value> {
+    fn is_expired(&self, now: Instant) -> bool {
+        matches!(self, State::Ready(_, Some(expires_at)) if *expires_at <= now)
+    }
+}
+
 /// Represents the next steps
 enum Step<Value> {
     /// The operation has been executed and the result must be returned.
@@ -42,16 +64,58 @@ enum Step<Value> {
     Await(broadcast::Receiver<Value>),
 
     /// The operation needs to be executed and the result needs to be sent to
-    /// the provided sender.
-    Init(Arc<broadcast::Sender<Value>>),
+    /// the provided sender. It counts as pending for as long as the guard
+    /// lives.
+    Init(Arc<broadcast::Sender<Value>>, PendingGuard),
+
+    /// The operation can't be executed because too many others are pending.
+    Reject(usize),
+}
+
+/// Counts an operation as pending until it's dropped, which happens whether
+/// the operation completes or is cancelled.
+struct PendingGuard(Arc<AtomicUsize>);
+
+impl PendingGuard {
+    fn new(pending: Arc<AtomicUsize>) -> Self {
+        pending.fetch_add(1, Ordering::SeqCst);
+        Self(pending)
+    }
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl<K: Key, V: Value> Dedupe<K, V> {
     pub fn new(size: usize, persist: bool) -> Self {
-        Self { cache: Arc::new(Mutex::new(HashMap::new())), size, persist }
+        Self {
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            size,
+            persist,
+            ttl: None,
+            max_pending: None,
+            pending: Arc::new(AtomicUsize::new(0)),
+        }
     }
 
-    pub async fn dedupe<'a, Fn, Fut>(&'a self, key: &'a K, or_else: Fn) -> V
+    /// Expires the persisted results after the given duration, if any. Expired
+    /// results are dropped when their key is next accessed, or when another
+    /// result is persisted.
+    pub fn with_ttl(self, ttl: Option<Duration>) -> Self {
+        Self { ttl, ..self }
+    }
+
+    /// Limits the number of operations in progress at once, if any. Operations
+    /// on a key that is already in progress still wait for its result, while
+    /// new ones fail with [DedupeError::TooManyPending] until others complete.
+    pub fn with_max_pending(self, max_pending: Option<usize>) -> Self {
+        Self { max_pending, ..self }
+    }
+
+    pub async fn dedupe<'a, Fn, Fut>(&'a self, key: &'a K, or_else: Fn) -> Result<V, DedupeError>
     where
         Fn: FnOnce() -> Fut,
         Fut: Future<Output = V>,
@@ -69,20 +133,30 @@ impl<K: Key, V: Value> Dedupe<K, V> {
                         continue;
                     }
                 },
-                Step::Init(tx) => {
+                Step::Init(tx, _pending) => {
                     let value = or_else().await;
                     let mut guard = self.cache.lock().unwrap();
                     if self.persist {
-                        guard.insert(key.to_owned(), State::Ready(value.clone()));
+                        // the clock is only read when results expire, it isn't
+                        // available on every target
+                        let expires_at = self.ttl.map(|ttl| {
+                            let now = Instant::now();
+                            guard.retain(|_, state| !state.is_expired(now));
+                            now + ttl
+                        });
+                        guard.insert(key.to_owned(), State::Ready(value.clone(), expires_at));
                     } else {
                         guard.remove(key);
                     }
                     let _ = tx.send(value.clone());
                     value
                 }
+                Step::Reject(max_pending) => {
+                    return Err(DedupeError::TooManyPending(max_pending));
+                }
             };
 
-            return value;
+            return Ok(value);
         }
     }
 
@@ -91,7 +165,12 @@ impl<K: Key, V: Value> Dedupe<K, V> {
 
         if let Some(state) = this.get(key) {
             match state {
-                State::Ready(value) => return Step::Return(value.clone()),
+                State::Ready(value, expires_at) => {
+                    // An expired result is a miss, and gets replaced below
+                    if !expires_at.is_some_and(|expires_at| expires_at <= Instant::now()) {
+                        return Step::Return(value.clone());
+                    }
+                }
                 State::Pending(tx) => {
                     // We can upgrade from Weak to Arc only in case when
                     // original tx is still alive
@@ -103,6 +182,13 @@ impl<K: Key, V: Value> Dedupe<K, V> {
             }
         }
 
+        if let Some(max_pending) = self.max_pending {
+            if self.pending.load(Ordering::SeqCst) >= max_pending {
+                return Step::Reject(max_pending);
+            }
+        }
+        let pending = PendingGuard::new(self.pending.clone());
+
         let (tx, _) = broadcast::channel(self.size);
         let tx = Arc::new(tx);
         // Store a Weak version of tx and pass actual tx to further handling
@@ -110,7 +196,7 @@ impl<K: Key, V: Value> Dedupe<K, V> {
         // Only single `strong` reference to tx should exist so we can
         // understand when the execution is still alive and we'll get the response
         this.insert(key.to_owned(), State::Pending(Arc::downgrade(&tx)));
-        Step::Init(tx)
+        Step::Init(tx, pending)
     }
 }
 
@@ -120,6 +206,16 @@ impl<K: Key, V: Value, E: Value> DedupeResult<K, V, E> {
     pub fn new(persist: bool) -> Self {
         Self(Dedupe::new(1, persist))
     }
+
+    /// See [Dedupe::with_ttl].
+    pub fn with_ttl(self, ttl: Option<Duration>) -> Self {
+        Self(self.0.with_ttl(ttl))
+    }
+
+    /// See [Dedupe::with_max_pending].
+    pub fn with_max_pending(self, max_pending: Option<usize>) -> Self {
+        Self(self.0.with_max_pending(max_pending))
+    }
 }
 
 impl<K: Key, V: Value, E: Value> DedupeResult<K, V, E> {
@@ -127,8 +223,12 @@ impl<K: Key, V: Value, E: Value> DedupeResult<K, V, E> {
     where
         Fn: FnOnce() -> Fut,
         Fut: Future<Output = Result<V, E>>,
+        E: From<DedupeError>,
     {
-        self.0.dedupe(key, or_else).await
+        self.0
+            .dedupe(key, or_else)
+            .await
+            .unwrap_or_else(|error| Err(error.into()))
     }
 }
 
@@ -147,28 +247,67 @@ mod tests {
     #[tokio::test]
     async fn test_no_key() {
         let cache = Arc::new(Dedupe::<u64, u64>::new(1000, true));
-        let actual = cache.dedupe(&1, || Box::pin(async { 1 })).await;
+        let actual = cache.dedupe(&1, || Box::pin(async { 1 })).await.unwrap();
         assert_eq!(actual, 1);
     }
 
     #[tokio::test]
     async fn test_with_key() {
         let cache = Arc::new(Dedupe::<u64, u64>::new(1000, true));
-        cache.dedupe(&1, || Box::pin(async { 1 })).await;
+        cache.dedupe(&1, || Box::pin(async { 1 })).await.unwrap();
 
-        let actual = cache.dedupe(&1, || Box::pin(async { 2 })).await;
+        let actual = cache.dedupe(&1, || Box::pin(async { 2 })).await.unwrap();
         assert_eq!(actual, 1);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_with_ttl() {
+        let cache = Dedupe::<u64, u64>::new(1000, true).with_ttl(Some(Duration::from_secs(10)));
+        cache.dedupe(&1, || Box::pin(async { 1 })).await.unwrap();
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        let actual = cache.dedupe(&1, || Box::pin(async { 2 })).await.unwrap();
+        assert_eq!(actual, 1);
+
+        tokio::time::advance(Duration::from_secs(6)).await;
+        let actual = cache.dedupe(&1, || Box::pin(async { 3 })).await.unwrap();
+        assert_eq!(actual, 3);
+
+        let actual = cache.dedupe(&1, || Box::pin(async { 4 })).await.unwrap();
+        assert_eq!(actual, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sweep_expired() {
+        let cache = Dedupe::<u64, u64>::new(1000, true).with_ttl(Some(Duration::from_secs(10)));
+        cache.dedupe(&1, || Box::pin(async { 1 })).await.unwrap();
+        cache.dedupe(&2, || Box::pin(async { 2 })).await.unwrap();
+
+        tokio::time::advance(Duration::from_secs(11)).await;
+        cache.dedupe(&3, || Box::pin(async { 3 })).await.unwrap();
+
+        let keys = cache
+            .cache
+            .lock()
+            .unwrap()
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![3]);
+    }
+
     #[tokio::test]
     async fn test_with_multi_get() {
         let cache = Arc::new(Dedupe::<u64, u64>::new(1000, true));
 
         for i in 0..100 {
-            cache.dedupe(&1, || Box::pin(async move { i })).await;
+            cache
+                .dedupe(&1, || Box::pin(async move { i }))
+                .await
+                .unwrap();
         }
 
-        let actual = cache.dedupe(&1, || Box::pin(async { 2 })).await;
+        let actual = cache.dedupe(&1, || Box::pin(async { 2 })).await.unwrap();
         assert_eq!(actual, 0);
     }
 
@@ -250,7 +389,8 @@ mod tests {
             .dedupe(&1, move || async move {
                 sleep(Duration::from_millis(100)).await;
             })
-            .await;
+            .await
+            .unwrap();
     }
 
     #[tokio::test]
@@ -283,7 +423,7 @@ mod tests {
         task_1.abort();
 
         let actual = task_2.await.unwrap();
-        assert_eq!(actual, 200)
+        assert_eq!(actual, Ok(200))
     }
 
     // TODO: This is a failing test
@@ -395,4 +535,68 @@ mod tests {
         let actual = status.lock().unwrap().deref().to_owned();
         assert_eq!(actual, Status { call_1: false, call_2: false })
     }
+
+    #[tokio::test(worker_threads = 4, flavor = "multi_thread")]
+    async fn test_max_pending() {
+        let cache =
+            Arc::new(DedupeResult::<u64, u64, DedupeError>::new(false).with_max_pending(Some(4)));
+        let started = Arc::new(tokio::sync::Barrier::new(5));
+        let release = Arc::new(tokio::sync::Semaphore::new(0));
+
+        // Fill up the cap with operations that wait to be released
+        let mut pending = (0..4)
+            .map(|key| {
+                let cache = cache.clone();
+                let started = started.clone();
+                let release = release.clone();
+                tokio::spawn(async move {
+                    cache
+                        .dedupe(&key, || async move {
+                            started.wait().await;
+                            release.acquire().await.unwrap().forget();
+                            Ok(key)
+                        })
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+        started.wait().await;
+
+        // New keys are rejected while the cap is reached
+        let rejected = (4..20).map(|key| {
+            let cache = cache.clone();
+            tokio::spawn(async move { cache.dedupe(&key, || async move { Ok(key) }).await })
+        });
+        for result in futures_util::future::join_all(rejected).await {
+            assert_eq!(result.unwrap(), Err(DedupeError::TooManyPending(4)));
+        }
+
+        // A key that is already in progress still waits for its result
+        let cache_1 = cache.clone();
+        let joined = tokio::spawn(async move { cache_1.dedupe(&0, || async { Ok(100) }).await });
+        let subscribers = || match cache.0.cache.lock().unwrap().get(&0) {
+            Some(State::Pending(tx)) => tx.upgrade().map_or(0, |tx| tx.receiver_count()),
+            _ => 0,
+        };
+        while subscribers() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // Cancelling a pending operation frees its slot
+        let cancelled = pending.pop().unwrap();
+        cancelled.abort();
+        assert!(cancelled.await.unwrap_err().is_cancelled());
+        let actual = cache.dedupe(&20, || async { Ok(20) }).await;
+        assert_eq!(actual, Ok(20));
+
+        release.add_permits(3);
+        for (key, task) in pending.into_iter().enumerate() {
+            assert_eq!(task.await.unwrap(), Ok(key as u64));
+        }
+        assert_eq!(joined.await.unwrap(), Ok(0));
+
+        // Completed operations free their slots as well
+        let actual = cache.dedupe(&21, || async { Ok(21) }).await;
+        assert_eq!(actual, Ok(21));
+    }
 }
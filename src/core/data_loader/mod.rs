@@ -8,5 +8,5 @@ mod storage;
 // Making public as it is unused and clippy gives warning.
 pub use cache::LruCache;
 pub use data_loader::DataLoader;
-pub use dedupe::DedupeResult;
+pub use dedupe::{DedupeError, DedupeResult};
 pub use loader::Loader;
@@ -21,6 +21,7 @@ pub struct RequestSample {
     pub field_name: String,
     pub operation_type: GraphQLOperationType,
     pub headers: Option<BTreeMap<String, String>>,
+    pub root_path: Vec<String>,
 }
 
 impl RequestSample {
@@ -33,9 +34,28 @@ impl RequestSample {
             req_body: Default::default(),
             headers: Default::default(),
             operation_type: Default::default(),
+            root_path: Default::default(),
         }
     }
 
+    /// Descends into the response body along `root_path`, so that the types
+    /// are inferred from the value wrapped in an envelope like `{ "data": ...
+    /// }`. The body is null if the path doesn't exist.
+    pub fn with_root_path(mut self, root_path: &[String]) -> Self {
+        if !root_path.is_empty() {
+            self.res_body = root_path
+                .iter()
+                .try_fold(&self.res_body, |value, key| match value {
+                    Value::Array(values) => values.get(key.parse::<usize>().ok()?),
+                    _ => value.get(key),
+                })
+                .cloned()
+                .unwrap_or_default();
+            self.root_path = root_path.to_vec();
+        }
+        self
+    }
+
     pub fn with_method(mut self, method: Method) -> Self {
         self.method = method;
         self
@@ -93,6 +113,14 @@ impl Transform for FromJsonGenerator<'_> {
         let type_name_gen = self.type_name_generator;
 
         Valid::from_iter(config_gen_req, |sample| {
+            if !sample.root_path.is_empty() && sample.res_body.is_null() {
+                return Valid::fail(format!(
+                    "Root path '{}' doesn't exist in the sample of '{}'",
+                    sample.root_path.join("."),
+                    sample.field_name
+                ));
+            }
+
             let (existing_name, suggested_name) = match sample.operation_type {
                 GraphQLOperationType::Query => (
                     GraphQLOperationType::Query
@@ -176,4 +204,24 @@ mod tests {
         insta::assert_snapshot!(config.to_sdl());
         Ok(())
     }
+
+    #[tokio::test]
+    async fn generate_mutation_input_type_from_json() -> anyhow::Result<()> {
+        let JsonFixture { request, response, is_mutation, field_name } =
+            JsonFixture::read("src/core/generator/tests/fixtures/json/create_post.json").await?;
+        let request_samples = [RequestSample::new(request.url, response, field_name)
+            .with_method(request.method)
+            .with_headers(request.headers)
+            .with_is_mutation(is_mutation)
+            .with_req_body(request.body.unwrap_or_default())];
+
+        let config =
+            FromJsonGenerator::new(&request_samples, &NameGenerator::new("T"), "Query", &None)
+                .pipe(Preset::default())
+                .generate()
+                .to_result()?;
+
+        insta::assert_snapshot!(config.to_sdl());
+        Ok(())
+    }
 }
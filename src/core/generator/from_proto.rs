@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap};
 
 use anyhow::{bail, Result};
 use derive_setters::Setters;
@@ -31,8 +31,8 @@ struct Context {
     /// Root GraphQL query type
     query: String,
 
-    /// Set of visited map types
-    map_types: HashSet<String>,
+    /// Visited map types along with the GraphQL type of their values
+    map_types: HashMap<String, String>,
 
     /// Optional field to store source code information, including comments, for
     /// each entity.
@@ -240,8 +240,20 @@ impl Context {
                 // map types in protobuf are encoded as nested type
                 // https://protobuf.dev/programming-guides/encoding/#maps
                 // since we encode it as JSON scalar type in graphQL
-                // record that this type is map and ignore it
-                self.map_types.insert(msg_type.id());
+                // record that this type is map with the type of its values and ignore it
+                let value_type = message
+                    .field
+                    .iter()
+                    .find(|field| field.name() == "value")
+                    .map(|field| match &field.type_name {
+                        Some(type_name) => Ok(graphql_type_from_ref(type_name)?
+                            .into_object_type()
+                            .to_string()),
+                        None => Ok(convert_primitive_type(field.r#type().as_str_name())),
+                    })
+                    .transpose()?
+                    .unwrap_or_else(|| "JSON".to_string());
+                self.map_types.insert(msg_type.id(), value_type);
                 continue;
             }
 
@@ -272,6 +284,7 @@ impl Context {
                     .into_field();
 
                 let mut cfg_field = Field::default();
+                let mut map_value_type = None;
 
                 cfg_field.type_of = match field.label() {
                     Label::Optional => cfg_field.type_of,
@@ -285,9 +298,10 @@ impl Context {
                     // it's done by checking that we've seen this type before
                     // inside the nested type. It works only if we explore nested types
                     // before the current type
-                    if self.map_types.contains(&type_name[1..]) {
+                    if let Some(value_type) = self.map_types.get(&type_name[1..]) {
                         // override type with single scalar
                         cfg_field.type_of = "JSON".to_string().into();
+                        map_value_type = Some(value_type.clone());
                     } else {
                         // for non-primitive types
                         let type_of = graphql_type_from_ref(type_name)?
@@ -304,7 +318,11 @@ impl Context {
 
                 let field_path =
                     PathBuilder::new(&msg_path).extend(PathField::Field, field_index as i32);
-                cfg_field.doc = self.comments_builder.get_comments(&field_path);
+                cfg_field.doc = self.comments_builder.get_comments(&field_path).or_else(|| {
+                    map_value_type.map(|value_type| {
+                        format!("Map of `{}` values keyed by string.", value_type)
+                    })
+                });
 
                 if let Some(oneof_index) = field.oneof_index {
                     oneof_fields[oneof_index as usize].push((field_name.to_string(), cfg_field));
@@ -376,6 +394,9 @@ impl Context {
                     dedupe: None,
                     select: None,
                     on_response_body: None,
+                    validate: false,
+                    service: None,
+                    protocol: Default::default(),
                 })
                 .into();
 
@@ -10,6 +10,7 @@ use url::Url;
 use super::from_proto::from_proto;
 use super::proto::connect_rpc::ConnectRPC;
 use super::{FromJsonGenerator, NameGenerator, RequestSample, PREFIX};
+use crate::core::config::transformer::InferNullability;
 use crate::core::config::{self, Config, ConfigModule, Link, LinkType};
 use crate::core::http::Method;
 use crate::core::merge_right::MergeRight;
@@ -25,6 +26,10 @@ pub struct Generator {
     mutation: Option<String>,
     inputs: Vec<Input>,
     type_name_prefix: String,
+    /// path of the value to infer the types from in the json samples.
+    root_path: Vec<String>,
+    /// marks the fields as required or nullable based on the json samples.
+    infer_nullability: bool,
     transformers: Vec<Box<dyn Transform<Value = Config, Error = String>>>,
 }
 
@@ -64,6 +69,8 @@ impl Generator {
             mutation: None,
             inputs: Vec::new(),
             type_name_prefix: PREFIX.into(),
+            root_path: Vec::new(),
+            infer_nullability: false,
             transformers: Default::default(),
         }
     }
@@ -108,6 +115,7 @@ impl Generator {
     pub fn generate(&self, use_transformers: bool) -> anyhow::Result<ConfigModule> {
         let mut config: Config = Config::default();
         let type_name_generator = NameGenerator::new(&self.type_name_prefix);
+        let mut json_samples = Vec::new();
 
         for input in self.inputs.iter() {
             match input {
@@ -131,10 +139,18 @@ impl Generator {
                     .with_method(method.to_owned())
                     .with_headers(headers.to_owned())
                     .with_is_mutation(is_mutation.to_owned())
-                    .with_req_body(req_body.to_owned());
-
-                    config = config
-                        .merge_right(self.generate_from_json(&type_name_generator, &[req_sample])?);
+                    .with_req_body(req_body.to_owned())
+                    .with_root_path(&self.root_path);
+
+                    config = config.merge_right(self.generate_from_json(
+                        &type_name_generator,
+                        std::slice::from_ref(&req_sample),
+                    )?);
+                    json_samples.push((
+                        req_sample.operation_type.clone(),
+                        req_sample.field_name,
+                        req_sample.res_body,
+                    ));
                 }
                 Input::Proto { metadata, url, connect_rpc } => {
                     let proto_config = self.generate_from_proto(metadata, &self.query, url)?;
@@ -154,6 +170,20 @@ impl Generator {
             }
         }
 
+        // Runs once the types are merged and renamed, so that the observations
+        // of all the samples of a type are combined.
+        if self.infer_nullability {
+            let samples = json_samples
+                .iter()
+                .map(|(operation_type, field_name, value)| {
+                    (operation_type.clone(), field_name.as_str(), value)
+                })
+                .collect();
+            config = InferNullability::new(samples)
+                .transform(config)
+                .to_result()?;
+        }
+
         Ok(ConfigModule::from(config))
     }
 }
@@ -187,6 +217,7 @@ pub mod test {
 
     use super::Generator;
     use crate::core::config::transformer::Preset;
+    use crate::core::config::Resolver;
     use crate::core::generator::generator::Input;
     use crate::core::http::Method;
     use crate::core::proto_reader::ProtoMetadata;
@@ -270,6 +301,7 @@ pub mod test {
                 metadata: ProtoMetadata {
                     descriptor_set: set,
                     path: "../../../tailcall-fixtures/fixtures/protobuf/news.proto".to_string(),
+                    sources: Default::default(),
                 },
                 url: "http://localhost:50051".to_string(),
                 connect_rpc: None,
@@ -324,6 +356,7 @@ pub mod test {
             metadata: ProtoMetadata {
                 descriptor_set: proto_set,
                 path: "../../../tailcall-fixtures/fixtures/protobuf/news.proto".to_string(),
+                sources: Default::default(),
             },
             url: "http://localhost:50051".to_string(),
             connect_rpc: None,
@@ -361,6 +394,46 @@ pub mod test {
         Ok(())
     }
 
+    #[test]
+    fn should_infer_relations_from_jsons() -> anyhow::Result<()> {
+        let posts = serde_json::json!([
+            {"id": 1, "userId": 1, "title": "Hello"},
+            {"id": 2, "userId": 2, "title": "World"}
+        ]);
+        let users = serde_json::json!([
+            {"id": 1, "name": "Leanne"},
+            {"id": 2, "name": "Ervin"}
+        ]);
+        let inputs = [("posts", posts), ("users", users)]
+            .into_iter()
+            .map(|(field_name, res_body)| Input::Json {
+                url: Url::parse(&format!("https://example.com/{field_name}")).unwrap(),
+                method: Method::GET,
+                req_body: Value::Null,
+                res_body,
+                field_name: field_name.to_string(),
+                is_mutation: false,
+                headers: None,
+            })
+            .collect();
+
+        let cfg_module = Generator::default()
+            .inputs(inputs)
+            .transformers(vec![Box::new(Preset::default().infer_relations(true))])
+            .generate(true)?;
+
+        let post = cfg_module.config().types.get("Post").unwrap();
+        let user = post.fields.get("user").unwrap();
+        let Some(Resolver::Http(http)) = user.resolvers.first() else {
+            panic!("expected an @http resolver on Post.user");
+        };
+
+        assert_eq!(user.type_of.name(), "User");
+        assert_eq!(http.url, "https://example.com/users");
+        assert_eq!(http.batch_key, vec!["id".to_string()]);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn generate_from_config_from_multiple_jsons() -> anyhow::Result<()> {
         let mut inputs = vec![];
@@ -32,6 +32,14 @@ impl OperationTypeGenerator {
         let http_directive_gen = HttpDirectiveGenerator::new(&request_sample.url);
         let mut http_resolver = http_directive_gen.generate_http_directive(&mut field);
 
+        // select the value the types were inferred from out of the envelope.
+        if !request_sample.root_path.is_empty() {
+            http_resolver.select = Some(serde_json::Value::String(format!(
+                "{{{{.{}}}}}",
+                request_sample.root_path.join(".")
+            )));
+        }
+
         if let GraphQLOperationType::Mutation = request_sample.operation_type {
             // generate the input type.
             let root_ty = TypeGenerator::new(name_generator)
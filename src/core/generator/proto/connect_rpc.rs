@@ -55,6 +55,7 @@ impl From<Grpc> for Http {
 
         Self {
             url: new_url,
+            service: grpc.service,
             body,
             method: crate::core::http::Method::POST,
             headers,
@@ -85,6 +86,9 @@ mod tests {
             dedupe: Default::default(),
             select: Default::default(),
             on_response_body: Default::default(),
+            validate: Default::default(),
+            protocol: Default::default(),
+            service: Default::default(),
         };
 
         let http = Http::from(grpc);
@@ -105,6 +109,9 @@ mod tests {
             dedupe: Default::default(),
             select: Default::default(),
             on_response_body: Default::default(),
+            validate: Default::default(),
+            protocol: Default::default(),
+            service: Default::default(),
         };
 
         let http = Http::from(grpc);
@@ -123,6 +130,9 @@ mod tests {
             dedupe: Default::default(),
             select: Default::default(),
             on_response_body: Default::default(),
+            validate: Default::default(),
+            protocol: Default::default(),
+            service: Default::default(),
         };
 
         let http = Http::from(grpc);
@@ -150,6 +160,9 @@ mod tests {
             dedupe: Some(true),
             select: Some(Value::String("select_value".to_string())),
             on_response_body: Some("on_response_body_value".to_string()),
+            validate: Default::default(),
+            protocol: Default::default(),
+            service: Default::default(),
         };
 
         let http = Http::from(grpc);
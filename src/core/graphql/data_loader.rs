@@ -8,7 +8,7 @@ use async_graphql::futures_util::future::join_all;
 
 use crate::core::config::Batch;
 use crate::core::data_loader::{DataLoader, Loader};
-use crate::core::http::{DataLoaderRequest, Response};
+use crate::core::http::{DataLoaderRequest, Response, UpstreamCalls};
 use crate::core::runtime::TargetRuntime;
 
 pub struct GraphqlDataLoader {
@@ -42,11 +42,17 @@ impl Loader<DataLoaderRequest> for GraphqlDataLoader {
         keys: &[DataLoaderRequest],
     ) -> async_graphql::Result<HashMap<DataLoaderRequest, Self::Value>, Self::Error> {
         if self.batch {
-            let batched_req = create_batched_request(keys);
+            // the requests that reached their limit of upstream calls are left out
+            let keys = UpstreamCalls::count_batch(keys, DataLoaderRequest::upstream_calls);
+            if keys.is_empty() {
+                return Ok(HashMap::new());
+            }
+            let batched_req = create_batched_request(&keys);
             let result = self.runtime.http.execute(batched_req).await?.to_json();
-            let hashmap = extract_responses(result, keys);
+            let hashmap = extract_responses(result, &keys);
             Ok(hashmap)
         } else {
+            let keys = UpstreamCalls::count_each(keys, DataLoaderRequest::upstream_calls);
             let results = keys.iter().map(|key| async {
                 let result = self.runtime.http.execute(key.to_request()).await;
                 (key.clone(), result)
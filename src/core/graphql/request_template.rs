@@ -63,7 +63,8 @@ impl RequestTemplate {
         let mut header_map = HeaderMap::new();
 
         for (k, v) in &self.headers {
-            if let Ok(header_value) = HeaderValue::from_str(&v.render_graphql(ctx)) {
+            if let Ok(mut header_value) = HeaderValue::from_str(&v.render_graphql(ctx)) {
+                header_value.set_sensitive(v.has_secrets());
                 header_map.insert(k, header_value);
             }
         }
@@ -14,7 +14,7 @@ use crate::core::config::group_by::GroupBy;
 use crate::core::config::Batch;
 use crate::core::data_loader::{DataLoader, Loader};
 use crate::core::grpc::request::create_grpc_request;
-use crate::core::http::Response;
+use crate::core::http::{Response, UpstreamCalls};
 use crate::core::json::JsonLike;
 use crate::core::runtime::TargetRuntime;
 
@@ -38,7 +38,15 @@ impl GrpcDataLoader {
     ) -> anyhow::Result<HashMap<DataLoaderRequest, Response<async_graphql::Value>>> {
         let results = keys.iter().map(|key| async {
             let result = match key.to_request() {
-                Ok(req) => execute_grpc_request(&self.runtime, &self.operation, req).await,
+                Ok(req) => {
+                    execute_grpc_request(
+                        &self.runtime,
+                        &self.operation,
+                        &key.template.protocol,
+                        req,
+                    )
+                    .await
+                }
                 Err(error) => Err(error),
             };
 
@@ -69,14 +77,17 @@ impl GrpcDataLoader {
             .convert_multiple_inputs(inputs, group_by.key())?;
 
         let first_request = keys[0].clone();
+        let protocol = first_request.template.protocol;
         let multiple_request = create_grpc_request(
             first_request.template.url,
             first_request.template.headers,
+            &protocol,
             multiple_body,
         );
 
         let response =
-            execute_grpc_request(&self.runtime, &self.operation, multiple_request).await?;
+            execute_grpc_request(&self.runtime, &self.operation, &protocol, multiple_request)
+                .await?;
 
         let path = &group_by.path();
         let response_body = response.body.group_by(path);
@@ -108,11 +119,17 @@ impl Loader<DataLoaderRequest> for GrpcDataLoader {
         keys: &[DataLoaderRequest],
     ) -> async_graphql::Result<HashMap<DataLoaderRequest, Self::Value>, Self::Error> {
         if let Some(group_by) = &self.group_by {
-            self.load_with_group_by(group_by, keys)
+            // the requests that reached their limit of upstream calls are left out
+            let keys = UpstreamCalls::count_batch(keys, DataLoaderRequest::upstream_calls);
+            if keys.is_empty() {
+                return Ok(HashMap::new());
+            }
+            self.load_with_group_by(group_by, &keys)
                 .await
                 .map_err(Arc::new)
         } else {
-            self.load_dedupe_only(keys).await.map_err(Arc::new)
+            let keys = UpstreamCalls::count_each(keys, DataLoaderRequest::upstream_calls);
+            self.load_dedupe_only(&keys).await.map_err(Arc::new)
         }
     }
 }
@@ -5,11 +5,15 @@ use anyhow::Result;
 use tailcall_hasher::TailcallHasher;
 
 use super::request_template::RenderedRequestTemplate;
+use crate::core::http::UpstreamCalls;
 
-#[derive(Debug, Clone, Eq)]
+#[derive(Debug, Clone)]
 pub struct DataLoaderRequest {
     pub template: RenderedRequestTemplate,
     batch_headers: BTreeSet<String>,
+    /// counts the call made for the request, so that the keys of different
+    /// requests are loaded for each of them.
+    upstream_calls: Option<UpstreamCalls>,
 }
 
 impl Hash for DataLoaderRequest {
@@ -23,6 +27,7 @@ impl Hash for DataLoaderRequest {
                 value.hash(state);
             }
         }
+        self.upstream_calls.hash(state);
     }
 }
 
@@ -40,9 +45,19 @@ impl PartialEq for DataLoaderRequest {
     }
 }
 
+impl Eq for DataLoaderRequest {}
+
 impl DataLoaderRequest {
     pub fn new(template: RenderedRequestTemplate, batch_headers: BTreeSet<String>) -> Self {
-        Self { template, batch_headers }
+        Self { template, batch_headers, upstream_calls: None }
+    }
+
+    pub fn with_upstream_calls(self, upstream_calls: Option<UpstreamCalls>) -> Self {
+        Self { upstream_calls, ..self }
+    }
+
+    pub fn upstream_calls(&self) -> Option<&UpstreamCalls> {
+        self.upstream_calls.as_ref()
     }
 
     pub fn to_request(&self) -> Result<reqwest::Request> {
@@ -111,6 +126,7 @@ mod tests {
             headers: HeaderMap::new(),
             operation: get_protobuf_op().await,
             body: "{}".to_owned(),
+            protocol: Default::default(),
         };
 
         let dl_req_1 = DataLoaderRequest::new(tmpl.clone(), batch_headers.clone());
@@ -130,6 +146,7 @@ mod tests {
             )]),
             operation: get_protobuf_op().await,
             body: "{}".to_owned(),
+            protocol: Default::default(),
         };
         let tmpl_2 = tmpl_1.clone();
 
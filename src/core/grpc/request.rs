@@ -1,15 +1,94 @@
+use std::time::Duration;
+
 use anyhow::{bail, Result};
-use http::{HeaderMap, Method};
+use http::header::{HeaderName, HeaderValue};
+use http::{HeaderMap, Method, StatusCode};
+use hyper::body::Bytes;
+use prost::bytes::BufMut;
 use reqwest::Request;
+use serde::Deserialize;
+use tonic::{Code, Status};
 use url::Url;
 
 use super::protobuf::ProtobufOperation;
+use crate::core::config::GrpcProtocol;
 use crate::core::http::Response;
 use crate::core::runtime::TargetRuntime;
 
 pub static GRPC_STATUS: &str = "grpc-status";
+pub static GRPC_TIMEOUT: &str = "grpc-timeout";
+
+/// Flag of the gRPC-Web frames that hold the trailers instead of a message.
+const GRPC_WEB_TRAILERS_FLAG: u8 = 0x80;
+
+/// The codes of the Connect errors, in the order of the gRPC codes starting
+/// from `Cancelled`.
+const CONNECT_CODES: [&str; 16] = [
+    "canceled",
+    "unknown",
+    "invalid_argument",
+    "deadline_exceeded",
+    "not_found",
+    "already_exists",
+    "permission_denied",
+    "resource_exhausted",
+    "failed_precondition",
+    "aborted",
+    "out_of_range",
+    "unimplemented",
+    "internal",
+    "unavailable",
+    "data_loss",
+    "unauthenticated",
+];
+
+/// Parses a `grpc-timeout` value, made of at most 8 digits followed by one of
+/// the units `H`, `M`, `S`, `m`, `u` or `n`.
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let unit_at = value
+        .len()
+        .checked_sub(1)
+        .filter(|i| value.is_char_boundary(*i))?;
+    let (amount, unit) = value.split_at(unit_at);
+    if amount.is_empty() || amount.len() > 8 {
+        return None;
+    }
+    let amount = amount.parse::<u64>().ok()?;
+
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 60 * 60)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Formats the duration as a `grpc-timeout` value in milliseconds, or in
+/// seconds if the milliseconds don't fit in 8 digits.
+pub fn to_grpc_timeout(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 100_000_000 {
+        format!("{millis}m")
+    } else {
+        format!("{}S", duration.as_secs().min(99_999_999))
+    }
+}
+
+/// Creates the request of the call from the length-prefixed message, that is
+/// sent without its prefix to Connect upstreams.
+pub fn create_grpc_request(
+    url: Url,
+    headers: HeaderMap,
+    protocol: &GrpcProtocol,
+    mut body: Vec<u8>,
+) -> Request {
+    if *protocol == GrpcProtocol::Connect {
+        body.drain(..5);
+    }
 
-pub fn create_grpc_request(url: Url, headers: HeaderMap, body: Vec<u8>) -> Request {
     let mut req = Request::new(Method::POST, url);
     req.headers_mut().extend(headers.clone());
     req.body_mut().replace(body.into());
@@ -20,9 +99,14 @@ pub fn create_grpc_request(url: Url, headers: HeaderMap, body: Vec<u8>) -> Reque
 pub async fn execute_grpc_request(
     runtime: &TargetRuntime,
     operation: &ProtobufOperation,
+    protocol: &GrpcProtocol,
     request: Request,
 ) -> Result<Response<async_graphql::Value>> {
-    let response = runtime.http2_only.execute(request).await?;
+    let response = match protocol {
+        GrpcProtocol::Grpc => runtime.http2_only.execute(request).await?,
+        GrpcProtocol::GrpcWeb => from_grpc_web(runtime.http.execute(request).await?)?,
+        GrpcProtocol::Connect => from_connect(runtime.http.execute(request).await?)?,
+    };
 
     let grpc_status = response
         .headers
@@ -39,9 +123,80 @@ pub async fn execute_grpc_request(
     bail!("Failed to execute request");
 }
 
+/// Turns the response of a gRPC-Web call into the one of a gRPC call: the
+/// first message frame becomes the body and the trailers frame is moved to
+/// the headers.
+fn from_grpc_web(mut response: Response<Bytes>) -> Result<Response<Bytes>> {
+    if !response.status.is_success() {
+        return Ok(response);
+    }
+
+    let body = std::mem::take(&mut response.body);
+    let mut message = None;
+    let mut offset = 0;
+    while offset < body.len() {
+        let Some(prefix) = body.get(offset..offset + 5) else {
+            bail!("Malformed gRPC-Web frame");
+        };
+        let end =
+            offset + 5 + u32::from_be_bytes([prefix[1], prefix[2], prefix[3], prefix[4]]) as usize;
+        if end > body.len() {
+            bail!("Malformed gRPC-Web frame");
+        }
+
+        if prefix[0] & GRPC_WEB_TRAILERS_FLAG != 0 {
+            for line in std::str::from_utf8(&body[offset + 5..end])?.split("\r\n") {
+                if let Some((name, value)) = line.split_once(':') {
+                    response.headers.insert(
+                        HeaderName::from_bytes(name.trim().to_ascii_lowercase().as_bytes())?,
+                        HeaderValue::from_str(value.trim())?,
+                    );
+                }
+            }
+        } else if message.is_none() {
+            message = Some(body.slice(offset..end));
+        }
+        offset = end;
+    }
+    response.body = message.unwrap_or_default();
+
+    Ok(response)
+}
+
+#[derive(Deserialize)]
+struct ConnectError {
+    code: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Turns the response of a unary Connect call into the one of a gRPC call: the
+/// message is length-prefixed, and an error is moved to the `grpc-status`
+/// headers of a successful response, as gRPC upstreams send it.
+fn from_connect(mut response: Response<Bytes>) -> Result<Response<Bytes>> {
+    if response.status.is_success() {
+        let mut body = Vec::with_capacity(response.body.len() + 5);
+        body.put_u8(0);
+        body.put_u32(response.body.len() as u32);
+        body.put_slice(&response.body);
+        response.body = body.into();
+    } else if let Ok(error) = serde_json::from_slice::<ConnectError>(&response.body) {
+        let code = CONNECT_CODES
+            .iter()
+            .position(|code| *code == error.code)
+            .map_or(Code::Unknown, |index| Code::from_i32(index as i32 + 1));
+        Status::new(code, error.message).add_header(&mut response.headers)?;
+        response.status = StatusCode::OK;
+        response.body = Bytes::default();
+    }
+
+    Ok(response)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
+    use std::time::Duration;
 
     use anyhow::Result;
     use async_trait::async_trait;
@@ -54,8 +209,9 @@ mod tests {
     use tonic::{Code, Status};
 
     use crate::core::blueprint::GrpcMethod;
+    use crate::core::config::GrpcProtocol;
     use crate::core::grpc::protobuf::{ProtobufOperation, ProtobufSet};
-    use crate::core::grpc::request::execute_grpc_request;
+    use crate::core::grpc::request::{execute_grpc_request, parse_grpc_timeout, to_grpc_timeout};
     use crate::core::http::Response;
     use crate::core::ir::Error;
     use crate::core::runtime::TargetRuntime;
@@ -65,6 +221,10 @@ mod tests {
         SuccessWithoutGrpcStatus,
         SuccessWithOkGrpcStatus,
         SuccessWithErrorGrpcStatus,
+        GrpcWeb,
+        GrpcWebWithErrorGrpcStatus,
+        Connect,
+        ConnectWithError,
         Error,
     }
 
@@ -94,6 +254,30 @@ mod tests {
                     status.add_header(&mut headers)?;
                     Ok(Response { status: StatusCode::OK, headers, body: Bytes::default() })
                 }
+                TestScenario::GrpcWeb => {
+                    let body = [message.as_ref(), &b"\x80\0\0\0\x0fgrpc-status:0\r\n"[..]].concat();
+                    Ok(Response { status: StatusCode::OK, headers, body: body.into() })
+                }
+                TestScenario::GrpcWebWithErrorGrpcStatus => {
+                    let body =
+                        b"\x80\0\0\0\x31grpc-status:3\r\ngrpc-message:description message\r\n";
+                    Ok(Response {
+                        status: StatusCode::OK,
+                        headers,
+                        body: Bytes::from_static(body),
+                    })
+                }
+                TestScenario::Connect => {
+                    Ok(Response { status: StatusCode::OK, headers, body: message.slice(5..) })
+                }
+                TestScenario::ConnectWithError => {
+                    let body = br#"{"code":"invalid_argument","message":"description message"}"#;
+                    Ok(Response {
+                        status: StatusCode::BAD_REQUEST,
+                        headers,
+                        body: Bytes::from_static(body),
+                    })
+                }
                 TestScenario::Error => Ok(Response {
                     status: StatusCode::NOT_FOUND,
                     headers,
@@ -106,7 +290,9 @@ mod tests {
         test_http: TestHttp,
     ) -> Result<(TargetRuntime, ProtobufOperation, Request)> {
         let mut runtime = crate::core::runtime::test::init(None);
-        runtime.http2_only = Arc::new(test_http);
+        let test_http = Arc::new(test_http);
+        runtime.http = test_http.clone();
+        runtime.http2_only = test_http;
 
         let file_descriptor_set =
             protox::compile([protobuf::GREETINGS, protobuf::ERRORS], [protobuf::SELF]);
@@ -124,7 +310,7 @@ mod tests {
         let test_http = TestHttp { scenario: TestScenario::SuccessWithoutGrpcStatus };
         let (runtime, operation, request) = prepare_args(test_http).await?;
 
-        let result = execute_grpc_request(&runtime, &operation, request).await;
+        let result = execute_grpc_request(&runtime, &operation, &GrpcProtocol::Grpc, request).await;
 
         assert!(
             result.is_ok(),
@@ -138,7 +324,7 @@ mod tests {
         let test_http = TestHttp { scenario: TestScenario::SuccessWithOkGrpcStatus };
         let (runtime, operation, request) = prepare_args(test_http).await?;
 
-        let result = execute_grpc_request(&runtime, &operation, request).await;
+        let result = execute_grpc_request(&runtime, &operation, &GrpcProtocol::Grpc, request).await;
 
         assert!(
             result.is_ok(),
@@ -152,7 +338,7 @@ mod tests {
         let test_http = TestHttp { scenario: TestScenario::SuccessWithErrorGrpcStatus };
         let (runtime, operation, request) = prepare_args(test_http).await?;
 
-        let result = execute_grpc_request(&runtime, &operation, request).await;
+        let result = execute_grpc_request(&runtime, &operation, &GrpcProtocol::Grpc, request).await;
 
         assert!(
             result.is_err(),
@@ -193,11 +379,99 @@ mod tests {
         let test_http = TestHttp { scenario: TestScenario::Error };
         let (runtime, operation, request) = prepare_args(test_http).await?;
 
-        let result = execute_grpc_request(&runtime, &operation, request).await;
+        let result = execute_grpc_request(&runtime, &operation, &GrpcProtocol::Grpc, request).await;
 
         assert!(result.is_err(), "Expected error");
         assert_eq!(result.unwrap_err().to_string(), "Failed to execute request");
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_grpc_web_request_success() -> Result<()> {
+        let test_http = TestHttp { scenario: TestScenario::GrpcWeb };
+        let (runtime, operation, request) = prepare_args(test_http).await?;
+
+        let result =
+            execute_grpc_request(&runtime, &operation, &GrpcProtocol::GrpcWeb, request).await?;
+
+        assert_eq!(result.body.into_json()?, json!({"message": "test message"}));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_grpc_web_request_with_error_grpc_status() -> Result<()> {
+        let test_http = TestHttp { scenario: TestScenario::GrpcWebWithErrorGrpcStatus };
+        let (runtime, operation, request) = prepare_args(test_http).await?;
+
+        let result =
+            execute_grpc_request(&runtime, &operation, &GrpcProtocol::GrpcWeb, request).await;
+
+        match result.unwrap_err().downcast_ref::<Error>() {
+            Some(Error::GRPC { grpc_code, grpc_status_message, .. }) => {
+                assert_eq!(*grpc_code, Code::InvalidArgument as i32);
+                assert_eq!(*grpc_status_message, "description message");
+            }
+            _ => panic!("Expected GRPCError"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_request_success() -> Result<()> {
+        let test_http = TestHttp { scenario: TestScenario::Connect };
+        let (runtime, operation, request) = prepare_args(test_http).await?;
+
+        let result =
+            execute_grpc_request(&runtime, &operation, &GrpcProtocol::Connect, request).await?;
+
+        assert_eq!(result.body.into_json()?, json!({"message": "test message"}));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_request_with_error() -> Result<()> {
+        let test_http = TestHttp { scenario: TestScenario::ConnectWithError };
+        let (runtime, operation, request) = prepare_args(test_http).await?;
+
+        let result =
+            execute_grpc_request(&runtime, &operation, &GrpcProtocol::Connect, request).await;
+
+        match result.unwrap_err().downcast_ref::<Error>() {
+            Some(Error::GRPC { grpc_code, grpc_status_message, .. }) => {
+                assert_eq!(*grpc_code, Code::InvalidArgument as i32);
+                assert_eq!(*grpc_status_message, "description message");
+            }
+            _ => panic!("Expected GRPCError"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_grpc_protocol_names() {
+        let protocols: Vec<GrpcProtocol> =
+            serde_json::from_str(r#"["grpc", "grpc-web", "connect"]"#).unwrap();
+
+        assert_eq!(
+            protocols,
+            vec![
+                GrpcProtocol::Grpc,
+                GrpcProtocol::GrpcWeb,
+                GrpcProtocol::Connect
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("2S"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_grpc_timeout("150m"), Some(Duration::from_millis(150)));
+        assert_eq!(parse_grpc_timeout("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_grpc_timeout("123456789m"), None);
+        assert_eq!(parse_grpc_timeout("10x"), None);
+        assert_eq!(parse_grpc_timeout("m"), None);
+
+        assert_eq!(to_grpc_timeout(Duration::from_millis(1500)), "1500m");
+        assert_eq!(to_grpc_timeout(Duration::from_secs(200_000)), "200000S");
+    }
 }
@@ -1,21 +1,25 @@
 use std::hash::{Hash, Hasher};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use derive_setters::Setters;
 use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use tailcall_hasher::TailcallHasher;
+use tailcall_valid::Validator;
 use url::Url;
 
 use super::request::create_grpc_request;
-use crate::core::config::GraphQLOperationType;
+use crate::core::config::{GraphQLOperationType, GrpcProtocol};
 use crate::core::grpc::protobuf::ProtobufOperation;
 use crate::core::has_headers::HasHeaders;
 use crate::core::helpers::headers::MustacheHeaders;
 use crate::core::ir::model::{CacheKey, IoId};
+use crate::core::json::JsonSchema;
 use crate::core::mustache::Mustache;
 use crate::core::path::PathString;
 
 static GRPC_MIME_TYPE: HeaderValue = HeaderValue::from_static("application/grpc");
+static GRPC_WEB_MIME_TYPE: HeaderValue = HeaderValue::from_static("application/grpc-web+proto");
+static CONNECT_MIME_TYPE: HeaderValue = HeaderValue::from_static("application/proto");
 
 #[derive(Setters, Debug, Clone)]
 pub struct RequestTemplate {
@@ -24,6 +28,16 @@ pub struct RequestTemplate {
     pub body: Option<RequestBody>,
     pub operation: ProtobufOperation,
     pub operation_type: GraphQLOperationType,
+    pub schemas: Option<MessageSchemas>,
+    pub protocol: GrpcProtocol,
+}
+
+/// Schemas of the input and output messages of the operation, that the
+/// request body and the response are validated against.
+#[derive(Debug, Clone)]
+pub struct MessageSchemas {
+    pub input: JsonSchema,
+    pub output: JsonSchema,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Setters)]
@@ -48,6 +62,7 @@ pub struct RenderedRequestTemplate {
     pub headers: HeaderMap,
     pub body: String,
     pub operation: ProtobufOperation,
+    pub protocol: GrpcProtocol,
 }
 
 impl Hash for RenderedRequestTemplate {
@@ -67,10 +82,16 @@ impl RequestTemplate {
     fn create_headers<C: PathString>(&self, ctx: &C) -> HeaderMap {
         let mut header_map = HeaderMap::new();
 
-        header_map.insert(CONTENT_TYPE, GRPC_MIME_TYPE.to_owned());
+        let content_type = match self.protocol {
+            GrpcProtocol::Grpc => &GRPC_MIME_TYPE,
+            GrpcProtocol::GrpcWeb => &GRPC_WEB_MIME_TYPE,
+            GrpcProtocol::Connect => &CONNECT_MIME_TYPE,
+        };
+        header_map.insert(CONTENT_TYPE, content_type.to_owned());
 
         for (k, v) in &self.headers {
-            if let Ok(header_value) = HeaderValue::from_str(&v.render(ctx)) {
+            if let Ok(mut header_value) = HeaderValue::from_str(&v.render(ctx)) {
+                header_value.set_sensitive(v.has_secrets());
                 header_map.insert(k, header_value);
             }
         }
@@ -82,7 +103,13 @@ impl RequestTemplate {
         let url = self.create_url(ctx)?;
         let headers = self.render_headers(ctx);
         let body = self.render_body(ctx);
-        Ok(RenderedRequestTemplate { url, headers, body, operation: self.operation.clone() })
+        Ok(RenderedRequestTemplate {
+            url,
+            headers,
+            body,
+            operation: self.operation.clone(),
+            protocol: self.protocol.clone(),
+        })
     }
 
     fn render_body<C: PathString + HasHeaders>(&self, ctx: &C) -> String {
@@ -93,6 +120,25 @@ impl RequestTemplate {
         }
     }
 
+    /// Validates the rendered request body against the input message, when
+    /// the template has the schemas of the messages.
+    pub fn validate_request(&self, body: &str) -> Result<()> {
+        if let Some(schemas) = &self.schemas {
+            let body = serde_json::from_str(body)?;
+            validate(&schemas.input, &body, "request", &self.operation)?;
+        }
+        Ok(())
+    }
+
+    /// Validates the response against the output message, when the template
+    /// has the schemas of the messages.
+    pub fn validate_response(&self, value: &async_graphql::Value) -> Result<()> {
+        if let Some(schemas) = &self.schemas {
+            validate(&schemas.output, value, "response", &self.operation)?;
+        }
+        Ok(())
+    }
+
     fn render_headers<C: PathString + HasHeaders>(&self, ctx: &C) -> HeaderMap {
         let mut req_headers = HeaderMap::new();
 
@@ -107,11 +153,40 @@ impl RequestTemplate {
     }
 }
 
+/// Fails with every mismatch between the value and the schema, along with its
+/// path in the value.
+fn validate(
+    schema: &JsonSchema,
+    value: &async_graphql::Value,
+    kind: &str,
+    operation: &ProtobufOperation,
+) -> Result<()> {
+    schema.validate(value).to_result().map_err(|error| {
+        let causes = error
+            .as_vec()
+            .iter()
+            .map(|cause| {
+                format!(
+                    "{} [{}]",
+                    cause.message,
+                    Vec::from(cause.trace.clone()).join(", ")
+                )
+            })
+            .collect::<Vec<_>>();
+        anyhow!(
+            "Invalid {kind} for {}: {}",
+            operation.name(),
+            causes.join(", ")
+        )
+    })
+}
+
 impl RenderedRequestTemplate {
     pub fn to_request(&self) -> Result<reqwest::Request> {
         Ok(create_grpc_request(
             self.url.clone(),
             self.headers.clone(),
+            &self.protocol,
             self.operation.convert_input(self.body.as_str())?,
         ))
     }
@@ -137,20 +212,28 @@ mod tests {
     use pretty_assertions::assert_eq;
     use tailcall_fixtures::protobuf;
 
-    use super::{RequestBody, RequestTemplate};
+    use super::{MessageSchemas, RequestBody, RequestTemplate};
     use crate::core::blueprint::GrpcMethod;
     use crate::core::config::reader::ConfigReader;
     use crate::core::config::{
-        Config, Field, GraphQLOperationType, Grpc, Link, LinkType, Resolver, Type,
+        Config, Field, GraphQLOperationType, Grpc, GrpcProtocol, Link, LinkType, Resolver, Type,
     };
     use crate::core::grpc::protobuf::{ProtobufOperation, ProtobufSet};
     use crate::core::ir::model::CacheKey;
+    use crate::core::json::JsonSchema;
     use crate::core::mustache::Mustache;
 
     async fn get_protobuf_op() -> ProtobufOperation {
-        let test_file = protobuf::GREETINGS;
+        protobuf_op(
+            protobuf::GREETINGS,
+            "greetings",
+            "greetings.Greeter.SayHello",
+        )
+        .await
+    }
 
-        let id = "greetings".to_string();
+    async fn protobuf_op(test_file: &str, id: &str, method: &str) -> ProtobufOperation {
+        let id = id.to_string();
 
         let runtime = crate::core::runtime::test::init(None);
         let reader = ConfigReader::init(runtime);
@@ -186,7 +269,7 @@ mod tests {
         )
         .unwrap();
 
-        let method = GrpcMethod::try_from("greetings.Greeter.SayHello").unwrap();
+        let method = GrpcMethod::try_from(method).unwrap();
         let service = protobuf_set.find_service(&method).unwrap();
 
         service.find_operation(&method).unwrap()
@@ -227,6 +310,8 @@ mod tests {
             operation: get_protobuf_op().await,
             body: None,
             operation_type: GraphQLOperationType::Query,
+            schemas: None,
+            protocol: Default::default(),
         };
         let ctx = Context::default();
         let rendered = tmpl.render(&ctx).unwrap();
@@ -264,6 +349,8 @@ mod tests {
                 value: Default::default(),
             }),
             operation_type: GraphQLOperationType::Query,
+            schemas: None,
+            protocol: Default::default(),
         };
         let ctx = Context::default();
         let rendered = tmpl.render(&ctx).unwrap();
@@ -274,6 +361,65 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn request_with_connect_protocol() {
+        let tmpl = RequestTemplate {
+            url: Mustache::parse("http://localhost:3000/"),
+            headers: vec![],
+            operation: get_protobuf_op().await,
+            body: Some(RequestBody {
+                mustache: Some(Mustache::parse(r#"{ "name": "test" }"#)),
+                value: Default::default(),
+            }),
+            operation_type: GraphQLOperationType::Query,
+            schemas: None,
+            protocol: GrpcProtocol::Connect,
+        };
+        let ctx = Context::default();
+        let rendered = tmpl.render(&ctx).unwrap();
+        let req = rendered.to_request().unwrap();
+
+        assert_eq!(
+            req.headers().get("content-type"),
+            Some(&HeaderValue::from_static("application/proto"))
+        );
+        if let Some(body) = req.body() {
+            assert_eq!(body.as_bytes(), Some(b"\n\x04test".as_ref()))
+        }
+    }
+
+    #[tokio::test]
+    async fn request_missing_required_field() {
+        let operation = protobuf_op(
+            protobuf::REGISTRY,
+            "registry",
+            "registry.PersonService.AddPerson",
+        )
+        .await;
+        let schemas = MessageSchemas {
+            input: JsonSchema::try_from(&operation.input_type).unwrap(),
+            output: JsonSchema::try_from(&operation.output_type).unwrap(),
+        };
+        let tmpl = RequestTemplate {
+            url: Mustache::parse("http://localhost:3000/"),
+            headers: vec![],
+            operation,
+            body: None,
+            operation_type: GraphQLOperationType::Mutation,
+            schemas: Some(schemas),
+            protocol: Default::default(),
+        };
+
+        let error = tmpl.validate_request(r#"{ "name": "test" }"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid request for AddPerson: expected field to be non-nullable [id]"
+        );
+        assert!(tmpl
+            .validate_request(r#"{ "name": "test", "id": 1 }"#)
+            .is_ok());
+    }
+
     async fn request_template_with_body(body_str: &str) -> RequestTemplate {
         RequestTemplate {
             url: Mustache::parse("http://localhost:3000/"),
@@ -284,6 +430,8 @@ mod tests {
                 value: Default::default(),
             }),
             operation_type: GraphQLOperationType::Query,
+            schemas: None,
+            protocol: Default::default(),
         }
     }
 
@@ -0,0 +1,194 @@
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use anyhow::Result;
+use hyper::body::HttpBody;
+use hyper::{Body, Method, Request, Response};
+use serde_json::Value;
+
+use crate::core::blueprint::AccessLog;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// The part of a GraphQL request that is logged once it's handled.
+pub struct AccessLogEntry {
+    /// When the request was received, unless the clock can't be read.
+    started: Option<Instant>,
+    operation_name: String,
+    variables: Value,
+}
+
+impl AccessLogEntry {
+    /// Reads the operation name and the variables of the request, before it's
+    /// handled. The body of `POST` requests is buffered and put back.
+    pub async fn new(req: Request<Body>, access_log: &AccessLog) -> Result<(Request<Body>, Self)> {
+        let started = now();
+        let (parts, body) = req.into_parts();
+
+        let (body, requests) = if parts.method == Method::POST {
+            let bytes = hyper::body::to_bytes(body).await?;
+            let requests = match serde_json::from_slice::<Value>(&bytes).unwrap_or_default() {
+                Value::Array(requests) => requests,
+                request => vec![request],
+            };
+            (Body::from(bytes), requests)
+        } else {
+            let query = parts.uri.query().unwrap_or_default();
+            let request = serde_urlencoded::from_str::<serde_json::Map<String, Value>>(query)
+                .map(|mut request| {
+                    // variables of GET requests are sent as a JSON string
+                    if let Some(Value::String(variables)) = request.remove("variables") {
+                        let variables = serde_json::from_str(&variables).unwrap_or_default();
+                        request.insert("variables".to_string(), variables);
+                    }
+                    Value::Object(request)
+                })
+                .unwrap_or_default();
+            (body, vec![request])
+        };
+
+        let operation_name = requests
+            .iter()
+            .filter_map(|request| request.get("operationName").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut variables = requests
+            .iter()
+            .map(|request| request.get("variables").cloned().unwrap_or_default())
+            .collect::<Vec<_>>();
+        for variables in variables.iter_mut() {
+            redact(variables, &access_log.redact_variables);
+        }
+        let variables = if variables.len() == 1 {
+            variables.pop().unwrap_or_default()
+        } else {
+            Value::Array(variables)
+        };
+
+        Ok((
+            Request::from_parts(parts, body),
+            Self { started, operation_name, variables },
+        ))
+    }
+
+    /// Emits the log event of the request with its `response`. The size and
+    /// the errors are only logged for the responses that are fully written,
+    /// streamed responses are passed as-is.
+    pub async fn finish(self, response: Result<Response<Body>>) -> Result<Response<Body>> {
+        let duration_ms = self
+            .started
+            .map(|started| started.elapsed().as_millis() as u64);
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                tracing::info!(
+                    target: "access_log",
+                    operation_name = %self.operation_name,
+                    variables = %self.variables,
+                    duration_ms,
+                    status = 500,
+                    "GraphQL request failed: {}",
+                    err
+                );
+                return Err(err);
+            }
+        };
+
+        let status = response.status().as_u16();
+        if response.body().size_hint().exact().is_none() {
+            tracing::info!(
+                target: "access_log",
+                operation_name = %self.operation_name,
+                variables = %self.variables,
+                duration_ms,
+                status,
+                "GraphQL request"
+            );
+            return Ok(response);
+        }
+
+        let (parts, body) = response.into_parts();
+        let bytes = hyper::body::to_bytes(body).await?;
+        let errors = match serde_json::from_slice::<Value>(&bytes).unwrap_or_default() {
+            Value::Array(responses) => responses.iter().map(count_errors).sum(),
+            response => count_errors(&response),
+        };
+
+        tracing::info!(
+            target: "access_log",
+            operation_name = %self.operation_name,
+            variables = %self.variables,
+            duration_ms,
+            status,
+            errors,
+            response_bytes = bytes.len(),
+            "GraphQL request"
+        );
+
+        Ok(Response::from_parts(parts, Body::from(bytes)))
+    }
+}
+
+/// Reads the clock the duration of the request is measured from.
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+// `Instant::now` panics on wasm32, so the duration isn't logged there.
+#[cfg(target_arch = "wasm32")]
+fn now() -> Option<Instant> {
+    None
+}
+
+fn count_errors(response: &Value) -> usize {
+    response
+        .get("errors")
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len)
+}
+
+/// Masks the values of the keys listed in `names`, at any depth of `value`.
+fn redact(value: &mut Value, names: &BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if names.contains(key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value, names);
+                }
+            }
+        }
+        Value::Array(list) => list.iter_mut().for_each(|value| redact(value, names)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let names = BTreeSet::from(["password".to_string()]);
+        let mut value = json!({
+            "name": "foo",
+            "password": "secret",
+            "users": [{"password": "secret", "id": 1}]
+        });
+
+        redact(&mut value, &names);
+
+        assert_eq!(
+            value,
+            json!({
+                "name": "foo",
+                "password": REDACTED,
+                "users": [{"password": REDACTED, "id": 1}]
+            })
+        );
+    }
+}
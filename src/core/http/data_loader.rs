@@ -11,8 +11,8 @@ use super::transformations::{BodyBatching, QueryBatching};
 use crate::core::config::group_by::GroupBy;
 use crate::core::config::Batch;
 use crate::core::data_loader::{DataLoader, Loader};
-use crate::core::http::{DataLoaderRequest, Response};
-use crate::core::json::JsonLike;
+use crate::core::http::{DataLoaderRequest, Response, UpstreamCalls};
+use crate::core::json::{composite_key, group_by_composite_key, JsonLike};
 use crate::core::runtime::TargetRuntime;
 use crate::core::transform::TransformerOps;
 use crate::core::Transform;
@@ -63,8 +63,13 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
         keys: &[DataLoaderRequest],
     ) -> async_graphql::Result<HashMap<DataLoaderRequest, Self::Value>, Self::Error> {
         if let Some(group_by) = &self.group_by {
-            let query_name = group_by.key();
-            let mut dl_requests = keys.to_vec();
+            let query_names = group_by.parts().map(GroupBy::key).collect::<Vec<_>>();
+            // the requests that reached their limit of upstream calls are left out
+            let mut dl_requests =
+                UpstreamCalls::count_batch(keys, DataLoaderRequest::upstream_calls);
+            if dl_requests.is_empty() {
+                return Ok(HashMap::new());
+            }
             if cfg!(debug_assertions) {
                 // Sort keys to build consistent URLs only in Testing environment.
                 dl_requests.sort_by(|a, b| a.to_request().url().cmp(b.to_request().url()));
@@ -72,9 +77,9 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
 
             if let Some(base_dl_request) = dl_requests.first().as_mut() {
                 let base_request = if base_dl_request.method() == http::Method::GET {
-                    QueryBatching::new(
+                    QueryBatching::with_keys(
                         &dl_requests.iter().skip(1).collect::<Vec<_>>(),
-                        Some(group_by.key()),
+                        query_names.clone(),
                     )
                     .transform(base_dl_request.to_request())
                     .to_result()
@@ -99,11 +104,13 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
                 #[allow(clippy::mutable_key_type)]
                 let mut hashmap = HashMap::with_capacity(dl_requests.len());
 
-                // Parse the response body and group it by batchKey
-                let path = &group_by.path();
-
                 // ResponseMap contains the response body grouped by the batchKey
-                let response_map = res.body.group_by(path);
+                let response_map = if group_by.is_composite() {
+                    let paths = group_by.parts().map(GroupBy::path).collect::<Vec<_>>();
+                    group_by_composite_key(&res.body, &paths)
+                } else {
+                    res.body.group_by(&group_by.path())
+                };
 
                 // depending on graphql type, it will extract the data out of the response.
                 let data_extractor = if self.is_list {
@@ -117,13 +124,22 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
                     for dl_req in dl_requests.iter() {
                         let url = dl_req.url();
                         let query_set: HashMap<_, _> = url.query_pairs().collect();
-                        let id = query_set.get(query_name).ok_or(anyhow::anyhow!(
-                            "Unable to find key {} in query params",
-                            query_name
-                        ))?;
+                        let ids = query_names
+                            .iter()
+                            .map(|query_name| {
+                                query_set.get(*query_name).ok_or(anyhow::anyhow!(
+                                    "Unable to find key {} in query params",
+                                    query_name
+                                ))
+                            })
+                            .collect::<anyhow::Result<Vec<_>>>()?;
+                        let id = match ids.as_slice() {
+                            [id] => id.to_string(),
+                            ids => composite_key(ids),
+                        };
 
                         // Clone the response and set the body
-                        let body = data_extractor(&response_map, id);
+                        let body = data_extractor(&response_map, &id);
                         let res = res.clone().body(body);
 
                         hashmap.insert(dl_req.clone(), res);
@@ -146,6 +162,7 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
                 Err(anyhow::anyhow!(error_message).into())
             }
         } else {
+            let keys = UpstreamCalls::count_each(keys, DataLoaderRequest::upstream_calls);
             let results = keys.iter().map(|key| async {
                 let result = self.runtime.http.execute(key.to_request()).await;
                 (key.clone(), result)
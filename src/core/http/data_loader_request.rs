@@ -4,18 +4,36 @@ use std::ops::Deref;
 
 use tailcall_hasher::TailcallHasher;
 
+use super::UpstreamCalls;
+
 #[derive(Debug)]
 pub struct DataLoaderRequest {
     request: reqwest::Request,
     headers: BTreeSet<String>,
     /// used for request body batching.
     batching_value: Option<String>,
+    /// counts the call made for the request, so that the keys of different
+    /// requests are loaded for each of them.
+    upstream_calls: Option<UpstreamCalls>,
 }
 
 impl DataLoaderRequest {
     pub fn new(req: reqwest::Request, headers: BTreeSet<String>) -> Self {
         // TODO: req should already have headers builtin, no?
-        Self { request: req, headers, batching_value: None }
+        Self {
+            request: req,
+            headers,
+            batching_value: None,
+            upstream_calls: None,
+        }
+    }
+
+    pub fn with_upstream_calls(self, upstream_calls: Option<UpstreamCalls>) -> Self {
+        Self { upstream_calls, ..self }
+    }
+
+    pub fn upstream_calls(&self) -> Option<&UpstreamCalls> {
+        self.upstream_calls.as_ref()
     }
 
     pub fn with_batching_value(self, body: Option<String>) -> Self {
@@ -51,6 +69,7 @@ impl Hash for DataLoaderRequest {
                 value.hash(state);
             }
         }
+        self.upstream_calls.hash(state);
     }
 }
 
@@ -81,6 +100,7 @@ impl Clone for DataLoaderRequest {
 
         DataLoaderRequest::new(req, self.headers.clone())
             .with_batching_value(self.batching_value.clone())
+            .with_upstream_calls(self.upstream_calls.clone())
     }
 }
 
@@ -4,15 +4,17 @@ pub use data_loader_request::*;
 use http::HeaderValue;
 pub use method::Method;
 pub use query_encoder::QueryEncoder;
-pub use request_context::RequestContext;
+pub use request_context::{RequestContext, UpstreamCalls};
 pub use request_handler::{handle_request, API_URL_PREFIX};
-pub use request_template::RequestTemplate;
+pub use request_template::{RequestTemplate, IDEMPOTENCY_KEY};
 pub use response::*;
 
+mod access_log;
 mod cache;
 mod data_loader;
 mod data_loader_request;
 mod method;
+pub mod multipart;
 mod query_encoder;
 mod request_context;
 mod request_handler;
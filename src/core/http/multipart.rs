@@ -0,0 +1,303 @@
+use std::hash::{Hash, Hasher};
+
+use async_graphql::Name;
+use async_graphql_value::ConstValue;
+use hyper::body::Bytes;
+use indexmap::IndexMap;
+use tailcall_hasher::TailcallHasher;
+
+const CRLF: &[u8] = b"\r\n";
+
+/// Keys of the object an `Upload` scalar is represented with.
+pub const FILENAME: &str = "filename";
+pub const CONTENT_TYPE: &str = "contentType";
+pub const CONTENT: &str = "content";
+
+/// A single part of a `multipart/form-data` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub content: Bytes,
+}
+
+impl Part {
+    /// Creates a part out of a value. `Upload` values are sent as files with
+    /// their content untouched, strings as text and everything else as JSON.
+    pub fn from_value(name: &str, value: &ConstValue) -> Self {
+        let name = name.to_string();
+        match value {
+            ConstValue::Object(map) if matches!(map.get(CONTENT), Some(ConstValue::Binary(_))) => {
+                let text = |key: &str| match map.get(key) {
+                    Some(ConstValue::String(text)) => Some(text.clone()),
+                    _ => None,
+                };
+                let content = match map.get(CONTENT) {
+                    Some(ConstValue::Binary(content)) => content.clone(),
+                    _ => Bytes::new(),
+                };
+
+                Part {
+                    name,
+                    filename: text(FILENAME),
+                    content_type: text(CONTENT_TYPE),
+                    content,
+                }
+            }
+            ConstValue::String(text) => Part {
+                name,
+                filename: None,
+                content_type: None,
+                content: Bytes::from(text.clone()),
+            },
+            value => Part {
+                name,
+                filename: None,
+                content_type: None,
+                content: Bytes::from(serde_json::to_string(value).unwrap_or_default()),
+            },
+        }
+    }
+
+    /// Converts the part into a value. Files become `Upload` values and text
+    /// is parsed as JSON, falling back to a string.
+    pub fn into_value(self) -> ConstValue {
+        if self.filename.is_some() || self.content_type.is_some() {
+            let mut upload = IndexMap::new();
+            if let Some(filename) = self.filename {
+                upload.insert(Name::new(FILENAME), ConstValue::String(filename));
+            }
+            if let Some(content_type) = self.content_type {
+                upload.insert(Name::new(CONTENT_TYPE), ConstValue::String(content_type));
+            }
+            upload.insert(Name::new(CONTENT), ConstValue::Binary(self.content));
+            return ConstValue::Object(upload);
+        }
+
+        let text = String::from_utf8_lossy(&self.content);
+        serde_json::from_str(&text).unwrap_or_else(|_| ConstValue::String(text.into_owned()))
+    }
+}
+
+/// Returns the boundary of a `multipart/form-data` content type.
+pub fn boundary(content_type: &str) -> Option<String> {
+    let mime = content_type.parse::<mime::Mime>().ok()?;
+    if mime.type_() != mime::MULTIPART || mime.subtype() != mime::FORM_DATA {
+        return None;
+    }
+
+    mime.get_param(mime::BOUNDARY)
+        .map(|boundary| boundary.to_string())
+}
+
+/// Encodes the parts into a `multipart/form-data` body and returns it along
+/// with the boundary it was encoded with. The boundary is derived from the
+/// content so that identical parts produce identical requests.
+pub fn encode(parts: &[Part]) -> (String, Vec<u8>) {
+    let mut hasher = TailcallHasher::default();
+    parts.iter().for_each(|part| {
+        part.name.hash(&mut hasher);
+        part.content.hash(&mut hasher);
+    });
+    let boundary = format!("tailcall-{:016x}", hasher.finish());
+
+    let mut body = Vec::new();
+    for part in parts {
+        body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        let mut disposition = format!(
+            "Content-Disposition: form-data; name=\"{}\"",
+            escape(&part.name)
+        );
+        if let Some(filename) = &part.filename {
+            disposition.push_str(&format!("; filename=\"{}\"", escape(filename)));
+        }
+        body.extend_from_slice(disposition.as_bytes());
+        body.extend_from_slice(CRLF);
+        if let Some(content_type) = &part.content_type {
+            let content_type = content_type.replace(['\r', '\n'], "");
+            body.extend_from_slice(format!("Content-Type: {content_type}\r\n").as_bytes());
+        }
+        body.extend_from_slice(CRLF);
+        body.extend_from_slice(&part.content);
+        body.extend_from_slice(CRLF);
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+    (boundary, body)
+}
+
+/// Percent-encodes the quotes and line breaks of a quoted parameter, the way
+/// browsers do (RFC 7578), so that it can't end the parameter or the header.
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Decodes a `multipart/form-data` body encoded with the given boundary.
+pub fn decode(boundary: &str, body: &[u8]) -> anyhow::Result<Vec<Part>> {
+    let delimiter = format!("--{boundary}").into_bytes();
+    let mut parts = Vec::new();
+
+    let mut pos = find(body, &delimiter, 0)
+        .ok_or_else(|| anyhow::anyhow!("Multipart body has no boundary"))?
+        + delimiter.len();
+
+    // The closing delimiter is followed by `--`
+    while !body[pos..].starts_with(b"--") {
+        let start = pos + CRLF.len();
+        let end = find(body, &delimiter, start)
+            .ok_or_else(|| anyhow::anyhow!("Multipart body is not terminated"))?;
+        // The content of the part ends with the CRLF preceding the delimiter
+        let part = &body[start..end.saturating_sub(CRLF.len()).max(start)];
+        parts.push(decode_part(part)?);
+        pos = end + delimiter.len();
+    }
+
+    Ok(parts)
+}
+
+fn decode_part(part: &[u8]) -> anyhow::Result<Part> {
+    let split = find(part, b"\r\n\r\n", 0)
+        .ok_or_else(|| anyhow::anyhow!("Multipart part has no headers"))?;
+    let headers = std::str::from_utf8(&part[..split])?;
+
+    let mut name = None;
+    let mut filename = None;
+    let mut content_type = None;
+    for header in headers.split("\r\n") {
+        let Some((key, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if key.eq_ignore_ascii_case("content-disposition") {
+            for param in split_params(value).into_iter().skip(1) {
+                match param.trim().split_once('=') {
+                    Some(("name", value)) => name = Some(unquote(value)),
+                    Some(("filename", value)) => filename = Some(unquote(value)),
+                    _ => {}
+                }
+            }
+        } else if key.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.to_string());
+        }
+    }
+
+    Ok(Part {
+        name: name.ok_or_else(|| anyhow::anyhow!("Multipart part has no name"))?,
+        filename,
+        content_type,
+        content: Bytes::copy_from_slice(&part[split + 4..]),
+    })
+}
+
+/// Splits the parameters of a header value on the `;` that aren't quoted.
+fn split_params(value: &str) -> Vec<&str> {
+    let mut params = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                params.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&value[start..]);
+    params
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| pos + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::body::Bytes;
+    use pretty_assertions::assert_eq;
+
+    use super::{boundary, decode, encode, Part};
+
+    #[test]
+    fn test_encode_decode() {
+        let parts = vec![
+            Part {
+                name: "title".to_string(),
+                filename: None,
+                content_type: None,
+                content: Bytes::from("Report"),
+            },
+            Part {
+                name: "file".to_string(),
+                filename: Some("report.bin".to_string()),
+                content_type: Some("application/octet-stream".to_string()),
+                content: Bytes::from_static(&[0, 159, 146, 150, 13, 10, 45, 45]),
+            },
+        ];
+
+        let (boundary, body) = encode(&parts);
+        assert_eq!(decode(&boundary, &body).unwrap(), parts);
+    }
+
+    #[test]
+    fn test_encode_escapes_parameters() {
+        let parts = vec![Part {
+            name: "file".to_string(),
+            filename: Some("a\"\r\nContent-Type: text/html\r\n\r\n.txt".to_string()),
+            content_type: Some("text/plain\r\nX-Injected: 1".to_string()),
+            content: Bytes::from("Report"),
+        }];
+
+        let (boundary, body) = encode(&parts);
+        let decoded = decode(&boundary, &body).unwrap();
+
+        assert_eq!(
+            decoded,
+            vec![Part {
+                name: "file".to_string(),
+                filename: Some("a%22%0D%0AContent-Type: text/html%0D%0A%0D%0A.txt".to_string()),
+                content_type: Some("text/plainX-Injected: 1".to_string()),
+                content: Bytes::from("Report"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_quoted_semicolon() {
+        let parts = vec![Part {
+            name: "file".to_string(),
+            filename: Some("a;b.txt".to_string()),
+            content_type: None,
+            content: Bytes::from("Report"),
+        }];
+
+        let (boundary, body) = encode(&parts);
+        assert_eq!(decode(&boundary, &body).unwrap(), parts);
+    }
+
+    #[test]
+    fn test_boundary() {
+        assert_eq!(
+            boundary("multipart/form-data; boundary=abc"),
+            Some("abc".to_string())
+        );
+        assert_eq!(boundary("application/json"), None);
+    }
+}
@@ -1,6 +1,9 @@
+use std::hash::{Hash, Hasher};
 use std::num::NonZeroU64;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use async_graphql_value::ConstValue;
 use cache_control::{Cachability, CacheControl};
@@ -12,12 +15,96 @@ use crate::core::blueprint::{Server, Upstream};
 use crate::core::data_loader::{DataLoader, DedupeResult};
 use crate::core::graphql::GraphqlDataLoader;
 use crate::core::grpc::data_loader::GrpcDataLoader;
-use crate::core::http::{DataLoaderRequest, HttpDataLoader};
+use crate::core::http::{DataLoaderRequest, HttpDataLoader, Response};
 use crate::core::ir::model::IoId;
 use crate::core::ir::Error;
 use crate::core::runtime::TargetRuntime;
 use crate::core::{cache, grpc};
 
+/// Counts the upstream calls made by a request, up to `max`. It travels with
+/// the keys of the data loaders, so that a batch is counted when it's sent.
+/// Counters are equal when they count the calls of the same request, which
+/// keeps the keys of concurrent requests apart in the app-wide data loaders.
+#[derive(Clone, Debug)]
+pub struct UpstreamCalls {
+    calls: Arc<AtomicUsize>,
+    max: usize,
+}
+
+impl Hash for UpstreamCalls {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.calls).hash(state);
+    }
+}
+
+impl PartialEq for UpstreamCalls {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.calls, &other.calls)
+    }
+}
+
+impl Eq for UpstreamCalls {}
+
+impl UpstreamCalls {
+    /// Counts a call about to be made, failing instead once `max` calls were
+    /// made.
+    pub fn count(&self) -> Result<(), Error> {
+        self.calls
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |calls| {
+                (calls < self.max).then_some(calls + 1)
+            })
+            .map(|_| ())
+            .map_err(|_| Error::UpstreamCallLimit(self.max))
+    }
+
+    /// Fails once `max` calls were made.
+    pub fn check(&self) -> Result<(), Error> {
+        if self.calls.load(Ordering::Relaxed) < self.max {
+            Ok(())
+        } else {
+            Err(Error::UpstreamCallLimit(self.max))
+        }
+    }
+
+    /// Counts the call made for a batch of keys once for every request with
+    /// keys in it, and returns the keys to load: those of the requests that
+    /// reached their limit are left out.
+    pub fn count_batch<'a, K: Clone>(
+        keys: &'a [K],
+        counter: impl Fn(&'a K) -> Option<&'a UpstreamCalls>,
+    ) -> Vec<K> {
+        let mut counted: Vec<(&Arc<AtomicUsize>, bool)> = Vec::new();
+        keys.iter()
+            .filter(|key| {
+                let Some(calls) = counter(*key) else {
+                    return true;
+                };
+                if let Some((_, allowed)) =
+                    counted.iter().find(|(c, _)| Arc::ptr_eq(*c, &calls.calls))
+                {
+                    return *allowed;
+                }
+                let allowed = calls.count().is_ok();
+                counted.push((&calls.calls, allowed));
+                allowed
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Counts a call for every key loaded on its own, and returns the keys to
+    /// load: those of the requests that reached their limit are left out.
+    pub fn count_each<'a, K: Clone>(
+        keys: &'a [K],
+        counter: impl Fn(&'a K) -> Option<&'a UpstreamCalls>,
+    ) -> Vec<K> {
+        keys.iter()
+            .filter(|key| counter(*key).map_or(true, |calls| calls.count().is_ok()))
+            .cloned()
+            .collect()
+    }
+}
+
 #[derive(Setters)]
 pub struct RequestContext {
     pub server: Server,
@@ -31,10 +118,21 @@ pub struct RequestContext {
     pub gql_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, GraphqlDataLoader>>>,
     pub grpc_data_loaders: Arc<Vec<DataLoader<grpc::DataLoaderRequest, GrpcDataLoader>>>,
     pub min_max_age: Arc<Mutex<Option<i32>>>,
+    pub min_stale_while_revalidate: Arc<Mutex<Option<u64>>>,
     pub cache_public: Arc<Mutex<Option<bool>>>,
     pub runtime: TargetRuntime,
     pub cache: DedupeResult<IoId, ConstValue, Error>,
     pub dedupe_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
+    /// Responses of the upstream HTTP requests made while resolving this
+    /// request, keyed by the hash of the rendered request.
+    pub http_cache: Arc<DedupeResult<u64, Response<ConstValue>, Error>>,
+    /// The time by which the request should be served.
+    pub deadline: Option<Instant>,
+    /// The body of the inbound request when it's left unread, along with the
+    /// argument of the `@http` that streams it to the upstream instead.
+    pub upload: Arc<Mutex<Option<(String, hyper::Body)>>>,
+    /// The number of upstream calls made while resolving this request.
+    pub upstream_calls: Arc<AtomicUsize>,
 }
 
 impl RequestContext {
@@ -48,13 +146,30 @@ impl RequestContext {
             gql_data_loaders: Arc::new(vec![]),
             grpc_data_loaders: Arc::new(vec![]),
             min_max_age: Arc::new(Mutex::new(None)),
+            min_stale_while_revalidate: Arc::new(Mutex::new(None)),
             cache_public: Arc::new(Mutex::new(None)),
             runtime: target_runtime,
             cache: DedupeResult::new(true),
             dedupe_handler: Arc::new(DedupeResult::new(false)),
+            http_cache: Arc::new(DedupeResult::new(true)),
             allowed_headers: HeaderMap::new(),
+            deadline: None,
+            upload: Arc::new(Mutex::new(None)),
+            upstream_calls: Arc::new(AtomicUsize::new(0)),
         }
     }
+
+    /// Returns the time left before the deadline of the request, if any.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn remaining_time(&self) -> Option<Duration> {
+        None
+    }
     fn set_min_max_age_conc(&self, min_max_age: i32) {
         *self.min_max_age.lock().unwrap() = Some(min_max_age);
     }
@@ -83,6 +198,68 @@ impl RequestContext {
         }
     }
 
+    pub fn get_min_stale_while_revalidate(&self) -> Option<u64> {
+        *self.min_stale_while_revalidate.lock().unwrap()
+    }
+
+    /// Keeps the smallest stale-while-revalidate window, in seconds, of the
+    /// cached values used to resolve the request.
+    pub fn set_min_stale_while_revalidate(&self, secs: u64) {
+        let mut min = self.min_stale_while_revalidate.lock().unwrap();
+        *min = Some(min.map_or(secs, |min| min.min(secs)));
+    }
+
+    /// Takes the unread body of the inbound request, if it's still there and
+    /// is streamed to the argument `arg`.
+    pub fn take_upload(&self, arg: &str) -> Option<hyper::Body> {
+        let mut upload = self.upload.lock().unwrap();
+        match upload.as_ref() {
+            Some((name, _)) if name == arg => upload.take().map(|(_, body)| body),
+            _ => None,
+        }
+    }
+
+    /// Returns the counter of the upstream calls made by the request, if
+    /// `server.maxUpstreamCalls` limits them.
+    pub fn upstream_call_counter(&self) -> Option<UpstreamCalls> {
+        self.server
+            .max_upstream_calls
+            .map(|max| UpstreamCalls { calls: self.upstream_calls.clone(), max })
+    }
+
+    /// Counts an upstream call about to be made by the request, failing
+    /// instead once `server.maxUpstreamCalls` calls were made.
+    pub fn count_upstream_call(&self) -> Result<(), Error> {
+        self.upstream_call_counter()
+            .map_or(Ok(()), |counter| counter.count())
+    }
+
+    /// Creates a context sharing the runtime and data loaders of this one, but
+    /// none of its response state, so that work can continue after the
+    /// response has been sent.
+    pub fn detached(&self) -> RequestContext {
+        RequestContext {
+            server: self.server.clone(),
+            upstream: self.upstream.clone(),
+            x_response_headers: Arc::new(Mutex::new(HeaderMap::new())),
+            cookie_headers: None,
+            allowed_headers: self.allowed_headers.clone(),
+            http_data_loaders: self.http_data_loaders.clone(),
+            gql_data_loaders: self.gql_data_loaders.clone(),
+            grpc_data_loaders: self.grpc_data_loaders.clone(),
+            min_max_age: Arc::new(Mutex::new(None)),
+            min_stale_while_revalidate: Arc::new(Mutex::new(None)),
+            cache_public: Arc::new(Mutex::new(None)),
+            runtime: self.runtime.clone(),
+            cache: DedupeResult::new(true).with_ttl(self.server.dedupe_ttl),
+            dedupe_handler: self.dedupe_handler.clone(),
+            http_cache: Arc::new(DedupeResult::new(true).with_ttl(self.server.dedupe_ttl)),
+            deadline: None,
+            upload: Arc::new(Mutex::new(None)),
+            upstream_calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
     pub fn set_cache_visibility(&self, cachability: &Option<Cachability>) {
         if let Some(Cachability::Private) = cachability {
             self.set_cache_public_false()
@@ -197,10 +374,17 @@ impl From<&AppContext> for RequestContext {
             gql_data_loaders: app_ctx.gql_data_loaders.clone(),
             grpc_data_loaders: app_ctx.grpc_data_loaders.clone(),
             min_max_age: Arc::new(Mutex::new(None)),
+            min_stale_while_revalidate: Arc::new(Mutex::new(None)),
             cache_public: Arc::new(Mutex::new(None)),
             runtime: app_ctx.runtime.clone(),
-            cache: DedupeResult::new(true),
+            cache: DedupeResult::new(true).with_ttl(app_ctx.blueprint.server.dedupe_ttl),
             dedupe_handler: app_ctx.dedupe_handler.clone(),
+            http_cache: Arc::new(
+                DedupeResult::new(true).with_ttl(app_ctx.blueprint.server.dedupe_ttl),
+            ),
+            deadline: None,
+            upload: Arc::new(Mutex::new(None)),
+            upstream_calls: Arc::new(AtomicUsize::new(0)),
         }
     }
 }
@@ -211,7 +395,8 @@ mod test {
 
     use crate::core::blueprint::{Server, Upstream};
     use crate::core::config::{self, Batch};
-    use crate::core::http::RequestContext;
+    use crate::core::http::{RequestContext, UpstreamCalls};
+    use crate::core::ir::Error;
 
     impl Default for RequestContext {
         fn default() -> Self {
@@ -248,6 +433,45 @@ mod test {
         assert_eq!(req_ctx.get_min_max_age(), Some(120));
     }
 
+    #[test]
+    fn test_update_min_stale_while_revalidate() {
+        let req_ctx = RequestContext::default();
+        req_ctx.set_min_stale_while_revalidate(30);
+        req_ctx.set_min_stale_while_revalidate(60);
+        assert_eq!(req_ctx.get_min_stale_while_revalidate(), Some(30));
+    }
+
+    #[test]
+    fn test_count_batch_once_per_request() {
+        fn counter(key: &(u32, Option<UpstreamCalls>)) -> Option<&UpstreamCalls> {
+            key.1.as_ref()
+        }
+        fn ids(keys: Vec<(u32, Option<UpstreamCalls>)>) -> Vec<u32> {
+            keys.into_iter().map(|(id, _)| id).collect()
+        }
+
+        let server = RequestContext::default().server.max_upstream_calls(Some(1));
+        let first = RequestContext::default().server(server.clone());
+        let second = RequestContext::default().server(server);
+        let keys = vec![
+            (1, first.upstream_call_counter()),
+            (2, first.upstream_call_counter()),
+            (3, second.upstream_call_counter()),
+            (4, None),
+        ];
+
+        // the batch is a single call for each request with keys in it
+        let loaded = UpstreamCalls::count_batch(&keys, counter);
+        assert_eq!(ids(loaded), vec![1, 2, 3, 4]);
+
+        let loaded = UpstreamCalls::count_batch(&keys, counter);
+        assert_eq!(ids(loaded), vec![4]);
+        assert!(matches!(
+            first.count_upstream_call(),
+            Err(Error::UpstreamCallLimit(1))
+        ));
+    }
+
     #[test]
     fn test_update_cache_visibility_private() {
         let req_ctx = RequestContext::default();
@@ -1,8 +1,10 @@
 use std::collections::BTreeSet;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Result;
+use async_graphql::futures_util::future::join_all;
 use async_graphql::ServerError;
 use hyper::header::{self, HeaderValue, CONTENT_TYPE};
 use hyper::http::request::Parts;
@@ -15,16 +17,27 @@ use serde::de::DeserializeOwned;
 use tracing::Instrument;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use super::access_log::AccessLogEntry;
 use super::request_context::RequestContext;
 use super::telemetry::{get_response_status_code, RequestCounter};
 use super::{showcase, telemetry, TAILCALL_HTTPS_ORIGIN, TAILCALL_HTTP_ORIGIN};
 use crate::core::app_context::AppContext;
-use crate::core::async_graphql_hyper::{GraphQLRequestLike, GraphQLResponse};
+use crate::core::async_graphql_hyper::{
+    GraphQLQuery, GraphQLRequest, GraphQLRequestLike, GraphQLResponse,
+};
 use crate::core::blueprint::telemetry::TelemetryExporter;
 use crate::core::config::{PrometheusExporter, PrometheusFormat};
-use crate::core::jit::JITExecutor;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::core::grpc::request::{parse_grpc_timeout, GRPC_TIMEOUT};
+use crate::core::jit::{JITExecutor, MULTIPART_BOUNDARY};
+use crate::core::rest;
 
 pub const API_URL_PREFIX: &str = "/api";
+pub const LIVENESS_ENDPOINT: &str = "/healthz";
+pub const READINESS_ENDPOINT: &str = "/readyz";
+const NDJSON: &str = "application/x-ndjson";
+const MULTIPART_MIXED: &str = "multipart/mixed";
+const PROBLEM_JSON: &str = "application/problem+json";
 
 fn prometheus_metrics(prometheus_exporter: &PrometheusExporter) -> Result<Response<Body>> {
     let metric_families = prometheus::default_registry().gather();
@@ -48,6 +61,41 @@ fn prometheus_metrics(prometheus_exporter: &PrometheusExporter) -> Result<Respon
         .body(Body::from(buffer))?)
 }
 
+fn json_response(status: StatusCode, body: serde_json::Value) -> Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))?)
+}
+
+/// Responds with `503` if any of the upstreams listed in
+/// `server.readinessChecks` doesn't respond successfully in time.
+async fn readiness(app_ctx: &AppContext) -> Result<Response<Body>> {
+    let unreachable = match app_ctx.blueprint.server.readiness.as_ref() {
+        Some(readiness) => {
+            let checks = readiness.urls.iter().map(|url| async move {
+                let req = reqwest::Request::new(reqwest::Method::GET, url.clone());
+                let res =
+                    tokio::time::timeout(readiness.timeout, app_ctx.runtime.http.execute(req))
+                        .await;
+                (!matches!(res, Ok(Ok(_)))).then_some(url.as_str())
+            });
+
+            join_all(checks).await.into_iter().flatten().collect()
+        }
+        None => vec![],
+    };
+
+    if unreachable.is_empty() {
+        json_response(StatusCode::OK, serde_json::json!({"message": "ready"}))
+    } else {
+        json_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            serde_json::json!({"message": "not ready", "unreachable": unreachable}),
+        )
+    }
+}
+
 fn not_found() -> Result<Response<Body>> {
     Ok(Response::builder()
         .status(StatusCode::NOT_FOUND)
@@ -57,7 +105,29 @@ fn not_found() -> Result<Response<Body>> {
 fn create_request_context(req: &Request<Body>, app_ctx: &AppContext) -> RequestContext {
     let allowed_headers =
         create_allowed_headers(req.headers(), &app_ctx.blueprint.upstream.allowed_headers);
-    RequestContext::from(app_ctx).allowed_headers(allowed_headers)
+    RequestContext::from(app_ctx)
+        .allowed_headers(allowed_headers)
+        .deadline(request_deadline(req, app_ctx))
+}
+
+/// The time by which the request should be served, from the shortest of the
+/// budget sent by the client and the configured maximum.
+#[cfg(not(target_arch = "wasm32"))]
+fn request_deadline(req: &Request<Body>, app_ctx: &AppContext) -> Option<Instant> {
+    req.headers()
+        .get(GRPC_TIMEOUT)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_grpc_timeout)
+        .into_iter()
+        .chain(app_ctx.blueprint.server.max_deadline)
+        .min()
+        .map(|timeout| Instant::now() + timeout)
+}
+
+// `Instant::now` panics on wasm32, so the requests have no deadline there.
+#[cfg(target_arch = "wasm32")]
+fn request_deadline(_: &Request<Body>, _: &AppContext) -> Option<Instant> {
+    None
 }
 
 pub fn update_response_headers(
@@ -79,6 +149,20 @@ pub fn update_response_headers(
 
     // Insert Experimental Headers
     req_ctx.extend_x_headers(resp.headers_mut());
+
+    // Allow clients to serve the response stale while the cache is revalidated
+    if let Some(swr) = req_ctx.get_min_stale_while_revalidate() {
+        let cache_control = resp
+            .headers()
+            .get(header::CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| value.contains("max-age"))
+            .map(|value| format!("{}, stale-while-revalidate={}", value, swr));
+
+        if let Some(value) = cache_control.and_then(|value| HeaderValue::from_str(&value).ok()) {
+            resp.headers_mut().insert(header::CACHE_CONTROL, value);
+        }
+    }
 }
 
 #[tracing::instrument(skip_all, fields(otel.name = "graphQL", otel.kind = ?SpanKind::Server))]
@@ -93,6 +177,23 @@ pub async fn graphql_request<T: DeserializeOwned + GraphQLRequestLike>(
     let bytes = hyper::body::to_bytes(body).await?;
     let graphql_request = serde_json::from_slice::<T>(&bytes);
     match graphql_request {
+        Ok(request) if accepts_ndjson(&req.headers) => match request.into_single() {
+            Ok(request) => stream_query(app_ctx, &req_ctx, request, req),
+            Err(request) => execute_query(app_ctx, &req_ctx, request, req).await,
+        },
+        Ok(request) if accepts(&req.headers, MULTIPART_MIXED) => match request.into_single() {
+            Ok(request) => {
+                let request = GraphQLRequest(request);
+                let operation_id = request.operation_id(&req.headers);
+                let exec = JITExecutor::new(app_ctx.clone(), req_ctx.clone(), operation_id);
+                if exec.is_incremental(&request.0) {
+                    stream_incremental(app_ctx, &req_ctx, exec, request)
+                } else {
+                    execute_query(app_ctx, &req_ctx, request, req).await
+                }
+            }
+            Err(request) => execute_query(app_ctx, &req_ctx, request, req).await,
+        },
         Ok(request) => {
             let resp = execute_query(app_ctx, &req_ctx, request, req).await?;
             Ok(resp)
@@ -103,16 +204,61 @@ pub async fn graphql_request<T: DeserializeOwned + GraphQLRequestLike>(
                 String::from_utf8(bytes.to_vec()).unwrap()
             );
 
-            let mut response = async_graphql::Response::default();
-            let server_error =
-                ServerError::new(format!("Unexpected GraphQL Request: {}", err), None);
-            response.errors = vec![server_error];
+            let message = format!("Unexpected GraphQL Request: {}", err);
+            graphql_error(message, StatusCode::OK, &req_ctx, app_ctx)
+        }
+    }
+}
+
+/// Executes the query sent in the query string of a GET request. Mutations are
+/// rejected, as GET requests must not change any state.
+pub async fn graphql_get_request(
+    req: Request<Body>,
+    app_ctx: &Arc<AppContext>,
+    req_counter: &mut RequestCounter,
+) -> Result<Response<Body>> {
+    req_counter.set_http_route("/graphql");
+    let req_ctx = Arc::new(create_request_context(&req, app_ctx));
+    let (req, _) = req.into_parts();
+    let query = req.uri.query().unwrap_or_default();
+
+    match serde_urlencoded::from_str::<GraphQLQuery>(query) {
+        Ok(query) => {
+            let mut request = GraphQLRequest::from(query);
+            if request.is_mutation() {
+                let message = "Mutations can't be sent with GET requests".to_string();
+                let mut response =
+                    graphql_error(message, StatusCode::METHOD_NOT_ALLOWED, &req_ctx, app_ctx)?;
+                response
+                    .headers_mut()
+                    .insert(header::ALLOW, HeaderValue::from_static("POST"));
+                return Ok(response);
+            }
 
-            Ok(GraphQLResponse::from(response).into_response()?)
+            execute_query(app_ctx, &req_ctx, request, req).await
+        }
+        Err(err) => {
+            let message = format!("Unexpected GraphQL Request: {}", err);
+            graphql_error(message, StatusCode::BAD_REQUEST, &req_ctx, app_ctx)
         }
     }
 }
 
+fn graphql_error(
+    message: String,
+    status: StatusCode,
+    req_ctx: &RequestContext,
+    app_ctx: &AppContext,
+) -> Result<Response<Body>> {
+    let mut response = async_graphql::Response::default();
+    response.errors = vec![ServerError::new(message, None)];
+
+    let mut response = GraphQLResponse::from(response).into_response()?;
+    *response.status_mut() = status;
+    update_response_headers(&mut response, req_ctx, app_ctx);
+    Ok(response)
+}
+
 async fn execute_query<T: DeserializeOwned + GraphQLRequestLike>(
     app_ctx: &Arc<AppContext>,
     req_ctx: &Arc<RequestContext>,
@@ -135,6 +281,74 @@ async fn execute_query<T: DeserializeOwned + GraphQLRequestLike>(
     Ok(response)
 }
 
+fn accepts_ndjson(headers: &HeaderMap) -> bool {
+    accepts(headers, NDJSON)
+}
+
+fn accepts(headers: &HeaderMap, media_type: &str) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.contains(media_type))
+}
+
+/// Streams the response to a single operation as newline delimited JSON, one
+/// line per element of its root list, to clients that accept it. The lines are
+/// fed from a spawned task so that the response starts before the whole list
+/// is written.
+fn stream_query(
+    app_ctx: &Arc<AppContext>,
+    req_ctx: &Arc<RequestContext>,
+    request: async_graphql::Request,
+    req: Parts,
+) -> Result<Response<Body>> {
+    let request = GraphQLRequest(request);
+    let operation_id = request.operation_id(&req.headers);
+    let exec = JITExecutor::new(app_ctx.clone(), req_ctx.clone(), operation_id);
+    let (mut sender, body) = Body::channel();
+    let task = async move { exec.execute_lines(request.0, &mut sender).await };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(task);
+    #[cfg(target_arch = "wasm32")]
+    async_std::task::spawn_local(task);
+
+    let mut response = Response::new(body);
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(NDJSON));
+    update_response_headers(&mut response, req_ctx, app_ctx);
+    Ok(response)
+}
+
+/// Delivers the response to a single operation using `@defer` or `@stream`
+/// incrementally, as the parts of a `multipart/mixed` body, to clients that
+/// accept it. Like [`stream_query`], the parts are fed from a spawned task.
+fn stream_incremental(
+    app_ctx: &Arc<AppContext>,
+    req_ctx: &Arc<RequestContext>,
+    exec: JITExecutor,
+    request: GraphQLRequest,
+) -> Result<Response<Body>> {
+    let (mut sender, body) = Body::channel();
+    let task = async move { exec.execute_incremental(request.0, &mut sender).await };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(task);
+    #[cfg(target_arch = "wasm32")]
+    async_std::task::spawn_local(task);
+
+    let content_type =
+        format!("{MULTIPART_MIXED}; boundary=\"{MULTIPART_BOUNDARY}\"; deferSpec=20220824");
+    let mut response = Response::new(body);
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_str(&content_type)?);
+    update_response_headers(&mut response, req_ctx, app_ctx);
+    Ok(response)
+}
+
 fn create_allowed_headers(headers: &HeaderMap, allowed: &BTreeSet<String>) -> HeaderMap {
     let mut new_headers = HeaderMap::with_capacity(allowed.len());
     for (k, v) in headers.iter() {
@@ -142,7 +356,7 @@ fn create_allowed_headers(headers: &HeaderMap, allowed: &BTreeSet<String>) -> He
             .iter()
             .any(|allowed_key| allowed_key.eq_ignore_ascii_case(k.as_str()))
         {
-            new_headers.insert(k, v.clone());
+            new_headers.append(k, v.clone());
         }
     }
     new_headers
@@ -251,8 +465,27 @@ async fn handle_rest_apis(
             { HTTP_ROUTE } = http_route
         );
         return async {
-            let graphql_request = p_request.into_request(request).await?;
-            let mut response = graphql_request
+            let envelope = p_request.envelope;
+            let json_api = p_request.json_api;
+            let problem_details = accepts(request.headers(), PROBLEM_JSON);
+            let instance = format!("{API_URL_PREFIX}{}", request.uri().path());
+            let graphql_request = match p_request.into_request(request, &req_ctx).await {
+                Ok(graphql_request) => graphql_request,
+                Err(error @ rest::Error::InvalidBody(_)) if problem_details => {
+                    let mut response = async_graphql::Response::default();
+                    response.errors = vec![ServerError::new(error.to_string(), None)];
+                    let mut response =
+                        GraphQLResponse::from(response).into_problem_details_response(&instance)?;
+                    update_response_headers(&mut response, &req_ctx, &app_ctx);
+                    return Ok(response);
+                }
+                Err(error @ rest::Error::InvalidBody(_)) => {
+                    let message = error.to_string();
+                    return graphql_error(message, StatusCode::BAD_REQUEST, &req_ctx, &app_ctx);
+                }
+                Err(error) => return Err(error.into()),
+            };
+            let response = graphql_request
                 .data(req_ctx.clone())
                 .execute(&app_ctx.schema)
                 .await
@@ -260,8 +493,15 @@ async fn handle_rest_apis(
                     app_ctx.blueprint.server.enable_cache_control_header,
                     req_ctx.get_min_max_age().unwrap_or(0),
                     req_ctx.is_cache_public().unwrap_or(true),
-                )
-                .into_rest_response()?;
+                );
+            let mut response = match (json_api, envelope) {
+                _ if problem_details && !response.0.is_ok() => {
+                    response.into_problem_details_response(&instance)?
+                }
+                (Some(json_api), _) => response.into_json_api_response(json_api)?,
+                (None, Some(envelope)) => response.into_rest_envelope_response(envelope)?,
+                (None, None) => response.into_rest_response()?,
+            };
             update_response_headers(&mut response, &req_ctx, &app_ctx);
             Ok(response)
         }
@@ -291,6 +531,9 @@ async fn handle_request_inner<T: DeserializeOwned + GraphQLRequestLike>(
         Method::POST if req.uri().path() == graphql_endpoint => {
             graphql_request::<T>(req, &app_ctx, req_counter).await
         }
+        Method::GET if req.uri().path() == graphql_endpoint && req.uri().query().is_some() => {
+            graphql_get_request(req, &app_ctx, req_counter).await
+        }
         Method::POST
             if app_ctx.blueprint.server.enable_showcase
                 && req.uri().path() == "/showcase/graphql" =>
@@ -310,6 +553,10 @@ async fn handle_request_inner<T: DeserializeOwned + GraphQLRequestLike>(
                 .body(Body::from(r#"{"message": "ready"}"#))?;
             Ok(status_response)
         }
+        Method::GET if req.uri().path() == LIVENESS_ENDPOINT => {
+            json_response(StatusCode::OK, serde_json::json!({"message": "alive"}))
+        }
+        Method::GET if req.uri().path() == READINESS_ENDPOINT => readiness(&app_ctx).await,
         Method::GET => {
             if let Some(TelemetryExporter::Prometheus(prometheus)) =
                 app_ctx.blueprint.telemetry.export.as_ref()
@@ -341,6 +588,17 @@ pub async fn handle_request<T: DeserializeOwned + GraphQLRequestLike>(
     telemetry::propagate_context(&req);
     let mut req_counter = RequestCounter::new(&app_ctx.blueprint.telemetry, &req);
 
+    let (req, access_log) = match app_ctx.blueprint.server.access_log.as_ref() {
+        Some(access_log)
+            if req.uri().path() == app_ctx.blueprint.server.routes.graphql()
+                && access_log.sample() =>
+        {
+            let (req, entry) = AccessLogEntry::new(req, access_log).await?;
+            (req, Some(entry))
+        }
+        _ => (req, None),
+    };
+
     let response = if app_ctx.blueprint.server.cors.is_some() {
         handle_request_with_cors::<T>(req, app_ctx, &mut req_counter).await
     } else if let Some(origin) = req.headers().get(&header::ORIGIN) {
@@ -353,6 +611,11 @@ pub async fn handle_request<T: DeserializeOwned + GraphQLRequestLike>(
         handle_request_inner::<T>(req, app_ctx, &mut req_counter).await
     };
 
+    let response = match access_log {
+        Some(entry) => entry.finish(response).await,
+        None => response,
+    };
+
     req_counter.update(&response);
     if let Ok(response) = &response {
         let status = get_response_status_code(response);
@@ -367,9 +630,8 @@ mod test {
     use tailcall_valid::Validator;
 
     use super::*;
-    use crate::core::async_graphql_hyper::GraphQLRequest;
     use crate::core::blueprint::Blueprint;
-    use crate::core::config::{Config, ConfigModule, Routes};
+    use crate::core::config::{AccessLog, Config, ConfigModule, ReadinessChecks, Routes};
     use crate::core::rest::EndpointSet;
     use crate::core::runtime::test::init;
 
@@ -399,6 +661,70 @@ mod test {
         Ok(())
     }
 
+    async fn get(
+        path: &str,
+        readiness_checks: Option<ReadinessChecks>,
+    ) -> anyhow::Result<Response<Body>> {
+        let sdl = tokio::fs::read_to_string(tailcall_fixtures::configs::JSONPLACEHOLDER).await?;
+        let mut config = Config::from_sdl(&sdl).to_result()?;
+        config.server.readiness_checks = readiness_checks;
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+        let app_ctx = Arc::new(AppContext::new(
+            blueprint,
+            init(None),
+            EndpointSet::default(),
+        ));
+
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri(format!("http://localhost:8000{path}"))
+            .body(Body::empty())?;
+
+        handle_request::<GraphQLRequest>(req, app_ctx).await
+    }
+
+    #[tokio::test]
+    async fn test_readiness_healthy_upstream() -> anyhow::Result<()> {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/");
+            then.status(200);
+        });
+
+        let checks = ReadinessChecks { urls: vec![server.base_url()], timeout: None };
+        let resp = get(READINESS_ENDPOINT, Some(checks)).await?;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        mock.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_readiness_unreachable_upstream() -> anyhow::Result<()> {
+        let url = "http://127.0.0.1:1/".to_string();
+        let checks = ReadinessChecks { urls: vec![url.clone()], timeout: Some(100) };
+        let resp = get(READINESS_ENDPOINT, Some(checks)).await?;
+
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        let body: serde_json::Value = serde_json::from_slice(&body)?;
+        assert_eq!(body["unreachable"], serde_json::json!([url]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_liveness_and_readiness_without_checks() -> anyhow::Result<()> {
+        let resp = get(LIVENESS_ENDPOINT, None).await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = get(READINESS_ENDPOINT, None).await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_graphql_endpoint() -> anyhow::Result<()> {
         let sdl = tokio::fs::read_to_string(tailcall_fixtures::configs::JSONPLACEHOLDER).await?;
@@ -429,6 +755,318 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_custom_headers_on_error() -> anyhow::Result<()> {
+        let sdl = tokio::fs::read_to_string(tailcall_fixtures::configs::JSONPLACEHOLDER).await?;
+        let config = Config::from_sdl(&sdl).to_result()?;
+        let mut blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+        blueprint
+            .server
+            .response_headers
+            .insert("x-frame-options", HeaderValue::from_static("DENY"));
+        let app_ctx = Arc::new(AppContext::new(
+            blueprint,
+            init(None),
+            EndpointSet::default(),
+        ));
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("http://localhost:8000/graphql")
+            .header("Content-Type", "application/json")
+            .body(Body::from("not a request"))?;
+
+        let resp = handle_request::<GraphQLRequest>(req, app_ctx).await?;
+
+        assert_eq!(resp.headers().get("x-frame-options").unwrap(), "DENY");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_graphql_get_query() -> anyhow::Result<()> {
+        let resp = get("/graphql?query=%7B%20__typename%20%7D", None).await?;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        let body: serde_json::Value = serde_json::from_slice(&body)?;
+        assert_eq!(body, serde_json::json!({"data": {"__typename": "Query"}}));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_graphql_get_mutation() -> anyhow::Result<()> {
+        let resp = get("/graphql?query=mutation%20%7B%20__typename%20%7D", None).await?;
+
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "POST");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_graphql_incremental() -> anyhow::Result<()> {
+        use hyper::body::HttpBody;
+
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/user");
+            then.status(200).json_body(serde_json::json!({"id": 1}));
+        });
+        // the initial payload has to be sent without waiting for the deferred posts
+        let delay = std::time::Duration::from_secs(2);
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/posts");
+            then.status(200)
+                .json_body(serde_json::json!([{"id": 1}]))
+                .delay(delay);
+        });
+        let sdl = format!(
+            r#"
+            schema {{
+              query: Query
+            }}
+
+            type Query {{
+              user: User @http(url: "{base}/user")
+            }}
+
+            type User {{
+              id: Int
+              posts: [Post] @http(url: "{base}/posts")
+            }}
+
+            type Post {{
+              id: Int
+            }}
+            "#,
+            base = server.base_url()
+        );
+        let config = Config::from_sdl(&sdl).to_result()?;
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+        let app_ctx = Arc::new(AppContext::new(
+            blueprint,
+            init(None),
+            EndpointSet::default(),
+        ));
+        let post = |query: &str| {
+            let body = serde_json::json!({ "query": query }).to_string();
+            Request::builder()
+                .method(Method::POST)
+                .uri("http://localhost:8000/graphql")
+                .header("Content-Type", "application/json")
+                .header("Accept", MULTIPART_MIXED)
+                .body(Body::from(body))
+        };
+
+        // the plan decides, not the text of the query
+        let resp =
+            handle_request::<GraphQLRequest>(post("{ user { id } } # @defer")?, app_ctx.clone())
+                .await?;
+        let content_type = resp.headers().get(CONTENT_TYPE).unwrap().to_str()?;
+        assert!(content_type.starts_with("application/json"));
+
+        let resp = handle_request::<GraphQLRequest>(
+            post("{ user { id ... @defer { posts { id } } } }")?,
+            app_ctx,
+        )
+        .await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let content_type = resp.headers().get(CONTENT_TYPE).unwrap().to_str()?;
+        assert!(content_type.starts_with(MULTIPART_MIXED));
+
+        let mut body = resp.into_body();
+        let initial = tokio::time::timeout(delay / 2, body.data())
+            .await?
+            .unwrap()?;
+        let mut rest = Vec::new();
+        while let Some(chunk) = body.data().await {
+            rest.extend_from_slice(&chunk?);
+        }
+
+        let body = String::from_utf8([initial.to_vec(), rest].concat())?;
+        let parts = body
+            .split(&format!("\r\n--{MULTIPART_BOUNDARY}"))
+            .filter_map(|part| part.split_once("\r\n\r\n"))
+            .map(|(_, json)| serde_json::from_str(json))
+            .collect::<std::result::Result<Vec<serde_json::Value>, _>>()?;
+
+        assert_eq!(
+            parts,
+            vec![
+                serde_json::json!({"data": {"user": {"id": 1}}, "hasNext": true}),
+                serde_json::json!({
+                    "incremental": [{"data": {"posts": [{"id": 1}]}, "path": ["user"]}],
+                    "hasNext": false
+                }),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_multipart_forwarding() -> anyhow::Result<()> {
+        use crate::core::http::multipart::{self, Part};
+
+        let server = httpmock::MockServer::start();
+        // the parts are encoded again, with the file sent as is
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/files")
+                .header_exists("content-type")
+                .body_contains("Content-Disposition: form-data; name=\"title\"\r\n\r\nReport")
+                .body_contains(
+                    "Content-Disposition: form-data; name=\"file\"; filename=\"report.txt\"\r\n\
+                     Content-Type: text/plain\r\n\r\nHello, World!",
+                );
+            then.status(200).json_body(serde_json::json!({"id": 1}));
+        });
+        let sdl = format!(
+            r#"
+            schema {{
+              query: Query
+              mutation: Mutation
+            }}
+
+            type Query {{
+              file(id: Int!): File @http(url: "{base}/files/{{{{.args.id}}}}")
+            }}
+
+            input FileForm {{
+              title: String
+              file: Upload
+            }}
+
+            type Mutation {{
+              upload(form: FileForm): File
+                @http(url: "{base}/files", method: POST, body: "{{{{.args.form}}}}", encoding: MultipartFormData)
+            }}
+
+            type File {{
+              id: Int!
+            }}
+            "#,
+            base = server.base_url()
+        );
+        let config = Config::from_sdl(&sdl).to_result()?;
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+        let endpoints = EndpointSet::try_new(
+            r#"
+            mutation ($form: FileForm) @rest(method: POST, path: "/files", body: $form) {
+              upload(form: $form) { id }
+            }
+            "#,
+        )?
+        .into_checked(&blueprint, init(None))
+        .await?;
+        let app_ctx = Arc::new(AppContext::new(blueprint, init(None), endpoints));
+
+        let (boundary, body) = multipart::encode(&[
+            Part {
+                name: "title".to_string(),
+                filename: None,
+                content_type: None,
+                content: "Report".into(),
+            },
+            Part {
+                name: "file".to_string(),
+                filename: Some("report.txt".to_string()),
+                content_type: Some("text/plain".to_string()),
+                content: "Hello, World!".into(),
+            },
+        ]);
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("http://localhost:8000/api/files")
+            .header(
+                "Content-Type",
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(Body::from(body))?;
+
+        let resp = handle_request::<GraphQLRequest>(req, app_ctx).await?;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        let body: serde_json::Value = serde_json::from_slice(&body)?;
+        assert_eq!(body, serde_json::json!({"id": 1}));
+        mock.assert();
+
+        Ok(())
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_access_log() -> anyhow::Result<()> {
+        let sdl = tokio::fs::read_to_string(tailcall_fixtures::configs::JSONPLACEHOLDER).await?;
+        let mut config = Config::from_sdl(&sdl).to_result()?;
+        config.server.access_log = Some(AccessLog {
+            enabled: true,
+            sample_rate: None,
+            redact_variables: vec!["password".to_string()],
+        });
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+        let app_ctx = Arc::new(AppContext::new(
+            blueprint,
+            init(None),
+            EndpointSet::default(),
+        ));
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let query = r#"{
+            "query": "query Login { __typename }",
+            "operationName": "Login",
+            "variables": {"user": "foo", "password": "secret"}
+        }"#;
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("http://localhost:8000/graphql")
+            .header("Content-Type", "application/json")
+            .body(Body::from(query))?;
+
+        let resp = handle_request::<GraphQLRequest>(req, app_ctx).await?;
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone())?;
+        let log = logs
+            .lines()
+            .find(|line| line.contains("access_log"))
+            .expect("access log event");
+
+        assert!(log.contains("operation_name=Login"));
+        assert!(log.contains(r#""password":"[REDACTED]""#));
+        assert!(log.contains(r#""user":"foo""#));
+        assert!(log.contains("status=200"));
+        assert!(log.contains("errors=0"));
+        assert!(log.contains(&format!("response_bytes={}", body.len())));
+        assert!(log.contains("duration_ms="));
+        assert!(!log.contains("secret"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_create_allowed_headers() {
         use std::collections::BTreeSet;
@@ -449,4 +1087,23 @@ mod test {
         assert_eq!(new_headers.get("x-foo").unwrap(), "bar");
         assert_eq!(new_headers.get("x-bar").unwrap(), "foo");
     }
+
+    #[test]
+    fn test_create_allowed_headers_multi_value() {
+        use std::collections::BTreeSet;
+
+        use http::header::{HeaderMap, HeaderValue};
+
+        use super::create_allowed_headers;
+
+        let mut headers = HeaderMap::new();
+        headers.append("x-forward", HeaderValue::from_static("a"));
+        headers.append("x-forward", HeaderValue::from_static("b"));
+
+        let allowed = BTreeSet::from_iter(vec!["x-forward".to_string()]);
+
+        let new_headers = create_allowed_headers(&headers, &allowed);
+        let values = new_headers.get_all("x-forward").iter().collect::<Vec<_>>();
+        assert_eq!(values, vec!["a", "b"]);
+    }
 }
@@ -1,11 +1,14 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
+use std::time::Duration;
 
 use derive_setters::Setters;
 use http::header::{HeaderMap, HeaderValue};
 use tailcall_hasher::TailcallHasher;
 use url::Url;
 
+use super::multipart::{self, Part};
 use super::query_encoder::QueryEncoder;
 use crate::core::config::Encoding;
 use crate::core::endpoint::Endpoint;
@@ -14,7 +17,9 @@ use crate::core::helpers::headers::MustacheHeaders;
 use crate::core::ir::model::{CacheKey, IoId};
 use crate::core::ir::DynamicRequest;
 use crate::core::mustache::{Eval, Mustache, Segment};
-use crate::core::path::{PathString, PathValue, ValueString};
+use crate::core::path::{convert_value, PathString, PathValue, ValueString};
+
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
 
 /// RequestTemplate is an extension of a Mustache template.
 /// Various parts of the template can be written as a mustache template.
@@ -23,13 +28,27 @@ use crate::core::path::{PathString, PathValue, ValueString};
 #[derive(Setters, Debug, Clone)]
 pub struct RequestTemplate {
     pub root_url: Mustache,
+    /// Separators of the expressions in `root_url` that join a list into the
+    /// path, e.g. `/tags/{{.args.tags | join:,}}`, keyed by segment index.
+    pub path_joins: BTreeMap<usize, String>,
     pub query: Vec<Query>,
     pub method: reqwest::Method,
     pub headers: MustacheHeaders,
     pub body_path: Option<Mustache>,
+    /// Names of the arguments sent as the keys of an object body, used when
+    /// there's no `body_path`.
+    pub body_args: Vec<String>,
     pub endpoint: Endpoint,
     pub encoding: Encoding,
     pub query_encoder: QueryEncoder,
+    /// Overrides the timeout of the client for this request, a zero duration
+    /// disables it.
+    pub timeout: Option<Duration>,
+    /// The number of times the request is retried when it fails.
+    pub retry: usize,
+    /// The template of the `Idempotency-Key` header, a template without
+    /// expressions generates a random key for every request.
+    pub idempotency_key: Option<Mustache>,
 }
 
 #[derive(Setters, Debug, Clone)]
@@ -43,7 +62,7 @@ impl RequestTemplate {
     /// Creates a URL for the context
     /// Fills in all the mustache templates with required values.
     fn create_url<C: PathString + PathValue>(&self, ctx: &C) -> anyhow::Result<Url> {
-        let mut url = url::Url::parse(self.root_url.render(ctx).as_str())?;
+        let mut url = url::Url::parse(self.render_root_url(ctx)?.as_str())?;
         if self.query.is_empty() && self.root_url.is_const() {
             return Ok(url);
         }
@@ -88,22 +107,83 @@ impl RequestTemplate {
         }
     }
 
+    /// Renders the root url, joining the lists of the expressions that have a
+    /// `join` filter with their separator. Headers rendered into the host have
+    /// to be DNS labels, so that a client can't point the request to another
+    /// host.
+    fn render_root_url<C: PathString + PathValue>(&self, ctx: &C) -> anyhow::Result<String> {
+        let mut url = String::new();
+
+        for (i, segment) in self.root_url.segments().iter().enumerate() {
+            match segment {
+                Segment::Literal(text) => url.push_str(text),
+                Segment::Expression(parts) => {
+                    let value = match self.path_joins.get(&i) {
+                        Some(separator) => join_value(ctx.raw_value(parts), separator),
+                        None => ctx
+                            .path_string(parts)
+                            .map(|a| a.to_string())
+                            .unwrap_or_default(),
+                    };
+
+                    if parts.first().is_some_and(|part| part == "headers")
+                        && is_in_authority(&url)
+                        && !is_dns_label(&value)
+                    {
+                        anyhow::bail!(
+                            "Header '{}' can't be used as the host of the url: '{}' is not a valid DNS label",
+                            parts[1..].join("."),
+                            value
+                        );
+                    }
+
+                    url.push_str(&value);
+                }
+            }
+        }
+
+        Ok(url)
+    }
+
     /// Checks if the template has any mustache templates or not
     /// Returns true if there are not templates
     pub fn is_const(&self) -> bool {
         self.root_url.is_const()
             && self.body_path.as_ref().map_or(true, |b| b.is_const())
+            && self.body_args.is_empty()
             && self.query.iter().all(|query| query.value.is_const())
             && self.headers.iter().all(|(_, v)| v.is_const())
+            && self.idempotency_key.is_none()
     }
 
-    /// Creates a HeaderMap for the context
-    fn create_headers<C: PathString>(&self, ctx: &C) -> HeaderMap {
+    /// Returns the argument sent as is as the body, ie. `{{.args.<name>}}`
+    /// with an encoding other than multipart, so that the unread body of the
+    /// inbound request can be streamed in its place.
+    pub fn forwarded_arg(&self) -> Option<&str> {
+        if self.encoding == Encoding::MultipartFormData {
+            return None;
+        }
+
+        match self.body_path.as_ref()?.segments().as_slice() {
+            [Segment::Expression(parts)] if parts.len() == 2 && parts[0] == "args" => {
+                Some(parts[1].as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// Creates a HeaderMap for the context. Headers sharing a name, and
+    /// templates that resolve to a list, emit repeated header entries. Values
+    /// rendered from secrets are marked sensitive, which hides them from logs.
+    fn create_headers<C: PathString + PathValue>(&self, ctx: &C) -> HeaderMap {
         let mut header_map = HeaderMap::new();
 
         for (k, v) in &self.headers {
-            if let Ok(header_value) = HeaderValue::from_str(&v.render(ctx)) {
-                header_map.insert(k, header_value);
+            for value in render_header_values(v, ctx) {
+                if let Ok(mut header_value) = HeaderValue::from_str(&value) {
+                    header_value.set_sensitive(v.has_secrets());
+                    header_map.append(k, header_value);
+                }
             }
         }
 
@@ -118,12 +198,34 @@ impl RequestTemplate {
         let url = self.create_url(ctx)?;
         let method = self.method.clone();
         let req = reqwest::Request::new(method, url);
+        let req = self.set_timeout(req);
         let req = self.set_headers(req, ctx);
         self.set_body(req, ctx)
     }
 
+    /// Sets the timeout of the field for the request
+    #[cfg(not(target_arch = "wasm32"))]
+    fn set_timeout(&self, mut req: reqwest::Request) -> reqwest::Request {
+        if let Some(timeout) = self.timeout {
+            // The client timeout applies to the requests without one, so a
+            // disabled timeout is replaced with one that never elapses.
+            let timeout = if timeout.is_zero() {
+                Duration::MAX
+            } else {
+                timeout
+            };
+            *req.timeout_mut() = Some(timeout);
+        }
+        req
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn set_timeout(&self, req: reqwest::Request) -> reqwest::Request {
+        req
+    }
+
     /// Sets the body for the request
-    fn set_body<C: PathString + HasHeaders>(
+    fn set_body<C: PathString + HasHeaders + PathValue>(
         &self,
         mut req: reqwest::Request,
         ctx: &C,
@@ -148,15 +250,70 @@ impl RequestTemplate {
                     req.body_mut().replace(form_data.into());
                     None
                 }
+                Encoding::MultipartFormData => {
+                    set_multipart_body(&mut req, self.body_value(body_path, ctx))?;
+                    None
+                }
+            }
+        } else if !self.body_args.is_empty() {
+            let body = self.body_args_value(ctx);
+            match &self.encoding {
+                Encoding::ApplicationJson => {
+                    req.body_mut().replace(serde_json::to_string(&body)?.into());
+                }
+                Encoding::ApplicationXWwwFormUrlencoded => {
+                    req.body_mut()
+                        .replace(serde_urlencoded::to_string(&body)?.into());
+                }
+                Encoding::MultipartFormData => set_multipart_body(&mut req, body)?,
             }
+            None
         } else {
             None
         };
         Ok(DynamicRequest::new(req).with_batching_value(batching_value))
     }
 
+    /// Builds the object of the arguments listed in `body_args`, leaving out
+    /// the ones that aren't provided.
+    fn body_args_value<C: PathValue>(&self, ctx: &C) -> async_graphql::Value {
+        let map = self
+            .body_args
+            .iter()
+            .filter_map(|name| {
+                let value = match ctx.raw_value(&["args", name.as_str()])? {
+                    ValueString::Value(value) => value.into_owned(),
+                    ValueString::String(text) => async_graphql::Value::String(text.into_owned()),
+                };
+                Some((async_graphql::Name::new(name), value))
+            })
+            .collect();
+
+        async_graphql::Value::Object(map)
+    }
+
+    /// Evaluates the body into a value. A body made of a single expression
+    /// keeps the value as is, so that binary content such as the one of an
+    /// `Upload` isn't lost by rendering it to a string.
+    fn body_value<C: PathString + PathValue>(
+        &self,
+        body_path: &Mustache,
+        ctx: &C,
+    ) -> async_graphql::Value {
+        if let [Segment::Expression(parts)] = body_path.segments().as_slice() {
+            return match ctx.raw_value(parts) {
+                Some(ValueString::Value(value)) => value.into_owned(),
+                Some(ValueString::String(text)) => async_graphql::Value::String(text.into_owned()),
+                None => async_graphql::Value::Null,
+            };
+        }
+
+        let body = body_path.render(ctx);
+        serde_json::from_str(&body).unwrap_or(async_graphql::Value::String(body))
+    }
+
     /// Sets the headers for the request
-    fn set_headers<C: PathString + HasHeaders>(
+    fn set_headers<C: PathString + HasHeaders + PathValue>(
         &self,
         mut req: reqwest::Request,
         ctx: &C,
@@ -170,7 +327,9 @@ impl RequestTemplate {
         // We want to set the header value based on encoding
         // TODO: potential of optimizations.
         // Can set content-type headers while creating the request template
-        if self.method != reqwest::Method::GET {
+        // The content type of multipart bodies carries their boundary and is
+        // set along with the body.
+        if self.method != reqwest::Method::GET && self.encoding != Encoding::MultipartFormData {
             headers.insert(
                 reqwest::header::CONTENT_TYPE,
                 match self.encoding {
@@ -178,24 +337,49 @@ impl RequestTemplate {
                     Encoding::ApplicationXWwwFormUrlencoded => {
                         HeaderValue::from_static("application/x-www-form-urlencoded")
                     }
+                    Encoding::MultipartFormData => HeaderValue::from_static("multipart/form-data"),
                 },
             );
         }
 
         headers.extend(ctx.headers().to_owned());
+
+        // The key is set once, the retries are clones of the request and keep it.
+        // It's inserted last so that a forwarded header can't replace it.
+        if let Some(key) = &self.idempotency_key {
+            let key = if key.is_const() {
+                // the same request gets the same key, so that it's still
+                // deduplicated and batched with its identical requests
+                self.cache_key(ctx)
+                    .map(|id| format!("{:016x}", id.as_u64()))
+                    .unwrap_or_default()
+            } else {
+                key.render(ctx)
+            };
+            if let Ok(value) = HeaderValue::from_str(&key) {
+                headers.insert(IDEMPOTENCY_KEY, value);
+            }
+        }
+
         req
     }
 
     pub fn new(root_url: &str) -> anyhow::Result<Self> {
+        let (path, path_joins) = parse_path(root_url);
         Ok(Self {
-            root_url: Mustache::parse(root_url),
+            root_url: path,
+            path_joins,
             query: Default::default(),
             method: reqwest::Method::GET,
             headers: Default::default(),
             body_path: Default::default(),
+            body_args: Default::default(),
             endpoint: Endpoint::new(root_url.to_string()),
             encoding: Default::default(),
             query_encoder: Default::default(),
+            timeout: None,
+            retry: 0,
+            idempotency_key: None,
         })
     }
 
@@ -213,7 +397,7 @@ impl RequestTemplate {
 impl TryFrom<Endpoint> for RequestTemplate {
     type Error = anyhow::Error;
     fn try_from(endpoint: Endpoint) -> anyhow::Result<Self> {
-        let path = Mustache::parse(endpoint.path.as_str());
+        let (path, path_joins) = parse_path(endpoint.path.as_str());
         let query = endpoint
             .query
             .iter()
@@ -240,17 +424,146 @@ impl TryFrom<Endpoint> for RequestTemplate {
 
         Ok(Self {
             root_url: path,
+            path_joins,
             query,
             method,
             headers,
             body_path: body,
+            body_args: Default::default(),
             endpoint,
             encoding,
             query_encoder: Default::default(),
+            timeout: None,
+            retry: 0,
+            idempotency_key: None,
         })
     }
 }
 
+/// Sets `value` as the body of `req`, each key of an object being a part.
+fn set_multipart_body(
+    req: &mut reqwest::Request,
+    value: async_graphql::Value,
+) -> anyhow::Result<()> {
+    let parts = match value {
+        async_graphql::Value::Object(map) => map
+            .iter()
+            .map(|(name, value)| Part::from_value(name, value))
+            .collect(),
+        value => {
+            anyhow::bail!("A multipart/form-data body has to be an object of parts, found: {value}")
+        }
+    };
+    let (boundary, body) = multipart::encode(&parts);
+
+    req.headers_mut().insert(
+        reqwest::header::CONTENT_TYPE,
+        HeaderValue::from_str(&format!("multipart/form-data; boundary={boundary}"))?,
+    );
+    req.body_mut().replace(body.into());
+    Ok(())
+}
+
+/// Checks if text appended to the rendered `prefix` of a url is part of its
+/// authority, ie. the scheme isn't complete yet or no path, query or fragment
+/// has started.
+fn is_in_authority(prefix: &str) -> bool {
+    match prefix.split_once("://") {
+        Some((_, rest)) => !rest.contains(['/', '?', '#']),
+        None => true,
+    }
+}
+
+/// Checks if the value is a valid DNS label, ie. 1 to 63 letters, digits or
+/// hyphens that don't start or end with a hyphen.
+fn is_dns_label(value: &str) -> bool {
+    (1..=63).contains(&value.len())
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        && !value.starts_with('-')
+        && !value.ends_with('-')
+}
+
+/// Parses the url template, taking the `join` filters out of its expressions.
+/// An expression like `{{.args.tags | join:,}}` renders the items of the list
+/// separated with `,`.
+fn parse_path(path: &str) -> (Mustache, BTreeMap<usize, String>) {
+    let mut template = String::with_capacity(path.len());
+    let mut separators = vec![];
+    let mut rest = path;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+        let expression = &rest[start + 2..end];
+        let (expression, separator) = match expression.split_once('|') {
+            Some((expression, filter)) => match filter.trim_start().strip_prefix("join:") {
+                Some(separator) => (expression.trim_end(), Some(separator.to_string())),
+                None => (expression, None),
+            },
+            None => (expression, None),
+        };
+
+        template.push_str(&rest[..start + 2]);
+        template.push_str(expression);
+        template.push_str("}}");
+        separators.push(separator);
+        rest = &rest[end + 2..];
+    }
+    template.push_str(rest);
+
+    let mustache = Mustache::parse(&template);
+    let path_joins = mustache
+        .segments()
+        .iter()
+        .enumerate()
+        .filter(|(_, segment)| matches!(segment, Segment::Expression(_)))
+        .zip(separators)
+        .filter_map(|((i, _), separator)| Some((i, separator?)))
+        .collect();
+
+    (mustache, path_joins)
+}
+
+/// Renders a list as its items separated with `separator`, any other value is
+/// rendered as is.
+fn join_value(value: Option<ValueString<'_>>, separator: &str) -> String {
+    match value {
+        Some(ValueString::Value(value)) => {
+            if let async_graphql::Value::List(list) = value.as_ref() {
+                return list
+                    .iter()
+                    .filter_map(|item| convert_value(Cow::Borrowed(item)))
+                    .collect::<Vec<_>>()
+                    .join(separator);
+            }
+            convert_value(value).unwrap_or_default().into_owned()
+        }
+        Some(ValueString::String(value)) => value.into_owned(),
+        None => String::new(),
+    }
+}
+
+/// Renders a header template into its values, one for each item when the
+/// template is a single expression that resolves to a list.
+fn render_header_values<C: PathString + PathValue>(mustache: &Mustache, ctx: &C) -> Vec<String> {
+    if let [Segment::Expression(parts)] = mustache.segments().as_slice() {
+        if let Some(ValueString::Value(value)) = ctx.raw_value(parts) {
+            if let async_graphql::Value::List(list) = value.as_ref() {
+                return list
+                    .iter()
+                    .filter_map(|item| convert_value(Cow::Borrowed(item)))
+                    .map(Cow::into_owned)
+                    .collect();
+            }
+        }
+    }
+
+    vec![mustache.render(ctx)]
+}
+
 impl<Ctx: PathString + HasHeaders + PathValue> CacheKey<Ctx> for RequestTemplate {
     fn cache_key(&self, ctx: &Ctx) -> Option<IoId> {
         let mut hasher = TailcallHasher::default();
@@ -270,9 +583,12 @@ impl<Ctx: PathString + HasHeaders + PathValue> CacheKey<Ctx> for RequestTemplate
 
         if let Some(body) = self.body_path.as_ref() {
             body.render(ctx).hash(state)
+        } else if !self.body_args.is_empty() {
+            self.body_args_value(ctx).to_string().hash(state)
         }
 
-        let url = self.create_url(ctx).unwrap();
+        // a url that can't be created fails the request, which isn't cached
+        let url = self.create_url(ctx).ok()?;
         url.hash(state);
 
         Some(IoId::new(hasher.finish()))
@@ -353,17 +669,26 @@ impl<'a, A: PathString> Eval<'a> for ExpressionValueEval<A> {
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
+    use std::sync::Arc;
+    use std::time::Duration;
 
     use derive_setters::Setters;
-    use http::header::{HeaderMap, HeaderName};
+    use http::header::{HeaderMap, HeaderName, HeaderValue};
+    use hyper::body::Bytes;
+    use indexmap::IndexMap;
     use pretty_assertions::assert_eq;
     use serde_json::json;
 
     use super::{Query, RequestTemplate};
+    use crate::core::config::Encoding;
     use crate::core::has_headers::HasHeaders;
+    use crate::core::http::multipart::{self, Part};
+    use crate::core::http::RequestContext;
+    use crate::core::ir::{EmptyResolverContext, EvalContext};
     use crate::core::json::JsonLike;
     use crate::core::mustache::Mustache;
     use crate::core::path::{PathString, PathValue, ValueString};
+    use crate::core::tests::TestEnvIO;
 
     #[derive(Setters)]
     struct Context {
@@ -507,6 +832,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_base_url_from_env() {
+        let tmpl = RequestTemplate::new("{{.env.TENANT_URL}}/users").unwrap();
+        let mut req_ctx = RequestContext::default();
+        req_ctx.runtime.env = Arc::new(TestEnvIO::from_iter([(
+            "TENANT_URL".to_string(),
+            "http://acme.localhost:3000".to_string(),
+        )]));
+        let ctx = EvalContext::new(&req_ctx, &EmptyResolverContext {});
+
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert_eq!(req.url().to_string(), "http://acme.localhost:3000/users");
+    }
+
+    #[test]
+    fn test_base_url_from_header() {
+        let tmpl =
+            RequestTemplate::new("http://{{.headers.x-tenant}}.localhost:3000/users").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-tenant", HeaderValue::from_static("acme"));
+        let req_ctx = RequestContext::default().allowed_headers(headers);
+        let ctx = EvalContext::new(&req_ctx, &EmptyResolverContext {});
+
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert_eq!(req.url().to_string(), "http://acme.localhost:3000/users");
+    }
+
+    #[test]
+    fn test_base_url_from_invalid_header() {
+        let tmpl =
+            RequestTemplate::new("http://{{.headers.x-tenant}}.localhost:3000/users").unwrap();
+        for tenant in [
+            "evil.com#",
+            "evil.com/",
+            "evil.com:80",
+            "user@evil.com",
+            "-acme",
+            "",
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-tenant", HeaderValue::from_str(tenant).unwrap());
+            let req_ctx = RequestContext::default().allowed_headers(headers);
+            let ctx = EvalContext::new(&req_ctx, &EmptyResolverContext {});
+
+            assert!(tmpl.to_request(&ctx).is_err(), "{tenant}");
+        }
+    }
+
+    #[test]
+    fn test_path_from_header() {
+        let tmpl = RequestTemplate::new("http://localhost:3000/{{.headers.x-path}}").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-path", HeaderValue::from_static("users/1"));
+        let req_ctx = RequestContext::default().allowed_headers(headers);
+        let ctx = EvalContext::new(&req_ctx, &EmptyResolverContext {});
+
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+        assert_eq!(req.url().to_string(), "http://localhost:3000/users/1");
+    }
+
+    #[test]
+    fn test_url_path_join() {
+        let tmpl =
+            RequestTemplate::new("http://localhost:3000/tags/{{.args.tags | join:,}}").unwrap();
+        let ctx = Context::default().value(json!({
+          "args": {
+            "tags": ["a", "b", "c"]
+          }
+        }));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+
+        assert_eq!(req.url().to_string(), "http://localhost:3000/tags/a,b,c");
+    }
+
+    #[test]
+    fn test_url_path_join_segments() {
+        let tmpl =
+            RequestTemplate::new("http://localhost:3000/x/{{.args.ids|join:/}}/end").unwrap();
+        let ctx = Context::default().value(json!({
+          "args": {
+            "ids": [1, 2, 3]
+          }
+        }));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+
+        assert_eq!(req.url().to_string(), "http://localhost:3000/x/1/2/3/end");
+        assert_eq!(req.url().path_segments().unwrap().count(), 5);
+    }
+
     #[test]
     fn test_url_query_params() {
         let query = vec![
@@ -628,6 +1047,40 @@ mod tests {
         assert_eq!(req.headers().get("baz").unwrap(), "2");
     }
 
+    #[test]
+    fn test_multi_value_headers() {
+        let headers = vec![
+            (HeaderName::from_static("x-forward"), Mustache::parse("a")),
+            (HeaderName::from_static("x-forward"), Mustache::parse("b")),
+            (
+                HeaderName::from_static("x-tag"),
+                Mustache::parse("{{tags}}"),
+            ),
+        ];
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .headers(headers);
+        let mut inbound = HeaderMap::new();
+        inbound.append("x-id", HeaderValue::from_static("1"));
+        inbound.append("x-id", HeaderValue::from_static("2"));
+        let ctx = Context::default()
+            .value(json!({ "tags": ["foo", "bar"] }))
+            .headers(inbound);
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        let req = request_wrapper.request();
+
+        let values = |name: &str| {
+            req.headers()
+                .get_all(name)
+                .iter()
+                .map(|value| value.to_str().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values("x-forward"), vec!["a", "b"]);
+        assert_eq!(values("x-tag"), vec!["foo", "bar"]);
+        assert_eq!(values("x-id"), vec!["1", "2"]);
+    }
+
     #[test]
     fn test_header_encoding_application_json() {
         let tmpl = RequestTemplate::new("http://localhost:3000")
@@ -669,6 +1122,58 @@ mod tests {
         assert_eq!(req.method(), reqwest::Method::POST);
     }
 
+    #[test]
+    fn test_timeout() {
+        let tmpl = RequestTemplate::new("http://localhost:3000").unwrap();
+        let ctx = Context::default();
+
+        // inherits the timeout of the client
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(request_wrapper.request().timeout(), None);
+
+        let tmpl = tmpl.timeout(Some(Duration::from_secs(5)));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().timeout(),
+            Some(&Duration::from_secs(5))
+        );
+
+        let tmpl = tmpl.timeout(Some(Duration::ZERO));
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(request_wrapper.request().timeout(), Some(&Duration::MAX));
+    }
+
+    #[test]
+    fn test_idempotency_key() {
+        let key = |tmpl: &RequestTemplate, ctx: &Context| {
+            let request_wrapper = tmpl.to_request(ctx).unwrap();
+            request_wrapper.request().headers()["idempotency-key"]
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .idempotency_key(Some(Mustache::parse("{{args.requestId}}")));
+        let ctx = Context::default().value(json!({"args": {"requestId": "abc"}}));
+        assert_eq!(key(&tmpl, &ctx), "abc");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("idempotency-key", HeaderValue::from_static("forwarded"));
+        let ctx = ctx.headers(headers);
+        assert_eq!(key(&tmpl, &ctx), "abc");
+
+        let tmpl = RequestTemplate::new("http://localhost:3000/{{args.id}}")
+            .unwrap()
+            .idempotency_key(Some(Mustache::parse("auto")));
+        let ctx = |id: u64| Context::default().value(json!({"args": {"id": id}}));
+        let first = key(&tmpl, &ctx(1));
+        assert_eq!(first.len(), 16);
+        assert_eq!(first, key(&tmpl, &ctx(1)));
+        assert_ne!(first, key(&tmpl, &ctx(2)));
+    }
+
     #[test]
     fn test_body() {
         let tmpl = RequestTemplate::new("http://localhost:3000")
@@ -708,6 +1213,176 @@ mod tests {
         assert_eq!(body, "baz");
     }
 
+    #[test]
+    fn test_body_args() {
+        let tmpl = RequestTemplate::new("http://localhost:3000/posts")
+            .unwrap()
+            .method(reqwest::Method::POST)
+            .body_args(vec![
+                "title".to_string(),
+                "userId".to_string(),
+                "content".to_string(),
+            ])
+            .query(vec![Query {
+                key: "draft".to_string(),
+                value: Mustache::parse("{{.args.draft}}"),
+                skip_empty: true,
+            }]);
+        let ctx = Context::default().value(json!({
+          "args": {
+            "title": "Hello",
+            "userId": 1,
+            "draft": true
+          }
+        }));
+
+        let request_wrapper = tmpl.to_request(&ctx).unwrap();
+        assert_eq!(
+            request_wrapper.request().url().to_string(),
+            "http://localhost:3000/posts?draft=true"
+        );
+
+        let body = tmpl.to_body(&ctx).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body, json!({"title": "Hello", "userId": 1}));
+    }
+
+    #[test]
+    fn test_body_encoding_multipart_form_data() {
+        struct UploadContext {
+            value: async_graphql::Value,
+            headers: HeaderMap,
+        }
+
+        impl PathValue for UploadContext {
+            fn raw_value<'a, T: AsRef<str>>(&'a self, path: &[T]) -> Option<ValueString<'a>> {
+                self.value
+                    .get_path(path)
+                    .map(|value| ValueString::Value(Cow::Borrowed(value)))
+            }
+        }
+
+        impl PathString for UploadContext {
+            fn path_string<'a, T: AsRef<str>>(&'a self, parts: &'a [T]) -> Option<Cow<'a, str>> {
+                self.value
+                    .get_path(parts)
+                    .map(|value| Cow::Owned(serde_json::to_string(value).unwrap()))
+            }
+        }
+
+        impl HasHeaders for UploadContext {
+            fn headers(&self) -> &HeaderMap {
+                &self.headers
+            }
+        }
+
+        let name = async_graphql::Name::new;
+        let content = Bytes::from_static(&[0, 159, 146, 150, 13, 10, 255]);
+        let upload = async_graphql::Value::Object(IndexMap::from([
+            (
+                name("filename"),
+                async_graphql::Value::String("report.bin".into()),
+            ),
+            (
+                name("contentType"),
+                async_graphql::Value::String("application/octet-stream".into()),
+            ),
+            (
+                name("content"),
+                async_graphql::Value::Binary(content.clone()),
+            ),
+        ]));
+        let args = async_graphql::Value::Object(IndexMap::from([
+            (name("title"), async_graphql::Value::String("Report".into())),
+            (name("file"), upload),
+        ]));
+        let ctx = UploadContext {
+            value: async_graphql::Value::Object(IndexMap::from([(name("args"), args)])),
+            headers: HeaderMap::new(),
+        };
+
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .method(reqwest::Method::POST)
+            .encoding(crate::core::config::Encoding::MultipartFormData)
+            .body_path(Some(Mustache::parse("{{.args}}")));
+        let req = tmpl.to_request(&ctx).unwrap().into_request();
+
+        let boundary = req
+            .headers()
+            .get("Content-Type")
+            .and_then(|value| value.to_str().ok())
+            .and_then(multipart::boundary)
+            .unwrap();
+        let body = req.body().and_then(|body| body.as_bytes()).unwrap();
+        let parts = multipart::decode(&boundary, body).unwrap();
+
+        assert_eq!(
+            parts,
+            vec![
+                Part {
+                    name: "title".to_string(),
+                    filename: None,
+                    content_type: None,
+                    content: Bytes::from("Report"),
+                },
+                Part {
+                    name: "file".to_string(),
+                    filename: Some("report.bin".to_string()),
+                    content_type: Some("application/octet-stream".to_string()),
+                    content,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_body_encoding_multipart_form_data_not_an_object() {
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .method(reqwest::Method::POST)
+            .encoding(crate::core::config::Encoding::MultipartFormData)
+            .body_path(Some(Mustache::parse("{{.args.title}}")));
+        let ctx = Context::default().value(json!({"args": {"title": "Report"}}));
+
+        let error = tmpl.to_request(&ctx).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            r#"A multipart/form-data body has to be an object of parts, found: "Report""#
+        );
+    }
+
+    #[test]
+    fn test_forwarded_arg() {
+        let tmpl = |body: &str, encoding| {
+            RequestTemplate::new("http://localhost:3000")
+                .unwrap()
+                .encoding(encoding)
+                .body_path(Some(Mustache::parse(body)))
+        };
+
+        assert_eq!(
+            tmpl("{{.args.file}}", Encoding::ApplicationJson).forwarded_arg(),
+            Some("file")
+        );
+        assert_eq!(
+            tmpl("{{.args}}", Encoding::ApplicationJson).forwarded_arg(),
+            None
+        );
+        assert_eq!(
+            tmpl("{{.args.file.content}}", Encoding::ApplicationJson).forwarded_arg(),
+            None
+        );
+        assert_eq!(
+            tmpl(r#"{"file": "{{.args.file}}"}"#, Encoding::ApplicationJson).forwarded_arg(),
+            None
+        );
+        assert_eq!(
+            tmpl("{{.args.file}}", Encoding::MultipartFormData).forwarded_arg(),
+            None
+        );
+    }
+
     mod endpoint {
         use http::header::HeaderMap;
         use serde_json::json;
@@ -914,12 +1589,13 @@ mod tests {
     mod cache_key {
         use std::collections::HashSet;
 
-        use http::header::HeaderMap;
+        use http::header::{HeaderMap, HeaderValue};
         use serde_json::json;
 
         use crate::core::http::request_template::tests::Context;
-        use crate::core::http::RequestTemplate;
+        use crate::core::http::{RequestContext, RequestTemplate};
         use crate::core::ir::model::{CacheKey, IoId};
+        use crate::core::ir::{EmptyResolverContext, EvalContext};
         use crate::core::mustache::Mustache;
 
         fn assert_no_duplicate<const N: usize>(arr: [Option<IoId>; N]) {
@@ -1000,5 +1676,17 @@ mod tests {
             assert_eq!(key_123_1, key_123_2);
             assert_eq!(key_234_1, key_234_2);
         }
+
+        #[test]
+        fn test_invalid_host() {
+            let tmpl =
+                RequestTemplate::new("http://{{.headers.x-tenant}}.localhost:3000/users").unwrap();
+            let mut headers = HeaderMap::new();
+            headers.insert("x-tenant", HeaderValue::from_static("evil.com#"));
+            let req_ctx = RequestContext::default().allowed_headers(headers);
+            let ctx = EvalContext::new(&req_ctx, &EmptyResolverContext {});
+
+            assert_eq!(tmpl.cache_key(&ctx), None);
+        }
     }
 }
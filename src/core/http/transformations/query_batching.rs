@@ -8,11 +8,16 @@ use crate::core::Transform;
 
 pub struct QueryBatching<'a> {
     dl_requests: &'a [&'a DataLoaderRequest],
-    group_by: Option<&'a str>,
+    group_by: Vec<&'a str>,
 }
 
 impl<'a> QueryBatching<'a> {
     pub fn new(dl_requests: &'a [&'a DataLoaderRequest], group_by: Option<&'a str>) -> Self {
+        QueryBatching { dl_requests, group_by: group_by.into_iter().collect() }
+    }
+
+    /// Merges only the query params that are part of a composite key.
+    pub fn with_keys(dl_requests: &'a [&'a DataLoaderRequest], group_by: Vec<&'a str>) -> Self {
         QueryBatching { dl_requests, group_by }
     }
 }
@@ -25,12 +30,12 @@ impl Transform for QueryBatching<'_> {
         for key in self.dl_requests.iter() {
             let request = key.to_request();
             let url = request.url();
-            let pairs: Vec<_> = if let Some(group_by_key) = self.group_by {
+            let pairs: Vec<_> = if self.group_by.is_empty() {
+                url.query_pairs().collect()
+            } else {
                 url.query_pairs()
-                    .filter(|(key, _)| group_by_key.eq(&key.to_string()))
+                    .filter(|(key, _)| self.group_by.contains(&key.as_ref()))
                     .collect()
-            } else {
-                url.query_pairs().collect()
             };
 
             if !pairs.is_empty() {
@@ -197,4 +202,19 @@ mod tests {
         // Should have no query parameters since grouped key doesn't exist
         assert!(result.url().query().is_none());
     }
+
+    #[test]
+    fn test_multiple_requests_with_composite_grouping() {
+        let req1 = create_request_with_params(&[("org", "a"), ("id", "1"), ("extra", "x")]);
+        let req2 = create_request_with_params(&[("org", "b"), ("id", "2"), ("extra", "y")]);
+        let requests = vec![&req1, &req2];
+        let base_request = create_base_request();
+
+        let result = QueryBatching::with_keys(&requests, vec!["org", "id"])
+            .transform(base_request)
+            .to_result()
+            .unwrap();
+
+        assert_eq!(result.url().query(), Some("org=a&id=1&org=b&id=2"));
+    }
 }
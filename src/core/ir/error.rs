@@ -5,6 +5,7 @@ use async_graphql::Value as ConstValue;
 use derive_more::From;
 use thiserror::Error;
 
+use crate::core::data_loader::DedupeError;
 use crate::core::jit::graphql_error::{Error as ExtensionError, ErrorExtensions};
 use crate::core::{auth, cache, worker, Errata};
 
@@ -12,6 +13,12 @@ use crate::core::{auth, cache, worker, Errata};
 pub enum Error {
     IO(String),
 
+    #[from(ignore)]
+    HTTP {
+        status: u16,
+        message: String,
+    },
+
     GRPC {
         grpc_code: i32,
         grpc_description: String,
@@ -33,8 +40,19 @@ pub enum Error {
 
     Cache(cache::Error),
 
+    Dedupe(DedupeError),
+
     #[from(ignore)]
     Entity(String),
+
+    #[from(ignore)]
+    UpstreamCallLimit(usize),
+
+    #[from(ignore)]
+    Step {
+        index: usize,
+        error: Box<Error>,
+    },
 }
 
 impl Display for Error {
@@ -47,6 +65,7 @@ impl From<Error> for Errata {
     fn from(value: Error) -> Self {
         match value {
             Error::IO(message) => Errata::new("IOException").description(message),
+            Error::HTTP { message, .. } => Errata::new("IOException").description(message),
             Error::GRPC {
                 grpc_code,
                 grpc_description,
@@ -67,20 +86,30 @@ impl From<Error> for Errata {
             }
             Error::Worker(err) => Errata::new("Worker Error").description(err.to_string()),
             Error::Cache(err) => Errata::new("Cache Error").description(err.to_string()),
-            Error::Entity(message) => Errata::new("Entity Resolver Error").description(message)
+            Error::Dedupe(err) => Errata::new("Dedupe Error").description(err.to_string()),
+            Error::Entity(message) => Errata::new("Entity Resolver Error").description(message),
+            Error::UpstreamCallLimit(max) => Errata::new("Upstream Call Limit Exceeded")
+                .description(format!("the request can't make more than {max} upstream calls")),
+            Error::Step { index, error } => Errata::new(&format!("@call failed at steps[{index}]"))
+                .description(Errata::from(*error).to_string())
         }
     }
 }
 
 impl ErrorExtensions for Error {
     fn extend(&self) -> ExtensionError {
+        let mut cause = self;
+        while let Error::Step { error, .. } = cause {
+            cause = error;
+        }
+
         ExtensionError::new(format!("{}", self)).extend_with(|_err, e| {
             if let Error::GRPC {
                 grpc_code,
                 grpc_description,
                 grpc_status_message,
                 grpc_status_details,
-            } = self
+            } = cause
             {
                 e.set("grpcCode", *grpc_code);
                 e.set("grpcDescription", grpc_description);
@@ -107,7 +136,7 @@ impl From<Arc<anyhow::Error>> for Error {
     fn from(error: Arc<anyhow::Error>) -> Self {
         match error.downcast_ref::<Error>() {
             Some(err) => err.clone(),
-            None => Error::IO(error.to_string()),
+            None => from_io_error(&error),
         }
     }
 }
@@ -119,7 +148,18 @@ impl From<anyhow::Error> for Error {
     fn from(value: anyhow::Error) -> Self {
         match value.downcast::<Error>() {
             Ok(err) => err,
-            Err(err) => Error::IO(err.to_string()),
+            Err(err) => from_io_error(&err),
         }
     }
 }
+
+/// Keeps the status code of the failed upstream HTTP requests around.
+fn from_io_error(error: &anyhow::Error) -> Error {
+    match error
+        .downcast_ref::<reqwest::Error>()
+        .and_then(|err| err.status())
+    {
+        Some(status) => Error::HTTP { status: status.as_u16(), message: error.to_string() },
+        None => Error::IO(error.to_string()),
+    }
+}
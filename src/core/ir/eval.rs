@@ -1,13 +1,13 @@
 use std::collections::HashMap;
 use std::future::Future;
-use std::ops::Deref;
 
 use async_graphql_value::ConstValue;
 use futures_util::future::join_all;
 use indexmap::IndexMap;
 
+use super::eval_cache::eval_cache;
 use super::eval_io::eval_io;
-use super::model::{Cache, CacheKey, Map, IR};
+use super::model::{Map, IR};
 use super::{Error, EvalContext, ResolverContextLike, TypedValue};
 use crate::core::auth::verify::{AuthVerifier, Verify};
 use crate::core::json::{JsonLike, JsonObjectLike};
@@ -44,25 +44,7 @@ impl IR {
                     expr.eval(ctx).await
                 }
                 IR::IO(io) => eval_io(io, ctx).await,
-                IR::Cache(Cache { max_age, io }) => {
-                    let io = io.deref();
-                    let key = io.cache_key(ctx);
-                    if let Some(key) = key {
-                        if let Some(val) = ctx.request_ctx.runtime.cache.get(&key).await? {
-                            Ok(val)
-                        } else {
-                            let val = eval_io(io, ctx).await?;
-                            ctx.request_ctx
-                                .runtime
-                                .cache
-                                .set(key, val.clone(), max_age.to_owned())
-                                .await?;
-                            Ok(val)
-                        }
-                    } else {
-                        eval_io(io, ctx).await
-                    }
-                }
+                IR::Cache(cache) => eval_cache(cache, ctx).await,
                 IR::Map(Map { input, map }) => {
                     fn recursive_map_enum(
                         val: Result<ConstValue, Error>,
@@ -118,6 +100,15 @@ impl IR {
                     .eval(ctx)
                     .await
                     .and_then(|value| Ok(discriminator.resolve_type(value)?)),
+                IR::Rename(rename, expr) => Ok(rename.apply(expr.eval(ctx).await?)),
+                IR::Values(expr) => Ok(match expr.eval(ctx).await? {
+                    ConstValue::Object(object) => ConstValue::List(object.into_values().collect()),
+                    value => value,
+                }),
+                IR::Step(index, expr) => expr
+                    .eval(ctx)
+                    .await
+                    .map_err(|error| Error::Step { index: *index, error: Box::new(error) }),
                 IR::Entity(map) => {
                     let representations = ctx.path_arg(&["representations"]);
 
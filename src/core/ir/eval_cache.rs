@@ -0,0 +1,247 @@
+use std::hash::{Hash, Hasher};
+
+use async_graphql_value::ConstValue;
+use tailcall_hasher::TailcallHasher;
+
+use super::eval_io::eval_io;
+use super::model::{Cache, CacheKey, IoId};
+use super::{Error, EvalContext, ResolverContextLike};
+use crate::core::EntityCache;
+
+pub async fn eval_cache<Ctx>(
+    cache: &Cache,
+    ctx: &mut EvalContext<'_, Ctx>,
+) -> Result<ConstValue, Error>
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    let io = cache.io.as_ref();
+    let Some(key) = io.cache_key(ctx) else {
+        return eval_io(io, ctx).await;
+    };
+    let entity_cache = ctx.request_ctx.runtime.cache.clone();
+
+    // rounded up, so that a sub-second window isn't advertised as none
+    if let Some(swr) = cache.stale_while_revalidate {
+        ctx.request_ctx
+            .set_min_stale_while_revalidate(swr.get().div_ceil(1000));
+    }
+
+    match entity_cache.get(&key).await? {
+        Some(val) => {
+            if cache.stale_while_revalidate.is_some() && !is_fresh(&entity_cache, &key).await? {
+                revalidate(cache, key, ctx).await?;
+            }
+            Ok(val)
+        }
+        None => {
+            let val = eval_io(io, ctx).await?;
+            store(cache, &entity_cache, key, val.clone()).await?;
+            Ok(val)
+        }
+    }
+}
+
+/// Stores the value for `maxAge` plus the stale-while-revalidate window, and
+/// marks it as fresh for `maxAge` only.
+async fn store(
+    cache: &Cache,
+    entity_cache: &EntityCache,
+    key: IoId,
+    val: ConstValue,
+) -> Result<(), Error> {
+    match cache.stale_while_revalidate {
+        Some(swr) => {
+            entity_cache
+                .set(freshness_key(&key), ConstValue::Null, cache.max_age)
+                .await?;
+            entity_cache
+                .set(key, val, cache.max_age.saturating_add(swr.get()))
+                .await?;
+        }
+        None => entity_cache.set(key, val, cache.max_age).await?,
+    }
+
+    Ok(())
+}
+
+async fn is_fresh(entity_cache: &EntityCache, key: &IoId) -> Result<bool, Error> {
+    Ok(entity_cache.get(&freshness_key(key)).await?.is_some())
+}
+
+/// Refreshes a stale value in the background. The value is marked as fresh
+/// for the rest of the window first, so that concurrent requests serving the
+/// same stale value don't trigger a refresh each.
+async fn revalidate<Ctx>(cache: &Cache, key: IoId, ctx: &EvalContext<'_, Ctx>) -> Result<(), Error>
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    let entity_cache = ctx.request_ctx.runtime.cache.clone();
+    if let Some(swr) = cache.stale_while_revalidate {
+        entity_cache
+            .set(freshness_key(&key), ConstValue::Null, swr)
+            .await?;
+    }
+
+    let cache = cache.clone();
+    let req_ctx = ctx.request_ctx.detached();
+    let graphql_ctx = ctx.detached();
+    let task = async move {
+        let mut ctx = EvalContext::new(&req_ctx, &graphql_ctx);
+        let result = match eval_io(&cache.io, &mut ctx).await {
+            Ok(val) => store(&cache, entity_cache.as_ref(), key, val).await,
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = result {
+            tracing::warn!("Failed to revalidate the cached value: {}", err);
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(task);
+    #[cfg(target_arch = "wasm32")]
+    async_std::task::spawn_local(task);
+
+    Ok(())
+}
+
+fn freshness_key(key: &IoId) -> IoId {
+    let mut hasher = TailcallHasher::default();
+    "stale-while-revalidate".hash(&mut hasher);
+    key.hash(&mut hasher);
+    IoId::new(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::num::NonZeroU64;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_graphql_value::ConstValue;
+    use hyper::body::Bytes;
+    use reqwest::StatusCode;
+    use tokio::time::Instant;
+
+    use crate::core::blueprint::Blueprint;
+    use crate::core::http::{RequestContext, RequestTemplate, Response};
+    use crate::core::ir::model::{Cache, IoId, IO, IR};
+    use crate::core::ir::{EmptyResolverContext, EvalContext};
+    use crate::core::{cache, HttpIO};
+
+    /// Expires its values on the clock of tokio, so that the tests can pause
+    /// and advance it.
+    #[derive(Default)]
+    struct PausedCache(Mutex<HashMap<IoId, (ConstValue, Instant)>>);
+
+    #[async_trait::async_trait]
+    impl crate::core::Cache for PausedCache {
+        type Key = IoId;
+        type Value = ConstValue;
+
+        async fn set<'a>(
+            &'a self,
+            key: IoId,
+            value: ConstValue,
+            ttl: NonZeroU64,
+        ) -> Result<(), cache::Error> {
+            let expires_at = Instant::now() + Duration::from_millis(ttl.get());
+            self.0.lock().unwrap().insert(key, (value, expires_at));
+            Ok(())
+        }
+
+        async fn get<'a>(&'a self, key: &'a IoId) -> Result<Option<ConstValue>, cache::Error> {
+            let data = self.0.lock().unwrap();
+            Ok(data
+                .get(key)
+                .filter(|(_, expires_at)| *expires_at > Instant::now())
+                .map(|(value, _)| value.clone()))
+        }
+
+        fn hit_rate(&self) -> Option<f64> {
+            None
+        }
+    }
+
+    /// Responds with the number of requests received so far.
+    #[derive(Default)]
+    struct CountingHttp(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl HttpIO for CountingHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let count = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Response {
+                status: StatusCode::OK,
+                headers: Default::default(),
+                body: Bytes::from(count.to_string()),
+            })
+        }
+    }
+
+    fn cached_ir(stale_while_revalidate: u64) -> IR {
+        let io = IO::Http {
+            req_template: RequestTemplate::new("http://localhost/count").unwrap(),
+            group_by: None,
+            dl_id: None,
+            is_list: false,
+            dedupe: false,
+            hook: None,
+            skip_if: None,
+            ok_status: vec![],
+        };
+
+        Cache::wrap(
+            NonZeroU64::new(50).unwrap(),
+            NonZeroU64::new(stale_while_revalidate),
+            IR::IO(io),
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_serve_stale_and_revalidate() {
+        let http = Arc::new(CountingHttp::default());
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = http.clone();
+        runtime.cache = Arc::new(PausedCache::default());
+        let req_ctx = RequestContext::new(runtime);
+        let res_ctx = EmptyResolverContext {};
+        let ir = cached_ir(60_000);
+
+        let actual = ir.eval(&mut EvalContext::new(&req_ctx, &res_ctx)).await;
+        assert_eq!(actual.unwrap(), ConstValue::from(1));
+
+        // Let the value expire
+        tokio::time::advance(Duration::from_millis(100)).await;
+
+        // The stale value is served while it's refreshed in the background
+        let actual = ir.eval(&mut EvalContext::new(&req_ctx, &res_ctx)).await;
+        assert_eq!(actual.unwrap(), ConstValue::from(1));
+
+        // The paused clock only moves on once the refresh is done
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(http.0.load(Ordering::SeqCst), 2);
+
+        // The refreshed value is served
+        let actual = ir.eval(&mut EvalContext::new(&req_ctx, &res_ctx)).await;
+        assert_eq!(actual.unwrap(), ConstValue::from(2));
+        assert_eq!(req_ctx.get_min_stale_while_revalidate(), Some(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sub_second_stale_while_revalidate() {
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = Arc::new(CountingHttp::default());
+        runtime.cache = Arc::new(PausedCache::default());
+        let req_ctx = RequestContext::new(runtime);
+        let res_ctx = EmptyResolverContext {};
+        let ir = cached_ir(500);
+
+        let actual = ir.eval(&mut EvalContext::new(&req_ctx, &res_ctx)).await;
+        assert_eq!(actual.unwrap(), ConstValue::from(1));
+        assert_eq!(req_ctx.get_min_stale_while_revalidate(), Some(1));
+    }
+}
@@ -5,7 +5,10 @@ use std::sync::Arc;
 use async_graphql::{ServerError, Value};
 use http::header::HeaderMap;
 
-use super::{GraphQLOperationContext, RelatedFields, ResolverContextLike, SelectionField};
+use super::{
+    DetachedResolverContext, GraphQLOperationContext, RelatedFields, ResolverContextLike,
+    SelectionField,
+};
 use crate::core::document::print_directives;
 use crate::core::http::RequestContext;
 
@@ -59,23 +62,23 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
     pub fn path_arg<T: AsRef<str>>(&self, path: &[T]) -> Option<Cow<'a, Value>> {
         // TODO: add unit tests for this
         if let Some(args) = self.graphql_ctx_args.as_ref() {
-            get_path_value(args.as_ref(), path).map(|a| Cow::Owned(a.clone()))
+            select_path_value(args.as_ref(), path).map(|a| Cow::Owned(a.into_owned()))
         } else if path.is_empty() {
             self.graphql_ctx
                 .args()
                 .map(|a| Cow::Owned(Value::Object(a.clone())))
         } else {
             let arg = self.graphql_ctx.args()?.get(path[0].as_ref())?;
-            get_path_value(arg, &path[1..]).map(Cow::Borrowed)
+            select_path_value(arg, &path[1..])
         }
     }
 
     pub fn path_value<T: AsRef<str>>(&self, path: &[T]) -> Option<Cow<'a, Value>> {
         // TODO: add unit tests for this
         if let Some(value) = self.graphql_ctx_value.as_ref() {
-            get_path_value(value.as_ref(), path).map(|a| Cow::Owned(a.clone()))
+            select_path_value(value.as_ref(), path).map(|a| Cow::Owned(a.into_owned()))
         } else {
-            get_path_value(self.graphql_ctx.value()?, path).map(Cow::Borrowed)
+            select_path_value(self.graphql_ctx.value()?, path)
         }
     }
 
@@ -93,6 +96,10 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
         self.request_ctx.runtime.env.get(key)
     }
 
+    pub fn secret(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.request_ctx.runtime.secret.get(key)
+    }
+
     pub fn var(&self, key: &str) -> Option<&str> {
         let vars = &self.request_ctx.server.vars;
 
@@ -106,6 +113,24 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
     pub fn add_error(&self, error: ServerError) {
         self.graphql_ctx.add_error(error)
     }
+
+    /// Copies the value, arguments and selection of the current field.
+    pub fn detached(&self) -> DetachedResolverContext {
+        let args = match self.path_arg::<&str>(&[]) {
+            Some(args) => match args.into_owned() {
+                Value::Object(args) => Some(args),
+                _ => None,
+            },
+            None => None,
+        };
+
+        DetachedResolverContext {
+            value: self.path_value::<&str>(&[]).map(Cow::into_owned),
+            args,
+            field: self.graphql_ctx.field(),
+            is_query: self.is_query(),
+        }
+    }
 }
 
 impl<Ctx: ResolverContextLike> GraphQLOperationContext for EvalContext<'_, Ctx> {
@@ -205,12 +230,44 @@ pub fn get_path_value<'a, T: AsRef<str>>(input: &'a Value, path: &[T]) -> Option
     value
 }
 
+/// Same as [`get_path_value`] but a `*` segment maps over the items of a list,
+/// or the values of an object, and collects the results into a list. The
+/// results of nested wildcards are flattened into a single list.
+pub fn select_path_value<'a, T: AsRef<str>>(
+    input: &'a Value,
+    path: &[T],
+) -> Option<Cow<'a, Value>> {
+    let Some(index) = path.iter().position(|name| name.as_ref() == "*") else {
+        return get_path_value(input, path).map(Cow::Borrowed);
+    };
+
+    let values: Vec<&Value> = match get_path_value(input, &path[..index])? {
+        Value::List(list) => list.iter().collect(),
+        Value::Object(map) => map.values().collect(),
+        _ => return None,
+    };
+
+    let rest = &path[index + 1..];
+    let flatten = rest.iter().any(|name| name.as_ref() == "*");
+    let mut list = Vec::with_capacity(values.len());
+
+    for value in values {
+        match select_path_value(value, rest).map(Cow::into_owned) {
+            Some(Value::List(items)) if flatten => list.extend(items),
+            Some(value) => list.push(value),
+            None => {}
+        }
+    }
+
+    Some(Cow::Owned(Value::List(list)))
+}
+
 #[cfg(test)]
 mod tests {
     use async_graphql::Value;
     use serde_json::json;
 
-    use crate::core::ir::eval_context::get_path_value;
+    use crate::core::ir::eval_context::{get_path_value, select_path_value};
 
     #[test]
     fn test_path_value() {
@@ -263,4 +320,61 @@ mod tests {
         assert!(result.is_some());
         assert_eq!(result.unwrap(), &Value::String("c".to_string()));
     }
+
+    #[test]
+    fn test_wildcard_path() {
+        let json = json!(
+        {
+            "a": [
+                { "b": 1 },
+                { "c": 2 },
+                { "b": 3 }
+            ]
+        });
+
+        let async_value = Value::from_json(json).unwrap();
+
+        let path = vec!["a", "*", "b"];
+        let result = select_path_value(&async_value, &path).unwrap();
+        assert_eq!(
+            result.into_owned(),
+            Value::from_json(json!([1, 3])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_nested_wildcard_path() {
+        let json = json!(
+        {
+            "a": {
+                "x": { "b": [{ "c": 1 }, { "c": 2 }] },
+                "y": { "b": [{ "c": 3 }] }
+            }
+        });
+
+        let async_value = Value::from_json(json).unwrap();
+
+        let path = vec!["a", "*", "b", "*", "c"];
+        let result = select_path_value(&async_value, &path).unwrap();
+        assert_eq!(
+            result.into_owned(),
+            Value::from_json(json!([1, 2, 3])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_select_numeric_path() {
+        let json = json!(
+        {
+            "a": [{
+                "b": "c"
+            }]
+        });
+
+        let async_value = Value::from_json(json).unwrap();
+
+        let path = vec!["a", "0", "b"];
+        let result = select_path_value(&async_value, &path).unwrap();
+        assert_eq!(result.into_owned(), Value::String("c".to_string()));
+    }
 }
@@ -1,18 +1,22 @@
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use async_graphql::from_value;
+use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::Request;
+use tailcall_hasher::TailcallHasher;
 use tailcall_valid::Validator;
 
 use super::model::DataLoaderId;
 use super::request::DynamicRequest;
 use super::{EvalContext, ResolverContextLike};
+use crate::core::config::{BatchFallback, GrpcProtocol};
 use crate::core::data_loader::{DataLoader, Loader};
 use crate::core::grpc::protobuf::ProtobufOperation;
-use crate::core::grpc::request::execute_grpc_request;
+use crate::core::grpc::request::{execute_grpc_request, to_grpc_timeout, GRPC_TIMEOUT};
 use crate::core::grpc::request_template::RenderedRequestTemplate;
 use crate::core::http::{
-    cache_policy, DataLoaderRequest, HttpDataLoader, RequestTemplate, Response,
+    cache_policy, DataLoaderRequest, HttpDataLoader, RequestTemplate, Response, IDEMPOTENCY_KEY,
 };
 use crate::core::ir::Error;
 use crate::core::json::JsonLike;
@@ -71,7 +75,26 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
 
     pub fn init_request(&self) -> Result<DynamicRequest<String>, Error> {
         let inner = self.request_template.to_request(self.evaluation_ctx)?;
-        Ok(inner)
+        Ok(self.stream_upload(inner))
+    }
+
+    /// Streams the unread body of the inbound request in place of the rendered
+    /// one when the template sends it as is, instead of buffering it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn stream_upload(&self, mut req: DynamicRequest<String>) -> DynamicRequest<String> {
+        if let Some(arg) = self.request_template.forwarded_arg() {
+            if let Some(body) = self.evaluation_ctx.request_ctx.take_upload(arg) {
+                req.request_mut()
+                    .body_mut()
+                    .replace(reqwest::Body::from(body));
+            }
+        }
+        req
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn stream_upload(&self, req: DynamicRequest<String>) -> DynamicRequest<String> {
+        req
     }
 
     pub async fn execute(
@@ -80,10 +103,21 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
     ) -> Result<Response<async_graphql::Value>, Error> {
         let ctx = &self.evaluation_ctx;
         let dl = &self.data_loader;
-        let response = if dl.is_some() {
-            execute_request_with_dl(ctx, req, self.data_loader).await?
-        } else {
-            execute_raw_request(ctx, req).await?
+        // Identical requests made while resolving the same query are only sent
+        // once, even when they aren't part of the same data loader batch.
+        let key = ctx.is_query().then(|| request_key(req.request()));
+        let retry = self.request_template.retry;
+        let dispatch = move || async move {
+            if dl.is_some() {
+                execute_request_with_dl(ctx, req, self.data_loader).await
+            } else {
+                execute_request_with_retry(ctx, req, retry).await
+            }
+        };
+
+        let response = match key {
+            Some(key) => ctx.request_ctx.http_cache.dedupe(&key, dispatch).await?,
+            None => dispatch().await?,
         };
 
         if ctx.request_ctx.server.get_enable_http_validation() {
@@ -144,6 +178,22 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
     }
 }
 
+/// Hashes everything that identifies a rendered request: the method, the url,
+/// the headers and the body.
+fn request_key(req: &Request) -> u64 {
+    let mut hasher = TailcallHasher::default();
+    req.method().hash(&mut hasher);
+    req.url().as_str().hash(&mut hasher);
+    for (name, value) in req.headers() {
+        name.as_str().hash(&mut hasher);
+        value.as_bytes().hash(&mut hasher);
+    }
+    req.body()
+        .and_then(|body| body.as_bytes())
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
 pub async fn execute_request_with_dl<
     'ctx,
     Ctx: ResolverContextLike,
@@ -162,15 +212,40 @@ pub async fn execute_request_with_dl<
         .unwrap_or_default();
 
     let (req, batching_value) = req.into_parts();
-    let endpoint_key =
-        crate::core::http::DataLoaderRequest::new(req, headers).with_batching_value(batching_value);
+    let upstream_calls = ctx.request_ctx.upstream_call_counter();
+    let endpoint_key = crate::core::http::DataLoaderRequest::new(req, headers)
+        .with_batching_value(batching_value)
+        .with_upstream_calls(upstream_calls.clone());
 
-    Ok(data_loader
-        .unwrap()
-        .load_one(endpoint_key)
-        .await
-        .map_err(Error::from)?
-        .unwrap_or_default())
+    // Keeps the key around to retry it individually if the batch fails
+    let fallback = (ctx.request_ctx.upstream.batch_fallback == BatchFallback::Individual)
+        .then(|| endpoint_key.clone());
+    let data_loader = data_loader.unwrap();
+
+    match data_loader.load_one(endpoint_key).await {
+        Ok(Some(response)) => Ok(response),
+        Ok(None) => {
+            // the key is left out of the batch once the request reached its limit
+            upstream_calls
+                .as_ref()
+                .map_or(Ok(()), UpstreamCalls::check)?;
+            Ok(Default::default())
+        }
+        Err(err) => match fallback {
+            Some(key) => {
+                tracing::warn!("Batched request failed, retrying individually: {}", err);
+                // A batch of its own, so that its value is extracted from the
+                // response the same way
+                let mut responses = data_loader
+                    .loader()
+                    .load(std::slice::from_ref(&key))
+                    .await
+                    .map_err(Error::from)?;
+                Ok(responses.remove(&key).unwrap_or_default())
+            }
+            None => Err(Error::from(err)),
+        },
+    }
 }
 
 pub fn set_headers<Ctx: ResolverContextLike>(
@@ -213,6 +288,7 @@ pub async fn execute_raw_request<Ctx: ResolverContextLike>(
     ctx: &EvalContext<'_, Ctx>,
     req: DynamicRequest<String>,
 ) -> Result<Response<async_graphql::Value>, Error> {
+    ctx.request_ctx.count_upstream_call()?;
     let response = ctx
         .request_ctx
         .runtime
@@ -225,16 +301,71 @@ pub async fn execute_raw_request<Ctx: ResolverContextLike>(
     Ok(response)
 }
 
+/// Sends the request, retrying it up to `retry` times while it fails with a
+/// network error or a 5xx status. Any other error is returned right away, as
+/// is any error of a request that can't be sent again safely.
+pub async fn execute_request_with_retry<Ctx: ResolverContextLike>(
+    ctx: &EvalContext<'_, Ctx>,
+    mut req: DynamicRequest<String>,
+    retry: usize,
+) -> Result<Response<async_graphql::Value>, Error> {
+    let mut attempt = 0;
+    loop {
+        let next = (attempt < retry && is_replayable(req.request()))
+            .then(|| req.request().try_clone())
+            .flatten();
+
+        match (execute_raw_request(ctx, req).await, next) {
+            (Err(err), Some(next)) if is_retryable(&err) => {
+                attempt += 1;
+                tracing::warn!("Request failed, retrying ({}/{}): {}", attempt, retry, err);
+                req = DynamicRequest::new(next);
+            }
+            (result, _) => return result,
+        }
+    }
+}
+
+/// Checks if the request can be sent again without repeating its side effects:
+/// its method is idempotent, or its `Idempotency-Key` lets the API recognize
+/// it.
+fn is_replayable(req: &Request) -> bool {
+    req.method().is_idempotent() || req.headers().contains_key(IDEMPOTENCY_KEY)
+}
+
+fn is_retryable(err: &Error) -> bool {
+    match err {
+        Error::IO(_) => true,
+        Error::HTTP { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
 pub async fn execute_raw_grpc_request<Ctx: ResolverContextLike>(
     ctx: &EvalContext<'_, Ctx>,
     req: Request,
     operation: &ProtobufOperation,
+    protocol: &GrpcProtocol,
 ) -> Result<Response<async_graphql::Value>, Error> {
-    execute_grpc_request(&ctx.request_ctx.runtime, operation, req)
+    ctx.request_ctx.count_upstream_call()?;
+    execute_grpc_request(&ctx.request_ctx.runtime, operation, protocol, req)
         .await
         .map_err(Error::from)
 }
 
+/// Sends the time left before the deadline of the request as the deadline of
+/// the gRPC call, so that it shrinks with every call made in sequence.
+pub fn set_grpc_deadline<Ctx: ResolverContextLike>(
+    ctx: &EvalContext<'_, Ctx>,
+    headers: &mut HeaderMap,
+) {
+    if let Some(remaining) = ctx.request_ctx.remaining_time() {
+        if let Ok(value) = HeaderValue::from_str(&to_grpc_timeout(remaining)) {
+            headers.insert(GRPC_TIMEOUT, value);
+        }
+    }
+}
+
 pub async fn execute_grpc_request_with_dl<
     Ctx: ResolverContextLike,
     Dl: Loader<
@@ -254,14 +385,25 @@ pub async fn execute_grpc_request_with_dl<
         .clone()
         .map(|s| s.headers)
         .unwrap_or_default();
-    let endpoint_key = grpc::DataLoaderRequest::new(rendered, headers);
+    let upstream_calls = ctx.request_ctx.upstream_call_counter();
+    let endpoint_key =
+        grpc::DataLoaderRequest::new(rendered, headers).with_upstream_calls(upstream_calls.clone());
 
-    Ok(data_loader
+    match data_loader
         .unwrap()
         .load_one(endpoint_key)
         .await
         .map_err(Error::from)?
-        .unwrap_or_default())
+    {
+        Some(response) => Ok(response),
+        None => {
+            // the key is left out of the batch once the request reached its limit
+            upstream_calls
+                .as_ref()
+                .map_or(Ok(()), UpstreamCalls::check)?;
+            Ok(Default::default())
+        }
+    }
 }
 
 pub fn parse_graphql_response<Ctx: ResolverContextLike>(
@@ -282,3 +424,328 @@ pub fn parse_graphql_response<Ctx: ResolverContextLike>(
         .map(|v| v.to_owned())
         .unwrap_or_default())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use async_graphql::futures_util::future::join_all;
+    use async_graphql_value::ConstValue;
+    use hyper::body::{Bytes, HttpBody};
+    use reqwest::header::HeaderMap;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use super::{execute_request_with_dl, execute_request_with_retry, set_grpc_deadline, EvalHttp};
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::group_by::GroupBy;
+    use crate::core::config::{Batch, BatchFallback};
+    use crate::core::grpc::request::{parse_grpc_timeout, GRPC_TIMEOUT};
+    use crate::core::http::{HttpDataLoader, RequestContext, RequestTemplate, Response};
+    use crate::core::ir::{DynamicRequest, EmptyResolverContext, EvalContext};
+    use crate::core::{HttpIO, Mustache};
+
+    /// Fails the batched requests and the requests for the user `3`, and
+    /// responds to the others with a batch of one user.
+    struct FlakyBatchHttp;
+
+    #[async_trait::async_trait]
+    impl HttpIO for FlakyBatchHttp {
+        async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let ids = req
+                .url()
+                .query_pairs()
+                .filter(|(key, _)| key == "id")
+                .map(|(_, id)| id.to_string())
+                .collect::<Vec<_>>();
+
+            match ids.as_slice() {
+                [id] if id != "3" => Ok(Response {
+                    status: StatusCode::OK,
+                    headers: Default::default(),
+                    body: Bytes::from(format!(r#"[{{"id":{id}}}]"#)),
+                }),
+                ids => Err(anyhow::anyhow!("Failed to load users {}", ids.join(","))),
+            }
+        }
+    }
+
+    async fn load_users(
+        batch_fallback: BatchFallback,
+    ) -> Vec<Result<Response<ConstValue>, super::Error>> {
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = Arc::new(FlakyBatchHttp);
+        let group_by = GroupBy::new(vec!["id".to_string()], Some("id".to_string()));
+        let data_loader = HttpDataLoader::new(runtime.clone(), Some(group_by), false)
+            .to_data_loader(Batch { delay: 10, ..Default::default() });
+
+        let mut req_ctx = RequestContext::new(runtime);
+        req_ctx.upstream.batch_fallback = batch_fallback;
+        let res_ctx = EmptyResolverContext {};
+        let ctx = EvalContext::new(&req_ctx, &res_ctx);
+
+        join_all((1..=3).map(|id| {
+            let url = format!("http://localhost/users?id={id}").parse().unwrap();
+            let req = reqwest::Request::new(reqwest::Method::GET, url);
+            execute_request_with_dl(&ctx, DynamicRequest::new(req), Some(&data_loader))
+        }))
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_batch_fallback_individual() {
+        let results = load_users(BatchFallback::Individual).await;
+        let bodies = results
+            .into_iter()
+            .map(|result| result.ok().map(|response| response.body))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            bodies,
+            vec![
+                Some(ConstValue::from_json(json!({"id": 1})).unwrap()),
+                Some(ConstValue::from_json(json!({"id": 2})).unwrap()),
+                None
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_fallback_fail() {
+        let results = load_users(BatchFallback::Fail).await;
+        assert!(results.iter().all(|result| result.is_err()));
+    }
+
+    /// Fails the first `failures` requests with the given status.
+    struct UnstableHttp {
+        failures: usize,
+        status: u16,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for UnstableHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                let message = format!("Request failed with {}", self.status);
+                Err(super::Error::HTTP { status: self.status, message }.into())
+            } else {
+                Ok(Response {
+                    status: StatusCode::OK,
+                    headers: Default::default(),
+                    body: Bytes::from(r#"{"id":1}"#),
+                })
+            }
+        }
+    }
+
+    /// Sends a GET request with the given number of retries and returns
+    /// whether it succeeded along with the number of requests made.
+    async fn send_with_retry(http: UnstableHttp, retry: usize) -> (bool, usize) {
+        send_method_with_retry(http, reqwest::Method::GET, retry).await
+    }
+
+    async fn send_method_with_retry(
+        http: UnstableHttp,
+        method: reqwest::Method,
+        retry: usize,
+    ) -> (bool, usize) {
+        let http = Arc::new(http);
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = http.clone();
+        let req_ctx = RequestContext::new(runtime);
+        let res_ctx = EmptyResolverContext {};
+        let ctx = EvalContext::new(&req_ctx, &res_ctx);
+
+        let url = "http://localhost/users/1".parse().unwrap();
+        let req = reqwest::Request::new(method, url);
+        let result = execute_request_with_retry(&ctx, DynamicRequest::new(req), retry).await;
+
+        (result.is_ok(), http.calls.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let http = UnstableHttp { failures: 2, status: 503, calls: AtomicUsize::new(0) };
+        assert_eq!(send_with_retry(http, 3).await, (true, 3));
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted() {
+        let http = UnstableHttp { failures: 5, status: 503, calls: AtomicUsize::new(0) };
+        assert_eq!(send_with_retry(http, 2).await, (false, 3));
+    }
+
+    #[tokio::test]
+    async fn test_retry_disabled() {
+        let http = UnstableHttp { failures: 1, status: 503, calls: AtomicUsize::new(0) };
+        assert_eq!(send_with_retry(http, 0).await, (false, 1));
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_client_errors() {
+        let http = UnstableHttp { failures: 1, status: 404, calls: AtomicUsize::new(0) };
+        assert_eq!(send_with_retry(http, 3).await, (false, 1));
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_non_idempotent_methods() {
+        let http = UnstableHttp { failures: 1, status: 503, calls: AtomicUsize::new(0) };
+        let actual = send_method_with_retry(http, reqwest::Method::POST, 3).await;
+        assert_eq!(actual, (false, 1));
+
+        let http = UnstableHttp { failures: 1, status: 503, calls: AtomicUsize::new(0) };
+        let actual = send_method_with_retry(http, reqwest::Method::PUT, 3).await;
+        assert_eq!(actual, (true, 2));
+    }
+
+    /// Fails the first request and records the idempotency keys it's sent.
+    #[derive(Default)]
+    struct IdempotentHttp {
+        keys: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for IdempotentHttp {
+        async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let mut keys = self.keys.lock().unwrap();
+            keys.push(req.headers()["idempotency-key"].to_str()?.to_string());
+            if keys.len() == 1 {
+                let message = "Request failed with 503".to_string();
+                Err(super::Error::HTTP { status: 503, message }.into())
+            } else {
+                Ok(Response {
+                    status: StatusCode::OK,
+                    headers: Default::default(),
+                    body: Bytes::from(r#"{"id":1}"#),
+                })
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_keeps_idempotency_key() {
+        let http = Arc::new(IdempotentHttp::default());
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = http.clone();
+        let req_ctx = RequestContext::new(runtime);
+        let res_ctx = EmptyResolverContext {};
+        let ctx = EvalContext::new(&req_ctx, &res_ctx);
+
+        let req = RequestTemplate::new("http://localhost/users")
+            .unwrap()
+            .method(reqwest::Method::POST)
+            .idempotency_key(Some(Mustache::parse("auto")))
+            .to_request(&ctx)
+            .unwrap();
+        let result = execute_request_with_retry(&ctx, req, 1).await;
+        assert!(result.is_ok());
+
+        let keys = http.keys.lock().unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0], keys[1]);
+    }
+
+    const UPLOAD_CHUNK: usize = 1024 * 1024;
+    const UPLOAD_CHUNKS: usize = 64;
+
+    /// Reads the body it's sent chunk by chunk, recording how much of it had
+    /// been produced before the request was sent and how much was received.
+    #[derive(Default)]
+    struct UploadHttp {
+        produced: Arc<AtomicUsize>,
+        received: Mutex<Option<(usize, usize)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for UploadHttp {
+        async fn execute(&self, mut req: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let produced = self.produced.load(Ordering::SeqCst);
+            let mut body = req.body_mut().take().unwrap();
+            let mut received = 0;
+            while let Some(chunk) = body.data().await {
+                received += chunk?.len();
+            }
+            *self.received.lock().unwrap() = Some((produced, received));
+
+            Ok(Response {
+                status: StatusCode::OK,
+                headers: Default::default(),
+                body: Bytes::from(r#"{"id":1}"#),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_upload() {
+        let http = Arc::new(UploadHttp::default());
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = http.clone();
+        let req_ctx = RequestContext::new(runtime);
+
+        let (mut sender, body) = hyper::Body::channel();
+        let produced = http.produced.clone();
+        tokio::spawn(async move {
+            for _ in 0..UPLOAD_CHUNKS {
+                let chunk = Bytes::from(vec![0; UPLOAD_CHUNK]);
+                if sender.send_data(chunk).await.is_err() {
+                    return;
+                }
+                produced.fetch_add(UPLOAD_CHUNK, Ordering::SeqCst);
+            }
+        });
+        *req_ctx.upload.lock().unwrap() = Some(("file".to_string(), body));
+
+        let res_ctx = EmptyResolverContext {};
+        let ctx = EvalContext::new(&req_ctx, &res_ctx);
+        let tmpl = |body: &str| {
+            RequestTemplate::new("http://localhost/files")
+                .unwrap()
+                .method(reqwest::Method::POST)
+                .body_path(Some(Mustache::parse(body)))
+        };
+
+        // a request forwarding another argument leaves the body for its own
+        let other = tmpl("{{.args.other}}");
+        EvalHttp::new(&ctx, &other, &None).init_request().unwrap();
+        assert!(req_ctx.upload.lock().unwrap().is_some());
+
+        let tmpl = tmpl("{{.args.file}}");
+        let eval_http = EvalHttp::new(&ctx, &tmpl, &None);
+        let req = eval_http.init_request().unwrap();
+        assert!(eval_http.execute(req).await.is_ok());
+
+        // the upstream starts receiving the body before most of it exists
+        let (produced, received) = http.received.lock().unwrap().unwrap();
+        assert!(produced <= 2 * UPLOAD_CHUNK, "{produced} bytes buffered");
+        assert_eq!(received, UPLOAD_CHUNKS * UPLOAD_CHUNK);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_deadline() {
+        let runtime = crate::cli::runtime::init(&Blueprint::default());
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let req_ctx = RequestContext::new(runtime).deadline(Some(deadline));
+        let res_ctx = EmptyResolverContext {};
+        let ctx = EvalContext::new(&req_ctx, &res_ctx);
+
+        let timeout = |headers: &HeaderMap| {
+            let value = headers.get(GRPC_TIMEOUT).unwrap().to_str().unwrap();
+            parse_grpc_timeout(value).unwrap()
+        };
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut headers = HeaderMap::new();
+        set_grpc_deadline(&ctx, &mut headers);
+        let first = timeout(&headers);
+        assert!(first <= Duration::from_millis(800), "{first:?}");
+
+        // a call made later in sequence gets what's left of the budget
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        set_grpc_deadline(&ctx, &mut headers);
+        let second = timeout(&headers);
+        assert!(second <= first - Duration::from_millis(200), "{second:?}");
+    }
+}
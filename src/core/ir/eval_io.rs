@@ -2,7 +2,8 @@ use async_graphql_value::ConstValue;
 
 use super::eval_http::{
     execute_grpc_request_with_dl, execute_raw_grpc_request, execute_raw_request,
-    execute_request_with_dl, parse_graphql_response, set_headers, EvalHttp, WorkerContext,
+    execute_request_with_dl, parse_graphql_response, set_grpc_deadline, set_headers, EvalHttp,
+    WorkerContext,
 };
 use super::model::{CacheKey, IO};
 use super::{DynamicRequest, EvalContext, ResolverContextLike};
@@ -45,7 +46,13 @@ where
     Ctx: ResolverContextLike + Sync,
 {
     match io {
-        IO::Http { req_template, dl_id, hook, .. } => {
+        IO::Http { req_template, dl_id, hook, skip_if, ok_status, .. } => {
+            if skip_if
+                .as_ref()
+                .is_some_and(|skip_if| is_falsy(&skip_if.render(ctx)))
+            {
+                return Ok(ConstValue::Null);
+            }
             let event_worker = &ctx.request_ctx.runtime.cmd_worker;
             let js_worker = &ctx.request_ctx.runtime.worker;
             let eval_http = EvalHttp::new(ctx, req_template, dl_id);
@@ -53,12 +60,18 @@ where
             let response = match (&event_worker, js_worker, hook) {
                 (Some(worker), Some(js_worker), Some(hook)) => {
                     let worker_ctx = WorkerContext::new(worker, js_worker, hook);
-                    eval_http.execute_with_worker(request, worker_ctx).await?
+                    eval_http.execute_with_worker(request, worker_ctx).await
                 }
-                _ => eval_http.execute(request).await?,
+                _ => eval_http.execute(request).await,
             };
 
-            Ok(response.body)
+            match response {
+                Ok(response) => Ok(response.body),
+                Err(Error::HTTP { status, .. }) if ok_status.contains(&status) => {
+                    Ok(ConstValue::Null)
+                }
+                Err(err) => Err(err),
+            }
         }
         IO::GraphQL { req_template, field_name, dl_id, .. } => {
             let req = req_template.to_request(ctx)?;
@@ -77,7 +90,9 @@ where
             parse_graphql_response(ctx, res, field_name)
         }
         IO::Grpc { req_template, dl_id, hook, .. } => {
-            let rendered = req_template.render(ctx)?;
+            let mut rendered = req_template.render(ctx)?;
+            req_template.validate_request(&rendered.body)?;
+            set_grpc_deadline(ctx, &mut rendered.headers);
             let worker = &ctx.request_ctx.runtime.worker;
 
             let res = if ctx.request_ctx.upstream.batch.is_some() &&
@@ -89,8 +104,10 @@ where
                 execute_grpc_request_with_dl(ctx, rendered, data_loader).await?
             } else {
                 let req = rendered.to_request()?;
-                execute_raw_grpc_request(ctx, req, &req_template.operation).await?
+                execute_raw_grpc_request(ctx, req, &req_template.operation, &req_template.protocol)
+                    .await?
             };
+            req_template.validate_response(&res.body)?;
 
             let res = match (worker.as_ref(), hook.as_ref()) {
                 (Some(worker), Some(hook)) => hook.on_response(worker, res).await?,
@@ -116,3 +133,170 @@ where
         }
     }
 }
+
+/// Checks if the rendered condition of a `skipIf` template should prevent the
+/// request from being made.
+fn is_falsy(rendered: &str) -> bool {
+    let rendered = rendered.trim();
+    rendered.is_empty() || rendered == "null" || rendered == "false"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_graphql_value::ConstValue;
+    use futures_util::future::join_all;
+    use hyper::body::Bytes;
+
+    use super::is_falsy;
+    use crate::core::blueprint::{Blueprint, Server};
+    use crate::core::config::{Batch, ConfigModule};
+    use crate::core::http::{HttpDataLoader, RequestContext, RequestTemplate, Response};
+    use crate::core::ir::model::{DataLoaderId, IO, IR};
+    use crate::core::ir::{EmptyResolverContext, Error, EvalContext};
+    use crate::core::HttpIO;
+
+    /// Responds with the status code found in the path of the request.
+    struct StatusHttp;
+
+    #[async_trait::async_trait]
+    impl HttpIO for StatusHttp {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let status = request
+                .url()
+                .path()
+                .trim_start_matches('/')
+                .parse::<u16>()?;
+            let response = http::Response::builder().status(status).body("{}")?;
+
+            Response::from_reqwest(reqwest::Response::from(response).error_for_status()?).await
+        }
+    }
+
+    /// Counts the requests it responds to.
+    #[derive(Default)]
+    struct CountingHttp(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl HttpIO for CountingHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            let response = http::Response::builder().body("{}")?;
+
+            Response::from_reqwest(reqwest::Response::from(response)).await
+        }
+    }
+
+    fn http_io(url: &str, dl_id: Option<DataLoaderId>) -> IR {
+        IR::IO(IO::Http {
+            req_template: RequestTemplate::new(url).unwrap(),
+            group_by: None,
+            dl_id,
+            is_list: false,
+            dedupe: false,
+            hook: None,
+            skip_if: None,
+            ok_status: vec![200, 404],
+        })
+    }
+
+    async fn eval_status(status: u16) -> Result<ConstValue, Error> {
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = Arc::new(StatusHttp);
+        let req_ctx = RequestContext::new(runtime);
+        let res_ctx = EmptyResolverContext {};
+        let ir = http_io(&format!("http://localhost/{status}"), None);
+
+        ir.eval(&mut EvalContext::new(&req_ctx, &res_ctx)).await
+    }
+
+    #[tokio::test]
+    async fn test_ok_status() {
+        let actual = eval_status(200).await.unwrap();
+        assert_eq!(actual, ConstValue::Object(Default::default()));
+
+        let actual = eval_status(404).await.unwrap();
+        assert_eq!(actual, ConstValue::Null);
+
+        let actual = eval_status(500).await.unwrap_err();
+        assert!(matches!(actual, Error::HTTP { status: 500, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_max_upstream_calls() {
+        let http = Arc::new(CountingHttp::default());
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = http.clone();
+        let server = Server::try_from(ConfigModule::default())
+            .unwrap()
+            .max_upstream_calls(Some(3));
+        let req_ctx = RequestContext::new(runtime).server(server);
+
+        // a list of 5 items each resolving a field from the upstream.
+        let ir = http_io("http://localhost/users", None);
+        let results = join_all((0..5).map(|_| async {
+            let res_ctx = EmptyResolverContext {};
+            ir.eval(&mut EvalContext::new(&req_ctx, &res_ctx)).await
+        }))
+        .await;
+
+        let (ok, err): (Vec<_>, Vec<_>) = results.into_iter().partition(Result::is_ok);
+        assert_eq!(ok.len(), 3);
+        assert_eq!(err.len(), 2);
+        for err in err {
+            let err = err.unwrap_err();
+            assert!(matches!(err, Error::UpstreamCallLimit(3)));
+            assert!(err
+                .to_string()
+                .contains("the request can't make more than 3 upstream calls"));
+        }
+        assert_eq!(http.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_max_upstream_calls_shared_key() {
+        let http = Arc::new(CountingHttp::default());
+        let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+        runtime.http = http.clone();
+        let server = Server::try_from(ConfigModule::default())
+            .unwrap()
+            .max_upstream_calls(Some(1));
+        // the data loaders are shared by the requests, like those of the app
+        let data_loader = HttpDataLoader::new(runtime.clone(), None, false)
+            .to_data_loader(Batch::default().delay(10));
+        let http_data_loaders = Arc::new(vec![data_loader]);
+        let request = || {
+            RequestContext::new(runtime.clone())
+                .server(server.clone())
+                .http_data_loaders(http_data_loaders.clone())
+        };
+        let exhausted = request();
+        exhausted.count_upstream_call().unwrap();
+        let other = request();
+
+        async fn eval(ir: &IR, req_ctx: &RequestContext) -> Result<ConstValue, Error> {
+            let res_ctx = EmptyResolverContext {};
+            ir.eval(&mut EvalContext::new(req_ctx, &res_ctx)).await
+        }
+
+        let ir = http_io("http://localhost/users", Some(DataLoaderId::new(0)));
+        let (exhausted, other) = futures_util::join!(eval(&ir, &exhausted), eval(&ir, &other));
+
+        assert!(matches!(exhausted, Err(Error::UpstreamCallLimit(1))));
+        assert_eq!(other.unwrap(), ConstValue::Object(Default::default()));
+        assert_eq!(http.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_is_falsy() {
+        assert!(is_falsy(""));
+        assert!(is_falsy("  "));
+        assert!(is_falsy("null"));
+        assert!(is_falsy("false"));
+        assert!(!is_falsy("1"));
+        assert!(!is_falsy("true"));
+        assert!(!is_falsy("abc"));
+    }
+}
@@ -1,9 +1,11 @@
 mod discriminator;
 mod error;
 mod eval;
+mod eval_cache;
 mod eval_context;
 mod eval_http;
 mod eval_io;
+mod rename;
 mod request;
 mod resolver_context_like;
 
@@ -14,9 +16,11 @@ use std::ops::Deref;
 pub use discriminator::*;
 pub use error::*;
 pub use eval_context::EvalContext;
+pub use rename::*;
 pub(crate) use request::DynamicRequest;
 pub use resolver_context_like::{
-    EmptyResolverContext, ResolverContext, ResolverContextLike, SelectionField,
+    DetachedResolverContext, EmptyResolverContext, ResolverContext, ResolverContextLike,
+    SelectionField,
 };
 
 /// Contains all the nested fields that are resolved with current parent
@@ -6,12 +6,13 @@ use async_graphql::Value;
 use strum_macros::Display;
 
 use super::discriminator::Discriminator;
+use super::rename::Rename;
 use super::{EvalContext, ResolverContextLike};
 use crate::core::blueprint::{Auth, DynamicValue};
 use crate::core::config::group_by::GroupBy;
 use crate::core::graphql::{self};
 use crate::core::worker_hooks::WorkerHooks;
-use crate::core::{grpc, http};
+use crate::core::{grpc, http, Mustache};
 
 #[derive(Clone, Debug, Display)]
 pub enum IR {
@@ -28,6 +29,12 @@ pub enum IR {
     /// Merges the result of multiple IRs together
     Merge(Vec<IR>),
     Discriminate(Discriminator, Box<IR>),
+    /// Renames the keys of the resolved objects
+    Rename(Rename, Box<IR>),
+    /// Converts a resolved keyed object into the list of its values
+    Values(Box<IR>),
+    /// A step of a `@call`, failing with the index of the step
+    Step(usize, Box<IR>),
     /// Apollo Federation _entities resolver
     Entity(HashMap<String, IR>),
     /// Apollo Federation _service resolver
@@ -50,6 +57,8 @@ pub enum IO {
         is_list: bool,
         dedupe: bool,
         hook: Option<WorkerHooks>,
+        skip_if: Option<Mustache>,
+        ok_status: Vec<u16>,
     },
     GraphQL {
         req_template: graphql::RequestTemplate,
@@ -114,6 +123,7 @@ pub trait CacheKey<Ctx> {
 #[derive(Clone, Debug)]
 pub struct Cache {
     pub max_age: NonZeroU64,
+    pub stale_while_revalidate: Option<NonZeroU64>,
     pub io: Box<IO>,
 }
 
@@ -122,9 +132,13 @@ impl Cache {
     /// Wraps an expression with the cache primitive.
     /// Performance DFS on the cache on the expression and identifies all the IO
     /// nodes. Then wraps each IO node with the cache primitive.
-    pub fn wrap(max_age: NonZeroU64, expr: IR) -> IR {
+    pub fn wrap(max_age: NonZeroU64, stale_while_revalidate: Option<NonZeroU64>, expr: IR) -> IR {
         expr.modify(&mut move |expr| match expr {
-            IR::IO(io) => Some(IR::Cache(Cache { max_age, io: Box::new(io.to_owned()) })),
+            IR::IO(io) => Some(IR::Cache(Cache {
+                max_age,
+                stale_while_revalidate,
+                io: Box::new(io.to_owned()),
+            })),
             _ => None,
         })
     }
@@ -136,9 +150,12 @@ impl IR {
         match self {
             IR::IO(io) => io_modifier(io),
             IR::Cache(cache) => io_modifier(&mut cache.io),
-            IR::Discriminate(_, ir) | IR::Protect(_, ir) | IR::Path(ir, _) => {
-                ir.modify_io(io_modifier)
-            }
+            IR::Discriminate(_, ir)
+            | IR::Rename(_, ir)
+            | IR::Values(ir)
+            | IR::Step(_, ir)
+            | IR::Protect(_, ir)
+            | IR::Path(ir, _) => ir.modify_io(io_modifier),
             IR::Pipe(ir1, ir2) => {
                 ir1.modify_io(io_modifier);
                 ir2.modify_io(io_modifier);
@@ -153,6 +170,57 @@ impl IR {
         }
     }
 
+    /// Returns the names of the fields of the parent value the IR reads. Only
+    /// the first step of a pipe sees the parent value.
+    pub fn parent_fields(&self) -> Vec<&str> {
+        match self {
+            IR::Dynamic(value) => value.parent_fields(),
+            IR::Discriminate(_, ir)
+            | IR::Rename(_, ir)
+            | IR::Values(ir)
+            | IR::Step(_, ir)
+            | IR::Protect(_, ir)
+            | IR::Path(ir, _) => ir.parent_fields(),
+            IR::Pipe(ir, _) => ir.parent_fields(),
+            IR::Map(map) => map.input.parent_fields(),
+            _ => vec![],
+        }
+    }
+
+    /// Checks if the IR reads the selection of the field, as the GraphQL
+    /// upstreams do to build their query.
+    pub fn reads_selection(&self) -> bool {
+        match self {
+            IR::IO(io) => matches!(io, IO::GraphQL { .. }),
+            IR::Cache(cache) => matches!(*cache.io, IO::GraphQL { .. }),
+            IR::Discriminate(_, ir)
+            | IR::Rename(_, ir)
+            | IR::Values(ir)
+            | IR::Step(_, ir)
+            | IR::Protect(_, ir)
+            | IR::Path(ir, _) => ir.reads_selection(),
+            IR::Pipe(ir1, ir2) => ir1.reads_selection() || ir2.reads_selection(),
+            IR::Map(map) => map.input.reads_selection(),
+            IR::Merge(irs) => irs.iter().any(IR::reads_selection),
+            IR::Entity(irs) => irs.values().any(IR::reads_selection),
+            _ => false,
+        }
+    }
+
+    /// Returns the argument that the `@http` request of the IR sends as is as
+    /// its body, see [http::RequestTemplate::forwarded_arg].
+    pub fn forwarded_arg(&self) -> Option<&str> {
+        match self {
+            IR::IO(IO::Http { req_template, .. }) => req_template.forwarded_arg(),
+            IR::Discriminate(_, ir)
+            | IR::Rename(_, ir)
+            | IR::Values(ir)
+            | IR::Protect(_, ir)
+            | IR::Path(ir, _) => ir.forwarded_arg(),
+            _ => None,
+        }
+    }
+
     pub fn pipe(self, next: Self) -> Self {
         IR::Pipe(Box::new(self), Box::new(next))
     }
@@ -178,10 +246,14 @@ impl IR {
                     IR::ContextPath(path) => IR::ContextPath(path),
                     IR::Dynamic(_) => expr,
                     IR::IO(_) => expr,
-                    IR::Cache(Cache { io, max_age }) => {
+                    IR::Cache(Cache { io, max_age, stale_while_revalidate }) => {
                         let expr = *IR::IO(*io).modify_box(modifier);
                         match expr {
-                            IR::IO(io) => IR::Cache(Cache { io: Box::new(io), max_age }),
+                            IR::IO(io) => IR::Cache(Cache {
+                                io: Box::new(io),
+                                max_age,
+                                stale_while_revalidate,
+                            }),
                             expr => expr,
                         }
                     }
@@ -193,6 +265,9 @@ impl IR {
                     IR::Discriminate(discriminator, expr) => {
                         IR::Discriminate(discriminator, expr.modify_box(modifier))
                     }
+                    IR::Rename(rename, expr) => IR::Rename(rename, expr.modify_box(modifier)),
+                    IR::Values(expr) => IR::Values(expr.modify_box(modifier)),
+                    IR::Step(index, expr) => IR::Step(index, expr.modify_box(modifier)),
                     IR::Entity(map) => IR::Entity(
                         map.into_iter()
                             .map(|(k, v)| (k, v.modify(modifier)))
@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use async_graphql::Value;
+use indexmap::IndexMap;
+
+/// Renames the keys of the objects resolved by an upstream, so that the
+/// value matches the fields of the GraphQL type before synthesis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rename {
+    /// Upstream key to GraphQL field name.
+    names: BTreeMap<String, String>,
+    /// Also renames the keys of the nested objects.
+    nested: bool,
+}
+
+impl Rename {
+    pub fn new(names: BTreeMap<String, String>, nested: bool) -> Self {
+        Self { names, nested }
+    }
+
+    pub fn apply(&self, value: Value) -> Value {
+        self.apply_inner(value, true)
+    }
+
+    fn apply_inner(&self, value: Value, is_root: bool) -> Value {
+        match value {
+            Value::List(list) => Value::List(
+                list.into_iter()
+                    .map(|value| self.apply_inner(value, is_root))
+                    .collect(),
+            ),
+            Value::Object(obj) if is_root || self.nested => Value::Object(
+                obj.into_iter()
+                    .map(|(key, value)| {
+                        let key = match self.names.get(key.as_str()) {
+                            Some(name) => async_graphql::Name::new(name),
+                            None => key,
+                        };
+                        (key, self.apply_inner(value, false))
+                    })
+                    .collect::<IndexMap<_, _>>(),
+            ),
+            value => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use async_graphql::Value;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::Rename;
+
+    fn names() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("user_name".to_string(), "name".to_string()),
+            ("e_mail".to_string(), "email".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_rename_list_of_objects() {
+        let value = Value::from_json(json!([
+            {"id": 1, "user_name": "Leanne", "e_mail": "leanne@example.com"},
+            {"id": 2, "user_name": "Ervin"}
+        ]))
+        .unwrap();
+
+        let actual = Rename::new(names(), false)
+            .apply(value)
+            .into_json()
+            .unwrap();
+        let expected = json!([
+            {"id": 1, "name": "Leanne", "email": "leanne@example.com"},
+            {"id": 2, "name": "Ervin"}
+        ]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_rename_nested() {
+        let value = Value::from_json(json!({
+            "user_name": "Leanne",
+            "friend": {"user_name": "Ervin"}
+        }))
+        .unwrap();
+
+        let actual = Rename::new(names(), false)
+            .apply(value.clone())
+            .into_json()
+            .unwrap();
+        let expected = json!({"name": "Leanne", "friend": {"user_name": "Ervin"}});
+        assert_eq!(actual, expected);
+
+        let actual = Rename::new(names(), true).apply(value).into_json().unwrap();
+        let expected = json!({"name": "Leanne", "friend": {"name": "Ervin"}});
+        assert_eq!(actual, expected);
+    }
+}
@@ -36,6 +36,36 @@ impl ResolverContextLike for EmptyResolverContext {
     fn add_error(&self, _: ServerError) {}
 }
 
+/// Owns a copy of the data of a resolver context, so that it can be used
+/// after the request is complete.
+#[derive(Clone)]
+pub struct DetachedResolverContext {
+    pub(super) value: Option<Value>,
+    pub(super) args: Option<IndexMap<Name, Value>>,
+    pub(super) field: Option<SelectionField>,
+    pub(super) is_query: bool,
+}
+
+impl ResolverContextLike for DetachedResolverContext {
+    fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+
+    fn args(&self) -> Option<&IndexMap<Name, Value>> {
+        self.args.as_ref()
+    }
+
+    fn field(&self) -> Option<SelectionField> {
+        self.field.clone()
+    }
+
+    fn is_query(&self) -> bool {
+        self.is_query
+    }
+
+    fn add_error(&self, _: ServerError) {}
+}
+
 #[derive(Clone)]
 pub struct ResolverContext<'a> {
     inner: Arc<async_graphql::dynamic::ResolverContext<'a>>,
@@ -69,7 +99,7 @@ impl ResolverContextLike for ResolverContext<'_> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SelectionField {
     name: String,
     args: Vec<(String, String)>,
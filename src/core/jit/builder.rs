@@ -8,10 +8,13 @@ use async_graphql::parser::types::{
 };
 use async_graphql::Positioned;
 use async_graphql_value::Value;
+use indexmap::IndexMap;
 
 use super::model::{Directive as JitDirective, *};
-use super::BuildError;
-use crate::core::blueprint::{Blueprint, Index, QueryField};
+use super::{BuildError, FieldUsage};
+use crate::core::blueprint::{
+    Blueprint, Complexity, FieldDefinition, Index, InputFieldDefinition, QueryField,
+};
 use crate::core::counter::{Count, Counter};
 use crate::core::jit::model::OperationPlan;
 use crate::core::{scalar, Type};
@@ -69,6 +72,9 @@ pub struct Builder<'a> {
     pub arg_id: Counter<usize>,
     pub field_id: Counter<usize>,
     pub document: &'a ExecutableDocument,
+    pub complexity: Complexity,
+    pub max_field_nodes: Option<usize>,
+    pub field_usage: Option<&'a FieldUsage>,
 }
 
 // TODO: make generic over Value (Input) type
@@ -81,9 +87,25 @@ impl<'a> Builder<'a> {
             index,
             arg_id: Counter::default(),
             field_id: Counter::default(),
+            complexity: blueprint.server.complexity.clone(),
+            max_field_nodes: blueprint.server.max_field_nodes,
+            field_usage: None,
         }
     }
 
+    /// Records the fields referenced by every built plan into `usage`.
+    pub fn with_field_usage(self, usage: &'a FieldUsage) -> Self {
+        Self { field_usage: Some(usage), ..self }
+    }
+
+    /// Computes the complexity of the plan, summing the costs of its fields by
+    /// the kind of their resolvers.
+    pub fn complexity(&self, plan: &OperationPlan<Value>) -> usize {
+        plan.iter_dfs()
+            .map(|field| self.complexity.cost(field.ir.as_ref()))
+            .sum()
+    }
+
     #[inline(always)]
     fn include(
         &self,
@@ -125,11 +147,32 @@ impl<'a> Builder<'a> {
         }
     }
 
+    /// Checks if the incremental delivery directive `name` is present and
+    /// isn't turned off with `if: false`. Incremental delivery is only a hint
+    /// to the server, so a condition given by a variable keeps it on.
+    #[inline(always)]
+    fn is_incremental(
+        &self,
+        directives: &[Positioned<async_graphql::parser::types::Directive>],
+        name: &str,
+    ) -> bool {
+        directives
+            .iter()
+            .find(|d| d.node.name.node.as_str() == name)
+            .is_some_and(|d| {
+                !matches!(
+                    d.node.get_argument("if").map(|pos| &pos.node),
+                    Some(Value::Boolean(false))
+                )
+            })
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline(always)]
     fn iter(
         &self,
         parent_fragment: Option<&str>,
+        defer: bool,
         selection: &SelectionSet,
         type_condition: &str,
         fragments: &HashMap<&str, &FragmentDefinition>,
@@ -149,7 +192,7 @@ impl<'a> Builder<'a> {
                     let mut directives = Vec::with_capacity(gql_field.directives.len());
                     for directive in &gql_field.directives {
                         let directive = &directive.node;
-                        if directive.name.node == "skip" || directive.name.node == "include" {
+                        if matches!(directive.name.node.as_str(), "skip" | "include" | "stream") {
                             continue;
                         }
                         let arguments = directive
@@ -171,8 +214,13 @@ impl<'a> Builder<'a> {
                         .collect::<HashMap<_, _>>();
 
                     let parent_fragment = parent_fragment.map(|s| s.to_owned());
-                    // Check if the field is present in the schema index
-                    if let Some(field_def) = self.index.get_field(type_condition, field_name) {
+                    // Check if the field is present in the schema index, internal
+                    // fields are not part of the schema
+                    if let Some(field_def) = self
+                        .index
+                        .get_field(type_condition, field_name)
+                        .filter(|field_def| !field_def.is_internal())
+                    {
                         let mut args = Vec::with_capacity(request_args.len());
                         if let QueryField::Field((_, schema_args)) = field_def {
                             for (arg_name, arg_value) in schema_args {
@@ -183,13 +231,12 @@ impl<'a> Builder<'a> {
                                     .default_value
                                     .as_ref()
                                     .and_then(|v| v.to_owned().try_into().ok());
-                                args.push(Arg {
-                                    id,
-                                    name,
-                                    type_of,
-                                    value: request_args.get(arg_name).cloned(),
-                                    default_value,
-                                });
+                                // Omitted arguments take the default from the schema
+                                let value = request_args
+                                    .get(arg_name)
+                                    .cloned()
+                                    .or_else(|| default_value.clone());
+                                args.push(Arg { id, name, type_of, value, default_value });
                             }
                         }
 
@@ -203,6 +250,7 @@ impl<'a> Builder<'a> {
                         // Recursively gather child fields for the selection set
                         let child_fields = self.iter(
                             None,
+                            false,
                             &gql_field.selection_set.node,
                             type_of.name(),
                             fragments,
@@ -222,6 +270,14 @@ impl<'a> Builder<'a> {
                         } else {
                             None
                         };
+                        let is_enum = self.index.type_is_enum(type_of.name());
+
+                        // Only the lists of objects are streamed, other lists are
+                        // small enough to be sent whole
+                        let stream = type_of.is_list()
+                            && scalar.is_none()
+                            && !is_enum
+                            && self.is_incremental(&gql_field.directives, "stream");
 
                         // Create the field with its child fields in `selection`
                         let field = Field {
@@ -235,7 +291,7 @@ impl<'a> Builder<'a> {
                                 .map(|a| a.node.to_string())
                                 .unwrap_or(field_name.to_owned()),
                             ir,
-                            is_enum: self.index.type_is_enum(type_of.name()),
+                            is_enum,
                             type_of,
                             type_condition: Some(type_condition.to_string()),
                             skip,
@@ -244,6 +300,11 @@ impl<'a> Builder<'a> {
                             pos: selection.pos.into(),
                             directives,
                             scalar,
+                            depends_on: vec![],
+                            internal: false,
+                            defer,
+                            stream,
+                            resolved_by: None,
                         };
 
                         fields.push(field);
@@ -264,6 +325,11 @@ impl<'a> Builder<'a> {
                             directives,
                             is_enum: false,
                             scalar: Some(scalar::Scalar::Empty),
+                            depends_on: vec![],
+                            internal: false,
+                            defer,
+                            stream: false,
+                            resolved_by: None,
                         };
 
                         fields.push(typename_field);
@@ -275,6 +341,7 @@ impl<'a> Builder<'a> {
                     {
                         fields.extend(self.iter(
                             Some(fragment.type_condition.node.on.node.as_str()),
+                            defer || self.is_incremental(&fragment_spread.directives, "defer"),
                             &fragment.selection_set.node,
                             fragment.type_condition.node.on.node.as_str(),
                             fragments,
@@ -289,6 +356,7 @@ impl<'a> Builder<'a> {
                         .unwrap_or(type_condition);
                     fields.extend(self.iter(
                         Some(type_of),
+                        defer || self.is_incremental(&fragment.directives, "defer"),
                         &fragment.selection_set.node,
                         type_of,
                         fragments,
@@ -297,8 +365,125 @@ impl<'a> Builder<'a> {
             }
         }
 
+        // Siblings read by a field are resolved first even when the query
+        // doesn't select them, as hidden fields. Internal fields can't be
+        // selected, so they are only ever resolved this way.
+        let mut i = 0;
+        while i < fields.len() {
+            let field = &fields[i];
+            i += 1;
+            let Some(type_condition) = field.type_condition.clone() else {
+                continue;
+            };
+            let pos = field.pos;
+            let names = field
+                .ir
+                .iter()
+                .flat_map(|ir| ir.parent_fields())
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            for name in names {
+                let is_added = fields.iter().any(|other| {
+                    other.name == name && other.type_condition.as_ref() == Some(&type_condition)
+                });
+                if is_added {
+                    continue;
+                }
+                if let Some(QueryField::Field((field_def, schema_args))) =
+                    self.index.get_field(&type_condition, &name)
+                {
+                    if field_def.resolver.is_some() {
+                        fields.push(self.internal_field(
+                            &type_condition,
+                            field_def,
+                            schema_args,
+                            pos,
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Fields that read the resolved value of a sibling have to wait for it
+        for i in 0..fields.len() {
+            let field = &fields[i];
+            let depends_on = field
+                .ir
+                .iter()
+                .flat_map(|ir| ir.parent_fields())
+                .flat_map(|name| {
+                    fields.iter().filter(move |sibling| {
+                        sibling.name == name
+                            && sibling.name != field.name
+                            && sibling.ir.is_some()
+                            && sibling.type_condition == field.type_condition
+                    })
+                })
+                .map(|sibling| sibling.id.clone())
+                .collect();
+            fields[i].depends_on = depends_on;
+        }
+
         fields
     }
+
+    /// Builds the hidden field of a sibling read by the field at `pos`, which
+    /// the query doesn't select. Its arguments take their defaults from the
+    /// schema.
+    fn internal_field(
+        &self,
+        type_condition: &str,
+        field_def: &FieldDefinition,
+        schema_args: &IndexMap<String, InputFieldDefinition>,
+        pos: Pos,
+    ) -> Field<Value> {
+        let args = schema_args
+            .iter()
+            .map(|(name, arg)| {
+                let default_value = arg
+                    .default_value
+                    .as_ref()
+                    .and_then(|v| v.to_owned().try_into().ok());
+                Arg {
+                    id: ArgId::new(self.arg_id.next()),
+                    name: name.clone(),
+                    type_of: arg.of_type.clone(),
+                    value: default_value.clone(),
+                    default_value,
+                }
+            })
+            .collect();
+        let type_of = field_def.of_type.clone();
+        let scalar = self.index.type_is_scalar(type_of.name()).then(|| {
+            scalar::Scalar::find(type_of.name())
+                .cloned()
+                .unwrap_or(scalar::Scalar::Empty)
+        });
+
+        Field {
+            id: FieldId::new(self.field_id.next()),
+            name: field_def.name.clone(),
+            output_name: field_def.name.clone(),
+            ir: field_def.resolver.clone(),
+            is_enum: self.index.type_is_enum(type_of.name()),
+            scalar,
+            type_of,
+            type_condition: Some(type_condition.to_string()),
+            skip: None,
+            include: None,
+            args,
+            selection: vec![],
+            parent_fragment: None,
+            pos,
+            directives: vec![],
+            depends_on: vec![],
+            internal: true,
+            defer: false,
+            stream: false,
+            resolved_by: None,
+        }
+    }
+
     #[inline(always)]
     fn get_type(&self, ty: OperationType) -> Option<&str> {
         match ty {
@@ -348,7 +533,11 @@ impl<'a> Builder<'a> {
         let name = self
             .get_type(operation.ty)
             .ok_or(BuildError::RootOperationTypeNotDefined { operation: operation.ty })?;
-        let fields = self.iter(None, &operation.selection_set.node, name, &fragments);
+        let mut fields = self.iter(None, false, &operation.selection_set.node, name, &fragments);
+        // Mutations are executed once per selected field
+        if operation.ty == OperationType::Query {
+            share_resolvers(&mut fields);
+        }
 
         let is_introspection_query = operation.selection_set.node.items.iter().any(|f| {
             if let Selection::Field(Positioned { node: gql_field, .. }) = &f.node {
@@ -367,10 +556,62 @@ impl<'a> Builder<'a> {
             is_introspection_query,
             Some(self.index.get_interfaces()),
         );
+
+        // Aliases select the same field multiple times, so every selection counts
+        if let Some(max) = self.max_field_nodes {
+            if let Some(field) = plan.iter_dfs().nth(max) {
+                return Err(BuildError::FieldNodeLimitExceeded { max, pos: field.pos });
+            }
+        }
+
+        if let Some(max) = self.complexity.max {
+            let complexity = self.complexity(&plan);
+            if complexity > max {
+                return Err(BuildError::ComplexityLimitExceeded { complexity, max });
+            }
+        }
+
+        if let Some(usage) = self.field_usage {
+            usage.record(&plan);
+        }
+
         Ok(plan)
     }
 }
 
+/// Lets the siblings that select the same field with the same arguments share
+/// a single execution of its resolver, the first of them being executed in
+/// place of the others.
+fn share_resolvers(fields: &mut [Field<Value>]) {
+    for i in 0..fields.len() {
+        let (siblings, rest) = fields.split_at_mut(i);
+        let field = &mut rest[0];
+        field.resolved_by = siblings
+            .iter()
+            .find(|sibling| sibling.resolved_by.is_none() && has_same_resolver(sibling, field))
+            .map(|sibling| sibling.id.clone());
+
+        share_resolvers(&mut field.selection);
+    }
+}
+
+/// Checks that both fields resolve to the same value. The resolvers that read
+/// the selection of the field, to query a GraphQL upstream, differ for every
+/// selection.
+fn has_same_resolver(a: &Field<Value>, b: &Field<Value>) -> bool {
+    a.ir.as_ref().is_some_and(|ir| !ir.reads_selection())
+        && b.ir.is_some()
+        && a.name == b.name
+        && a.type_condition == b.type_condition
+        && a.skip == b.skip
+        && a.include == b.include
+        && a.args.len() == b.args.len()
+        && a.args
+            .iter()
+            .zip(&b.args)
+            .all(|(a, b)| a.name == b.name && a.value == b.value)
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -694,4 +935,110 @@ mod tests {
         assert!(plan.is_query());
         insta::assert_debug_snapshot!(plan.selection);
     }
+
+    #[test]
+    fn test_complexity() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let mut blueprint = Blueprint::try_from(&config.into()).unwrap();
+        blueprint.server.complexity.max = Some(20);
+
+        let io_heavy = async_graphql::parser::parse_query(
+            "query { posts { id } users { id } user(id: 1) { id } }",
+        )
+        .unwrap();
+        let scalar_heavy =
+            async_graphql::parser::parse_query("query { posts { id userId title body } }").unwrap();
+
+        let builder = Builder::new(&blueprint, &io_heavy);
+        let error = builder.build(None).unwrap_err();
+        assert_eq!(
+            error,
+            BuildError::ComplexityLimitExceeded { complexity: 33, max: 20 }
+        );
+
+        let builder = Builder::new(&blueprint, &scalar_heavy);
+        let plan = builder.build(None).unwrap();
+        assert_eq!(builder.complexity(&plan), 14);
+    }
+
+    #[test]
+    fn test_max_field_nodes() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let mut blueprint = Blueprint::try_from(&config.into()).unwrap();
+        blueprint.server.max_field_nodes = Some(4);
+
+        let aliased = async_graphql::parser::parse_query(
+            "query { a: posts { id } b: posts { id } c: posts { id } }",
+        )
+        .unwrap();
+        let error = Builder::new(&blueprint, &aliased).build(None).unwrap_err();
+        assert_eq!(
+            error,
+            BuildError::FieldNodeLimitExceeded { max: 4, pos: Pos { line: 1, column: 41 } }
+        );
+
+        let within =
+            async_graphql::parser::parse_query("query { a: posts { id } b: posts { id } }")
+                .unwrap();
+        assert!(Builder::new(&blueprint, &within).build(None).is_ok());
+    }
+
+    #[test]
+    fn test_field_usage() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&config.into()).unwrap();
+        let usage = FieldUsage::default();
+
+        for query in [
+            "query { posts { id title user { name } } }",
+            "query { posts { id } users { id name } }",
+        ] {
+            let document = async_graphql::parser::parse_query(query).unwrap();
+            Builder::new(&blueprint, &document)
+                .with_field_usage(&usage)
+                .build(None)
+                .unwrap();
+        }
+
+        let actual = usage.into_inner().into_iter().collect::<Vec<_>>();
+        let expected = [
+            (("Post", "id"), 2),
+            (("Post", "title"), 1),
+            (("Post", "user"), 1),
+            (("Query", "posts"), 2),
+            (("Query", "users"), 1),
+            (("User", "id"), 1),
+            (("User", "name"), 2),
+        ]
+        .into_iter()
+        .map(|((ty, field), count)| ((ty.to_string(), field.to_string()), count))
+        .collect::<Vec<_>>();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_defer_and_stream() {
+        let plan = plan(
+            r#"
+            query {
+                users @stream { id ... @defer { name } }
+                user(id: 1) @stream { ...UserName @defer(if: false) }
+            }
+            fragment UserName on User { name }
+            "#,
+        );
+
+        let users = &plan.selection[0];
+        assert!(users.stream && !users.defer);
+        assert!(users.directives.is_empty());
+        assert!(!users.selection[0].defer);
+        assert!(users.selection[1].defer);
+
+        // `@stream` only applies to lists
+        let user = &plan.selection[1];
+        assert!(!user.stream);
+        assert!(!user.selection[0].defer);
+        assert!(plan.is_incremental());
+    }
 }
@@ -63,6 +63,16 @@ impl<'a, Input: Clone, Output> Context<'a, Input, Output> {
         }
     }
 
+    /// Same context, for a sibling field of the current one.
+    pub fn with_field(&self, field: &'a Field<Input>) -> Self {
+        Self {
+            request: self.request,
+            args: Self::build_args(field),
+            value: self.value,
+            field,
+        }
+    }
+
     pub fn value(&self) -> Option<&Output> {
         self.value
     }
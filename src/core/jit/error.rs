@@ -2,6 +2,7 @@ use async_graphql::parser::types::OperationType;
 use thiserror::Error;
 
 use super::graphql_error::ErrorExtensions;
+use super::Pos;
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 #[error("Error while building the plan")]
@@ -14,6 +15,10 @@ pub enum BuildError {
     OperationNotFound(String),
     #[error("Operation name required in request")]
     OperationNameRequired,
+    #[error("Query is too complex: complexity {complexity} exceeds the limit of {max}")]
+    ComplexityLimitExceeded { complexity: usize, max: usize },
+    #[error("Query selects more than {max} fields")]
+    FieldNodeLimitExceeded { max: usize, pos: Pos },
 }
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
@@ -38,6 +43,8 @@ pub enum ValidationError {
     EnumInvalid { type_of: String },
     #[error("internal: non-null types require a return value")]
     ValueRequired,
+    #[error(r#"unknown field "{field}" in the upstream value of "{type_of}""#)]
+    UnknownField { type_of: String, field: String },
 }
 
 #[derive(Debug, Clone, Error)]
@@ -52,6 +59,8 @@ pub enum Error {
     Validation(#[from] ValidationError),
     #[error("{0}")]
     ServerError(async_graphql::ServerError),
+    #[error("Response is larger than the limit of {max} bytes")]
+    ResponseTooLarge { max: usize },
     #[error("Unexpected error")]
     Unknown,
 }
@@ -64,7 +73,19 @@ impl ErrorExtensions for Error {
             Error::IR(error) => error.extend(),
             Error::Validation(error) => error.extend(),
             Error::ServerError(error) => error.extend(),
-            Error::Unknown => super::graphql_error::Error::new(self.to_string()),
+            Error::ResponseTooLarge { .. } | Error::Unknown => {
+                super::graphql_error::Error::new(self.to_string())
+            }
+        }
+    }
+}
+
+impl Error {
+    /// Returns the position in the query the error refers to, if any.
+    pub fn pos(&self) -> Option<Pos> {
+        match self {
+            Error::BuildError(BuildError::FieldNodeLimitExceeded { pos, .. }) => Some(*pos),
+            _ => None,
         }
     }
 }
@@ -1,19 +1,27 @@
+use std::borrow::Cow;
 use std::fmt::Debug;
 use std::mem;
 use std::sync::{Arc, Mutex};
 
 use derive_getters::Getters;
 use futures_util::future::join_all;
+use hyper::body::Sender;
+use serde::Serialize;
 
 use super::context::{Context, RequestContext};
-use super::{OperationPlan, Positioned, Response, Store};
+use super::graphql_error::GraphQLError;
+use super::{
+    truncate_errors, AnyResponse, Field, IncrementalResponse, OperationPlan, Positioned, Response,
+    Store,
+};
 use crate::core::ir::model::IR;
 use crate::core::ir::TypedValue;
 use crate::core::jit;
 use crate::core::jit::synth::Synth;
-use crate::core::json::{JsonLike, JsonLikeList};
+use crate::core::json::{JsonLike, JsonLikeList, JsonObjectLike};
 
 type SharedStore<Output, Error> = Arc<Mutex<Store<Result<Output, Positioned<Error>>>>>;
+type ValueStore<Value> = Store<Result<Value, Positioned<jit::Error>>>;
 
 ///
 /// Default GraphQL executor that takes in a GraphQL Request and produces a
@@ -21,6 +29,7 @@ type SharedStore<Output, Error> = Arc<Mutex<Store<Result<Output, Positioned<Erro
 pub struct Executor<'a, IRExec, Input> {
     ctx: RequestContext<'a, Input>,
     exec: IRExec,
+    max_errors: Option<usize>,
 }
 
 impl<'a, Input, Value, Exec> Executor<'a, Exec, Input>
@@ -30,12 +39,42 @@ where
     Exec: IRExecutor<Input = Input, Output = Value, Error = jit::Error>,
 {
     pub fn new(plan: &'a OperationPlan<Input>, exec: Exec) -> Self {
-        Self { exec, ctx: RequestContext::new(plan) }
+        Self { exec, ctx: RequestContext::new(plan), max_errors: None }
     }
 
-    pub async fn store(&self) -> Store<Result<Value, Positioned<jit::Error>>> {
-        let store = Arc::new(Mutex::new(Store::new()));
-        let mut ctx = ExecutorInner::new(store.clone(), &self.exec, &self.ctx);
+    /// Caps the errors of the responses, see [`truncate_errors`].
+    pub fn max_errors(self, max_errors: Option<usize>) -> Self {
+        Self { max_errors, ..self }
+    }
+
+    pub async fn store(&self) -> ValueStore<Value> {
+        self.resolve(self.new_store(), Phase::Complete).await
+    }
+
+    /// Resolves the data of the initial payload of an incremental response,
+    /// leaving out the fields of the `@defer` fragments and the fields nested
+    /// in the items of the `@stream` lists.
+    pub async fn store_initial(&self) -> ValueStore<Value> {
+        self.resolve(self.new_store(), Phase::Initial).await
+    }
+
+    /// Resolves the data left out of `store` by [`Executor::store_initial`],
+    /// without resolving again what's already in it.
+    pub async fn store_remaining(&self, store: ValueStore<Value>) -> ValueStore<Value> {
+        self.resolve(store, Phase::Remaining).await
+    }
+
+    fn new_store(&self) -> ValueStore<Value> {
+        if cfg!(debug_assertions) {
+            Store::strict(self.ctx.plan())
+        } else {
+            Store::new()
+        }
+    }
+
+    async fn resolve(&self, store: ValueStore<Value>, phase: Phase) -> ValueStore<Value> {
+        let store = Arc::new(Mutex::new(store));
+        let ctx = ExecutorInner::new(store.clone(), &self.exec, &self.ctx, phase);
         ctx.init().await;
 
         let store = mem::replace(&mut *store.lock().unwrap(), Store::new());
@@ -47,9 +86,154 @@ where
         Output: JsonLike<'a> + Default,
     {
         let mut response = Response::new(synth.synthesize());
+        if response.errors.is_empty() {
+            response.add_errors(synth.masked_errors());
+        }
         response.add_errors(self.ctx.errors().clone());
         response
     }
+
+    /// Same as [`Executor::execute`] but serializes the data straight into the
+    /// response body instead of building an intermediate value first.
+    pub async fn execute_bytes(self, synth: &'a Synth<'a, Value>) -> AnyResponse<Vec<u8>>
+    where
+        Value: Serialize,
+    {
+        AnyResponse::from_writer(
+            |buf| synth.synthesize_to(buf).map(|_| synth.masked_errors()),
+            self.ctx.errors().clone(),
+            self.max_errors,
+        )
+    }
+
+    /// Same as [`Executor::execute_bytes`] but sends the elements of the root
+    /// list as newline delimited JSON, each one as soon as it's written. The
+    /// errors, if any, follow in a last `{"errors":[...]}` line and an element
+    /// that fails ends the stream. Operations without a single root list send
+    /// the whole response as the only line.
+    pub async fn execute_lines(self, synth: &'a Synth<'a, Value>, sender: &mut Sender)
+    where
+        Value: Serialize,
+    {
+        let Some(lines) = synth.lines() else {
+            let response = self.execute_bytes(synth).await;
+            send_line(sender, response.body.as_ref().clone()).await;
+            return;
+        };
+
+        let mut errors: Vec<GraphQLError> = Vec::new();
+        for line in lines {
+            match line {
+                Ok(line) => {
+                    if !send_line(sender, line).await {
+                        return;
+                    }
+                }
+                Err(error) => errors.push(error.into()),
+            }
+        }
+        errors.extend(synth.masked_errors().into_iter().map(|e| e.into()));
+        errors.extend(self.ctx.errors().iter().cloned().map(|e| e.into()));
+        truncate_errors(&mut errors, self.max_errors);
+
+        if !errors.is_empty() {
+            let mut line = b"{\"errors\":".to_vec();
+            // serializing into a `Vec` can't fail
+            let _ = serde_json::to_writer(&mut line, &errors);
+            line.push(b'}');
+            send_line(sender, line).await;
+        }
+    }
+
+    /// Same as [`Executor::execute`] but delivers the response incrementally,
+    /// as the parts of a `multipart/mixed` body. The initial payload is
+    /// synthesized from `store`, resolved by [`Executor::store_initial`], and
+    /// sent before the data left out of it is resolved. The fields of the
+    /// `@defer` fragments and the items of the `@stream` lists then follow in
+    /// a part each. `synth` has to create the synths in the incremental mode.
+    pub async fn execute_incremental(
+        self,
+        store: ValueStore<Value>,
+        synth: impl Fn(ValueStore<Value>) -> Synth<'a, Value>,
+        sender: &mut Sender,
+    ) where
+        Value: Serialize,
+    {
+        let initial = synth(store);
+        let (data, patches) = initial.synthesize_stream::<Value>();
+        let has_next = !patches.is_empty();
+
+        let mut response = Response::new(data);
+        if response.errors.is_empty() {
+            response.add_errors(initial.masked_errors());
+        }
+        response.add_errors(self.ctx.errors().clone());
+        let response = response.truncate_errors(self.max_errors);
+
+        let part = IncrementalResponse::Initial { response, has_next };
+        if !send_part(sender, serde_json::to_vec(&part).unwrap_or_default()).await {
+            return;
+        }
+        if !has_next {
+            close_parts(sender).await;
+            return;
+        }
+
+        let rest = synth(self.store_remaining(initial.into_store()).await);
+        let mut patches = rest.patches::<Value>().peekable();
+        while let Some(patch) = patches.next() {
+            let subsequent = IncrementalResponse::Subsequent {
+                incremental: vec![patch],
+                has_next: patches.peek().is_some(),
+            };
+            if !send_part(sender, serde_json::to_vec(&subsequent).unwrap_or_default()).await {
+                return;
+            }
+        }
+        close_parts(sender).await;
+    }
+}
+
+/// Boundary of the parts of a `multipart/mixed` incremental response.
+pub const MULTIPART_BOUNDARY: &str = "graphql";
+
+/// Sends `part`, a JSON document, into the body as a part of a
+/// `multipart/mixed` response. Returns `false` once the client is gone.
+pub async fn send_part(sender: &mut Sender, part: Vec<u8>) -> bool {
+    let mut body = format!(
+        "\r\n--{MULTIPART_BOUNDARY}\r\nContent-Type: application/json; charset=utf-8\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend(part);
+    sender.send_data(body.into()).await.is_ok()
+}
+
+/// Sends the delimiter closing the parts of a `multipart/mixed` response.
+pub async fn close_parts(sender: &mut Sender) {
+    let _ = sender
+        .send_data(format!("\r\n--{MULTIPART_BOUNDARY}--\r\n").into())
+        .await;
+}
+
+/// Sends `line` terminated by a newline into the body. Returns `false` once the
+/// client is gone.
+pub async fn send_line(sender: &mut Sender, mut line: Vec<u8>) -> bool {
+    line.push(b'\n');
+    sender.send_data(line.into()).await.is_ok()
+}
+
+/// What [`ExecutorInner`] resolves of the plan.
+#[derive(Clone, Copy, PartialEq)]
+enum Phase {
+    /// Every field.
+    Complete,
+    /// The fields of the initial payload of an incremental response: the
+    /// fields of the `@defer` fragments are skipped, and so are the fields
+    /// nested in the `@stream` lists.
+    Initial,
+    /// The fields left out of the store by [`Phase::Initial`], the others are
+    /// taken from the store.
+    Remaining,
 }
 
 #[derive(Getters)]
@@ -57,29 +241,51 @@ struct ExecutorInner<'a, Input, Output, Error, Exec> {
     store: SharedStore<Output, Error>,
     ir_exec: &'a Exec,
     request: &'a RequestContext<'a, Input>,
+    phase: Phase,
 }
 
 impl<'a, Input, Output, Error, Exec> ExecutorInner<'a, Input, Output, Error, Exec>
 where
     for<'i> Output: JsonLike<'i> + JsonLikeList<'i> + TypedValue<'i> + Debug + Clone,
     Input: Clone + Debug,
+    Error: Clone,
     Exec: IRExecutor<Input = Input, Output = Output, Error = Error>,
 {
     fn new(
         store: SharedStore<Output, Error>,
         ir_exec: &'a Exec,
         env: &'a RequestContext<Input>,
+        phase: Phase,
     ) -> Self {
-        Self { store, ir_exec, request: env }
+        Self { store, ir_exec, request: env, phase }
+    }
+
+    /// Checks if `field` is executed in the current phase.
+    fn is_executed(&self, field: &Field<Input>) -> bool {
+        field.resolved_by.is_none() && !(self.phase == Phase::Initial && field.defer)
+    }
+
+    /// Checks if the fields nested in the value of `field` are executed in
+    /// the current phase.
+    fn is_iterated(&self, field: &Field<Input>) -> bool {
+        !(self.phase == Phase::Initial && field.stream)
     }
 
-    async fn init(&mut self) {
-        join_all(self.request.plan().selection.iter().map(|field| async {
-            let ctx = Context::new(field, self.request);
-            // TODO: with_args should be called on inside iter_field on any level, not only
-            // for root fields
-            self.execute(&ctx).await
-        }))
+    async fn init(&self) {
+        let fields = self.request.plan().selection.iter().collect::<Vec<_>>();
+        join_all(
+            fields
+                .iter()
+                .copied()
+                .filter(|field| self.is_executed(field))
+                .map(|field| {
+                    // TODO: with_args should be called on inside iter_field on any level, not
+                    // only for root fields
+                    let ctx = Context::new(field, self.request);
+                    let shared = resolved_by(&fields, field);
+                    async move { self.execute(&ctx, &shared).await }
+                }),
+        )
         .await;
     }
 
@@ -89,29 +295,123 @@ where
         value: &'b Output,
     ) -> Result<(), Error> {
         let field = ctx.field();
+        if field.iter().all(|child| child.depends_on.is_empty()) {
+            self.execute_fields(ctx, value, field.iter()).await;
+            return Ok(());
+        }
+
+        // Execute the fields in layers, so that the fields that depend on the
+        // resolved value of a sibling see it in the value of the parent
+        let mut value = Cow::Borrowed(value);
+        let mut pending = field.iter().collect::<Vec<_>>();
+        while !pending.is_empty() {
+            let (mut ready, mut blocked): (Vec<_>, Vec<_>) =
+                pending.iter().copied().partition(|child| {
+                    child
+                        .depends_on
+                        .iter()
+                        .all(|id| pending.iter().all(|other| other.id != *id))
+                });
+            // Cycles are rejected while building the blueprint
+            if ready.is_empty() {
+                ready = mem::take(&mut blocked);
+            }
+
+            self.execute_fields(ctx, &value, ready.iter().copied())
+                .await;
+
+            let dependencies = ready
+                .iter()
+                .filter(|child| {
+                    blocked
+                        .iter()
+                        .any(|other| other.depends_on.contains(&child.id))
+                })
+                .collect::<Vec<_>>();
+            if !dependencies.is_empty() {
+                let mut parent = value.into_owned();
+                let store = self.store.lock().unwrap();
+                for child in dependencies {
+                    if let Some(Ok(resolved)) = store.get(&child.id) {
+                        set_resolved(&mut parent, &child.name, resolved);
+                    }
+                }
+                value = Cow::Owned(parent);
+            }
+
+            pending = blocked;
+        }
+
+        Ok(())
+    }
+
+    async fn execute_fields<'b>(
+        &'b self,
+        ctx: &'b Context<'b, Input, Output>,
+        value: &'b Output,
+        fields: impl Iterator<Item = &'b Field<Input>>,
+    ) {
         // TODO: Validate if the value is an Object
         // Has to be an Object, we don't do anything while executing if its a Scalar
-        join_all(field.iter().map(|child| {
-            let ctx = ctx.with_value_and_field(value, child);
-            async move { self.execute(&ctx).await }
-        }))
+        let fields = fields.collect::<Vec<_>>();
+        join_all(
+            fields
+                .iter()
+                .copied()
+                .filter(|child| self.is_executed(child))
+                .map(|child| {
+                    let ctx = ctx.with_value_and_field(value, child);
+                    let shared = resolved_by(&fields, child);
+                    async move { self.execute(&ctx, &shared).await }
+                }),
+        )
         .await;
-
-        Ok(())
     }
 
-    async fn execute<'b>(&'b self, ctx: &'b Context<'b, Input, Output>) -> Result<(), Error> {
+    /// Executes the field, along with the `shared` siblings that are resolved
+    /// by its resolver.
+    async fn execute<'b>(
+        &'b self,
+        ctx: &'b Context<'b, Input, Output>,
+        shared: &[&'b Field<Input>],
+    ) -> Result<(), Error> {
         let field = ctx.field();
 
         if let Some(ir) = &field.ir {
-            let result = self.ir_exec.execute(ir, ctx).await;
+            // Every sibling goes through its own selection of the resolved value
+            let shared = shared
+                .iter()
+                .map(|&sibling| ctx.with_field(sibling))
+                .collect::<Vec<_>>();
+
+            let resolved = match self.phase {
+                Phase::Remaining => self.store.lock().unwrap().get(&field.id).cloned(),
+                _ => None,
+            };
+            if let Some(result) = resolved {
+                // resolved for the initial payload, only the nested fields are left
+                if let Ok(value) = &result {
+                    self.iter_fields(std::iter::once(ctx).chain(&shared), value)
+                        .await?;
+                }
+                return Ok(());
+            }
 
+            let result = self.ir_exec.execute(ir, ctx).await;
             if let Ok(value) = &result {
-                self.iter_field(ctx, value).await?;
+                self.iter_fields(std::iter::once(ctx).chain(&shared), value)
+                    .await?;
             }
 
             let mut store = self.store.lock().unwrap();
 
+            for ctx in &shared {
+                let sibling = ctx.field();
+                store.set(
+                    &sibling.id,
+                    result.clone().map_err(|e| Positioned::new(e, sibling.pos)),
+                );
+            }
             store.set(&field.id, result.map_err(|e| Positioned::new(e, field.pos)));
         } else {
             let value = match ctx.value() {
@@ -134,11 +434,58 @@ where
                 None => Output::null(),
             };
 
-            self.iter_field(ctx, &value).await?;
+            if self.is_iterated(field) {
+                self.iter_field(ctx, &value).await?;
+            }
         }
 
         Ok(())
     }
+
+    /// Executes the fields nested in `value`, resolved for each of the fields
+    /// of `ctxs`.
+    async fn iter_fields<'b>(
+        &'b self,
+        ctxs: impl Iterator<Item = &'b Context<'b, Input, Output>>,
+        value: &'b Output,
+    ) -> Result<(), Error> {
+        join_all(
+            ctxs.filter(|ctx| self.is_iterated(ctx.field()))
+                .map(|ctx| self.iter_field(ctx, value)),
+        )
+        .await
+        .into_iter()
+        .collect()
+    }
+}
+
+/// The fields that are resolved by the resolver of `field`.
+fn resolved_by<'a, Input>(
+    fields: &[&'a Field<Input>],
+    field: &Field<Input>,
+) -> Vec<&'a Field<Input>> {
+    fields
+        .iter()
+        .copied()
+        .filter(|sibling| sibling.resolved_by.as_ref() == Some(&field.id))
+        .collect()
+}
+
+/// Sets the key of every object in the value to the matching entry of the
+/// resolved value, which has the same shape as the value.
+fn set_resolved<'a, Output>(value: &mut Output, key: &'a str, resolved: &Output)
+where
+    Output: JsonLike<'a> + Clone,
+{
+    if let Some(values) = value.as_array_mut() {
+        if let Some(resolved) = resolved.as_array() {
+            for (value, resolved) in values.iter_mut().zip(resolved) {
+                set_resolved(value, key, resolved);
+            }
+        }
+    } else if let Some(object) = value.as_object_mut() {
+        object.insert_key(key, resolved.clone());
+    }
 }
 
 /// Executor for IR
@@ -2,12 +2,16 @@ use std::sync::Arc;
 
 use async_graphql_value::{ConstValue, Value};
 use futures_util::future::join_all;
+use hyper::body::Sender;
 use tailcall_valid::Validator;
 
 use super::context::Context;
-use super::exec::{Executor, IRExecutor};
+use super::exec::{close_parts, send_line, send_part, Executor, IRExecutor};
 use super::graphql_error::GraphQLError;
-use super::{transform, AnyResponse, BuildError, Error, OperationPlan, Request, Response, Result};
+use super::{
+    transform, AnyResponse, BuildError, Error, OperationPlan, Positioned, Request, Response,
+    Result, Store, Variables,
+};
 use crate::core::app_context::AppContext;
 use crate::core::http::RequestContext;
 use crate::core::ir::model::IR;
@@ -40,6 +44,141 @@ impl ConstValueExecutor {
         req_ctx: &RequestContext,
         request: Request<ConstValue>,
     ) -> AnyResponse<Vec<u8>> {
+        let is_introspection_query =
+            req_ctx.server.get_enable_introspection() && self.plan.is_introspection_query;
+
+        let plan = match self.prepare(req_ctx, &request.variables).await {
+            Ok(plan) => plan,
+            Err(response) => return response,
+        };
+
+        let exec = ConstValueExec::new(&plan, req_ctx);
+        // PERF: remove this particular clone?
+        let vars = request.variables.clone();
+        let exe = Executor::new(&plan, exec).max_errors(req_ctx.server.max_errors);
+        let store = match static_store(&plan) {
+            Some(store) => store,
+            None => exe.store().await,
+        };
+        let synth = Synth::new(&plan, store, vars)
+            .unknown_fields(req_ctx.server.unknown_fields.clone())
+            .lenient_dates(req_ctx.server.lenient_dates)
+            .scalar_coercion(req_ctx.server.scalar_coercion.clone())
+            .max_response_bytes(req_ctx.server.max_response_bytes)
+            .unwrap_root(req_ctx.server.unwrap_root)
+            .omit_nulls(req_ctx.server.omit_nulls);
+
+        if is_introspection_query {
+            let resp: Response<serde_json_borrow::Value> = exe.execute(&synth).await;
+            let async_req = async_graphql::Request::from(request).only_introspection();
+            let async_resp = app_ctx.execute(async_req).await;
+
+            resp.merge_with(&async_resp)
+                .truncate_errors(req_ctx.server.max_errors)
+                .into()
+        } else {
+            // serialize straight into the body to avoid building the response twice
+            exe.execute_bytes(&synth).await
+        }
+    }
+
+    /// Executes the request like [`ConstValueExecutor::execute`], but sends the
+    /// response into `sender` as newline delimited JSON, see
+    /// [`Executor::execute_lines`]. Introspection queries are sent as a
+    /// single line.
+    pub async fn execute_lines(
+        self,
+        app_ctx: &Arc<AppContext>,
+        req_ctx: &RequestContext,
+        request: Request<ConstValue>,
+        sender: &mut Sender,
+    ) {
+        if self.plan.is_introspection_query {
+            let response = self.execute(app_ctx, req_ctx, request).await;
+            send_line(sender, response.body.as_ref().clone()).await;
+            return;
+        }
+
+        let plan = match self.prepare(req_ctx, &request.variables).await {
+            Ok(plan) => plan,
+            Err(response) => {
+                send_line(sender, response.body.as_ref().clone()).await;
+                return;
+            }
+        };
+
+        let exec = ConstValueExec::new(&plan, req_ctx);
+        let exe = Executor::new(&plan, exec).max_errors(req_ctx.server.max_errors);
+        let store = match static_store(&plan) {
+            Some(store) => store,
+            None => exe.store().await,
+        };
+        let synth = Synth::new(&plan, store, request.variables)
+            .unknown_fields(req_ctx.server.unknown_fields.clone())
+            .lenient_dates(req_ctx.server.lenient_dates)
+            .scalar_coercion(req_ctx.server.scalar_coercion.clone())
+            .max_response_bytes(req_ctx.server.max_response_bytes)
+            .unwrap_root(req_ctx.server.unwrap_root)
+            .omit_nulls(req_ctx.server.omit_nulls);
+
+        exe.execute_lines(&synth, sender).await
+    }
+
+    /// Executes the request like [`ConstValueExecutor::execute`], but delivers
+    /// the response into `sender` incrementally, see
+    /// [`Executor::execute_incremental`]. Introspection queries are sent as a
+    /// single part.
+    pub async fn execute_incremental(
+        self,
+        app_ctx: &Arc<AppContext>,
+        req_ctx: &RequestContext,
+        request: Request<ConstValue>,
+        sender: &mut Sender,
+    ) {
+        if self.plan.is_introspection_query {
+            let response = self.execute(app_ctx, req_ctx, request).await;
+            if send_part(sender, response.body.as_ref().clone()).await {
+                close_parts(sender).await;
+            }
+            return;
+        }
+
+        let plan = match self.prepare(req_ctx, &request.variables).await {
+            Ok(plan) => plan,
+            Err(response) => {
+                if send_part(sender, response.body.as_ref().clone()).await {
+                    close_parts(sender).await;
+                }
+                return;
+            }
+        };
+
+        let exec = ConstValueExec::new(&plan, req_ctx);
+        let exe = Executor::new(&plan, exec).max_errors(req_ctx.server.max_errors);
+        // only the data of the initial payload is resolved before it's sent
+        let store = match static_store(&plan) {
+            Some(store) => store,
+            None => exe.store_initial().await,
+        };
+        let synth = |store| {
+            Synth::new(&plan, store, request.variables.clone())
+                .unknown_fields(req_ctx.server.unknown_fields.clone())
+                .lenient_dates(req_ctx.server.lenient_dates)
+                .scalar_coercion(req_ctx.server.scalar_coercion.clone())
+                .omit_nulls(req_ctx.server.omit_nulls)
+                .incremental(true)
+        };
+
+        exe.execute_incremental(store, synth, sender).await
+    }
+
+    /// Runs the before chain and resolves the plan against the variables of
+    /// the request. Fails with the response to send back instead.
+    async fn prepare(
+        self,
+        req_ctx: &RequestContext,
+        variables: &Variables<ConstValue>,
+    ) -> std::result::Result<OperationPlan<ConstValue>, AnyResponse<Vec<u8>>> {
         // Run all the IRs in the before chain
         if let Some(ir) = &self.plan.before {
             let mut eval_context = EvalContext::new(req_ctx, &EmptyResolverContext {});
@@ -47,17 +186,13 @@ impl ConstValueExecutor {
                 Ok(_) => (),
                 Err(err) => {
                     let resp: Response<ConstValue> = Response::default();
-                    return resp
+                    return Err(resp
                         .with_errors(vec![GraphQLError::new(err.to_string(), None)])
-                        .into();
+                        .into());
                 }
             }
         }
 
-        let is_introspection_query =
-            req_ctx.server.get_enable_introspection() && self.plan.is_introspection_query;
-        let variables = &request.variables;
-
         // Attempt to skip unnecessary fields
         let Ok(plan) = transform::Skip::new(variables)
             .transform(self.plan)
@@ -65,9 +200,9 @@ impl ConstValueExecutor {
         else {
             let resp: Response<ConstValue> = Response::default();
             // this shouldn't actually ever happen
-            return resp
+            return Err(resp
                 .with_errors(vec![GraphQLError::new(Error::Unknown.to_string(), None)])
-                .into();
+                .into());
         };
 
         // Attempt to replace variables in the plan with the actual values
@@ -76,37 +211,37 @@ impl ConstValueExecutor {
         // [InputResolver] to resolve defaults properly
         let result = InputResolver::new(plan).resolve_input(variables);
 
-        let plan = match result {
-            Ok(plan) => plan,
+        match result {
+            Ok(plan) => Ok(plan),
             Err(err) => {
                 let resp: Response<ConstValue> = Response::default();
-                return resp
+                Err(resp
                     .with_errors(vec![GraphQLError::new(
                         BuildError::from(err).to_string(),
                         None,
                     )])
-                    .into();
+                    .into())
             }
-        };
-
-        let exec = ConstValueExec::new(&plan, req_ctx);
-        // PERF: remove this particular clone?
-        let vars = request.variables.clone();
-        let exe = Executor::new(&plan, exec);
-        let store = exe.store().await;
-        let synth = Synth::new(&plan, store, vars);
-
-        let resp: Response<serde_json_borrow::Value> = exe.execute(&synth).await;
+        }
+    }
+}
 
-        if is_introspection_query {
-            let async_req = async_graphql::Request::from(request).only_introspection();
-            let async_resp = app_ctx.execute(async_req).await;
+/// Resolves the constants selected by a static plan straight into the store,
+/// without going through the executor. Returns `None` for the other plans.
+fn static_store(
+    plan: &OperationPlan<ConstValue>,
+) -> Option<Store<std::result::Result<ConstValue, Positioned<Error>>>> {
+    if !plan.is_static {
+        return None;
+    }
 
-            resp.merge_with(&async_resp).into()
-        } else {
-            resp.into()
+    let mut store = Store::new();
+    for field in plan.selection.iter() {
+        if let Some(value) = field.ir.as_ref().and_then(transform::static_value) {
+            store.set(&field.id, Ok(value));
         }
     }
+    Some(store)
 }
 
 struct ConstValueExec<'a> {
@@ -191,3 +326,133 @@ impl IRExecutor for ConstValueExec<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_graphql_value::ConstValue;
+    use serde_json::json;
+    use tailcall_valid::Validator;
+
+    use super::static_store;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::ir::model::IR;
+    use crate::core::jit::context::Context;
+    use crate::core::jit::exec::{Executor, IRExecutor};
+    use crate::core::jit::synth::Synth;
+    use crate::core::jit::transform::InputResolver;
+    use crate::core::jit::{Error, OperationPlan, Request, Response, Result, Variables};
+
+    const CONFIG: &str = r#"
+        schema {
+          query: Query
+        }
+
+        type Query {
+          greeting: String @expr(body: "Hello")
+          settings: Settings @expr(body: {version: 1, tags: ["a", "b"]})
+          posts: [Post] @http(url: "http://localhost/posts")
+        }
+
+        type Settings {
+          version: Int
+          tags: [String]
+        }
+
+        type Post {
+          id: Int
+          title: String
+        }
+    "#;
+
+    /// Fails the test whenever it's asked to resolve a field.
+    struct Unreachable;
+
+    impl IRExecutor for Unreachable {
+        type Input = ConstValue;
+        type Output = ConstValue;
+        type Error = Error;
+
+        async fn execute<'a>(
+            &'a self,
+            _: &'a IR,
+            _: &'a Context<'a, Self::Input, Self::Output>,
+        ) -> Result<Self::Output> {
+            unreachable!("the executor shouldn't run for a static plan")
+        }
+    }
+
+    /// Resolves every field to the same list of posts, counting the calls.
+    struct Counting(Arc<AtomicUsize>);
+
+    impl IRExecutor for Counting {
+        type Input = ConstValue;
+        type Output = ConstValue;
+        type Error = Error;
+
+        async fn execute<'a>(
+            &'a self,
+            _: &'a IR,
+            _: &'a Context<'a, Self::Input, Self::Output>,
+        ) -> Result<Self::Output> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(ConstValue::from_json(json!([{"id": 1, "title": "Hello"}])).unwrap())
+        }
+    }
+
+    fn plan(query: &str) -> OperationPlan<ConstValue> {
+        let config = ConfigModule::from(Config::from_sdl(CONFIG).to_result().unwrap());
+        let blueprint = Blueprint::try_from(&config).unwrap();
+        let plan = Request::new(query).create_plan(&blueprint).unwrap();
+
+        InputResolver::new(plan)
+            .resolve_input(&Variables::new())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_static_plan() {
+        let plan = plan("query { greeting settings { version tags } }");
+        assert!(plan.is_static);
+
+        let store = static_store(&plan).unwrap();
+        let synth = Synth::new(&plan, store, Variables::new());
+        let response: Response<serde_json::Value> =
+            Executor::new(&plan, Unreachable).execute(&synth).await;
+
+        assert!(response.errors.is_empty());
+        assert_eq!(
+            response.data,
+            json!({"greeting": "Hello", "settings": {"version": 1, "tags": ["a", "b"]}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shared_resolver() {
+        let plan = plan("query { a: posts { id } b: posts { title } }");
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let exe = Executor::new(&plan, Counting(calls.clone()));
+        let store = exe.store().await;
+        let synth = Synth::new(&plan, store, Variables::new());
+        let response: Response<serde_json::Value> = exe.execute(&synth).await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(response.errors.is_empty());
+        assert_eq!(
+            response.data,
+            json!({"a": [{"id": 1}], "b": [{"title": "Hello"}]})
+        );
+    }
+
+    #[test]
+    fn test_plan_with_io_is_not_static() {
+        let plan = plan("query { greeting posts { id } }");
+
+        assert!(!plan.is_static);
+        assert!(static_store(&plan).is_none());
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+use super::{Field, OperationPlan};
+
+/// Accumulates the number of plans selecting each field, keyed by the name of
+/// the type and the name of the field.
+#[derive(Default)]
+pub struct FieldUsage(Mutex<BTreeMap<(String, String), usize>>);
+
+impl FieldUsage {
+    /// Records every field referenced by the plan once, however many times it
+    /// is selected within it.
+    pub fn record<Input>(&self, plan: &OperationPlan<Input>) {
+        let mut fields = BTreeSet::new();
+        collect(&plan.root_name, &plan.selection, &mut fields);
+
+        let mut usage = self.0.lock().unwrap();
+        for key in fields {
+            *usage.entry(key).or_default() += 1;
+        }
+    }
+
+    /// Returns the number of plans that selected the field.
+    pub fn get(&self, type_name: &str, field_name: &str) -> usize {
+        self.0
+            .lock()
+            .unwrap()
+            .get(&(type_name.to_string(), field_name.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn into_inner(self) -> BTreeMap<(String, String), usize> {
+        self.0.into_inner().unwrap()
+    }
+}
+
+fn collect<Input>(
+    parent: &str,
+    selection: &[Field<Input>],
+    fields: &mut BTreeSet<(String, String)>,
+) {
+    for field in selection {
+        // Skip introspection fields like `__typename`
+        if field.name.starts_with("__") {
+            continue;
+        }
+
+        let type_name = field.type_condition.as_deref().unwrap_or(parent);
+        fields.insert((type_name.to_string(), field.name.clone()));
+        collect(field.type_of.name(), &field.selection, fields);
+    }
+}
@@ -7,13 +7,15 @@ use async_graphql::{BatchRequest, Value};
 use async_graphql_value::{ConstValue, Extensions};
 use futures_util::stream::FuturesOrdered;
 use futures_util::StreamExt;
+use hyper::body::Sender;
 use tailcall_hasher::TailcallHasher;
 
+use super::exec::{close_parts, send_line, send_part};
 use super::{AnyResponse, BatchResponse, Response};
 use crate::core::app_context::AppContext;
 use crate::core::async_graphql_hyper::OperationId;
 use crate::core::http::RequestContext;
-use crate::core::jit::{self, ConstValueExecutor, OPHash, Pos, Positioned};
+use crate::core::jit::{self, ConstValueExecutor, OPHash, Positioned};
 
 #[derive(Clone)]
 pub struct JITExecutor {
@@ -61,6 +63,33 @@ impl JITExecutor {
         out.unwrap_or_default()
     }
 
+    /// Creates the executor from the cached plan of the operation, building and
+    /// caching the plan on first use.
+    fn plan(
+        &self,
+        hash: &OPHash,
+        jit_request: &jit::Request<ConstValue>,
+    ) -> Result<ConstValueExecutor, AnyResponse<Vec<u8>>> {
+        if let Some(op) = self.app_ctx.operation_plans.get(hash) {
+            return Ok(ConstValueExecutor::from(op.value().clone()));
+        }
+
+        match ConstValueExecutor::try_new(jit_request, &self.app_ctx) {
+            Ok(exec) => {
+                self.app_ctx
+                    .operation_plans
+                    .insert(hash.clone(), exec.plan.clone());
+                Ok(exec)
+            }
+            Err(error) => {
+                let pos = error.pos().unwrap_or_default();
+                Err(Response::<async_graphql::Value>::default()
+                    .with_errors(vec![Positioned::new(error, pos)])
+                    .into())
+            }
+        }
+    }
+
     #[inline(always)]
     fn req_hash(request: &async_graphql::Request) -> OPHash {
         let mut hasher = TailcallHasher::default();
@@ -85,21 +114,9 @@ impl JITExecutor {
             }
 
             let jit_request = jit::Request::from(request);
-            let exec = if let Some(op) = self.app_ctx.operation_plans.get(&hash) {
-                ConstValueExecutor::from(op.value().clone())
-            } else {
-                let exec = match ConstValueExecutor::try_new(&jit_request, &self.app_ctx) {
-                    Ok(exec) => exec,
-                    Err(error) => {
-                        return Response::<async_graphql::Value>::default()
-                            .with_errors(vec![Positioned::new(error, Pos::default())])
-                            .into()
-                    }
-                };
-                self.app_ctx
-                    .operation_plans
-                    .insert(hash.clone(), exec.plan.clone());
-                exec
+            let exec = match self.plan(&hash, &jit_request) {
+                Ok(exec) => exec,
+                Err(response) => return response,
             };
 
             let is_const = exec.plan.is_const;
@@ -122,6 +139,55 @@ impl JITExecutor {
         }
     }
 
+    /// Executes the request and streams the response into `sender` as newline
+    /// delimited JSON, one line per element of the root list. The response is
+    /// neither cached nor shared with identical requests in flight.
+    pub async fn execute_lines(&self, request: async_graphql::Request, sender: &mut Sender) {
+        let hash = Self::req_hash(&request);
+        let jit_request = jit::Request::from(request);
+        match self.plan(&hash, &jit_request) {
+            Ok(exec) => {
+                exec.execute_lines(&self.app_ctx, &self.req_ctx, jit_request, sender)
+                    .await
+            }
+            Err(response) => {
+                send_line(sender, response.body.as_ref().clone()).await;
+            }
+        }
+    }
+
+    /// Checks if the operation asks for incremental delivery, i.e. if a field
+    /// of its plan is marked with `@defer` or `@stream`. The plan is cached
+    /// for the execution of the request.
+    pub fn is_incremental(&self, request: &async_graphql::Request) -> bool {
+        let hash = Self::req_hash(request);
+        let jit_request = jit::Request {
+            operation_name: request.operation_name.clone(),
+            ..jit::Request::new(&request.query)
+        };
+        self.plan(&hash, &jit_request)
+            .is_ok_and(|exec| exec.plan.is_incremental())
+    }
+
+    /// Executes the request and delivers the response into `sender`
+    /// incrementally, as the parts of a `multipart/mixed` body. The response
+    /// is neither cached nor shared with identical requests in flight.
+    pub async fn execute_incremental(&self, request: async_graphql::Request, sender: &mut Sender) {
+        let hash = Self::req_hash(&request);
+        let jit_request = jit::Request::from(request);
+        match self.plan(&hash, &jit_request) {
+            Ok(exec) => {
+                exec.execute_incremental(&self.app_ctx, &self.req_ctx, jit_request, sender)
+                    .await
+            }
+            Err(response) => {
+                if send_part(sender, response.body.as_ref().clone()).await {
+                    close_parts(sender).await;
+                }
+            }
+        }
+    }
+
     /// Execute a GraphQL batch query.
     pub async fn execute_batch(&self, batch_request: BatchRequest) -> BatchResponse<Vec<u8>> {
         match batch_request {
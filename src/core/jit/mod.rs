@@ -1,11 +1,12 @@
 mod exec;
+mod field_usage;
 pub mod graphql_error;
 mod model;
 mod store;
 mod synth;
 mod transform;
 
-use builder::*;
+pub use builder::*;
 use store::*;
 mod context;
 mod error;
@@ -20,7 +21,9 @@ mod graphql_executor;
 
 // Public Exports
 pub use error::*;
+pub use exec::MULTIPART_BOUNDARY;
 pub use exec_const::*;
+pub use field_usage::*;
 pub use graphql_executor::*;
 pub use model::*;
 pub use request::*;
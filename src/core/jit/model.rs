@@ -192,6 +192,22 @@ pub struct Field<Input> {
     pub directives: Vec<Directive<Input>>,
     pub is_enum: bool,
     pub scalar: Option<Scalar>,
+    /// Sibling fields whose resolved values are read by the resolver of this
+    /// field, so they have to be executed before it
+    pub depends_on: Vec<FieldId>,
+    /// Internal fields are executed for the siblings that read them but are
+    /// left out of the response
+    pub internal: bool,
+    /// Set on the fields of a fragment marked with `@defer`, which are
+    /// delivered after the initial payload of an incremental response
+    pub defer: bool,
+    /// Set on the fields of lists of objects marked with `@stream`, whose items
+    /// are delivered one by one after the initial payload of an incremental
+    /// response
+    pub stream: bool,
+    /// Set when a sibling with the same resolver and arguments is executed in
+    /// place of this field, sharing its resolved value with it
+    pub resolved_by: Option<FieldId>,
 }
 
 pub struct DFS<'a, Input> {
@@ -262,6 +278,11 @@ impl<Input> Field<Input> {
                 .collect::<Result<_, _>>()?,
             is_enum: self.is_enum,
             scalar: self.scalar,
+            depends_on: self.depends_on,
+            internal: self.internal,
+            defer: self.defer,
+            stream: self.stream,
+            resolved_by: self.resolved_by,
         })
     }
 }
@@ -289,6 +310,21 @@ impl<Input: Debug> Debug for Field<Input> {
         if self.include.is_some() {
             debug_struct.field("include", &self.include);
         }
+        if !self.depends_on.is_empty() {
+            debug_struct.field("depends_on", &self.depends_on);
+        }
+        if self.internal {
+            debug_struct.field("internal", &self.internal);
+        }
+        if self.defer {
+            debug_struct.field("defer", &self.defer);
+        }
+        if self.stream {
+            debug_struct.field("stream", &self.stream);
+        }
+        if self.resolved_by.is_some() {
+            debug_struct.field("resolved_by", &self.resolved_by);
+        }
         debug_struct.field("directives", &self.directives);
 
         debug_struct.finish()
@@ -313,6 +349,9 @@ pub struct OperationPlan<Input> {
     pub is_introspection_query: bool,
     pub is_dedupe: bool,
     pub is_const: bool,
+    /// Set when the operation only selects constants that are resolved
+    /// without running the executor, see `CheckConst`
+    pub is_static: bool,
     pub is_protected: bool,
     pub min_cache_ttl: Option<NonZeroU64>,
     pub selection: Vec<Field<Input>>,
@@ -339,6 +378,7 @@ impl<Input> OperationPlan<Input> {
             is_introspection_query: self.is_introspection_query,
             is_dedupe: self.is_dedupe,
             is_const: self.is_const,
+            is_static: self.is_static,
             is_protected: self.is_protected,
             min_cache_ttl: self.min_cache_ttl,
             before: self.before,
@@ -368,6 +408,7 @@ impl<Input> OperationPlan<Input> {
             is_introspection_query,
             is_dedupe: false,
             is_const: false,
+            is_static: false,
             is_protected: false,
             min_cache_ttl: None,
             before: Default::default(),
@@ -395,6 +436,17 @@ impl<Input> OperationPlan<Input> {
         DFS { stack: vec![self.selection.iter()] }
     }
 
+    /// Check if a field of the plan, at any depth, is marked with `@defer` or
+    /// `@stream`
+    pub fn is_incremental(&self) -> bool {
+        self.iter_dfs().any(|field| field.defer || field.stream)
+    }
+
+    /// Check if a field of the plan, at any depth, has the `id`
+    pub fn contains_field(&self, id: &FieldId) -> bool {
+        self.iter_dfs().any(|field| field.id == *id)
+    }
+
     /// Returns number of fields in plan
     pub fn size(&self) -> usize {
         fn count<A>(field: &Field<A>) -> usize {
@@ -7,6 +7,7 @@ use tailcall_valid::Validator;
 
 use super::{transform, Builder, OperationPlan, Result, Variables};
 use crate::core::blueprint::Blueprint;
+use crate::core::config::ProtectedDenialMode;
 use crate::core::transform::TransformerOps;
 use crate::core::Transform;
 
@@ -47,7 +48,10 @@ impl Request<ConstValue> {
 
         transform::CheckConst::new()
             .pipe(transform::CheckProtected::new())
-            .pipe(transform::AuthPlanner::new())
+            .pipe(
+                transform::AuthPlanner::new()
+                    .when(blueprint.server.protected_denial_mode == ProtectedDenialMode::Error),
+            )
             .pipe(transform::CheckDedupe::new())
             .pipe(transform::CheckCache::new())
             .pipe(transform::GraphQL::new())
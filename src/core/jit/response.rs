@@ -4,7 +4,7 @@ use derive_setters::Setters;
 use serde::Serialize;
 
 use super::graphql_error::GraphQLError;
-use super::Positioned;
+use super::{PathSegment, Positioned};
 use crate::core::async_graphql_hyper::CacheControl;
 use crate::core::jit;
 use crate::core::json::{JsonLike, JsonObjectLike};
@@ -55,6 +55,28 @@ impl<Value: Default> Response<Value> {
     pub fn add_errors(&mut self, new_errors: Vec<Positioned<jit::Error>>) {
         self.errors.extend(new_errors.into_iter().map(|e| e.into()));
     }
+
+    /// Caps the errors of the response, see [`truncate_errors`].
+    pub fn truncate_errors(mut self, max_errors: Option<usize>) -> Self {
+        truncate_errors(&mut self.errors, max_errors);
+        self
+    }
+}
+
+/// Keeps the first `max_errors` errors and replaces the rest with a single
+/// error telling how many were omitted.
+pub fn truncate_errors(errors: &mut Vec<GraphQLError>, max_errors: Option<usize>) {
+    let Some(max_errors) = max_errors else {
+        return;
+    };
+    if errors.len() > max_errors {
+        let omitted = errors.len() - max_errors;
+        errors.truncate(max_errors);
+        errors.push(GraphQLError::new(
+            format!("{omitted} additional errors omitted"),
+            None,
+        ));
+    }
 }
 
 impl<'a, Value> Response<Value>
@@ -134,6 +156,91 @@ impl<V: Serialize> From<Response<V>> for AnyResponse<Vec<u8>> {
     }
 }
 
+impl AnyResponse<Vec<u8>> {
+    /// Builds a response whose `data` is written by `write_data` directly into
+    /// the body. `write_data` returns the field errors raised while writing,
+    /// which are listed before `errors`. The result is identical to
+    /// serializing a [`Response`] with the same data and errors. The errors
+    /// are capped to `max_errors`, see [`truncate_errors`].
+    pub fn from_writer<F>(
+        write_data: F,
+        errors: Vec<Positioned<jit::Error>>,
+        max_errors: Option<usize>,
+    ) -> Self
+    where
+        F: FnOnce(&mut Vec<u8>) -> Result<Vec<Positioned<jit::Error>>, Positioned<jit::Error>>,
+    {
+        const PREFIX: &[u8] = b"{\"data\":";
+
+        let mut body = PREFIX.to_vec();
+        let mut all_errors: Vec<GraphQLError> = Vec::new();
+
+        match write_data(&mut body) {
+            Ok(field_errors) => all_errors.extend(field_errors.into_iter().map(|e| e.into())),
+            Err(error) => {
+                body.truncate(PREFIX.len());
+                body.extend_from_slice(b"null");
+                all_errors.push(error.into());
+            }
+        }
+        all_errors.extend(errors.into_iter().map(|e| e.into()));
+        truncate_errors(&mut all_errors, max_errors);
+
+        if !all_errors.is_empty() {
+            body.extend_from_slice(b",\"errors\":");
+            // serializing into a `Vec` can't fail
+            let _ = serde_json::to_writer(&mut body, &all_errors);
+        }
+        body.push(b'}');
+
+        Self {
+            body: Arc::new(body),
+            cache_control: CacheControl::default(),
+            is_ok: all_errors.is_empty(),
+        }
+    }
+}
+
+/// Data delivered after the initial payload of an incremental response, for
+/// the fields of a fragment marked with `@defer` or an item of a list marked
+/// with `@stream`.
+#[derive(Clone, Serialize, Debug)]
+pub struct Patch<Value> {
+    #[serde(flatten)]
+    pub payload: PatchPayload<Value>,
+    /// Path of the object holding the deferred fields, or of the streamed item
+    pub path: Vec<PathSegment<'static>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<GraphQLError>,
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum PatchPayload<Value> {
+    /// The fields of a deferred fragment, `null` when a non-null one fails.
+    Data(Value),
+    /// The streamed items as a list, `null` when a non-null one fails.
+    Items(Value),
+}
+
+/// A part of an incremental response, following the GraphQL incremental
+/// delivery proposal. `has_next` tells whether more parts follow.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum IncrementalResponse<Value> {
+    Initial {
+        #[serde(flatten)]
+        response: Response<Value>,
+        #[serde(rename = "hasNext")]
+        has_next: bool,
+    },
+    Subsequent {
+        incremental: Vec<Patch<Value>>,
+        #[serde(rename = "hasNext")]
+        has_next: bool,
+    },
+}
+
 pub enum BatchResponse<Body> {
     Single(AnyResponse<Body>),
     Batch(Vec<AnyResponse<Body>>),
@@ -168,7 +275,7 @@ impl<Body> BatchResponse<Body> {
 mod test {
     use async_graphql_value::ConstValue;
 
-    use super::Response;
+    use super::{AnyResponse, Response};
     use crate::core::jit::graphql_error::GraphQLError;
     use crate::core::jit::{self, Pos, Positioned};
 
@@ -215,6 +322,87 @@ mod test {
         insta::assert_debug_snapshot!(response);
     }
 
+    #[test]
+    fn test_from_writer_matches_serialized_response() {
+        let value = ConstValue::String("Tailcall - Modern GraphQL Runtime".into());
+        let expected: AnyResponse<Vec<u8>> = Response::<ConstValue>::new(Ok(value.clone())).into();
+        let actual = AnyResponse::from_writer(
+            |buf| {
+                serde_json::to_writer(buf, &value).unwrap();
+                Ok(vec![])
+            },
+            vec![],
+            None,
+        );
+
+        assert_eq!(actual.body, expected.body);
+        assert!(actual.is_ok);
+    }
+
+    #[test]
+    fn test_from_writer_with_error() {
+        let error = Positioned::new(
+            jit::Error::Validation(jit::ValidationError::ValueRequired),
+            Pos { line: 1, column: 2 },
+        );
+        let expected: AnyResponse<Vec<u8>> = Response::<ConstValue>::new(Err(error.clone())).into();
+        let actual = AnyResponse::from_writer(
+            |buf| {
+                buf.extend_from_slice(b"{\"partial\":");
+                Err(error)
+            },
+            vec![],
+            None,
+        );
+
+        assert_eq!(actual.body, expected.body);
+        assert!(!actual.is_ok);
+    }
+
+    #[test]
+    fn test_from_writer_truncates_errors() {
+        let error = Positioned::new(
+            jit::Error::Validation(jit::ValidationError::ValueRequired),
+            Pos { line: 1, column: 2 },
+        );
+        let actual = AnyResponse::from_writer(
+            |buf| {
+                buf.extend_from_slice(b"null");
+                Ok(vec![error.clone(); 3])
+            },
+            vec![error.clone(); 2],
+            Some(2),
+        );
+        let actual: serde_json::Value = serde_json::from_slice(&actual.body).unwrap();
+        let errors = actual["errors"].as_array().unwrap();
+
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0]["message"], errors[1]["message"]);
+        assert_eq!(errors[2]["message"], "3 additional errors omitted");
+    }
+
+    #[test]
+    fn test_truncate_errors() {
+        let errors = (0..5)
+            .map(|i| GraphQLError::new(format!("Error-{i}"), None))
+            .collect::<Vec<_>>();
+        let response = Response::<ConstValue>::new(Ok(ConstValue::Null)).with_errors(errors);
+
+        let truncated = response.clone().truncate_errors(Some(2));
+        let messages = truncated
+            .errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            ["Error-0", "Error-1", "3 additional errors omitted"]
+        );
+
+        assert_eq!(response.clone().truncate_errors(Some(5)).errors.len(), 5);
+        assert_eq!(response.truncate_errors(None).errors.len(), 5);
+    }
+
     #[test]
     fn test_conversion_to_async_graphql() {
         let error1 = Positioned::new(
@@ -1,6 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::core::jit::model::FieldId;
+#[cfg(feature = "spill-store")]
+pub use self::spill::Budget;
+#[cfg(feature = "spill-store")]
+use self::spill::Spilled;
+use crate::core::jit::model::{FieldId, OperationPlan};
 
 /// Path to the data in the store with info
 /// to resolve nested multiple data
@@ -37,6 +41,13 @@ impl DataPath {
 #[derive(Debug)]
 pub struct Store<Data> {
     data: HashMap<usize, Data>,
+    /// Ids of the fields of the plan in strict mode, data can only be set for
+    /// them.
+    field_ids: Option<HashSet<usize>>,
+    #[cfg(feature = "spill-store")]
+    budget: Option<Budget<Data>>,
+    #[cfg(feature = "spill-store")]
+    spilled: HashMap<usize, Spilled<Data>>,
 }
 
 impl<Data> Default for Store<Data> {
@@ -47,18 +58,259 @@ impl<Data> Default for Store<Data> {
 
 impl<Data> Store<Data> {
     pub fn new() -> Self {
-        Store { data: HashMap::new() }
+        Store {
+            data: HashMap::new(),
+            field_ids: None,
+            #[cfg(feature = "spill-store")]
+            budget: None,
+            #[cfg(feature = "spill-store")]
+            spilled: HashMap::new(),
+        }
+    }
+
+    /// Creates a store in strict mode, that panics when data is set for a
+    /// field that isn't part of `plan`. It catches the ids that went out of
+    /// sync with the plan, that would otherwise resolve to `null`.
+    pub fn strict<Input>(plan: &OperationPlan<Input>) -> Self {
+        let field_ids = plan.iter_dfs().map(|field| field.id.as_usize()).collect();
+        Store { field_ids: Some(field_ids), ..Self::new() }
     }
 
     pub fn set_data(&mut self, field_id: FieldId, data: Data) {
-        self.data.insert(field_id.as_usize(), data);
+        self.check(&field_id);
+        self.insert(field_id.as_usize(), data);
     }
 
     pub fn set(&mut self, field_id: &FieldId, data: Data) {
-        self.data.insert(field_id.as_usize(), data);
+        self.check(field_id);
+        self.insert(field_id.as_usize(), data);
+    }
+
+    fn check(&self, field_id: &FieldId) {
+        if let Some(field_ids) = &self.field_ids {
+            assert!(
+                field_ids.contains(&field_id.as_usize()),
+                "Field {:?} is not part of the operation plan",
+                field_id
+            );
+        }
     }
 
     pub fn get(&self, field_id: &FieldId) -> Option<&Data> {
-        self.data.get(&field_id.as_usize())
+        let id = field_id.as_usize();
+
+        #[cfg(feature = "spill-store")]
+        if let Some(spilled) = self.spilled.get(&id) {
+            return spilled.load();
+        }
+
+        self.data.get(&id)
+    }
+
+    #[cfg(not(feature = "spill-store"))]
+    fn insert(&mut self, id: usize, data: Data) {
+        self.data.insert(id, data);
+    }
+
+    #[cfg(feature = "spill-store")]
+    fn insert(&mut self, id: usize, data: Data) {
+        self.spilled.remove(&id);
+        if let Some(budget) = self.budget.as_mut() {
+            budget.track(id, &data);
+        }
+        self.data.insert(id, data);
+        self.spill();
+    }
+}
+
+#[cfg(feature = "spill-store")]
+impl<Value, Error> Store<Result<Value, Error>>
+where
+    Value: serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// Creates a store that keeps at most `limit` bytes of successfully
+    /// resolved values in memory. Once the limit is exceeded the biggest
+    /// entries are written to temporary files and read back lazily on
+    /// [`Store::get`].
+    pub fn with_budget(limit: usize) -> Self {
+        Store { budget: Some(Budget::new(limit)), ..Self::new() }
+    }
+}
+
+#[cfg(feature = "spill-store")]
+impl<Data> Store<Data> {
+    /// Returns true if the data for the field currently lives on disk.
+    pub fn is_spilled(&self, field_id: &FieldId) -> bool {
+        self.spilled.contains_key(&field_id.as_usize())
+    }
+
+    /// Moves the biggest in-memory entries to disk until the store fits
+    /// within its budget again.
+    fn spill(&mut self) {
+        let Some(budget) = self.budget.as_mut() else {
+            return;
+        };
+
+        while let Some(id) = budget.next_to_spill() {
+            let Some(data) = self.data.get(&id) else {
+                budget.untrack(id);
+                continue;
+            };
+
+            match budget.write(id, data) {
+                Ok(spilled) => {
+                    self.data.remove(&id);
+                    self.spilled.insert(id, spilled);
+                }
+                Err(err) => {
+                    tracing::warn!("Failed to spill store entry to disk: {}", err);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "spill-store")]
+mod spill {
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{self, BufReader, BufWriter, Read, Write};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::OnceLock;
+
+    static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    type Encode<Data> = fn(&Data, &mut dyn Write) -> Option<io::Result<()>>;
+    type Decode<Data> = fn(&mut dyn Read) -> Option<Data>;
+
+    /// Memory budget of a [`super::Store`]. Only the entries that can be
+    /// serialized are accounted for, errors always stay in memory.
+    #[derive(Debug)]
+    pub struct Budget<Data> {
+        limit: usize,
+        used: usize,
+        sizes: HashMap<usize, usize>,
+        encode: Encode<Data>,
+        decode: Decode<Data>,
+    }
+
+    impl<Value, Error> Budget<Result<Value, Error>>
+    where
+        Value: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        pub fn new(limit: usize) -> Self {
+            Self {
+                limit,
+                used: 0,
+                sizes: HashMap::new(),
+                encode: |data, writer| {
+                    let value = data.as_ref().ok()?;
+                    Some(serde_json::to_writer(writer, value).map_err(io::Error::from))
+                },
+                decode: |reader| serde_json::from_reader(reader).ok().map(Ok),
+            }
+        }
+    }
+
+    impl<Data> Budget<Data> {
+        pub fn track(&mut self, id: usize, data: &Data) {
+            self.untrack(id);
+
+            let mut counter = ByteCounter(0);
+            if let Some(Ok(())) = (self.encode)(data, &mut counter) {
+                self.used += counter.0;
+                self.sizes.insert(id, counter.0);
+            }
+        }
+
+        pub fn untrack(&mut self, id: usize) {
+            if let Some(size) = self.sizes.remove(&id) {
+                self.used -= size;
+            }
+        }
+
+        /// The biggest tracked entry if the budget is exceeded.
+        pub fn next_to_spill(&self) -> Option<usize> {
+            if self.used <= self.limit {
+                return None;
+            }
+
+            self.sizes
+                .iter()
+                .max_by_key(|(_, size)| **size)
+                .map(|(id, _)| *id)
+        }
+
+        pub fn write(&mut self, id: usize, data: &Data) -> io::Result<Spilled<Data>> {
+            let path = std::env::temp_dir().join(format!(
+                "tailcall-store-{}-{}.json",
+                std::process::id(),
+                SPILL_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+
+            let mut writer = BufWriter::new(File::create(&path)?);
+            let result = (self.encode)(data, &mut writer)
+                .unwrap_or_else(|| Err(io::Error::other("entry can't be serialized")))
+                .and_then(|_| writer.flush());
+
+            if let Err(err) = result {
+                let _ = std::fs::remove_file(&path);
+                return Err(err);
+            }
+
+            self.untrack(id);
+
+            Ok(Spilled { path, decode: self.decode, data: OnceLock::new() })
+        }
+    }
+
+    /// An entry of the store that has been written to a temporary file. The
+    /// file is streamed back the first time the entry is read and removed
+    /// once the store is dropped.
+    #[derive(Debug)]
+    pub struct Spilled<Data> {
+        path: PathBuf,
+        decode: Decode<Data>,
+        data: OnceLock<Option<Data>>,
+    }
+
+    impl<Data> Spilled<Data> {
+        pub fn load(&self) -> Option<&Data> {
+            self.data
+                .get_or_init(|| {
+                    let data = File::open(&self.path)
+                        .ok()
+                        .and_then(|file| (self.decode)(&mut BufReader::new(file)));
+
+                    if data.is_none() {
+                        tracing::warn!("Failed to read spilled store entry: {:?}", self.path);
+                    }
+
+                    data
+                })
+                .as_ref()
+        }
+    }
+
+    impl<Data> Drop for Spilled<Data> {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    /// Writer that only counts the bytes written to it.
+    struct ByteCounter(usize);
+
+    impl Write for ByteCounter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
     }
 }
@@ -1,9 +1,18 @@
 use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+use std::marker::PhantomData;
+use std::sync::Mutex;
 
-use crate::core::jit::model::{Field, OperationPlan, Variables};
+use serde::Serialize;
+
+use crate::core::config::transformer::UNKNOWN_FIELDS_FIELD_NAME;
+use crate::core::config::{ScalarCoercion, UnknownFields};
+use crate::core::ir;
+use crate::core::jit::model::{Field, FieldId, OperationPlan, Variables};
 use crate::core::jit::store::{DataPath, Store};
-use crate::core::jit::{Error, PathSegment, Positioned, ValidationError};
-use crate::core::json::{JsonLike, JsonObjectLike};
+use crate::core::jit::{Error, Patch, PatchPayload, PathSegment, Positioned, ValidationError};
+use crate::core::json::{JsonLike, JsonObjectLike, JsonPrimitive};
+use crate::core::scalar::{Coerced, Scalar};
 
 type ValueStore<Value> = Store<Result<Value, Positioned<Error>>>;
 
@@ -11,6 +20,16 @@ pub struct Synth<'a, Value> {
     plan: &'a OperationPlan<Value>,
     store: ValueStore<Value>,
     variables: Variables<Value>,
+    unwrap_root: bool,
+    omit_nulls: bool,
+    unknown_fields: UnknownFields,
+    lenient_dates: bool,
+    scalar_coercion: ScalarCoercion,
+    max_response_bytes: Option<usize>,
+    incremental: bool,
+    masked: Mutex<Vec<Positioned<Error>>>,
+    /// Fields whose values were coerced, to warn about each of them once.
+    coerced: Mutex<HashSet<FieldId>>,
 }
 
 impl<'a, Value> Synth<'a, Value> {
@@ -20,17 +39,175 @@ impl<'a, Value> Synth<'a, Value> {
         store: ValueStore<Value>,
         variables: Variables<Value>,
     ) -> Self {
-        Self { plan, store, variables }
+        Self {
+            plan,
+            store,
+            variables,
+            unwrap_root: false,
+            omit_nulls: false,
+            unknown_fields: UnknownFields::default(),
+            lenient_dates: false,
+            scalar_coercion: ScalarCoercion::default(),
+            max_response_bytes: None,
+            incremental: false,
+            masked: Mutex::new(Vec::new()),
+            coerced: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Synthesizes the bare value of the root field, instead of an object
+    /// keyed by its name, when the operation selects exactly one root field.
+    pub fn unwrap_root(mut self, unwrap_root: bool) -> Self {
+        self.unwrap_root = unwrap_root;
+        self
+    }
+
+    /// Omits the keys of the nullable fields that resolve to `null`, instead
+    /// of emitting them with a `null` value. Lets a response tell an absent
+    /// field apart from an explicit `null`.
+    pub fn omit_nulls(mut self, omit_nulls: bool) -> Self {
+        self.omit_nulls = omit_nulls;
+        self
+    }
+
+    /// Sets how the keys of upstream objects that aren't fields of their type
+    /// are handled. They are dropped by default.
+    pub fn unknown_fields(mut self, unknown_fields: UnknownFields) -> Self {
+        self.unknown_fields = unknown_fields;
+        self
+    }
+
+    /// Accepts the `Date` and `DateTime` values written in other common
+    /// formats than RFC 3339, normalizing them to RFC 3339.
+    pub fn lenient_dates(mut self, lenient_dates: bool) -> Self {
+        self.lenient_dates = lenient_dates;
+        self
+    }
+
+    /// Sets whether the values that aren't valid for their scalar are coerced
+    /// when possible, instead of failing the field.
+    pub fn scalar_coercion(mut self, scalar_coercion: ScalarCoercion) -> Self {
+        self.scalar_coercion = scalar_coercion;
+        self
+    }
+
+    /// Fails the writing of the response once it grows past
+    /// `max_response_bytes`, instead of buffering it whole.
+    pub fn max_response_bytes(mut self, max_response_bytes: Option<usize>) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    /// Leaves the fields of the fragments marked with `@defer` and the items of
+    /// the lists marked with `@stream` out of the synthesized data, for
+    /// [`Synth::synthesize_stream`] to deliver them in patches.
+    pub fn incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    /// Takes the errors of the fields that were masked with `null` while
+    /// synthesizing, because they were denied by `@protected`.
+    pub fn masked_errors(&self) -> Vec<Positioned<Error>> {
+        std::mem::take(&mut *self.masked.lock().unwrap())
+    }
+
+    /// Gives back the store the data is synthesized from.
+    pub fn into_store(self) -> ValueStore<Value> {
+        self.store
     }
 }
 
+fn is_denial(error: &Error) -> bool {
+    matches!(error, Error::IR(ir::Error::Auth(_)))
+}
+
 impl<'a, Value> Synth<'a, Value>
 where
     Value: JsonLike<'a> + Clone + std::fmt::Debug,
 {
     #[inline(always)]
     fn include(&self, field: &Field<Value>) -> bool {
-        !field.skip(&self.variables)
+        !field.internal && !field.skip(&self.variables)
+    }
+
+    /// Checks if the field is left out of the data, to be delivered in a
+    /// patch of its own.
+    #[inline(always)]
+    fn is_deferred(&self, field: &Field<Value>) -> bool {
+        self.incremental && field.defer
+    }
+
+    /// Checks if the items of the list resolved for `node` at `path` are left
+    /// out of the data, to be delivered in patches of their own. Only the
+    /// outermost list of a field is streamed.
+    #[inline(always)]
+    fn is_streamed(&self, node: &Field<Value>, path: &[PathSegment]) -> bool {
+        self.incremental && node.stream && matches!(path.last(), Some(PathSegment::Field(_)))
+    }
+
+    /// Normalizes a value that isn't valid for its scalar, when the scalar
+    /// allows it.
+    fn normalize(&self, scalar: &Scalar, value: &'a Value) -> Option<String> {
+        if self.lenient_dates {
+            scalar.normalize(value)
+        } else {
+            None
+        }
+    }
+
+    /// Coerces a value that isn't valid for its scalar in the lenient mode,
+    /// with a warning so that the upstream can be fixed. The warning is logged
+    /// once per field and leaves the value out, as it may be sensitive.
+    fn coerce(&self, scalar: &Scalar, node: &Field<Value>, value: &'a Value) -> Option<Coerced> {
+        if self.scalar_coercion != ScalarCoercion::Lenient {
+            return None;
+        }
+
+        let coerced = match scalar {
+            Scalar::Empty => Scalar::coerce_builtin(node.type_of.name(), value)?,
+            scalar => scalar.coerce(value)?,
+        };
+        if self.coerced.lock().unwrap().insert(node.id.clone()) {
+            tracing::warn!(
+                "Coerced a value of the field {} to {}",
+                node.output_name,
+                node.type_of.name()
+            );
+        }
+        Some(coerced)
+    }
+
+    /// Collects the keys of an upstream object that aren't fields of its type
+    /// in the schema. Nothing is collected when they are dropped, and the
+    /// first one fails when they aren't allowed.
+    fn unknown_keys(
+        &self,
+        node: &'a Field<Value>,
+        value: &'a Value,
+        obj: &'a Value::JsonObject,
+    ) -> Result<Vec<(&'a str, &'a Value)>, Error> {
+        if self.unknown_fields == UnknownFields::Drop {
+            return Ok(Vec::new());
+        }
+
+        let type_name = node.value_type(value);
+        let mut unknown = obj.iter().filter(|(key, _)| {
+            *key != "__typename" && self.plan.index.get_field(type_name, key).is_none()
+        });
+
+        match self.unknown_fields {
+            UnknownFields::Drop => Ok(Vec::new()),
+            UnknownFields::Error => match unknown.next() {
+                Some((key, _)) => Err(ValidationError::UnknownField {
+                    type_of: type_name.to_string(),
+                    field: key.to_string(),
+                }
+                .into()),
+                None => Ok(Vec::new()),
+            },
+            UnknownFields::Passthrough => Ok(unknown.collect()),
+        }
     }
 
     #[inline(always)]
@@ -38,105 +215,166 @@ where
     where
         Output: JsonLike<'a>,
     {
-        let mut data = Output::JsonObject::with_capacity(self.plan.selection.len());
+        self.walk_root(&mut ValueBuilder::new())
+    }
+
+    /// Emits the object of the root fields of the operation.
+    #[inline(always)]
+    fn walk_root<E>(&'a self, out: &mut E) -> Result<E::Output, Positioned<Error>>
+    where
+        E: Emitter<'a, Value>,
+    {
         let mut path = Vec::new();
         let root_name = self.plan.root_name();
 
+        if self.unwrap_root {
+            let mut roots = self
+                .plan
+                .selection
+                .iter()
+                .filter(|child| self.include(child) && !self.is_deferred(child));
+            if let (Some(child), None) = (roots.next(), roots.next()) {
+                return self.walk(
+                    out,
+                    child,
+                    None,
+                    &DataPath::new(),
+                    &mut path,
+                    Some(root_name),
+                );
+            }
+        }
+
+        let mut data = out.start_object(self.plan.selection.len());
+
         for child in self.plan.selection.iter() {
-            if !self.include(child) {
+            if !self.include(child) || self.is_deferred(child) {
                 continue;
             }
             // TODO: in case of error set `child.output_name` to null
             // and append error to response error array
-            let val = self.iter(child, None, &DataPath::new(), &mut path, Some(root_name))?;
-            data.insert_key(&child.output_name, val);
+            self.emit_field(out, &mut data, &child.output_name, |out| {
+                self.walk(
+                    out,
+                    child,
+                    None,
+                    &DataPath::new(),
+                    &mut path,
+                    Some(root_name),
+                )
+            })?;
         }
 
-        Ok(Output::object(data))
+        Ok(out.end_object(data))
+    }
+
+    /// Emits the field `key` of `object`, leaving it out when it's `null` and
+    /// nulls are omitted.
+    #[inline(always)]
+    fn emit_field<E>(
+        &self,
+        out: &mut E,
+        object: &mut E::Object,
+        key: &'a str,
+        value: impl FnOnce(&mut E) -> Result<E::Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>>
+    where
+        E: Emitter<'a, Value>,
+    {
+        if self.omit_nulls {
+            out.field_unless_null(object, key, value)
+        } else {
+            out.field(object, key, value)
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
     #[inline(always)]
-    fn iter<Output>(
+    fn walk<E>(
         &'a self,
+        out: &mut E,
         node: &'a Field<Value>,
         value: Option<&'a Value>,
         data_path: &DataPath,
         path: &mut Vec<PathSegment<'a>>,
         root_name: Option<&'a str>,
-    ) -> Result<Output, Positioned<Error>>
+    ) -> Result<E::Output, Positioned<Error>>
     where
-        Output: JsonLike<'a>,
+        E: Emitter<'a, Value>,
     {
         path.push(PathSegment::Field(Cow::Borrowed(&node.output_name)));
+        let mark = out.mark();
 
         let result = match self.store.get(&node.id) {
-            Some(value) => {
-                let mut value = value.as_ref().map_err(Clone::clone)?;
-
-                for index in data_path.as_slice() {
-                    if let Some(arr) = value.as_array() {
-                        value = &arr[*index];
-                    } else {
-                        return Ok(Output::null());
-                    }
+            Some(Err(error)) => Err(self.locate_denial(error, node, path)),
+            Some(Ok(value)) => match select(value, data_path) {
+                Some(value) if node.type_of.is_list() == value.as_array().is_some() => {
+                    self.walk_inner(out, node, value, data_path, path)
                 }
-
-                if node.type_of.is_list() != value.as_array().is_some() {
-                    return self.node_nullable_guard(node, path, None);
-                }
-                self.iter_inner(node, value, data_path, path)
-            }
+                Some(_) => self.node_nullable_guard(out, node, path, None),
+                None => Ok(out.null()),
+            },
             None => match value {
-                Some(result) => self.iter_inner(node, result, data_path, path),
-                None => self.node_nullable_guard(node, path, root_name),
+                Some(result) => self.walk_inner(out, node, result, data_path, path),
+                None => self.node_nullable_guard(out, node, path, root_name),
             },
         };
 
+        let result = match result {
+            Err(error) if self.is_masked(node, &error) => {
+                // drop whatever was emitted for the field before the denial
+                out.reset(mark);
+                self.masked.lock().unwrap().push(error);
+                Ok(out.null())
+            }
+            result => result,
+        };
+
         path.pop();
         result
     }
 
     /// This guard ensures to return Null value only if node type permits it, in
     /// case it does not it throws an Error
-    fn node_nullable_guard<Output>(
+    fn node_nullable_guard<E>(
         &'a self,
+        out: &mut E,
         node: &'a Field<Value>,
         path: &[PathSegment],
         root_name: Option<&'a str>,
-    ) -> Result<Output, Positioned<Error>>
+    ) -> Result<E::Output, Positioned<Error>>
     where
-        Output: JsonLike<'a>,
+        E: Emitter<'a, Value>,
     {
         if let Some(root_name) = root_name {
             if node.name.eq("__typename") {
-                return Ok(Output::string(Cow::Borrowed(root_name)));
+                return Ok(out.string(Cow::Borrowed(root_name)));
             }
         }
         // according to GraphQL spec https://spec.graphql.org/October2021/#sec-Handling-Field-Errors
         if node.type_of.is_nullable() {
-            Ok(Output::null())
+            Ok(out.null())
         } else {
             Err(ValidationError::ValueRequired.into())
                 .map_err(|e| self.to_location_error(e, node, path))
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
     #[inline(always)]
-    fn iter_inner<Output>(
+    fn walk_inner<E>(
         &'a self,
+        out: &mut E,
         node: &'a Field<Value>,
         value: &'a Value,
         data_path: &DataPath,
         path: &mut Vec<PathSegment<'a>>,
-    ) -> Result<Output, Positioned<Error>>
+    ) -> Result<E::Output, Positioned<Error>>
     where
-        Output: JsonLike<'a>,
+        E: Emitter<'a, Value>,
     {
         // skip the field if field is not included in schema
         if !self.include(node) {
-            return Ok(Output::null());
+            return Ok(out.null());
         }
 
         let eval_result = if value.is_null() {
@@ -146,18 +384,32 @@ where
                 crate::core::Type::List { of_type, .. } => of_type.is_nullable(),
             };
             if is_nullable {
-                Ok(Output::null())
+                Ok(out.null())
             } else {
                 Err(ValidationError::ValueRequired.into())
             }
-        } else if node.scalar.is_some() {
-            let scalar = node.scalar.as_ref().unwrap();
+        } else if let Some(scalar) = node.scalar.as_ref() {
+            // every value is valid for the built-in scalars, so the ones that
+            // don't have their type are coerced before validating
+            let builtin = match scalar {
+                Scalar::Empty => self.coerce(scalar, node, value),
+                _ => None,
+            };
 
             // TODO: add validation for input type as well. But input types are not checked
             // by async_graphql anyway so it should be done after replacing
             // default engine with JIT
-            if scalar.validate(value) {
-                Ok(Output::clone_from(value))
+            if let Some(value) = builtin {
+                Ok(out.coerced(value))
+            } else if scalar.validate(value) {
+                Ok(match scalar.stringify(value) {
+                    Some(value) => out.string(Cow::Owned(value)),
+                    None => out.value(value),
+                })
+            } else if let Some(value) = self.normalize(scalar, value) {
+                Ok(out.string(Cow::Owned(value)))
+            } else if let Some(value) = self.coerce(scalar, node, value) {
+                Ok(out.coerced(value))
             } else {
                 Err(
                     ValidationError::ScalarInvalid { type_of: node.type_of.name().to_string() }
@@ -179,7 +431,7 @@ where
             };
 
             if is_valid_enum {
-                Ok(Output::clone_from(value))
+                Ok(out.value(value))
             } else {
                 Err(
                     ValidationError::EnumInvalid { type_of: node.type_of.name().to_string() }
@@ -189,43 +441,63 @@ where
         } else {
             match (value.as_array(), value.as_object()) {
                 (_, Some(obj)) => {
-                    let mut fields = Vec::with_capacity(node.selection.len());
+                    let unknown = match self.unknown_keys(node, value, obj) {
+                        Ok(unknown) => unknown,
+                        Err(error) => return Err(self.to_location_error(error, node, path)),
+                    };
+                    let mut fields = out.start_object(node.selection.len());
 
                     for child in node
                         .iter()
                         .filter(|field| self.plan.field_is_part_of_value(field, value))
                     {
-                        // all checks for skip must occur in `iter_inner`
-                        // and include be checked before calling `iter` or recursing.
-                        if self.include(child) {
-                            let value = if child.name == "__typename" {
-                                Output::string(node.value_type(value).into())
-                            } else {
-                                let val = obj.get_key(child.name.as_str());
-                                self.iter(child, val, data_path, path, None)?
-                            };
-                            fields.push((child.output_name.as_str(), value));
+                        // all checks for skip must occur in `walk_inner`
+                        // and include be checked before calling `walk` or recursing.
+                        if self.include(child) && !self.is_deferred(child) {
+                            self.emit_field(out, &mut fields, &child.output_name, |out| {
+                                if child.name == "__typename" {
+                                    Ok(out.string(Cow::Borrowed(node.value_type(value))))
+                                } else if child.name == UNKNOWN_FIELDS_FIELD_NAME {
+                                    let mut unknown_fields = out.start_object(unknown.len());
+                                    for &(key, value) in unknown.iter() {
+                                        out.field(&mut unknown_fields, key, |out| {
+                                            Ok(out.value(value))
+                                        })?;
+                                    }
+                                    Ok(out.end_object(unknown_fields))
+                                } else {
+                                    let val = obj.get_key(child.name.as_str());
+                                    self.walk(out, child, val, data_path, path, None)
+                                }
+                            })?;
                         }
                     }
 
-                    Ok(Output::object(Output::JsonObject::from_vec(fields)))
+                    Ok(out.end_object(fields))
+                }
+                (Some(_), _) if self.is_streamed(node, path) => {
+                    let items = out.start_list(0);
+                    Ok(out.end_list(items))
                 }
                 (Some(arr), _) => {
-                    let mut ans = Vec::with_capacity(arr.len());
+                    let mut items = out.start_list(arr.len());
                     for (i, val) in arr.iter().enumerate() {
                         path.push(PathSegment::Index(i));
-                        let val =
-                            self.iter_inner(node, val, &data_path.clone().with_index(i), path)?;
+                        let result = out.item(&mut items, |out| {
+                            self.walk_inner(out, node, val, &data_path.clone().with_index(i), path)
+                        });
                         path.pop();
-                        ans.push(val);
+                        result?;
                     }
-                    Ok(Output::array(ans))
+                    Ok(out.end_list(items))
                 }
-                _ => Ok(Output::clone_from(value)),
+                _ => Ok(out.value(value)),
             }
         };
 
-        eval_result.map_err(|e| self.to_location_error(e, node, path))
+        eval_result
+            .and_then(|output| out.check().map(|_| output))
+            .map_err(|e| self.to_location_error(e, node, path))
     }
 
     fn to_location_error(
@@ -234,17 +506,728 @@ where
         node: &'a Field<Value>,
         path: &[PathSegment],
     ) -> Positioned<Error> {
-        Positioned::new(error, node.pos).with_path(
-            path.iter()
-                .map(|x| match x {
-                    PathSegment::Field(cow) => {
-                        PathSegment::Field(Cow::Owned(cow.clone().into_owned()))
+        Positioned::new(error, node.pos).with_path(to_owned_path(path))
+    }
+
+    /// Attaches the location of `node` to the errors of denied `@protected`
+    /// fields, leaving other errors of the store untouched.
+    fn locate_denial(
+        &'a self,
+        error: &Positioned<Error>,
+        node: &'a Field<Value>,
+        path: &[PathSegment],
+    ) -> Positioned<Error> {
+        if is_denial(&error.value) {
+            self.to_location_error(error.value.clone(), node, path)
+        } else {
+            error.clone()
+        }
+    }
+
+    /// Denials of `@protected` fields only reach the synth when the plan
+    /// keeps the checks on the fields, i.e. in the `null` denial mode. They
+    /// are masked by the closest nullable field, like any field error.
+    fn is_masked(&self, node: &Field<Value>, error: &Positioned<Error>) -> bool {
+        node.type_of.is_nullable() && is_denial(&error.value)
+    }
+}
+
+/// Data left out of the synthesized payload, to be delivered in a patch.
+enum Pending<'a, Value> {
+    /// The deferred fields of the object `value` of `parent`, both `None` for
+    /// the fields of the root.
+    Defer {
+        nodes: Vec<&'a Field<Value>>,
+        parent: Option<&'a Field<Value>>,
+        value: Option<&'a Value>,
+        data_path: DataPath,
+        path: Vec<PathSegment<'a>>,
+    },
+    /// An item of the list resolved for a streamed field.
+    Item {
+        node: &'a Field<Value>,
+        item: &'a Value,
+        data_path: DataPath,
+        path: Vec<PathSegment<'a>>,
+    },
+}
+
+impl<'a, Value> Synth<'a, Value>
+where
+    Value: JsonLike<'a> + Clone + std::fmt::Debug,
+{
+    /// Synthesizes the initial payload of an incremental response, along with
+    /// the patches delivering the data left out of it in the
+    /// [`Synth::incremental`] mode. The patches are synthesized lazily, each
+    /// one after the patches of the data it's nested in. Nothing follows an
+    /// initial payload that fails.
+    pub fn synthesize_stream<Output>(
+        &'a self,
+    ) -> (
+        Result<Output, Positioned<Error>>,
+        Patches<'a, Value, Output>,
+    )
+    where
+        Output: JsonLike<'a>,
+    {
+        let data = self.synthesize();
+        let patches = if data.is_ok() {
+            self.patches()
+        } else {
+            Patches { synth: self, pending: VecDeque::new(), output: PhantomData }
+        };
+
+        (data, patches)
+    }
+
+    /// Synthesizes the patches of [`Synth::synthesize_stream`] alone, for a
+    /// store in which the data left out of the initial payload is resolved
+    /// after it's sent.
+    pub fn patches<Output>(&'a self) -> Patches<'a, Value, Output>
+    where
+        Output: JsonLike<'a>,
+    {
+        let mut pending = VecDeque::new();
+        self.collect_fields(
+            None,
+            None,
+            self.plan.selection.iter(),
+            &DataPath::new(),
+            &mut Vec::new(),
+            &mut pending,
+        );
+
+        Patches { synth: self, pending, output: PhantomData }
+    }
+
+    /// Finds the value of `node` like [`Synth::walk`] does, in the store or in
+    /// the `value` of its parent.
+    fn resolve(
+        &'a self,
+        node: &'a Field<Value>,
+        value: Option<&'a Value>,
+        data_path: &DataPath,
+    ) -> Option<&'a Value> {
+        match self.store.get(&node.id) {
+            Some(Ok(value)) => select(value, data_path),
+            Some(Err(_)) => None,
+            None => value,
+        }
+    }
+
+    /// Collects the data left out of the payload among the `fields` of the
+    /// object `value` of `parent`, recursing into the fields that are part of
+    /// the payload.
+    fn collect_fields(
+        &'a self,
+        parent: Option<&'a Field<Value>>,
+        value: Option<&'a Value>,
+        fields: impl Iterator<Item = &'a Field<Value>>,
+        data_path: &DataPath,
+        path: &mut Vec<PathSegment<'a>>,
+        pending: &mut VecDeque<Pending<'a, Value>>,
+    ) {
+        let obj = value.and_then(|value| value.as_object());
+        let mut deferred = Vec::new();
+
+        for child in fields.filter(|child| self.include(child)) {
+            if self.is_deferred(child) {
+                deferred.push(child);
+            } else {
+                let value = obj.and_then(|obj| obj.get_key(child.name.as_str()));
+                self.collect_field(child, value, data_path, path, pending);
+            }
+        }
+
+        if !deferred.is_empty() {
+            pending.push_back(Pending::Defer {
+                nodes: deferred,
+                parent,
+                value,
+                data_path: data_path.clone(),
+                path: path.clone(),
+            });
+        }
+    }
+
+    /// Collects the data left out of the payload under `node`, whose value in
+    /// its parent object is `value`.
+    fn collect_field(
+        &'a self,
+        node: &'a Field<Value>,
+        value: Option<&'a Value>,
+        data_path: &DataPath,
+        path: &mut Vec<PathSegment<'a>>,
+        pending: &mut VecDeque<Pending<'a, Value>>,
+    ) {
+        let stream = self.incremental && node.stream;
+        if node.selection.is_empty() && !stream {
+            return;
+        }
+        let Some(value) = self.resolve(node, value, data_path) else {
+            return;
+        };
+
+        path.push(PathSegment::Field(Cow::Borrowed(&node.output_name)));
+        match value.as_array() {
+            Some(items) if stream => {
+                for (i, item) in items.iter().enumerate() {
+                    path.push(PathSegment::Index(i));
+                    pending.push_back(Pending::Item {
+                        node,
+                        item,
+                        data_path: data_path.clone().with_index(i),
+                        path: path.clone(),
+                    });
+                    path.pop();
+                }
+            }
+            _ => self.collect_value(node, value, data_path, path, pending),
+        }
+        path.pop();
+    }
+
+    /// Collects the data left out of the payload under `value`, resolved for
+    /// `node`.
+    fn collect_value(
+        &'a self,
+        node: &'a Field<Value>,
+        value: &'a Value,
+        data_path: &DataPath,
+        path: &mut Vec<PathSegment<'a>>,
+        pending: &mut VecDeque<Pending<'a, Value>>,
+    ) {
+        if let Some(items) = value.as_array() {
+            for (i, item) in items.iter().enumerate() {
+                path.push(PathSegment::Index(i));
+                let data_path = data_path.clone().with_index(i);
+                self.collect_value(node, item, &data_path, path, pending);
+                path.pop();
+            }
+        } else if value.as_object().is_some() {
+            let fields = node
+                .iter()
+                .filter(|field| self.plan.field_is_part_of_value(field, value));
+            self.collect_fields(Some(node), Some(value), fields, data_path, path, pending);
+        }
+    }
+
+    /// Synthesizes the patch of `pending`, queueing the data left out of it.
+    /// The data nested in a patch that fails is dropped along with it.
+    fn patch<Output>(
+        &'a self,
+        pending: Pending<'a, Value>,
+        queue: &mut VecDeque<Pending<'a, Value>>,
+    ) -> Patch<Output>
+    where
+        Output: JsonLike<'a>,
+    {
+        let mut nested = VecDeque::new();
+        let (result, path) = match pending {
+            Pending::Defer { nodes, parent, value, data_path, mut path } => {
+                let obj = value.and_then(|value| value.as_object());
+                let root_name = parent.is_none().then(|| self.plan.root_name());
+                let mut fields = Vec::with_capacity(nodes.len());
+                let mut result = Ok(());
+
+                for node in nodes.iter().copied() {
+                    let field = obj.and_then(|obj| obj.get_key(node.name.as_str()));
+                    let field = match (parent, value) {
+                        (Some(parent), Some(value)) if node.name == "__typename" => {
+                            Ok(Output::string(parent.value_type(value).into()))
+                        }
+                        _ => self.walk(
+                            &mut ValueBuilder::new(),
+                            node,
+                            field,
+                            &data_path,
+                            &mut path,
+                            root_name,
+                        ),
+                    };
+                    match field {
+                        Ok(field) if self.omit_nulls && field.is_null() => {}
+                        Ok(field) => fields.push((node.output_name.as_str(), field)),
+                        Err(error) => {
+                            result = Err(error);
+                            break;
+                        }
                     }
-                    PathSegment::Index(i) => PathSegment::Index(*i),
-                })
-                .collect(),
-        )
+                }
+                if result.is_ok() {
+                    for node in nodes {
+                        let field = obj.and_then(|obj| obj.get_key(node.name.as_str()));
+                        self.collect_field(node, field, &data_path, &mut path, &mut nested);
+                    }
+                }
+
+                let result = result
+                    .map(|_| {
+                        PatchPayload::Data(Output::object(Output::JsonObject::from_vec(fields)))
+                    })
+                    .map_err(|error| (error, PatchPayload::Data(Output::null())));
+                (result, path)
+            }
+            Pending::Item { node, item, data_path, mut path } => {
+                let result = self.walk_inner(
+                    &mut ValueBuilder::<Output>::new(),
+                    node,
+                    item,
+                    &data_path,
+                    &mut path,
+                );
+                if result.is_ok() {
+                    self.collect_value(node, item, &data_path, &mut path, &mut nested);
+                }
+
+                let result = result
+                    .map(|item| PatchPayload::Items(Output::array(vec![item])))
+                    .map_err(|error| (error, PatchPayload::Items(Output::null())));
+                (result, path)
+            }
+        };
+
+        let mut errors = Vec::new();
+        let payload = match result {
+            Ok(payload) => {
+                queue.append(&mut nested);
+                payload
+            }
+            Err((error, payload)) => {
+                errors.push(error.into());
+                payload
+            }
+        };
+        errors.extend(self.masked_errors().into_iter().map(|error| error.into()));
+
+        Patch { payload, path: to_owned_path(&path), errors }
+    }
+}
+
+impl<'a, Value> Synth<'a, Value>
+where
+    Value: JsonLike<'a> + Clone + std::fmt::Debug + Serialize,
+{
+    /// Writes the synthesized data as JSON directly into `buf` while walking
+    /// the plan, without building an intermediate value tree. Produces the
+    /// same bytes as serializing the result of [`Synth::synthesize`]. On
+    /// error `buf` may contain partially written data.
+    #[inline(always)]
+    pub fn synthesize_to(&'a self, buf: &mut Vec<u8>) -> Result<(), Positioned<Error>> {
+        self.walk_root(&mut JsonWriter::new(buf, self.max_response_bytes))
+    }
+
+    /// Writes every element of the list resolved by the root field as its own
+    /// JSON document, one per item of the returned iterator, instead of a
+    /// single document with the whole data. Returns `None` unless the
+    /// operation selects exactly one root field that resolved to a list.
+    pub fn lines(&'a self) -> Option<Lines<'a, Value>> {
+        let mut roots = self
+            .plan
+            .selection
+            .iter()
+            .filter(|child| self.include(child));
+        let (Some(node), None) = (roots.next(), roots.next()) else {
+            return None;
+        };
+        if !node.type_of.is_list() {
+            return None;
+        }
+        let Some(Ok(value)) = self.store.get(&node.id) else {
+            return None;
+        };
+        let items = value.as_array()?;
+
+        Some(Lines { synth: self, node, items, index: 0, written: 0, done: false })
+    }
+}
+
+/// Iterator over the elements of a root list written as JSON documents,
+/// created by [`Synth::lines`]. Ends after the first element that fails.
+pub struct Lines<'a, Value> {
+    synth: &'a Synth<'a, Value>,
+    node: &'a Field<Value>,
+    items: &'a [Value],
+    index: usize,
+    /// Bytes of the elements written so far.
+    written: usize,
+    done: bool,
+}
+
+impl<'a, Value> Iterator for Lines<'a, Value>
+where
+    Value: JsonLike<'a> + Clone + std::fmt::Debug + Serialize,
+{
+    type Item = Result<Vec<u8>, Positioned<Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.index >= self.items.len() {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+
+        let mut buf = Vec::new();
+        let mut path = vec![
+            PathSegment::Field(Cow::Borrowed(&self.node.output_name)),
+            PathSegment::Index(index),
+        ];
+        // the limit applies to all the elements, so the bytes already sent
+        // count towards it
+        let mut out = JsonWriter::new(&mut buf, self.synth.max_response_bytes).offset(self.written);
+        let result = self.synth.walk_inner(
+            &mut out,
+            self.node,
+            &self.items[index],
+            &DataPath::new().with_index(index),
+            &mut path,
+        );
+        self.written += buf.len();
+        self.done = result.is_err();
+
+        Some(result.map(|_| buf))
+    }
+}
+
+/// Iterator over the patches of an incremental response, created by
+/// [`Synth::synthesize_stream`].
+pub struct Patches<'a, Value, Output> {
+    synth: &'a Synth<'a, Value>,
+    pending: VecDeque<Pending<'a, Value>>,
+    output: PhantomData<Output>,
+}
+
+impl<Value, Output> Patches<'_, Value, Output> {
+    /// Checks if no patch follows the initial payload.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl<'a, Value, Output> Iterator for Patches<'a, Value, Output>
+where
+    Value: JsonLike<'a> + Clone + std::fmt::Debug,
+    Output: JsonLike<'a>,
+{
+    type Item = Patch<Output>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pending = self.pending.pop_front()?;
+        Some(self.synth.patch(pending, &mut self.pending))
+    }
+}
+
+/// Finds the item of a value resolved for a list of parents, at the indices of
+/// `data_path`.
+fn select<'a, Value: JsonLike<'a>>(value: &'a Value, data_path: &DataPath) -> Option<&'a Value> {
+    data_path
+        .as_slice()
+        .iter()
+        .try_fold(value, |value, index| value.as_array()?.get(*index))
+}
+
+/// Produces the output of [`Synth`] as it walks the plan, so that building a
+/// value and writing JSON bytes share the same traversal.
+trait Emitter<'a, Value> {
+    /// What is produced for a value: the value itself, or nothing when it's
+    /// written out.
+    type Output;
+    /// An object being emitted.
+    type Object;
+    /// A list being emitted.
+    type List;
+
+    fn null(&mut self) -> Self::Output;
+    fn string(&mut self, value: Cow<'a, str>) -> Self::Output;
+    fn coerced(&mut self, value: Coerced) -> Self::Output;
+    /// Emits a value of the store as is.
+    fn value(&mut self, value: &'a Value) -> Self::Output;
+
+    fn start_object(&mut self, len: usize) -> Self::Object;
+    /// Emits the field `key` of `object`, whose value is emitted by `value`.
+    fn field(
+        &mut self,
+        object: &mut Self::Object,
+        key: &'a str,
+        value: impl FnOnce(&mut Self) -> Result<Self::Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>>;
+    /// Emits the field `key` of `object` like [`Emitter::field`], but leaves
+    /// it out when its value is `null`.
+    fn field_unless_null(
+        &mut self,
+        object: &mut Self::Object,
+        key: &'a str,
+        value: impl FnOnce(&mut Self) -> Result<Self::Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>>;
+    fn end_object(&mut self, object: Self::Object) -> Self::Output;
+
+    fn start_list(&mut self, len: usize) -> Self::List;
+    /// Emits an item of `list`, whose value is emitted by `value`.
+    fn item(
+        &mut self,
+        list: &mut Self::List,
+        value: impl FnOnce(&mut Self) -> Result<Self::Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>>;
+    fn end_list(&mut self, list: Self::List) -> Self::Output;
+
+    /// Marks the current position, to drop what's emitted after it with
+    /// [`Emitter::reset`].
+    fn mark(&self) -> usize;
+    fn reset(&mut self, mark: usize);
+
+    /// Fails once the output grows past its limit, if any.
+    fn check(&self) -> Result<(), Error>;
+}
+
+/// Builds the synthesized data as a value of type `Output`.
+struct ValueBuilder<Output>(PhantomData<Output>);
+
+impl<Output> ValueBuilder<Output> {
+    fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<'a, Value, Output> Emitter<'a, Value> for ValueBuilder<Output>
+where
+    Value: JsonLike<'a>,
+    Output: JsonLike<'a>,
+{
+    type Output = Output;
+    type Object = Vec<(&'a str, Output)>;
+    type List = Vec<Output>;
+
+    fn null(&mut self) -> Output {
+        Output::null()
     }
+
+    fn string(&mut self, value: Cow<'a, str>) -> Output {
+        Output::string(value)
+    }
+
+    fn coerced(&mut self, value: Coerced) -> Output {
+        match value {
+            Coerced::Number(n) => Output::from_primitive(JsonPrimitive::Number(n)),
+            Coerced::String(s) => Output::string(Cow::Owned(s)),
+            Coerced::Boolean(b) => Output::from_primitive(JsonPrimitive::Bool(b)),
+        }
+    }
+
+    fn value(&mut self, value: &'a Value) -> Output {
+        Output::clone_from(value)
+    }
+
+    fn start_object(&mut self, len: usize) -> Self::Object {
+        Vec::with_capacity(len)
+    }
+
+    fn field(
+        &mut self,
+        object: &mut Self::Object,
+        key: &'a str,
+        value: impl FnOnce(&mut Self) -> Result<Self::Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>> {
+        object.push((key, value(self)?));
+        Ok(())
+    }
+
+    fn field_unless_null(
+        &mut self,
+        object: &mut Self::Object,
+        key: &'a str,
+        value: impl FnOnce(&mut Self) -> Result<Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>> {
+        let value = value(self)?;
+        if !value.is_null() {
+            object.push((key, value));
+        }
+        Ok(())
+    }
+
+    fn end_object(&mut self, object: Self::Object) -> Output {
+        Output::object(Output::JsonObject::from_vec(object))
+    }
+
+    fn start_list(&mut self, len: usize) -> Self::List {
+        Vec::with_capacity(len)
+    }
+
+    fn item(
+        &mut self,
+        list: &mut Self::List,
+        value: impl FnOnce(&mut Self) -> Result<Self::Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>> {
+        list.push(value(self)?);
+        Ok(())
+    }
+
+    fn end_list(&mut self, list: Self::List) -> Output {
+        Output::array(list)
+    }
+
+    // the value of a failed field is dropped with its error, so there's
+    // nothing to roll back
+    fn mark(&self) -> usize {
+        0
+    }
+
+    fn reset(&mut self, _: usize) {}
+
+    fn check(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Writes the synthesized data as JSON into a buffer, failing once the
+/// response grows past `max` bytes.
+struct JsonWriter<'b> {
+    buf: &'b mut Vec<u8>,
+    max: Option<usize>,
+    /// Bytes of the response written before the buffer.
+    offset: usize,
+}
+
+impl<'b> JsonWriter<'b> {
+    fn new(buf: &'b mut Vec<u8>, max: Option<usize>) -> Self {
+        Self { buf, max, offset: 0 }
+    }
+
+    fn offset(self, offset: usize) -> Self {
+        Self { offset, ..self }
+    }
+}
+
+impl<'a, Value> Emitter<'a, Value> for JsonWriter<'_>
+where
+    Value: Serialize,
+{
+    type Output = ();
+    /// Whether no field has been written yet.
+    type Object = bool;
+    /// Whether no item has been written yet.
+    type List = bool;
+
+    fn null(&mut self) {
+        self.buf.extend_from_slice(b"null");
+    }
+
+    fn string(&mut self, value: Cow<'a, str>) {
+        write_json(self.buf, value.as_ref());
+    }
+
+    fn coerced(&mut self, value: Coerced) {
+        match value {
+            Coerced::Number(n) => write_json(self.buf, &n),
+            Coerced::String(s) => write_json(self.buf, &s),
+            Coerced::Boolean(b) => write_json(self.buf, &b),
+        }
+    }
+
+    fn value(&mut self, value: &'a Value) {
+        write_json(self.buf, value);
+    }
+
+    fn start_object(&mut self, _: usize) -> bool {
+        self.buf.push(b'{');
+        true
+    }
+
+    fn field(
+        &mut self,
+        first: &mut bool,
+        key: &'a str,
+        value: impl FnOnce(&mut Self) -> Result<Self::Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>> {
+        write_key(self.buf, key, first);
+        value(self)
+    }
+
+    fn field_unless_null(
+        &mut self,
+        first: &mut bool,
+        key: &'a str,
+        value: impl FnOnce(&mut Self) -> Result<(), Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>> {
+        let start = self.buf.len();
+        let was_first = *first;
+        write_key(self.buf, key, first);
+        let value_start = self.buf.len();
+        value(self)?;
+        if &self.buf[value_start..] == b"null" {
+            self.buf.truncate(start);
+            *first = was_first;
+        }
+        Ok(())
+    }
+
+    fn end_object(&mut self, _: bool) {
+        self.buf.push(b'}');
+    }
+
+    fn start_list(&mut self, _: usize) -> bool {
+        self.buf.push(b'[');
+        true
+    }
+
+    fn item(
+        &mut self,
+        first: &mut bool,
+        value: impl FnOnce(&mut Self) -> Result<Self::Output, Positioned<Error>>,
+    ) -> Result<(), Positioned<Error>> {
+        if !*first {
+            self.buf.push(b',');
+        }
+        *first = false;
+        value(self)
+    }
+
+    fn end_list(&mut self, _: bool) {
+        self.buf.push(b']');
+    }
+
+    fn mark(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn reset(&mut self, mark: usize) {
+        self.buf.truncate(mark);
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        match self.max {
+            Some(max) if self.offset + self.buf.len() > max => Err(Error::ResponseTooLarge { max }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Copies a path borrowing the names of the fields into an owned one.
+fn to_owned_path(path: &[PathSegment]) -> Vec<PathSegment<'static>> {
+    path.iter()
+        .map(|x| match x {
+            PathSegment::Field(cow) => PathSegment::Field(Cow::Owned(cow.clone().into_owned())),
+            PathSegment::Index(i) => PathSegment::Index(*i),
+        })
+        .collect()
+}
+
+/// Writes an object key followed by `:`, prefixed by a `,` for every key but
+/// the first one.
+#[inline(always)]
+fn write_key(buf: &mut Vec<u8>, key: &str, first: &mut bool) {
+    if !*first {
+        buf.push(b',');
+    }
+    *first = false;
+    write_json(buf, key);
+    buf.push(b':');
+}
+
+#[inline(always)]
+fn write_json<T: Serialize + ?Sized>(buf: &mut Vec<u8>, value: &T) {
+    // Writing into a `Vec` can't fail and the values are valid JSON, so the
+    // result can be safely ignored.
+    let _ = serde_json::to_writer(&mut *buf, value);
 }
 
 #[cfg(test)]
@@ -255,13 +1238,14 @@ mod tests {
 
     use super::ValueStore;
     use crate::core::blueprint::Blueprint;
-    use crate::core::config::{Config, ConfigModule};
+    use crate::core::config::{Config, ConfigModule, ScalarCoercion, UnknownFields};
     use crate::core::jit::builder::Builder;
     use crate::core::jit::fixtures::JP;
     use crate::core::jit::model::{FieldId, Variables};
     use crate::core::jit::store::Store;
     use crate::core::jit::synth::Synth;
-    use crate::core::jit::OperationPlan;
+    use crate::core::jit::transform::InputResolver;
+    use crate::core::jit::{Error, OperationPlan, Request};
     use crate::core::json::JsonLike;
 
     const POSTS: &str = r#"
@@ -382,6 +1366,16 @@ mod tests {
         let val_borrow: serde_json_borrow::Value = synth_borrow.synthesize().unwrap();
         let val_borrow = serde_json::to_string_pretty(&val_borrow).unwrap();
         assert_eq!(val_const, val_borrow);
+
+        #[cfg(feature = "simd-json")]
+        {
+            let (plan, value_store, vars) =
+                make_store::<simd_json::OwnedValue>(query, store.clone());
+            let synth_simd = Synth::new(&plan, value_store, vars);
+            let val_simd: simd_json::OwnedValue = synth_simd.synthesize().unwrap();
+            let val_simd = serde_json::to_string_pretty(&val_simd).unwrap();
+            assert_eq!(val_const, val_simd);
+        }
     }
 
     #[test]
@@ -438,6 +1432,552 @@ mod tests {
         assert_synths(query, store);
     }
 
+    #[test]
+    fn test_unwrap_root() {
+        let synthesize = |query: &str, store: Vec<(FieldId, TestData)>, unwrap_root: bool| {
+            let (plan, value_store, vars) = make_store::<ConstValue>(query, store);
+            let synth = Synth::new(&plan, value_store, vars).unwrap_root(unwrap_root);
+            let value: ConstValue = synth.synthesize().unwrap();
+            let mut bytes = Vec::new();
+            synth.synthesize_to(&mut bytes).unwrap();
+            assert_eq!(bytes, serde_json::to_vec(&value).unwrap());
+            value.into_json().unwrap()
+        };
+
+        let query = "query { __typename }";
+        assert_eq!(
+            synthesize(query, vec![], false),
+            serde_json::json!({ "__typename": "Query" })
+        );
+        assert_eq!(synthesize(query, vec![], true), serde_json::json!("Query"));
+
+        let store = vec![(FieldId::new(0), TestData::Posts)];
+        let query = "query { posts { id } }";
+        assert_eq!(
+            synthesize(query, store.clone(), true),
+            serde_json::json!([{ "id": 1 }, { "id": 2 }])
+        );
+
+        let store = vec![
+            (FieldId::new(0), TestData::Posts),
+            (FieldId::new(2), TestData::Users),
+        ];
+        let query = "query { posts { id } users { id } }";
+        assert_eq!(
+            synthesize(query, store, true),
+            serde_json::json!({
+                "posts": [{ "id": 1 }, { "id": 2 }],
+                "users": [{ "id": 1 }, { "id": 2 }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_omit_nulls() {
+        let store = vec![(FieldId::new(0), TestData::Users)];
+        let query = "query { users { name phone } }";
+        let synthesize = |omit_nulls: bool| {
+            let (plan, value_store, vars) = make_store::<ConstValue>(query, store.clone());
+            let synth = Synth::new(&plan, value_store, vars).omit_nulls(omit_nulls);
+            let value: ConstValue = synth.synthesize().unwrap();
+            let mut bytes = Vec::new();
+            synth.synthesize_to(&mut bytes).unwrap();
+            assert_eq!(bytes, serde_json::to_vec(&value).unwrap());
+            value.into_json().unwrap()
+        };
+
+        assert_eq!(
+            synthesize(true),
+            serde_json::json!({
+                "users": [{ "name": "Leanne Graham" }, { "name": "Ervin Howell" }]
+            })
+        );
+        assert_eq!(
+            synthesize(false),
+            serde_json::json!({
+                "users": [
+                    { "name": "Leanne Graham", "phone": null },
+                    { "name": "Ervin Howell", "phone": null }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let sdl = r#"
+            schema @server(unknownFields: passthrough) {
+              query: Query
+            }
+
+            type Query {
+              users: [User] @http(url: "http://localhost/users")
+            }
+
+            type User {
+              name: String
+            }
+        "#;
+        let config = ConfigModule::from(Config::from_sdl(sdl).to_result().unwrap());
+        let blueprint = Blueprint::try_from(&config).unwrap();
+        let users = serde_json::json!([
+            { "name": "Leanne Graham", "nickname": "Lee" }
+        ]);
+        let synthesize = |query: &str, unknown_fields: UnknownFields| {
+            let plan = Request::new(query).create_plan(&blueprint).unwrap();
+            let plan = InputResolver::new(plan)
+                .resolve_input(&Variables::new())
+                .unwrap();
+            let mut store = Store::new();
+            store.set_data(
+                FieldId::new(0),
+                Ok(ConstValue::from_json(users.clone()).unwrap()),
+            );
+            let synth = Synth::new(&plan, store, Variables::new()).unknown_fields(unknown_fields);
+            let value: Result<ConstValue, _> = synth.synthesize();
+            let mut bytes = Vec::new();
+            let written = synth.synthesize_to(&mut bytes);
+
+            match value {
+                Ok(value) => {
+                    written.unwrap();
+                    assert_eq!(bytes, serde_json::to_vec(&value).unwrap());
+                    Ok(value.into_json().unwrap())
+                }
+                Err(error) => {
+                    assert!(written.is_err());
+                    Err(error.value.to_string())
+                }
+            }
+        };
+
+        assert_eq!(
+            synthesize("query { users { name } }", UnknownFields::Drop),
+            Ok(serde_json::json!({ "users": [{ "name": "Leanne Graham" }] }))
+        );
+        assert_eq!(
+            synthesize("query { users { name } }", UnknownFields::Passthrough),
+            Ok(serde_json::json!({ "users": [{ "name": "Leanne Graham" }] }))
+        );
+        assert_eq!(
+            synthesize(
+                "query { users { name _unknownFields } }",
+                UnknownFields::Passthrough
+            ),
+            Ok(serde_json::json!({
+                "users": [{ "name": "Leanne Graham", "_unknownFields": { "nickname": "Lee" } }]
+            }))
+        );
+        assert_eq!(
+            synthesize("query { users { name } }", UnknownFields::Error),
+            Err(r#"unknown field "nickname" in the upstream value of "User""#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_lenient_dates() {
+        let sdl = r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              events: [Event] @http(url: "http://localhost/events")
+            }
+
+            type Event {
+              at: DateTime
+            }
+        "#;
+        let config = ConfigModule::from(Config::from_sdl(sdl).to_result().unwrap());
+        let blueprint = Blueprint::try_from(&config).unwrap();
+        let plan = Request::new("query { events { at } }")
+            .create_plan(&blueprint)
+            .unwrap();
+        let plan = InputResolver::new(plan)
+            .resolve_input(&Variables::new())
+            .unwrap();
+
+        let synthesize = |at: serde_json::Value, lenient_dates: bool| {
+            let mut store = Store::new();
+            let events = serde_json::json!([{ "at": at }]);
+            store.set_data(FieldId::new(0), Ok(ConstValue::from_json(events).unwrap()));
+            let synth = Synth::new(&plan, store, Variables::new()).lenient_dates(lenient_dates);
+            let value: Result<ConstValue, _> = synth.synthesize();
+            let mut bytes = Vec::new();
+            let written = synth.synthesize_to(&mut bytes);
+
+            match value {
+                Ok(value) => {
+                    written.unwrap();
+                    assert_eq!(bytes, serde_json::to_vec(&value).unwrap());
+                    Some(value.into_json().unwrap()["events"][0]["at"].clone())
+                }
+                Err(_) => {
+                    assert!(written.is_err());
+                    None
+                }
+            }
+        };
+
+        let rfc2822 = serde_json::json!("Tue, 1 Jul 2003 10:52:37 +0200");
+        let timestamp = serde_json::json!(1700000000);
+        assert_eq!(
+            synthesize(rfc2822.clone(), true),
+            Some(serde_json::json!("2003-07-01T10:52:37+02:00"))
+        );
+        assert_eq!(
+            synthesize(timestamp.clone(), true),
+            Some(serde_json::json!("2023-11-14T22:13:20Z"))
+        );
+        assert_eq!(synthesize(rfc2822, false), None);
+        assert_eq!(synthesize(timestamp, false), None);
+    }
+
+    #[test]
+    fn test_scalar_coercion() {
+        let sdl = r#"
+            schema {
+              query: Query
+            }
+
+            type Query {
+              stats: [Stat] @http(url: "http://localhost/stats")
+            }
+
+            type Stat {
+              count: Int32
+              total: Int
+            }
+        "#;
+        let config = ConfigModule::from(Config::from_sdl(sdl).to_result().unwrap());
+        let blueprint = Blueprint::try_from(&config).unwrap();
+        let plan = Request::new("query { stats { count total } }")
+            .create_plan(&blueprint)
+            .unwrap();
+        let plan = InputResolver::new(plan)
+            .resolve_input(&Variables::new())
+            .unwrap();
+
+        let synthesize = |field: &str,
+                          value: serde_json::Value,
+                          scalar_coercion: ScalarCoercion| {
+            let mut store = Store::new();
+            let stats = serde_json::json!([{ field: value }]);
+            store.set_data(FieldId::new(0), Ok(ConstValue::from_json(stats).unwrap()));
+            let synth = Synth::new(&plan, store, Variables::new()).scalar_coercion(scalar_coercion);
+            let value: Result<ConstValue, _> = synth.synthesize();
+            let mut bytes = Vec::new();
+            let written = synth.synthesize_to(&mut bytes);
+
+            match value {
+                Ok(value) => {
+                    written.unwrap();
+                    assert_eq!(bytes, serde_json::to_vec(&value).unwrap());
+                    Some(value.into_json().unwrap()["stats"][0][field].clone())
+                }
+                Err(_) => {
+                    assert!(written.is_err());
+                    None
+                }
+            }
+        };
+
+        let numeric = serde_json::json!("42");
+        assert_eq!(
+            synthesize("count", numeric.clone(), ScalarCoercion::Lenient),
+            Some(serde_json::json!(42))
+        );
+        assert_eq!(
+            synthesize("count", numeric.clone(), ScalarCoercion::Strict),
+            None
+        );
+        assert_eq!(
+            synthesize(
+                "count",
+                serde_json::json!("forty-two"),
+                ScalarCoercion::Lenient
+            ),
+            None
+        );
+        assert_eq!(
+            synthesize("total", numeric.clone(), ScalarCoercion::Lenient),
+            Some(serde_json::json!(42))
+        );
+        // the values of the built-in scalars aren't validated in the strict mode
+        assert_eq!(
+            synthesize("total", numeric.clone(), ScalarCoercion::Strict),
+            Some(numeric)
+        );
+    }
+
+    #[cfg(feature = "spill-store")]
+    #[test]
+    fn test_spilled_store() {
+        let query = r#"
+                query {
+                    posts { id title }
+                    users { id name }
+                }
+            "#;
+        let posts = ConstValue::array(
+            (1..=5000)
+                .map(|id| serde_json::json!({"id": id, "userId": 1, "title": "Some Title"}))
+                .map(|post| ConstValue::from_json(post).unwrap())
+                .collect(),
+        );
+        let users = ConstValue::array(
+            (1..=5000)
+                .map(|id| serde_json::json!({"id": id, "name": format!("User {id}")}))
+                .map(|user| ConstValue::from_json(user).unwrap())
+                .collect(),
+        );
+        let entries = vec![(FieldId::new(0), posts), (FieldId::new(3), users)];
+
+        let (plan, _, vars) = make_store::<ConstValue>(query, vec![]);
+        let memory = entries
+            .iter()
+            .cloned()
+            .fold(Store::new(), |mut store, (id, data)| {
+                store.set_data(id, Ok(data));
+                store
+            });
+        let spilled =
+            entries
+                .into_iter()
+                .fold(Store::with_budget(1024), |mut store, (id, data)| {
+                    store.set_data(id, Ok(data));
+                    store
+                });
+        assert!(spilled.is_spilled(&FieldId::new(0)));
+        assert!(spilled.is_spilled(&FieldId::new(3)));
+
+        let expected: ConstValue = Synth::new(&plan, memory, vars.clone())
+            .synthesize()
+            .unwrap();
+        let actual: ConstValue = Synth::new(&plan, spilled, vars).synthesize().unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    fn assert_synthesize_to(query: &str, store: Vec<(FieldId, TestData)>) {
+        let (plan, value_store, vars) = make_store::<ConstValue>(query, store);
+        let synth = Synth::new(&plan, value_store, vars);
+
+        let val: serde_json_borrow::Value = synth.synthesize().unwrap();
+        let expected = serde_json::to_vec(&val).unwrap();
+        let mut actual = Vec::new();
+        synth.synthesize_to(&mut actual).unwrap();
+
+        assert_eq!(
+            String::from_utf8(actual).unwrap(),
+            String::from_utf8(expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_synthesize_to() {
+        let store = vec![
+            (FieldId::new(0), TestData::Posts),
+            (FieldId::new(3), TestData::UsersData),
+            (FieldId::new(6), TestData::Users),
+        ];
+        let query = r#"
+                query {
+                    posts { id title user { id name } }
+                    users { id name }
+                }
+            "#;
+        assert_synthesize_to(query, store);
+    }
+
+    #[test]
+    fn test_lines() {
+        let store = vec![(FieldId::new(0), TestData::Users)];
+        let (plan, value_store, vars) =
+            make_store::<ConstValue>("query { users { id name } }", store);
+        let synth = Synth::new(&plan, value_store, vars);
+        let lines = synth
+            .lines()
+            .unwrap()
+            .map(|line| String::from_utf8(line.unwrap()).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"id":1,"name":"Leanne Graham"}"#,
+                r#"{"id":2,"name":"Ervin Howell"}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lines_without_root_list() {
+        let store = vec![(FieldId::new(0), TestData::User1)];
+        let (plan, value_store, vars) =
+            make_store::<ConstValue>("query { user(id: 1) { id } }", store);
+        assert!(Synth::new(&plan, value_store, vars).lines().is_none());
+
+        let store = vec![
+            (FieldId::new(0), TestData::Posts),
+            (FieldId::new(2), TestData::Users),
+        ];
+        let query = "query { posts { id } users { id } }";
+        let (plan, value_store, vars) = make_store::<ConstValue>(query, store);
+        assert!(Synth::new(&plan, value_store, vars).lines().is_none());
+    }
+
+    #[test]
+    fn test_strict_store() {
+        let (plan, _, _) = make_store::<ConstValue>("query { users { id name } }", vec![]);
+        assert!(plan.contains_field(&FieldId::new(0)));
+        assert!(!plan.contains_field(&FieldId::new(3)));
+
+        let mut store: ValueStore<ConstValue> = Store::strict(&plan);
+        store.set_data(FieldId::new(0), Ok(ConstValue::Null));
+        assert!(store.get(&FieldId::new(0)).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Field 3 is not part of the operation plan")]
+    fn test_strict_store_unknown_field() {
+        let (plan, _, _) = make_store::<ConstValue>("query { users { id name } }", vec![]);
+        let mut store: ValueStore<ConstValue> = Store::strict(&plan);
+        store.set_data(FieldId::new(3), Ok(ConstValue::Null));
+    }
+
+    #[test]
+    fn test_lines_stop_at_error() {
+        let users = serde_json::json!([
+            { "id": 1, "name": "Leanne Graham" },
+            { "id": 2 },
+            { "id": 3, "name": "Clementine Bauch" }
+        ]);
+        let (plan, _, vars) = make_store::<ConstValue>("query { users { id name } }", vec![]);
+        let mut store = Store::new();
+        store.set_data(FieldId::new(0), Ok(ConstValue::from_json(users).unwrap()));
+        let synth = Synth::new(&plan, store, vars);
+        let mut lines = synth.lines().unwrap();
+
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            br#"{"id":1,"name":"Leanne Graham"}"#
+        );
+        assert!(lines.next().unwrap().is_err());
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn test_synthesize_stream() {
+        let store = vec![(FieldId::new(0), TestData::Users)];
+        let query = "query { users { id ... @defer { name } } }";
+        let (plan, value_store, vars) = make_store::<ConstValue>(query, store);
+        let synth = Synth::new(&plan, value_store, vars).incremental(true);
+        let (data, patches) = synth.synthesize_stream::<ConstValue>();
+        let patches = patches
+            .map(|patch| serde_json::to_string(&patch).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            serde_json::to_string(&data.unwrap()).unwrap(),
+            r#"{"users":[{"id":1},{"id":2}]}"#
+        );
+        assert_eq!(
+            patches,
+            vec![
+                r#"{"data":{"name":"Leanne Graham"},"path":["users",0]}"#,
+                r#"{"data":{"name":"Ervin Howell"},"path":["users",1]}"#
+            ]
+        );
+
+        let store = vec![(FieldId::new(0), TestData::Users)];
+        let query = "query { users @stream { id name } }";
+        let (plan, value_store, vars) = make_store::<ConstValue>(query, store);
+        let synth = Synth::new(&plan, value_store, vars).incremental(true);
+        let (data, patches) = synth.synthesize_stream::<ConstValue>();
+        let patches = patches
+            .map(|patch| serde_json::to_string(&patch).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            serde_json::to_string(&data.unwrap()).unwrap(),
+            r#"{"users":[]}"#
+        );
+        assert_eq!(
+            patches,
+            vec![
+                r#"{"items":[{"id":1,"name":"Leanne Graham"}],"path":["users",0]}"#,
+                r#"{"items":[{"id":2,"name":"Ervin Howell"}],"path":["users",1]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_synthesize_stream_error() {
+        let users = serde_json::json!([{ "id": 1, "name": "Leanne Graham" }, { "id": 2 }]);
+        let query = "query { users { id ... @defer { name } } }";
+        let (plan, _, vars) = make_store::<ConstValue>(query, vec![]);
+        let mut store = Store::new();
+        store.set_data(FieldId::new(0), Ok(ConstValue::from_json(users).unwrap()));
+        let synth = Synth::new(&plan, store, vars).incremental(true);
+        let (data, patches) = synth.synthesize_stream::<ConstValue>();
+        let patches = patches
+            .map(|patch| serde_json::to_value(&patch).unwrap())
+            .collect::<Vec<_>>();
+
+        // the error fails the patch, not the initial payload
+        assert!(data.is_ok());
+        assert_eq!(
+            patches[0]["data"],
+            serde_json::json!({ "name": "Leanne Graham" })
+        );
+        assert_eq!(patches[1]["data"], serde_json::Value::Null);
+        assert_eq!(patches[1]["path"], serde_json::json!(["users", 1]));
+        assert_eq!(
+            patches[1]["errors"][0]["path"],
+            serde_json::json!(["users", 1, "name"])
+        );
+    }
+
+    #[test]
+    fn test_max_response_bytes() {
+        let users = (0..10_000)
+            .map(|id| serde_json::json!({ "id": id, "name": "Leanne Graham" }))
+            .collect::<Vec<_>>();
+        let (plan, _, vars) = make_store::<ConstValue>("query { users { id name } }", vec![]);
+        let mut store = Store::new();
+        store.set_data(
+            FieldId::new(0),
+            Ok(ConstValue::from_json(users.into()).unwrap()),
+        );
+
+        let synth = Synth::new(&plan, store, vars).max_response_bytes(Some(1024));
+        let mut buf = Vec::new();
+        let error = synth.synthesize_to(&mut buf).unwrap_err();
+        assert!(matches!(error.value, Error::ResponseTooLarge { max: 1024 }));
+        // stops shortly after the limit instead of writing the whole list
+        assert!(buf.len() < 1024 + 64);
+
+        let mut lines = synth.lines().unwrap();
+        assert!(lines.by_ref().take(20).all(|line| line.is_ok()));
+        assert!(lines.any(|line| line.is_err()));
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn test_synthesize_to_typename() {
+        let jp: JP<async_graphql::Value> =
+            JP::init("{ __typename posts { id __typename user { __typename id } } }", None);
+        let synth = jp.synth();
+        let val: serde_json_borrow::Value = synth.synthesize().unwrap();
+        let expected = serde_json::to_vec(&val).unwrap();
+        let mut actual = Vec::new();
+        synth.synthesize_to(&mut actual).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_json_placeholder() {
         let jp: JP<async_graphql::Value> =
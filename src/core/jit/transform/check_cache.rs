@@ -29,6 +29,7 @@ fn check_cache(ir: &IR) -> Option<NonZeroU64> {
         },
         IR::Merge(vec) => vec.iter().map(check_cache).min().unwrap_or_default(),
         IR::Discriminate(_, ir) => check_cache(ir),
+        IR::Rename(_, ir) | IR::Values(ir) | IR::Step(_, ir) => check_cache(ir),
         IR::Entity(hash_map) => hash_map.values().map(check_cache).min().unwrap_or_default(),
         IR::Dynamic(_) | IR::ContextPath(_) | IR::Map(_) | IR::Service(_) => None,
     }
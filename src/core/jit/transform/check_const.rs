@@ -1,10 +1,11 @@
 use std::convert::Infallible;
 use std::marker::PhantomData;
 
+use async_graphql_value::ConstValue;
 use tailcall_valid::Valid;
 
 use crate::core::ir::model::IR;
-use crate::core::jit::OperationPlan;
+use crate::core::jit::{Field, OperationPlan};
 use crate::core::Transform;
 
 pub struct CheckConst<A>(PhantomData<A>);
@@ -27,11 +28,38 @@ pub fn is_const(ir: &IR) -> bool {
         IR::Pipe(ir, ir1) => is_const(ir) && is_const(ir1),
         IR::Merge(vec) => vec.iter().all(is_const),
         IR::Discriminate(_, ir) => is_const(ir),
+        IR::Rename(_, ir) | IR::Values(ir) | IR::Step(_, ir) => is_const(ir),
         IR::Entity(hash_map) => hash_map.values().all(is_const),
         IR::Service(_) => true,
     }
 }
 
+/// Returns the value of an IR that is known without evaluating it, ie. a
+/// constant that needs neither IO nor the parent value
+pub fn static_value(ir: &IR) -> Option<ConstValue> {
+    match ir {
+        IR::Dynamic(dynamic_value) if dynamic_value.is_const() => {
+            ConstValue::try_from(dynamic_value).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Checks if the field and its selection can be resolved from the static value
+/// of the field alone
+fn is_static<A>(field: &Field<A>) -> bool {
+    fn has_ir<A>(field: &Field<A>) -> bool {
+        field.ir.is_some() || field.iter().any(has_ir)
+    }
+
+    let resolves = match field.ir {
+        Some(ref ir) => static_value(ir).is_some(),
+        None => true,
+    };
+
+    resolves && !field.iter().any(has_ir)
+}
+
 impl<A> Transform for CheckConst<A> {
     type Value = OperationPlan<A>;
     type Error = Infallible;
@@ -43,6 +71,7 @@ impl<A> Transform for CheckConst<A> {
         });
 
         plan.is_const = is_const;
+        plan.is_static = plan.before.is_none() && plan.selection.iter().all(is_static);
 
         Valid::succeed(plan)
     }
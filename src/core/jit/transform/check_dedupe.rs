@@ -23,6 +23,7 @@ fn check_dedupe(ir: &IR) -> bool {
         IR::Pipe(ir, ir1) => check_dedupe(ir) && check_dedupe(ir1),
         IR::Merge(vec) => vec.iter().all(check_dedupe),
         IR::Discriminate(_, ir) => check_dedupe(ir),
+        IR::Rename(_, ir) | IR::Values(ir) | IR::Step(_, ir) => check_dedupe(ir),
         IR::Entity(hash_map) => hash_map.values().all(check_dedupe),
         IR::Dynamic(_) => true,
         IR::ContextPath(_) => true,
@@ -27,6 +27,7 @@ pub fn is_protected(ir: &IR) -> bool {
         IR::Pipe(ir, ir1) => is_protected(ir) || is_protected(ir1),
         IR::Merge(vec) => vec.iter().all(is_protected),
         IR::Discriminate(_, ir) => is_protected(ir),
+        IR::Rename(_, ir) | IR::Values(ir) | IR::Step(_, ir) => is_protected(ir),
         IR::Entity(hash_map) => hash_map.values().any(is_protected),
         IR::Service(_) => false,
     }
@@ -37,9 +38,9 @@ impl<A> Transform for CheckProtected<A> {
     type Error = Infallible;
 
     fn transform(&self, mut plan: Self::Value) -> Valid<Self::Value, Self::Error> {
-        plan.is_protected = plan.iter_dfs().all(|field| match field.ir {
+        plan.is_protected = plan.iter_dfs().any(|field| match field.ir {
             Some(ref ir) => is_protected(ir),
-            None => true,
+            None => false,
         });
 
         Valid::succeed(plan)
@@ -82,6 +82,7 @@ where
             is_introspection_query: self.plan.is_introspection_query,
             is_dedupe: self.plan.is_dedupe,
             is_const: self.plan.is_const,
+            is_static: self.plan.is_static,
             is_protected: self.plan.is_protected,
             min_cache_ttl: self.plan.min_cache_ttl,
             interfaces: None,
@@ -171,6 +172,11 @@ where
             return Ok(None);
         };
 
+        // A single value given for a list is coerced to a list of that value
+        if type_of.is_list() && !value.is_null() && value.as_array().is_none() {
+            value = Output::array(vec![value]);
+        }
+
         let Some(def) = index.get_input_type_definition(type_of.name()) else {
             return Ok(Some(value));
         };
@@ -196,6 +202,10 @@ where
                 }
             }
         } else if let Some(arr) = value.as_array_mut() {
+            let item_type = match type_of {
+                Type::List { of_type, .. } => of_type.as_ref(),
+                Type::Named { .. } => type_of,
+            };
             for (i, item) in arr.iter_mut().enumerate() {
                 let parent_name = format!("{}.{}.{}", parent_name, arg_name, i);
 
@@ -203,7 +213,7 @@ where
                     index,
                     &parent_name,
                     &i.to_string(),
-                    type_of,
+                    item_type,
                     &None,
                     Some(item.clone()),
                 )?
@@ -214,3 +224,68 @@ where
         Ok(Some(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_graphql_value::ConstValue;
+    use tailcall_valid::Validator;
+
+    use super::InputResolver;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::jit::Request;
+
+    const SDL: &str = r#"
+        schema @server @upstream {
+          query: Query
+        }
+
+        type Query {
+          posts(tags: [String]): String @expr(body: "{{.args.tags}}")
+        }
+    "#;
+
+    fn resolve_tags(request: Request<ConstValue>) -> Option<ConstValue> {
+        let config = Config::from_sdl(SDL).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        let plan = request.clone().create_plan(&blueprint).unwrap();
+        let plan = InputResolver::new(plan)
+            .resolve_input(&request.variables)
+            .unwrap();
+
+        plan.selection[0].args[0].value.clone()
+    }
+
+    fn tags(tags: &[&str]) -> Option<ConstValue> {
+        Some(ConstValue::List(
+            tags.iter()
+                .map(|tag| ConstValue::String(tag.to_string()))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_single_value_to_list() {
+        let actual = resolve_tags(Request::new(r#"{ posts(tags: "a") }"#));
+        assert_eq!(actual, tags(&["a"]));
+
+        let request = Request::new("query($tags: [String]) { posts(tags: $tags) }")
+            .variables([("tags".to_string(), ConstValue::String("a".to_string()))]);
+        assert_eq!(resolve_tags(request), tags(&["a"]));
+    }
+
+    #[test]
+    fn test_list_stays_list() {
+        let actual = resolve_tags(Request::new(r#"{ posts(tags: ["a", "b"]) }"#));
+        assert_eq!(actual, tags(&["a", "b"]));
+    }
+
+    #[test]
+    fn test_null_list() {
+        let actual = resolve_tags(Request::new("{ posts(tags: null) }"));
+        assert_eq!(actual, Some(ConstValue::Null));
+
+        let actual = resolve_tags(Request::new("{ posts }"));
+        assert_eq!(actual, None);
+    }
+}
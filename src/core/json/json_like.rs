@@ -89,7 +89,7 @@ mod tests {
 
     use super::super::gather_path_matches;
     use super::{JsonLike, JsonObjectLike};
-    use crate::core::json::group_by_key;
+    use crate::core::json::{composite_key, group_by_composite_key, group_by_key};
 
     // for lifetime testing purposes
     #[allow(dead_code)]
@@ -252,4 +252,30 @@ mod tests {
 
         assert_eq!(actual, expected)
     }
+
+    #[test]
+    fn test_group_by_composite_key() {
+        let input = json!({
+            "items": [
+                {"org": "a", "id": 1},
+                {"org": "b", "id": 1},
+                {"org": "a", "id": 2},
+                {"org": "a"},
+            ]
+        });
+        let paths = vec![
+            vec!["items".to_string(), "org".to_string()],
+            vec!["items".to_string(), "id".to_string()],
+        ];
+
+        let actual = serde_json::to_value(group_by_composite_key(&input, &paths)).unwrap();
+
+        let expected = json!({
+            composite_key(["a", "1"]): [{"org": "a", "id": 1}],
+            composite_key(["b", "1"]): [{"org": "b", "id": 1}],
+            composite_key(["a", "2"]): [{"org": "a", "id": 2}],
+        });
+
+        assert_eq!(actual, expected)
+    }
 }
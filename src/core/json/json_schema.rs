@@ -11,6 +11,8 @@ use tailcall_valid::{Valid, Validator};
 pub enum JsonSchema {
     Obj(BTreeMap<String, JsonSchema>),
     Arr(Box<JsonSchema>),
+    /// An object with arbitrary string keys whose values share the same schema
+    Map(Box<JsonSchema>),
     Opt(Box<JsonSchema>),
     Enum(BTreeSet<String>),
     Str,
@@ -18,6 +20,14 @@ pub enum JsonSchema {
     Bool,
     Empty,
     Any,
+    /// A protobuf message with `oneof` groups. `fields` holds every field of
+    /// the message while each set in `groups` lists the fields that are
+    /// mutually exclusive with each other.
+    #[schemars(skip)]
+    OneOf {
+        fields: BTreeMap<String, JsonSchema>,
+        groups: Vec<BTreeSet<String>>,
+    },
 }
 
 impl Display for JsonSchema {
@@ -36,6 +46,9 @@ impl Display for JsonSchema {
             JsonSchema::Arr(schema) => {
                 write!(f, "[{}]", schema)
             }
+            JsonSchema::Map(schema) => {
+                write!(f, "Map<String, {}>", schema)
+            }
             JsonSchema::Opt(schema) => {
                 write!(f, "Option<{}>", schema)
             }
@@ -59,6 +72,25 @@ impl Display for JsonSchema {
             JsonSchema::Any => {
                 write!(f, "Any")
             }
+            JsonSchema::OneOf { fields, groups } => {
+                let groups = groups
+                    .iter()
+                    .map(|group| {
+                        group
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<_>>()
+                            .join(" | ")
+                    })
+                    .collect::<Vec<String>>();
+
+                write!(
+                    f,
+                    "{} oneof [{}]",
+                    JsonSchema::Obj(fields.clone()),
+                    groups.join(", ")
+                )
+            }
         }
     }
 }
@@ -122,33 +154,42 @@ impl JsonSchema {
                 }
                 _ => Valid::fail("expected array"),
             },
-            JsonSchema::Obj(fields) => {
-                let field_schema_list: Vec<(&String, &JsonSchema)> = fields.iter().collect();
-                match value {
-                    async_graphql::Value::Object(map) => {
-                        Valid::from_iter(field_schema_list, |(name, schema)| {
-                            if schema.is_required() {
-                                if let Some(field_value) = map.get::<str>(name.as_ref()) {
-                                    schema.validate(field_value).trace(name)
-                                } else {
-                                    Valid::fail("expected field to be non-nullable").trace(name)
-                                }
-                            } else if let Some(field_value) = map.get::<str>(name.as_ref()) {
-                                schema.validate(field_value).trace(name)
-                            } else {
-                                Valid::succeed(())
-                            }
-                        })
-                        .unit()
-                    }
-                    _ => Valid::fail("expected object"),
+            JsonSchema::Map(schema) => match value {
+                async_graphql::Value::Object(map) => {
+                    Valid::from_iter(map.iter(), |(key, value)| {
+                        schema.validate(value).trace(key.as_str())
+                    })
+                    .unit()
                 }
-            }
+                _ => Valid::fail("expected object"),
+            },
+            JsonSchema::Obj(fields) => validate_fields(fields, value),
             JsonSchema::Opt(schema) => match value {
                 async_graphql::Value::Null => Valid::succeed(()),
                 _ => schema.validate(value),
             },
             JsonSchema::Enum(_) => Valid::succeed(()),
+            JsonSchema::OneOf { fields, groups } => {
+                validate_fields(fields, value).and_then(|_| match value {
+                    async_graphql::Value::Object(map) => Valid::from_iter(groups, |group| {
+                        let set = group
+                            .iter()
+                            .filter(|name| {
+                                map.get::<str>(name.as_ref())
+                                    .is_some_and(|value| !value.is_null())
+                            })
+                            .count();
+
+                        if set > 1 {
+                            Valid::fail("expected at most one field of oneof to be set")
+                        } else {
+                            Valid::succeed(())
+                        }
+                    })
+                    .unit(),
+                    _ => Valid::succeed(()),
+                })
+            }
         }
     }
 
@@ -159,6 +200,14 @@ impl JsonSchema {
             return Valid::succeed(());
         }
 
+        // GraphQL types are compared against all the fields of the message, the
+        // exclusivity of oneof fields is checked while validating values
+        if let JsonSchema::OneOf { fields, .. } = sub_type {
+            if !matches!(super_type, JsonSchema::OneOf { .. }) {
+                return JsonSchema::Obj(fields.clone()).is_a(super_type, name);
+            }
+        }
+
         let fail = Valid::fail(format!(
             "Type '{}' is not assignable to type '{}'",
             sub_type, super_type
@@ -206,6 +255,13 @@ impl JsonSchema {
                     return fail;
                 }
             }
+            JsonSchema::Map(expected) => {
+                if let JsonSchema::Map(actual) = sub_type {
+                    return actual.is_a(expected, name);
+                } else {
+                    return fail;
+                }
+            }
             JsonSchema::Opt(expected) => {
                 if let JsonSchema::Opt(actual) = sub_type {
                     return actual.is_a(expected, name);
@@ -222,6 +278,17 @@ impl JsonSchema {
                     return fail;
                 }
             }
+            JsonSchema::OneOf { fields: expected, groups: expected_groups } => {
+                if let JsonSchema::OneOf { fields: actual, groups } = sub_type {
+                    if groups.ne(expected_groups) {
+                        return fail;
+                    }
+                    return JsonSchema::Obj(actual.clone())
+                        .is_a(&JsonSchema::Obj(expected.clone()), name);
+                } else {
+                    return fail;
+                }
+            }
         }
         Valid::succeed(())
     }
@@ -239,13 +306,42 @@ impl JsonSchema {
     }
 }
 
+/// Validates the fields of an object, shared by messages with and without
+/// `oneof` groups.
+fn validate_fields(
+    fields: &BTreeMap<String, JsonSchema>,
+    value: &async_graphql::Value,
+) -> Valid<(), &'static str> {
+    match value {
+        async_graphql::Value::Object(map) => Valid::from_iter(fields.iter(), |(name, schema)| {
+            if schema.is_required() {
+                if let Some(field_value) = map.get::<str>(name.as_ref()) {
+                    schema.validate(field_value).trace(name)
+                } else {
+                    Valid::fail("expected field to be non-nullable").trace(name)
+                }
+            } else if let Some(field_value) = map.get::<str>(name.as_ref()) {
+                schema.validate(field_value).trace(name)
+            } else {
+                Valid::succeed(())
+            }
+        })
+        .unit(),
+        _ => Valid::fail("expected object"),
+    }
+}
+
 impl TryFrom<&MessageDescriptor> for JsonSchema {
     type Error = tailcall_valid::ValidationError<String>;
 
     fn try_from(value: &MessageDescriptor) -> Result<Self, Self::Error> {
         if value.is_map_entry() {
-            // we encode protobuf's map as JSON scalar
-            return Ok(JsonSchema::Any);
+            // protobuf's map is encoded as JSON object with string keys, so only
+            // the type of the values is kept
+            return match JsonSchema::try_from(&value.map_entry_value_field())? {
+                JsonSchema::Opt(schema) => Ok(*schema),
+                schema => Ok(schema),
+            };
         }
 
         let mut map = BTreeMap::new();
@@ -260,10 +356,25 @@ impl TryFrom<&MessageDescriptor> for JsonSchema {
             map.insert(field.name().to_case(Case::Camel), field_schema);
         }
 
+        // proto3 `optional` fields are wrapped into synthetic oneofs that
+        // don't represent an actual choice between fields
+        let groups = value
+            .oneofs()
+            .filter(|oneof| !oneof.is_synthetic())
+            .map(|oneof| {
+                oneof
+                    .fields()
+                    .map(|field| field.name().to_case(Case::Camel))
+                    .collect::<BTreeSet<_>>()
+            })
+            .collect::<Vec<_>>();
+
         if map.is_empty() {
             Ok(JsonSchema::Empty)
-        } else {
+        } else if groups.is_empty() {
             Ok(JsonSchema::Obj(map))
+        } else {
+            Ok(JsonSchema::OneOf { fields: map, groups })
         }
     }
 }
@@ -300,6 +411,9 @@ impl TryFrom<&FieldDescriptor> for JsonSchema {
             Kind::Bool => JsonSchema::Bool,
             Kind::String => JsonSchema::Str,
             Kind::Bytes => JsonSchema::Str,
+            Kind::Message(msg) if msg.is_map_entry() => {
+                JsonSchema::Map(Box::new(JsonSchema::try_from(&msg)?))
+            }
             Kind::Message(msg) => JsonSchema::try_from(&msg)?,
             Kind::Enum(enm) => JsonSchema::try_from(&enm)?,
         };
@@ -311,7 +425,10 @@ impl TryFrom<&FieldDescriptor> for JsonSchema {
         } else {
             field_schema
         };
-        let field_schema = if value.is_list() {
+        let field_schema = if value.is_map() {
+            // a map is already modelled by its entry, so it's only made optional.
+            JsonSchema::Opt(Box::new(field_schema))
+        } else if value.is_list() {
             // if value is of type list then we treat it as optional.
             JsonSchema::Opt(Box::new(JsonSchema::Arr(Box::new(field_schema))))
         } else {
@@ -484,6 +601,131 @@ mod tests {
 
         Ok(())
     }
+    #[tokio::test]
+    async fn test_from_protobuf_oneof_conversion() -> anyhow::Result<()> {
+        let grpc_method = GrpcMethod::try_from("oneof.OneOfService.GetOneOf").unwrap();
+
+        let file = ProtobufSet::from_proto_file(get_proto_file(protobuf::ONEOF).await?)?;
+        let service = file.find_service(&grpc_method)?;
+        let operation = service.find_operation(&grpc_method)?;
+
+        let payload = JsonSchema::from([("payload", JsonSchema::Str.optional())]);
+        let command = JsonSchema::from([("command", JsonSchema::Str.optional())]);
+
+        let schema = JsonSchema::try_from(&operation.output_type)?;
+        assert_eq!(
+            schema,
+            JsonSchema::OneOf {
+                fields: BTreeMap::from_iter([
+                    ("usual".to_owned(), JsonSchema::Num.optional()),
+                    ("payload".to_owned(), payload.clone().optional()),
+                    ("command".to_owned(), command.clone().optional()),
+                    ("response".to_owned(), JsonSchema::Str.optional()),
+                ]),
+                groups: vec![BTreeSet::from_iter([
+                    "command".to_owned(),
+                    "payload".to_owned(),
+                    "response".to_owned()
+                ])]
+            }
+        );
+
+        let schema = JsonSchema::try_from(&operation.input_type)?;
+        assert_eq!(
+            schema,
+            JsonSchema::OneOf {
+                fields: BTreeMap::from_iter([
+                    ("usual".to_owned(), JsonSchema::Str.optional()),
+                    ("payload".to_owned(), payload.clone().optional()),
+                    ("command".to_owned(), command.optional()),
+                    ("flag".to_owned(), JsonSchema::Bool.optional()),
+                    ("optPayload".to_owned(), payload.optional()),
+                ]),
+                groups: vec![
+                    BTreeSet::from_iter(["command".to_owned(), "payload".to_owned()]),
+                    BTreeSet::from_iter(["flag".to_owned(), "optPayload".to_owned()]),
+                ]
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_from_protobuf_map_conversion() -> anyhow::Result<()> {
+        let grpc_method = GrpcMethod::try_from("map.MapService.GetCounts").unwrap();
+
+        let file = ProtobufSet::from_proto_file(get_proto_file(protobuf::MAP).await?)?;
+        let service = file.find_service(&grpc_method)?;
+        let operation = service.find_operation(&grpc_method)?;
+
+        let schema = JsonSchema::try_from(&operation.output_type)?;
+        assert_eq!(
+            schema,
+            JsonSchema::from([("counts", JsonSchema::Map(JsonSchema::Num.into()).optional())])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_map() {
+        let schema = JsonSchema::Map(JsonSchema::Num.into());
+        let value = async_graphql::Value::from_json(serde_json::json!({"a": 1, "b": 2})).unwrap();
+        assert_eq!(schema.validate(&value), Valid::succeed(()));
+
+        let value = async_graphql::Value::from_json(serde_json::json!({"a": 1, "b": "2"})).unwrap();
+        assert_eq!(
+            schema.validate(&value),
+            Valid::fail("expected number").trace("b")
+        );
+    }
+
+    #[test]
+    fn test_validate_oneof() {
+        let schema = JsonSchema::OneOf {
+            fields: BTreeMap::from_iter([
+                ("a".to_owned(), JsonSchema::Str.optional()),
+                ("b".to_owned(), JsonSchema::Num.optional()),
+            ]),
+            groups: vec![BTreeSet::from_iter(["a".to_owned(), "b".to_owned()])],
+        };
+
+        let value = async_graphql::Value::Object({
+            let mut map = IndexMap::new();
+            map.insert(Name::new("a"), async_graphql::Value::String("a".to_owned()));
+            map.insert(Name::new("b"), async_graphql::Value::Null);
+            map
+        });
+        assert_eq!(schema.validate(&value), Valid::succeed(()));
+
+        let value = async_graphql::Value::Object({
+            let mut map = IndexMap::new();
+            map.insert(Name::new("a"), async_graphql::Value::String("a".to_owned()));
+            map.insert(Name::new("b"), async_graphql::Value::Number(1.into()));
+            map
+        });
+        assert_eq!(
+            schema.validate(&value),
+            Valid::fail("expected at most one field of oneof to be set")
+        );
+    }
+
+    #[test]
+    fn test_oneof_is_a_object() {
+        let schema = JsonSchema::OneOf {
+            fields: BTreeMap::from_iter([
+                ("a".to_owned(), JsonSchema::Str.optional()),
+                ("b".to_owned(), JsonSchema::Num.optional()),
+            ]),
+            groups: vec![BTreeSet::from_iter(["a".to_owned(), "b".to_owned()])],
+        };
+        let graphql = JsonSchema::from([("a", JsonSchema::Str.optional())]);
+
+        assert!(schema.is_a(&graphql, "foo").is_succeed());
+        assert!(schema.is_a(&JsonSchema::Any, "foo").is_succeed());
+    }
+
     #[test]
     fn test_compare_enum() {
         let mut en = BTreeSet::new();
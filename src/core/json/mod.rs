@@ -4,6 +4,8 @@ mod json_like;
 mod json_like_list;
 mod json_schema;
 mod serde;
+#[cfg(feature = "simd-json")]
+mod simd;
 
 use std::collections::HashMap;
 
@@ -40,13 +42,7 @@ fn group_by_key<'json, J: JsonLike<'json>>(
 ) -> HashMap<String, Vec<&'json J>> {
     let mut map: HashMap<String, Vec<&'json J>> = HashMap::new();
     for (key, value) in src {
-        // Need to handle number and string keys
-        let key_str = key
-            .as_str()
-            .map(|a| a.to_string())
-            .or_else(|| key.as_f64().map(|a| a.to_string()));
-
-        if let Some(key) = key_str {
+        if let Some(key) = to_key_string(key) {
             if let Some(values) = map.get_mut(&key) {
                 values.push(value);
             } else {
@@ -56,3 +52,47 @@ fn group_by_key<'json, J: JsonLike<'json>>(
     }
     map
 }
+
+// Need to handle number and string keys
+fn to_key_string<'json, J: JsonLike<'json>>(key: &J) -> Option<String> {
+    key.as_str()
+        .map(|a| a.to_string())
+        .or_else(|| key.as_f64().map(|a| a.to_string()))
+}
+
+/// Builds the key used to group items by more than one value.
+pub fn composite_key<I: IntoIterator<Item = S>, S: AsRef<str>>(parts: I) -> String {
+    let parts = parts
+        .into_iter()
+        .map(|part| part.as_ref().to_string())
+        .collect::<Vec<_>>();
+    serde_json::Value::from(parts).to_string()
+}
+
+/// Groups the items found in `root` by the composite of the values at `paths`.
+/// The paths are expected to differ only in their last segment, so that all of
+/// them point into the same item.
+pub fn group_by_composite_key<'json, J: JsonLike<'json>>(
+    root: &'json J,
+    paths: &[Vec<String>],
+) -> HashMap<String, Vec<&'json J>> {
+    let mut map: HashMap<String, Vec<&'json J>> = HashMap::new();
+    let Some((first, rest)) = paths.split_first() else {
+        return map;
+    };
+
+    for (key, value) in gather_path_matches(root, first, Vec::new()) {
+        let parts = std::iter::once(to_key_string(key))
+            .chain(rest.iter().map(|path| {
+                path.last()
+                    .and_then(|last| value.get_key(last))
+                    .and_then(to_key_string)
+            }))
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(parts) = parts {
+            map.entry(composite_key(parts)).or_default().push(value);
+        }
+    }
+    map
+}
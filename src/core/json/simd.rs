@@ -0,0 +1,281 @@
+use std::borrow::Cow;
+
+use simd_json::owned::{Object, Value};
+use simd_json::StaticNode;
+
+use super::{gather_path_matches, group_by_key, JsonLike, JsonObjectLike, JsonPrimitive};
+
+/// The objects of simd-json are `halfbrown` maps, which keep the insertion
+/// order of their keys only while they're small enough to be stored as a
+/// vector (up to 32 keys). The keys of the larger objects come out in the
+/// order of their hashes instead, so they aren't guaranteed to follow the
+/// selection set of the query.
+impl<'obj> JsonObjectLike<'obj> for Object {
+    type Value = Value;
+
+    fn new() -> Self {
+        Object::default()
+    }
+
+    fn with_capacity(n: usize) -> Self {
+        Object::with_capacity_and_hasher(n, Default::default())
+    }
+
+    fn from_vec(v: Vec<(&'obj str, Self::Value)>) -> Self {
+        let mut obj = Self::with_capacity(v.len());
+        for (key, value) in v {
+            obj.insert(key.to_string(), value);
+        }
+        obj
+    }
+
+    fn get_key(&self, key: &str) -> Option<&Self::Value> {
+        self.get(key)
+    }
+
+    fn insert_key(&mut self, key: &'obj str, value: Self::Value) {
+        self.insert(key.to_string(), value);
+    }
+
+    fn iter<'slf>(&'slf self) -> impl Iterator<Item = (&'slf str, &'slf Self::Value)>
+    where
+        Self::Value: 'slf,
+        'obj: 'slf,
+    {
+        self.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Converts the number into a node of simd-json, failing for the numbers that
+/// don't fit any of its number types.
+fn number(n: &serde_json::Number) -> Result<StaticNode, String> {
+    if let Some(n) = n.as_i64() {
+        Ok(StaticNode::I64(n))
+    } else if let Some(n) = n.as_u64() {
+        Ok(StaticNode::U64(n))
+    } else if let Some(n) = n.as_f64() {
+        Ok(StaticNode::F64(n))
+    } else {
+        Err(format!("The number {n} can't be represented by simd-json"))
+    }
+}
+
+impl<'json> JsonLike<'json> for Value {
+    type JsonObject = Object;
+
+    fn null() -> Self {
+        Value::Static(StaticNode::Null)
+    }
+
+    fn object(obj: Self::JsonObject) -> Self {
+        Value::Object(Box::new(obj))
+    }
+
+    fn array(arr: Vec<Self>) -> Self {
+        Value::Array(Box::new(arr))
+    }
+
+    fn string(s: Cow<'json, str>) -> Self {
+        Value::String(s.into_owned())
+    }
+
+    fn from_primitive(x: JsonPrimitive<'json>) -> Self {
+        match x {
+            JsonPrimitive::Null => Value::Static(StaticNode::Null),
+            JsonPrimitive::Bool(x) => Value::Static(StaticNode::Bool(x)),
+            JsonPrimitive::Str(s) => Value::String(s.to_string()),
+            JsonPrimitive::Number(n) => match number(&n) {
+                Ok(node) => Value::Static(node),
+                Err(err) => {
+                    tracing::error!("{err}");
+                    Value::Static(StaticNode::Null)
+                }
+            },
+        }
+    }
+
+    fn as_primitive(&self) -> Option<JsonPrimitive> {
+        let val = match self {
+            Value::Static(StaticNode::Null) => JsonPrimitive::Null,
+            Value::Static(StaticNode::Bool(x)) => JsonPrimitive::Bool(*x),
+            Value::Static(StaticNode::I64(n)) => JsonPrimitive::Number((*n).into()),
+            Value::Static(StaticNode::U64(n)) => JsonPrimitive::Number((*n).into()),
+            Value::Static(StaticNode::F64(n)) => serde_json::Number::from_f64(*n)
+                .map(JsonPrimitive::Number)
+                .unwrap_or(JsonPrimitive::Null),
+            Value::String(s) => JsonPrimitive::Str(s.as_str()),
+            _ => return None,
+        };
+
+        Some(val)
+    }
+
+    fn as_array(&self) -> Option<&Vec<Self>> {
+        match self {
+            Value::Array(arr) => Some(&**arr),
+            _ => None,
+        }
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Value::Array(arr) => Some(&mut **arr),
+            _ => None,
+        }
+    }
+
+    fn into_array(self) -> Option<Vec<Self>> {
+        match self {
+            Value::Array(arr) => Some(*arr),
+            _ => None,
+        }
+    }
+
+    fn as_object(&self) -> Option<&Self::JsonObject> {
+        match self {
+            Value::Object(obj) => Some(&**obj),
+            _ => None,
+        }
+    }
+
+    fn as_object_mut(&mut self) -> Option<&mut Self::JsonObject> {
+        match self {
+            Value::Object(obj) => Some(&mut **obj),
+            _ => None,
+        }
+    }
+
+    fn into_object(self) -> Option<Self::JsonObject> {
+        match self {
+            Value::Object(obj) => Some(*obj),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Static(StaticNode::I64(n)) => Some(*n),
+            Value::Static(StaticNode::U64(n)) => i64::try_from(*n).ok(),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Static(StaticNode::U64(n)) => Some(*n),
+            Value::Static(StaticNode::I64(n)) => u64::try_from(*n).ok(),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Static(StaticNode::F64(n)) => Some(*n),
+            Value::Static(StaticNode::I64(n)) => Some(*n as f64),
+            Value::Static(StaticNode::U64(n)) => Some(*n as f64),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Static(StaticNode::Bool(x)) => Some(*x),
+            _ => None,
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self, Value::Static(StaticNode::Null))
+    }
+
+    fn get_path<T: AsRef<str>>(&'json self, path: &[T]) -> Option<&'json Self> {
+        let mut val = self;
+        for token in path {
+            val = match val {
+                Value::Array(arr) => {
+                    let index = token.as_ref().parse::<usize>().ok()?;
+                    arr.get(index)?
+                }
+                Value::Object(map) => map.get(token.as_ref())?,
+                _ => return None,
+            };
+        }
+        Some(val)
+    }
+
+    fn get_key(&'json self, path: &str) -> Option<&'json Self> {
+        match self {
+            Value::Object(map) => map.get(path),
+            _ => None,
+        }
+    }
+
+    fn group_by(
+        &'json self,
+        path: &[String],
+    ) -> std::collections::HashMap<String, Vec<&'json Self>> {
+        let src = gather_path_matches(self, path, vec![]);
+        group_by_key(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use simd_json::owned::{Object, Value};
+
+    use crate::core::json::{JsonLike, JsonObjectLike};
+
+    fn object(n: usize) -> Value {
+        let keys = (0..n).map(|i| format!("key{i}")).collect::<Vec<_>>();
+        let mut obj = <Object as JsonObjectLike>::new();
+        for (i, key) in keys.iter().enumerate() {
+            obj.insert_key(key, Value::from(i as u64));
+        }
+        Value::object(obj)
+    }
+
+    fn keys(value: &Value) -> Vec<String> {
+        let json = serde_json::to_string(value).unwrap();
+        let map: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&json).unwrap();
+        map.keys().cloned().collect()
+    }
+
+    #[test]
+    fn test_small_object_keeps_order() {
+        let value = object(32);
+        let expected = (0..32).map(|i| format!("key{i}")).collect::<Vec<_>>();
+        assert_eq!(keys(&value), expected);
+    }
+
+    #[test]
+    fn test_large_object_keeps_keys() {
+        let value = object(40);
+        let obj = value.as_object().unwrap();
+        assert_eq!(obj.len(), 40);
+        for i in 0..40 {
+            let key = format!("key{i}");
+            assert_eq!(obj.get_key(&key).and_then(|v| v.as_u64()), Some(i as u64));
+        }
+
+        let mut actual = keys(&value);
+        actual.sort();
+        let mut expected = (0..40).map(|i| format!("key{i}")).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(actual, expected);
+    }
+}
@@ -26,6 +26,8 @@ pub mod json;
 mod lift;
 pub mod merge_right;
 pub mod mustache;
+#[cfg(feature = "object-storage")]
+pub mod object_storage;
 pub mod path;
 pub mod primitive;
 pub mod print_schema;
@@ -35,6 +37,7 @@ pub mod rest;
 pub mod runtime;
 pub mod scalar;
 pub mod schema_extension;
+pub mod secret;
 mod serde_value_ext;
 pub mod tracing;
 mod transform;
@@ -69,6 +72,13 @@ pub trait EnvIO: Send + Sync + 'static {
     fn get(&self, key: &str) -> Option<Cow<'_, str>>;
 }
 
+/// Provides the secrets that templates refer to as `{{.secret.NAME}}`. Unlike
+/// environment variables, their values are kept out of the logs, which is why
+/// they can only be used in headers.
+pub trait SecretIO: Send + Sync + 'static {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>>;
+}
+
 #[async_trait::async_trait]
 pub trait HttpIO: Sync + Send + 'static {
     async fn execute(
@@ -157,6 +167,21 @@ pub mod tests {
             Self(HashMap::from_iter(iter))
         }
     }
+
+    #[derive(Clone, Default)]
+    pub struct TestSecretIO(HashMap<String, String>);
+
+    impl SecretIO for TestSecretIO {
+        fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+            self.0.get(key).map(Cow::from)
+        }
+    }
+
+    impl FromIterator<(String, String)> for TestSecretIO {
+        fn from_iter<T: IntoIterator<Item = (String, String)>>(iter: T) -> Self {
+            Self(HashMap::from_iter(iter))
+        }
+    }
     #[test]
     fn test_include_config() {
         let cfg = include_config!("fixtures/helloworld.graphql")
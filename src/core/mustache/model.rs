@@ -47,6 +47,24 @@ impl Mustache {
             .collect()
     }
 
+    /// Returns the names of the environment variables referenced by the
+    /// template.
+    pub fn env_vars(&self) -> Vec<&str> {
+        self.expression_segments()
+            .into_iter()
+            .filter(|parts| parts.len() > 1 && parts[0] == "env")
+            .map(|parts| parts[1].as_str())
+            .collect()
+    }
+
+    /// Checks if the template refers to a secret, so that the rendered value
+    /// can be kept out of the logs.
+    pub fn has_secrets(&self) -> bool {
+        self.expression_segments()
+            .into_iter()
+            .any(|parts| parts.len() > 1 && parts[0] == "secret")
+    }
+
     /// Checks if the mustache template contains the given expression
     pub fn expression_contains(&self, expression: &str) -> bool {
         self.segments()
@@ -34,13 +34,45 @@ fn parse_name(input: &str) -> IResult<&str, String> {
     })(input)
 }
 
+/// Parses the name of a header, which unlike other names can contain a hyphen.
+fn parse_header_name(input: &str) -> IResult<&str, String> {
+    let spaces = nom::character::complete::multispace0;
+    let alpha = nom::character::complete::alpha1;
+    let alphanumeric_or_symbol = nom::multi::many0(nom::branch::alt((
+        nom::character::complete::alphanumeric1,
+        nom::bytes::complete::tag("_"),
+        nom::bytes::complete::tag("-"),
+    )));
+
+    let parser = nom::sequence::tuple((spaces, alpha, alphanumeric_or_symbol, spaces));
+
+    nom::combinator::map(parser, |(_, a, b, _)| {
+        let b: String = b.into_iter().collect();
+        format!("{}{}", a, b)
+    })(input)
+}
+
+/// Parses `headers.<name>`, e.g. `{{.headers.x-tenant}}`.
+fn parse_header_path(input: &str) -> IResult<&str, Vec<String>> {
+    let spaces = nom::character::complete::multispace0;
+    let parser =
+        nom::sequence::tuple((spaces, tag("headers"), spaces, char('.'), parse_header_name));
+
+    map(parser, |(_, headers, _, _, name)| {
+        vec![headers.to_string(), name]
+    })(input)
+}
+
 fn parse_expression(input: &str) -> IResult<&str, Segment> {
     delimited(
         tag("{{"),
         map(
             nom::sequence::tuple((
                 nom::combinator::opt(char('.')), // Optional leading dot
-                nom::multi::separated_list1(char('.'), parse_name),
+                alt((
+                    parse_header_path,
+                    nom::multi::separated_list1(char('.'), parse_name),
+                )),
             )),
             |(_, expr_parts)| Segment::Expression(expr_parts),
         ),
@@ -269,4 +301,30 @@ mod tests {
             ])])
         );
     }
+
+    #[test]
+    fn test_hyphenated_header_name() {
+        let s = r"http://{{.headers.x-tenant}}.example.com";
+        let mustache: Mustache = Mustache::parse(s);
+        assert_eq!(
+            mustache,
+            Mustache::from(vec![
+                Segment::Literal("http://".to_string()),
+                Segment::Expression(vec!["headers".to_string(), "x-tenant".to_string()]),
+                Segment::Literal(".example.com".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_hyphenated_name() {
+        let s = r"/users/{{.args.user-id}}";
+        let mustache: Mustache = Mustache::parse(s);
+        assert_eq!(
+            mustache,
+            Mustache::from(vec![Segment::Literal(
+                "/users/{{.args.user-id}}".to_string()
+            )])
+        );
+    }
 }
@@ -38,7 +38,7 @@ impl PathString for serde_json::Value {
     }
 }
 
-fn convert_value(value: Cow<'_, async_graphql::Value>) -> Option<Cow<'_, str>> {
+pub(crate) fn convert_value(value: Cow<'_, async_graphql::Value>) -> Option<Cow<'_, str>> {
     match value {
         Cow::Owned(async_graphql::Value::String(s)) => Some(Cow::Owned(s)),
         Cow::Owned(async_graphql::Value::Number(n)) => Some(Cow::Owned(n.to_string())),
@@ -94,6 +94,7 @@ impl<Ctx: ResolverContextLike> EvalContext<'_, Ctx> {
                     ctx.var(tail[0].as_ref())?,
                 ))),
                 "env" => Some(ValueString::String(ctx.env_var(tail[0].as_ref())?)),
+                "secret" => Some(ValueString::String(ctx.secret(tail[0].as_ref())?)),
                 _ => None,
             })
     }
@@ -143,6 +144,7 @@ mod tests {
         use crate::core::http::RequestContext;
         use crate::core::ir::{EvalContext, ResolverContextLike, SelectionField};
         use crate::core::path::{PathGraphql, PathString, PathValue, ValueString};
+        use crate::core::tests::TestSecretIO;
         use crate::core::EnvIO;
 
         struct Env {
@@ -244,6 +246,10 @@ mod tests {
 
             req_ctx.server.vars = TEST_VARS.clone();
             req_ctx.runtime.env = Arc::new(Env::init(TEST_ENV_VARS.clone()));
+            req_ctx.runtime.secret = Arc::new(TestSecretIO::from_iter([(
+                "existing".to_owned(),
+                "secret".to_owned(),
+            )]));
 
             req_ctx
         });
@@ -450,6 +456,13 @@ mod tests {
             );
             assert_eq!(EVAL_CTX.path_string(&["env", "x-missing"]), None);
 
+            // secrets
+            assert_eq!(
+                EVAL_CTX.path_string(&["secret", "existing"]),
+                Some(Cow::Borrowed("secret"))
+            );
+            assert_eq!(EVAL_CTX.path_string(&["secret", "x-missing"]), None);
+
             // other value types
             assert_eq!(EVAL_CTX.path_string(&["foo", "key"]), None);
             assert_eq!(EVAL_CTX.path_string(&["bar", "key"]), None);
@@ -167,6 +167,8 @@ impl GrpcReflection {
             body: body_,
             operation: operation.clone(),
             operation_type: Default::default(),
+            schemas: None,
+            protocol: Default::default(),
         };
 
         let ctx = ConfigReaderContext::new(&self.target_runtime);
@@ -1,6 +1,6 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Context;
 use futures_util::future::{join_all, BoxFuture};
@@ -17,18 +17,24 @@ use crate::core::runtime::TargetRuntime;
 pub struct ProtoReader {
     reader: ResourceReader<Cached>,
     runtime: TargetRuntime,
+    /// Collects the content of the files read, when set.
+    sources: Option<Arc<Mutex<BTreeMap<String, String>>>>,
 }
 
 #[derive(Clone)]
 pub struct ProtoMetadata {
     pub descriptor_set: FileDescriptorSet,
     pub path: String,
+    /// The content of the proto file and of the files it imports, keyed by
+    /// their resolved path. Well-known google files and files fetched with
+    /// reflection aren't included.
+    pub sources: BTreeMap<String, String>,
 }
 
 impl ProtoReader {
     /// Initializes the proto reader with a resource reader and target runtime
     pub fn init(reader: ResourceReader<Cached>, runtime: TargetRuntime) -> Self {
-        Self { reader, runtime }
+        Self { reader, runtime, sources: None }
     }
 
     /// Fetches proto files from a grpc server (grpc reflection)
@@ -57,6 +63,7 @@ impl ProtoReader {
             let metadata = ProtoMetadata {
                 descriptor_set: FileDescriptorSet { file: descriptors },
                 path: url.as_ref().to_string(),
+                sources: Default::default(),
             };
             proto_metadata.push(metadata);
         }
@@ -78,19 +85,23 @@ impl ProtoReader {
         path: T,
         proto_paths: Option<&[String]>,
     ) -> anyhow::Result<ProtoMetadata> {
-        let file_read = self.read_proto(path.as_ref(), None, None).await?;
+        let sources = Arc::new(Mutex::new(BTreeMap::new()));
+        let reader = Self { sources: Some(sources.clone()), ..self.clone() };
+        let file_read = reader.read_proto(path.as_ref(), None, None).await?;
         Self::check_package(&file_read)?;
 
-        let descriptors = self
+        let descriptors = reader
             .file_resolve(
                 file_read,
                 PathBuf::from(path.as_ref()).parent(),
                 proto_paths,
             )
             .await?;
+        let sources = std::mem::take(&mut *sources.lock().unwrap());
         let metadata = ProtoMetadata {
             descriptor_set: FileDescriptorSet { file: descriptors },
             path: path.as_ref().to_string(),
+            sources,
         };
         Ok(metadata)
     }
@@ -185,7 +196,14 @@ impl ProtoReader {
                 .to_string()
         } else {
             let path = Self::resolve_path(path.as_ref(), parent_dir, proto_paths);
-            self.reader.read_file(path).await?.content
+            let file = self.reader.read_file(path).await?;
+            if let Some(sources) = &self.sources {
+                sources
+                    .lock()
+                    .unwrap()
+                    .insert(file.path, file.content.clone());
+            }
+            file.content
         };
         Ok(protox_parse::parse(path.as_ref(), &content)?)
     }
@@ -4,19 +4,24 @@ use std::sync::{Arc, Mutex};
 
 use futures_util::future::join_all;
 use futures_util::TryFutureExt;
+use hyper::body::Bytes;
+use hyper::header::CONTENT_TYPE;
 use tailcall_hasher::TailcallHasher;
 use url::Url;
 
+use crate::core::http::Response;
 use crate::core::mustache::PathStringEval;
 use crate::core::path::PathString;
 use crate::core::runtime::TargetRuntime;
-use crate::core::Mustache;
+use crate::core::{EnvIO, Mustache};
 
 /// Response of a file read operation
 #[derive(Debug)]
 pub struct FileRead {
     pub content: String,
     pub path: String,
+    /// The `Content-Type` the file was served with, when read over HTTP.
+    pub content_type: Option<String>,
 }
 
 impl FileRead {
@@ -127,15 +132,36 @@ impl std::fmt::Display for Resource {
     }
 }
 
+/// Resolves URLs of a custom scheme such as `s3://` to the HTTP request that
+/// fetches their content.
+pub trait SchemeHandler: Send + Sync {
+    fn request(&self, url: &Url, env: &dyn EnvIO) -> anyhow::Result<reqwest::Request>;
+}
+
 /// Reads the files directly from the filesystem or from an HTTP URL
 #[derive(Clone)]
 pub struct Direct {
     runtime: TargetRuntime,
+    schemes: HashMap<String, Arc<dyn SchemeHandler>>,
 }
 
 impl Direct {
     pub fn init(runtime: TargetRuntime) -> Self {
-        Self { runtime }
+        #[cfg(feature = "object-storage")]
+        let schemes = crate::core::object_storage::handlers()
+            .into_iter()
+            .map(|(scheme, handler)| (scheme.to_string(), handler))
+            .collect();
+        #[cfg(not(feature = "object-storage"))]
+        let schemes = HashMap::new();
+
+        Self { runtime, schemes }
+    }
+
+    /// Registers the handler for the URLs of the given scheme
+    pub fn with_scheme(mut self, scheme: &str, handler: Arc<dyn SchemeHandler>) -> Self {
+        self.schemes.insert(scheme.to_string(), handler);
+        self
     }
 }
 
@@ -154,37 +180,55 @@ impl Reader for Direct {
                             .execute(reqwest::Request::new(reqwest::Method::GET, url))
                             .await?;
 
+                        let content_type = content_type(&response);
                         let content = String::from_utf8(response.body.to_vec())?;
-                        FileRead { path: file_path, content }
+                        FileRead { path: file_path, content, content_type }
+                    } else if let Some(handler) = self.schemes.get(url.scheme()) {
+                        // Is a URL of a registered scheme
+                        let request = handler.request(&url, self.runtime.env.as_ref())?;
+                        let response = self.runtime.http.execute(request).await?;
+
+                        let content_type = content_type(&response);
+                        let content = String::from_utf8(response.body.to_vec())?;
+                        FileRead { path: file_path, content, content_type }
                     } else {
                         // Is a file path on Windows
                         let content = self.runtime.file.read(&file_path).await?;
-                        FileRead { path: file_path, content }
+                        FileRead { path: file_path, content, content_type: None }
                     }
                 } else {
                     // Is a file path
                     let content = self.runtime.file.read(&file_path).await?;
-                    FileRead { path: file_path, content }
+                    FileRead { path: file_path, content, content_type: None }
                 }
             }
             Resource::Request(request) => {
                 let request_url = request.url().to_string();
                 let response = self.runtime.http.execute(request).await?;
+                let content_type = content_type(&response);
                 let content = String::from_utf8(response.body.to_vec())?;
 
-                FileRead { path: request_url, content }
+                FileRead { path: request_url, content, content_type }
             }
         };
         Ok(content)
     }
 }
 
+fn content_type(response: &Response<Bytes>) -> Option<String> {
+    response
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
 /// Reads the files from the filesystem or from an HTTP URL with cache
 #[derive(Clone)]
 pub struct Cached {
     direct: Direct,
-    // Cache file content, path -> content
-    cache: Arc<Mutex<HashMap<String, String>>>,
+    // Cache file content, path -> content and content type
+    cache: Arc<Mutex<HashMap<String, (String, Option<String>)>>>,
 }
 
 impl Cached {
@@ -207,19 +251,20 @@ impl Reader for Cached {
             .unwrap()
             .get(&resource.calculate_hash().to_string())
             .map(|v| v.to_owned());
-        let content = if let Some(content) = content {
+        let (content, content_type) = if let Some(content) = content {
             content.to_owned()
         } else {
             let file_read = self.direct.read(resource).await?;
+            let content = (file_read.content, file_read.content_type);
             self.cache
                 .as_ref()
                 .lock()
                 .unwrap()
-                .insert(file_path.to_owned(), file_read.content.clone());
-            file_read.content
+                .insert(file_path.to_owned(), content.clone());
+            content
         };
 
-        Ok(FileRead { content, path: file_path })
+        Ok(FileRead { content, path: file_path, content_type })
     }
 }
 
@@ -4,7 +4,9 @@ use async_graphql::parser::types::Directive;
 use async_graphql_value::Value;
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
+use tailcall_valid::ValidationError;
 
+use super::json_api::{Format, Resource};
 use super::{Error, Result};
 use crate::core::http::Method;
 use crate::core::is_default;
@@ -15,12 +17,71 @@ use crate::core::is_default;
 #[derive(Default, Debug, Deserialize, Serialize, PartialEq, Setters)]
 pub(crate) struct Rest {
     pub path: String,
+    /// Prefix of the `path`, so that related endpoints are grouped under a
+    /// common path, e.g. `@rest(group: "v1", path: "/users")` is served at
+    /// `/v1/users`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub group: Option<String>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub method: Option<Method>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub query: BTreeMap<String, String>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub envelope: Option<Envelope>,
+    /// `jsonapi` formats the response as a JSON:API document, in place of the
+    /// envelope.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub format: Option<Format>,
+    /// The resource settings of the JSON:API format, keyed by GraphQL type.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub resources: BTreeMap<String, Resource>,
+}
+
+/// Keys of the envelope wrapping the response of a REST endpoint, as in
+/// `{"data": {...}, "errors": [...]}`. Set with `@rest(envelope: true)`, or
+/// `@rest(envelope: {data: "result", errors: "problems", meta: "meta"})` to
+/// rename the keys.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Envelope {
+    /// key of the flattened response data.
+    pub data: String,
+    /// key of the list of GraphQL errors.
+    pub errors: String,
+    /// key of the response extensions, left out of the envelope when unset.
+    pub meta: Option<String>,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            data: "data".to_string(),
+            errors: "errors".to_string(),
+            meta: None,
+        }
+    }
+}
+
+impl Rest {
+    /// The `path` prefixed with the `group`, if any.
+    pub fn full_path(&self) -> String {
+        match &self.group {
+            Some(group) => format!(
+                "/{}/{}",
+                group.trim_matches('/'),
+                self.path.trim_start_matches('/')
+            ),
+            None => self.path.clone(),
+        }
+    }
+}
+
+fn invalid_envelope(value: &Value) -> Error {
+    Error::Validation(ValidationError::new(format!(
+        "Invalid envelope: {value}, expected a boolean or an object of keys"
+    )))
 }
 
 impl TryFrom<&Directive> for Rest {
@@ -38,6 +99,9 @@ impl TryFrom<&Directive> for Rest {
                     rest.path = serde_json::from_str(v.node.to_string().as_str())?;
                     has_path = true;
                 }
+                "group" => {
+                    rest.group = serde_json::from_str(v.node.to_string().as_str())?;
+                }
                 "method" => {
                     let value = serde_json::Value::String(v.node.to_string().to_uppercase());
                     rest.method = serde_json::from_value(value)?;
@@ -63,6 +127,28 @@ impl TryFrom<&Directive> for Rest {
                         rest.body = Some(v.to_string());
                     }
                 }
+                "envelope" => {
+                    rest.envelope = match &v.node {
+                        Value::Boolean(enabled) => enabled.then(Envelope::default),
+                        Value::Object(_) => {
+                            let value = v
+                                .node
+                                .clone()
+                                .into_const()
+                                .ok_or_else(|| invalid_envelope(&v.node))?;
+                            Some(serde_json::from_value(value.into_json()?)?)
+                        }
+                        value => return Err(invalid_envelope(value)),
+                    };
+                }
+                "format" => {
+                    let value = serde_json::Value::String(v.node.to_string());
+                    rest.format = serde_json::from_value(value)?;
+                }
+                "resources" => {
+                    let value = v.node.clone().into_const().unwrap_or_default();
+                    rest.resources = serde_json::from_value(value.into_json()?)?;
+                }
                 _ => {}
             };
         }
@@ -215,6 +301,82 @@ mod tests {
         pretty_assertions::assert_eq!(directives, vec![true; 5]);
     }
 
+    #[test]
+    fn test_directive_to_rest_envelope() {
+        let directives = [
+            "method: GET, path: \"/foo\", envelope: true",
+            "method: GET, path: \"/foo\", envelope: false",
+            "method: GET, path: \"/foo\", envelope: {data: \"result\", meta: \"meta\"}",
+        ]
+        .iter()
+        .map(|query| generate_query_with_directive(query, DEFAULT_QUERY_PARAM))
+        .map(|query| {
+            Rest::try_from(&query_to_directive(&query))
+                .unwrap()
+                .envelope
+        })
+        .collect::<Vec<_>>();
+
+        let custom = Envelope {
+            data: "result".to_string(),
+            errors: "errors".to_string(),
+            meta: Some("meta".to_string()),
+        };
+        pretty_assertions::assert_eq!(
+            directives,
+            vec![Some(Envelope::default()), None, Some(custom)]
+        );
+    }
+
+    #[test]
+    fn test_directive_to_rest_invalid_envelope() {
+        let errors = [
+            "method: GET, path: \"/foo\", envelope: \"yes\"",
+            "method: GET, path: \"/foo\", envelope: {data: $data}",
+        ]
+        .iter()
+        .map(|query| generate_query_with_directive(query, DEFAULT_QUERY_PARAM))
+        .map(|query| {
+            Rest::try_from(&query_to_directive(&query))
+                .unwrap_err()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+
+        assert!(errors[0].contains("Invalid envelope: \"yes\""));
+        assert!(errors[1].contains("Invalid envelope: {data: $data}"));
+    }
+
+    #[test]
+    fn test_directive_to_rest_json_api() {
+        let query = generate_query_with_directive(
+            "method: GET, path: \"/foo\", format: jsonapi, resources: {User: {type: \"people\"}}",
+            DEFAULT_QUERY_PARAM,
+        );
+        let rest = Rest::try_from(&query_to_directive(&query)).unwrap();
+
+        pretty_assertions::assert_eq!(rest.format, Some(Format::JsonApi));
+        pretty_assertions::assert_eq!(
+            rest.resources,
+            BTreeMap::from([(
+                "User".to_string(),
+                Resource { type_name: Some("people".to_string()), id: "id".to_string() }
+            )])
+        );
+    }
+
+    #[test]
+    fn test_directive_to_rest_group() {
+        let query = generate_query_with_directive(
+            "method: GET, group: \"v1\", path: \"/foo\"",
+            DEFAULT_QUERY_PARAM,
+        );
+        let rest = Rest::try_from(&query_to_directive(&query)).unwrap();
+
+        pretty_assertions::assert_eq!(rest.group, Some("v1".to_string()));
+        pretty_assertions::assert_eq!(rest.full_path(), "/v1/foo");
+    }
+
     #[test]
     #[should_panic(expected = "Path not provided in the directive")]
     fn directive_to_rest_without_path() {
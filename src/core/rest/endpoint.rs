@@ -1,20 +1,27 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
-use async_graphql::parser::types::{Directive, DocumentOperations, ExecutableDocument};
+use async_graphql::parser::types::{
+    BaseType, Directive, DocumentOperations, ExecutableDocument, OperationType, Selection,
+    SelectionSet,
+};
 use async_graphql::{Positioned, Variables};
-use async_graphql_value::{ConstValue, Name};
+use async_graphql_value::{ConstValue, Name, Value};
 use derive_setters::Setters;
 
-use super::directive::Rest;
+use super::directive::{Envelope, Rest};
+use super::json_api::{Format, JsonApi};
 use super::partial_request::PartialRequest;
 use super::path::{Path, Segment};
 use super::query_params::QueryParams;
 use super::type_map::TypeMap;
 use super::{Request, Result};
 use crate::core::async_graphql_hyper::GraphQLRequest;
+use crate::core::blueprint::{Index, QueryField};
 use crate::core::directive::DirectiveCodec;
 use crate::core::http::Method;
+use crate::core::json::JsonSchema;
 use crate::core::rest::typed_variables::{UrlParamType, N};
+use crate::core::Type;
 
 /// An executable Http Endpoint created from a GraphQL query
 #[derive(Debug, Setters, Clone)]
@@ -25,6 +32,13 @@ pub struct Endpoint {
     // Can use persisted queries for better performance
     query_params: QueryParams,
     body: Option<String>,
+    envelope: Option<Envelope>,
+    json_api: Option<JsonApi>,
+    // Set once the endpoint is checked against the blueprint
+    body_schema: Option<JsonSchema>,
+    // The argument the body is streamed to, set once the endpoint is checked
+    // against the blueprint
+    streamed_arg: Option<String>,
     pub doc: ExecutableDocument,
 }
 
@@ -66,15 +80,25 @@ impl Endpoint {
 
             if let Some(rest) = rest {
                 let rest = rest?;
+                let json_api =
+                    (rest.format == Some(Format::JsonApi)).then(|| JsonApi::new(rest.resources));
+                let mut endpoint_doc = ExecutableDocument {
+                    operations: DocumentOperations::Single(op.clone()),
+                    fragments: doc.fragments.clone(),
+                };
+                if json_api.is_some() {
+                    JsonApi::select_typename(&mut endpoint_doc);
+                }
                 let endpoint = Self {
                     method: rest.method.unwrap_or_default(),
-                    path: Path::parse(&type_map, &rest.path)?,
+                    path: Path::parse(&type_map, &rest.full_path())?,
                     query_params: QueryParams::try_from_map(&type_map, rest.query)?,
                     body: rest.body,
-                    doc: ExecutableDocument {
-                        operations: DocumentOperations::Single(op.clone()),
-                        fragments: doc.fragments.clone(),
-                    },
+                    envelope: rest.envelope,
+                    json_api,
+                    body_schema: None,
+                    streamed_arg: None,
+                    doc: endpoint_doc,
                 };
                 endpoints.push(endpoint);
             }
@@ -96,7 +120,9 @@ impl Endpoint {
             match segment {
                 Segment::Literal(_) => {}
                 Segment::Param(p) => {
-                    if !p.nullable() {
+                    if p.is_list() {
+                        variables.insert(Name::new(p.name()), ConstValue::List(Vec::new()));
+                    } else if !p.nullable() {
                         let default_value = match p.ty() {
                             UrlParamType::String => ConstValue::String(String::new()),
                             UrlParamType::Number(n) => match n {
@@ -131,17 +157,87 @@ impl Endpoint {
         doc
     }
 
+    /// Creates the schema that the body of a request is validated against,
+    /// from the declared type of the body variable.
+    pub fn to_body_schema(&self, index: &Index) -> Option<JsonSchema> {
+        let name = self.body.as_ref()?;
+        let type_of = self.doc.operations.iter().find_map(|(_, operation)| {
+            operation
+                .node
+                .variable_definitions
+                .iter()
+                .find(|variable| variable.node.name.node == name)
+                .map(|variable| Type::from(&variable.node.var_type.node))
+        })?;
+
+        Some(to_json_schema(&type_of, index, &mut HashSet::new()))
+    }
+
+    /// Finds the argument the body can be streamed to instead of being
+    /// buffered. The body variable has to be an `Upload` that is only passed to
+    /// the single root field of the operation, whose `@http` sends the argument
+    /// as is.
+    pub fn to_streamed_arg(&self, index: &Index) -> Option<String> {
+        let name = self.body.as_ref()?;
+        let (_, operation) = self.doc.operations.iter().next()?;
+        let operation = &operation.node;
+
+        let is_upload = operation.variable_definitions.iter().any(|variable| {
+            let variable = &variable.node;
+            variable.name.node == name
+                && matches!(
+                    &variable.var_type.node.base,
+                    BaseType::Named(type_name) if type_name == "Upload"
+                )
+        });
+        if !is_upload || count_variable(&self.doc, name) != 1 {
+            return None;
+        }
+
+        let [selection] = operation.selection_set.node.items.as_slice() else {
+            return None;
+        };
+        let Selection::Field(field) = &selection.node else {
+            return None;
+        };
+        let field = &field.node;
+        let arg = field.arguments.iter().find_map(|(arg, value)| {
+            matches!(&value.node, Value::Variable(variable) if variable.as_str() == name)
+                .then_some(arg.node.as_str())
+        })?;
+
+        let root = match operation.ty {
+            OperationType::Query => index.get_query().as_str(),
+            OperationType::Mutation => index.get_mutation()?,
+            OperationType::Subscription => return None,
+        };
+        match index.get_field(root, &field.name.node)? {
+            QueryField::Field((definition, _)) => definition
+                .resolver
+                .as_ref()?
+                .forwarded_arg()
+                .filter(|forwarded| *forwarded == arg)
+                .map(|forwarded| forwarded.to_string()),
+            QueryField::InputField(_) => None,
+        }
+    }
+
     fn drop_rest_directive(directives: &mut Vec<Positioned<Directive>>) {
         let name = Name::new("rest");
         directives.retain(|v| v.node.name.node != name)
     }
 
     pub fn matches<'a>(&'a self, request: &Request) -> Option<PartialRequest<'a>> {
-        let query_params = request
-            .uri()
-            .query()
-            .map(|query| serde_urlencoded::from_str(query).unwrap_or_else(|_| BTreeMap::new()))
-            .unwrap_or_default();
+        // Repeated keys keep all their values, for the list variables
+        let mut query_params: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        if let Some(query) = request.uri().query() {
+            for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+                query_params
+                    .entry(key.into_owned())
+                    .or_default()
+                    .push(value.into_owned());
+            }
+        }
 
         let mut variables = Variables::default();
 
@@ -162,6 +258,10 @@ impl Endpoint {
 
         Some(PartialRequest {
             body: self.body.as_ref(),
+            envelope: self.envelope.as_ref(),
+            json_api: self.json_api.as_ref(),
+            body_schema: self.body_schema.as_ref(),
+            streamed_arg: self.streamed_arg.as_ref(),
             doc: &self.doc,
             variables,
             path: &self.path,
@@ -169,6 +269,103 @@ impl Endpoint {
     }
 }
 
+/// Counts the references to a variable in the selections of the operations
+/// and fragments of the document.
+fn count_variable(doc: &ExecutableDocument, name: &str) -> usize {
+    let operations = doc
+        .operations
+        .iter()
+        .map(|(_, operation)| count_in_selection_set(&operation.node.selection_set.node, name));
+    let fragments = doc.fragments.values().map(|fragment| {
+        count_in_directives(&fragment.node.directives, name)
+            + count_in_selection_set(&fragment.node.selection_set.node, name)
+    });
+
+    operations.chain(fragments).sum()
+}
+
+fn count_in_selection_set(selection_set: &SelectionSet, name: &str) -> usize {
+    selection_set
+        .items
+        .iter()
+        .map(|selection| match &selection.node {
+            Selection::Field(field) => {
+                let field = &field.node;
+                field
+                    .arguments
+                    .iter()
+                    .map(|(_, value)| count_in_value(&value.node, name))
+                    .sum::<usize>()
+                    + count_in_directives(&field.directives, name)
+                    + count_in_selection_set(&field.selection_set.node, name)
+            }
+            Selection::FragmentSpread(spread) => count_in_directives(&spread.node.directives, name),
+            Selection::InlineFragment(fragment) => {
+                count_in_directives(&fragment.node.directives, name)
+                    + count_in_selection_set(&fragment.node.selection_set.node, name)
+            }
+        })
+        .sum()
+}
+
+fn count_in_directives(directives: &[Positioned<Directive>], name: &str) -> usize {
+    directives
+        .iter()
+        .flat_map(|directive| directive.node.arguments.iter())
+        .map(|(_, value)| count_in_value(&value.node, name))
+        .sum()
+}
+
+fn count_in_value(value: &Value, name: &str) -> usize {
+    match value {
+        Value::Variable(variable) => usize::from(variable.as_str() == name),
+        Value::List(items) => items.iter().map(|item| count_in_value(item, name)).sum(),
+        Value::Object(fields) => fields
+            .values()
+            .map(|field| count_in_value(field, name))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Creates the schema of the values of an input type. An input type that
+/// refers to itself accepts any value from the second level of nesting on.
+fn to_json_schema(type_of: &Type, index: &Index, visited: &mut HashSet<String>) -> JsonSchema {
+    let schema = match type_of {
+        Type::List { of_type, .. } => {
+            JsonSchema::Arr(Box::new(to_json_schema(of_type, index, visited)))
+        }
+        Type::Named { name, .. } => match index.get_input_type_definition(name) {
+            Some(input) if visited.insert(name.clone()) => {
+                let fields = input
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let schema = to_json_schema(&field.of_type, index, visited);
+                        // Fields with a default value can be left out
+                        let schema = if field.default_value.is_some() && schema.is_required() {
+                            schema.optional()
+                        } else {
+                            schema
+                        };
+                        (field.name.clone(), schema)
+                    })
+                    .collect();
+                visited.remove(name);
+                JsonSchema::Obj(fields)
+            }
+            Some(_) => JsonSchema::Any,
+            None => JsonSchema::from_scalar_type(name),
+        },
+    };
+
+    if type_of.is_nullable() {
+        schema.optional()
+    } else {
+        schema
+    }
+}
+
 fn merge_variables(a: Variables, b: Variables) -> Variables {
     let mut variables = Variables::default();
 
@@ -351,6 +548,44 @@ mod tests {
             pretty_assertions::assert_eq!(actual, None)
         }
 
+        #[test]
+        fn test_float_url_param() {
+            let query = r#"
+                query ($a: Float) @rest(method: GET, path: "/foo/$a") {
+                    value
+                }
+            "#;
+            let actual = test_matches(query, Method::GET, "http://localhost:8080/foo/12.5");
+            let expected = &btreemap! {
+                Name::new("a") => ConstValue::from(12.5),
+            };
+            assert_eq!(actual.as_deref(), Some(expected));
+        }
+
+        #[test]
+        fn test_large_int_url_param() {
+            let query = r#"
+                query ($a: Int) @rest(method: GET, path: "/foo/$a") {
+                    value
+                }
+            "#;
+            let actual = test_matches(
+                query,
+                Method::GET,
+                &format!("http://localhost:8080/foo/{}", u64::MAX),
+            );
+            let expected = &btreemap! {
+                Name::new("a") => ConstValue::from(u64::MAX),
+            };
+            assert_eq!(actual.as_deref(), Some(expected));
+
+            let actual = test_matches(query, Method::GET, "http://localhost:8080/foo/-1");
+            let expected = &btreemap! {
+                Name::new("a") => ConstValue::from(-1),
+            };
+            assert_eq!(actual.as_deref(), Some(expected));
+        }
+
         #[test]
         fn test_query_params_optional() {
             let actual = test_matches(TEST_QUERY, Method::POST, "http://localhost:8080/foo/1");
@@ -367,6 +602,29 @@ mod tests {
             pretty_assertions::assert_eq!(actual.as_deref(), Some(expected))
         }
 
+        #[test]
+        fn test_list_query_param() {
+            let query = r#"
+                query ($ids: [Int]) @rest(method: GET, path: "/foo", query: {ids: $ids}) {
+                    value
+                }
+            "#;
+            let actual = test_matches(query, Method::GET, "http://localhost:8080/foo?ids=1&ids=2");
+            let expected = &btreemap! {
+                Name::new("ids") => ConstValue::List(vec![ConstValue::from(1), ConstValue::from(2)]),
+            };
+            assert_eq!(actual.as_deref(), Some(expected));
+
+            let actual = test_matches(query, Method::GET, "http://localhost:8080/foo?ids=1");
+            let expected = &btreemap! {
+                Name::new("ids") => ConstValue::List(vec![ConstValue::from(1)]),
+            };
+            assert_eq!(actual.as_deref(), Some(expected));
+
+            let actual = test_matches(query, Method::GET, "http://localhost:8080/foo?ids=1&ids=a");
+            assert_eq!(actual, None);
+        }
+
         #[test]
         fn test_invalid_query_param() {
             let actual = test_matches(
@@ -386,5 +644,260 @@ mod tests {
             );
             assert_eq!(actual, None)
         }
+
+        #[test]
+        fn test_group() {
+            let query = r#"
+                query ($a: Int) @rest(method: GET, group: "v1", path: "/foo/$a") {
+                    value
+                }
+            "#;
+            let endpoint = &Endpoint::try_new(query).unwrap()[0];
+            assert_eq!(endpoint.get_path().as_str(), "/v1/foo/$a");
+
+            let actual = test_matches(query, Method::GET, "http://localhost:8080/v1/foo/1");
+            let expected = &btreemap! {
+                Name::new("a") => ConstValue::from(1),
+            };
+            assert_eq!(actual.as_deref(), Some(expected));
+
+            let actual = test_matches(query, Method::GET, "http://localhost:8080/foo/1");
+            assert_eq!(actual, None);
+        }
+    }
+
+    mod body {
+        use async_graphql_value::ConstValue;
+        use http::{Method, Request};
+        use hyper::Body;
+        use serde_json::json;
+        use tailcall_valid::Validator;
+
+        use crate::core::async_graphql_hyper::GraphQLRequest;
+        use crate::core::blueprint::Blueprint;
+        use crate::core::config::{Config, ConfigModule};
+        use crate::core::http::RequestContext;
+        use crate::core::rest::endpoint::Endpoint;
+        use crate::core::rest::{Error, Result};
+        use crate::core::runtime::test::init;
+
+        const SCHEMA: &str = r#"
+            schema {
+              query: Query
+              mutation: Mutation
+            }
+
+            input PostInput {
+              title: String!
+              userId: Int!
+            }
+
+            type Query {
+              post(id: Int!): Post @http(url: "http://jsonplaceholder.typicode.com/posts/{{.args.id}}")
+            }
+
+            type Mutation {
+              createPost(input: PostInput): Post
+                @http(url: "http://jsonplaceholder.typicode.com/posts", method: POST, body: "{{.args.input}}")
+            }
+
+            type Post {
+              id: Int!
+              title: String!
+            }
+        "#;
+
+        async fn into_request(query: &str, body: &str) -> Result<GraphQLRequest> {
+            let config = Config::from_sdl(SCHEMA).to_result().unwrap();
+            let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+            let endpoint = Endpoint::try_new(query).unwrap().remove(0);
+            let body_schema = endpoint.to_body_schema(&blueprint.index());
+            let endpoint = endpoint.body_schema(body_schema);
+
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri("http://localhost:8080/posts")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let partial_request = endpoint.matches(&request).unwrap();
+
+            partial_request
+                .into_request(request, &RequestContext::new(init(None)))
+                .await
+        }
+
+        const REQUIRED_BODY: &str = r#"
+            mutation ($input: PostInput!) @rest(method: POST, path: "/posts", body: $input) {
+                createPost(input: $input) { id }
+            }
+        "#;
+
+        const NULLABLE_BODY: &str = r#"
+            mutation ($input: PostInput) @rest(method: POST, path: "/posts", body: $input) {
+                createPost(input: $input) { id }
+            }
+        "#;
+
+        #[tokio::test]
+        async fn test_valid_body() {
+            let request = into_request(REQUIRED_BODY, r#"{"title": "foo", "userId": 1}"#)
+                .await
+                .unwrap();
+            let input = request.0.variables.get("input");
+            let expected = ConstValue::from_json(json!({"title": "foo", "userId": 1})).unwrap();
+
+            assert_eq!(input, Some(&expected));
+        }
+
+        #[tokio::test]
+        async fn test_missing_required_field() {
+            let actual = into_request(REQUIRED_BODY, r#"{"title": "foo"}"#).await;
+
+            assert!(matches!(
+                actual,
+                Err(Error::InvalidBody(message))
+                    if message == "expected field to be non-nullable [userId]"
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_array_instead_of_object() {
+            let actual = into_request(REQUIRED_BODY, r#"[{"title": "foo", "userId": 1}]"#).await;
+
+            assert!(matches!(actual, Err(Error::InvalidBody(_))));
+        }
+
+        #[tokio::test]
+        async fn test_malformed_body() {
+            let actual = into_request(REQUIRED_BODY, r#"{"title": "foo","#).await;
+
+            assert!(matches!(actual, Err(Error::InvalidBody(_))));
+        }
+
+        #[tokio::test]
+        async fn test_empty_body() {
+            let request = into_request(NULLABLE_BODY, "").await.unwrap();
+            let input = request.0.variables.get("input");
+
+            assert_eq!(input, Some(&ConstValue::Null));
+
+            let actual = into_request(REQUIRED_BODY, "").await;
+
+            assert!(matches!(actual, Err(Error::InvalidBody(_))));
+        }
+    }
+
+    mod streamed_arg {
+        use tailcall_valid::Validator;
+
+        use crate::core::blueprint::Blueprint;
+        use crate::core::config::{Config, ConfigModule};
+        use crate::core::rest::endpoint::Endpoint;
+
+        const SCHEMA: &str = r#"
+            schema {
+              query: Query
+              mutation: Mutation
+            }
+
+            type Query {
+              file(id: Int!): File @http(url: "http://localhost:3000/files/{{.args.id}}")
+            }
+
+            type Mutation {
+              upload(file: Upload, name: Upload): File
+                @http(url: "http://localhost:3000/files", method: POST, body: "{{.args.file}}")
+              wrap(file: Upload): File
+                @http(url: "http://localhost:3000/files", method: POST, body: "{\"file\": {{.args.file}}}")
+            }
+
+            type File {
+              id: Int!
+            }
+        "#;
+
+        fn streamed_arg(operation: &str) -> Option<String> {
+            let config = Config::from_sdl(SCHEMA).to_result().unwrap();
+            let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+            let endpoint = Endpoint::try_new(operation).unwrap().remove(0);
+
+            endpoint.to_streamed_arg(&blueprint.index())
+        }
+
+        #[test]
+        fn test_forwarded_upload() {
+            let actual = streamed_arg(
+                r#"
+                mutation ($file: Upload) @rest(method: POST, path: "/files", body: $file) {
+                    upload(file: $file) { id }
+                }
+                "#,
+            );
+
+            assert_eq!(actual, Some("file".to_string()));
+        }
+
+        #[test]
+        fn test_upload_to_another_arg() {
+            let actual = streamed_arg(
+                r#"
+                mutation ($file: Upload) @rest(method: POST, path: "/files", body: $file) {
+                    upload(name: $file) { id }
+                }
+                "#,
+            );
+
+            assert_eq!(actual, None);
+        }
+
+        #[test]
+        fn test_transformed_upload() {
+            let actual = streamed_arg(
+                r#"
+                mutation ($file: Upload) @rest(method: POST, path: "/files", body: $file) {
+                    wrap(file: $file) { id }
+                }
+                "#,
+            );
+
+            assert_eq!(actual, None);
+        }
+
+        #[test]
+        fn test_upload_used_twice() {
+            let actual = streamed_arg(
+                r#"
+                mutation ($file: Upload) @rest(method: POST, path: "/files", body: $file) {
+                    upload(file: $file, name: $file) { id }
+                }
+                "#,
+            );
+
+            assert_eq!(actual, None);
+
+            let actual = streamed_arg(
+                r#"
+                mutation ($file: Upload) @rest(method: POST, path: "/files", body: $file) {
+                    upload(file: $file) { id }
+                    copy: upload(file: $file) { id }
+                }
+                "#,
+            );
+
+            assert_eq!(actual, None);
+        }
+
+        #[test]
+        fn test_not_an_upload() {
+            let actual = streamed_arg(
+                r#"
+                mutation ($file: JSON) @rest(method: POST, path: "/files", body: $file) {
+                    upload(file: $file) { id }
+                }
+                "#,
+            );
+
+            assert_eq!(actual, None);
+        }
     }
 }
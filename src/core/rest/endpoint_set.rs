@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use tailcall_valid::Validator;
+use tailcall_valid::{Valid, Validator};
 
 use super::endpoint::Endpoint;
 use super::partial_request::PartialRequest;
@@ -72,18 +72,116 @@ impl EndpointSet<Unchecked> {
             let operation_qry = OperationQuery::new(req, req_ctx.clone())?;
             operations.push(operation_qry);
         }
-        super::operation::validate_operations(blueprint, operations)
-            .await
+        validate_collisions(&self.endpoints)
+            .and(super::operation::validate_operations(blueprint, operations).await)
             .to_result()?;
-        Ok(EndpointSet {
-            marker: std::marker::PhantomData::<Checked>,
-            endpoints: self.endpoints,
-        })
+
+        let index = blueprint.index();
+        let mut endpoints: Vec<_> = self
+            .endpoints
+            .into_iter()
+            .map(|endpoint| {
+                let body_schema = endpoint.to_body_schema(&index);
+                let streamed_arg = endpoint.to_streamed_arg(&index);
+                endpoint.body_schema(body_schema).streamed_arg(streamed_arg)
+            })
+            .collect();
+        // a request matching both `/user/me` and `/user/$id` goes to the former
+        endpoints.sort_by_cached_key(|endpoint| endpoint.get_path().params());
+
+        Ok(EndpointSet { marker: std::marker::PhantomData::<Checked>, endpoints })
     }
 }
 
+/// Checks that no request could be matched by more than one endpoint, since
+/// the match would then depend on the order of the operations.
+fn validate_collisions(endpoints: &[Endpoint]) -> Valid<(), String> {
+    Valid::from_iter(endpoints.iter().enumerate(), |(i, a)| {
+        Valid::from_iter(endpoints.iter().skip(i + 1), |b| {
+            if a.get_method() == b.get_method() && a.get_path().overlaps(b.get_path()) {
+                Valid::fail(format!(
+                    "`{} {}` collides with `{} {}`",
+                    a.get_method(),
+                    a.get_path().as_str(),
+                    b.get_method(),
+                    b.get_path().as_str()
+                ))
+            } else {
+                Valid::succeed(())
+            }
+        })
+    })
+    .unit()
+}
+
 impl EndpointSet<Checked> {
     pub fn matches(&self, request: &Request) -> Option<PartialRequest> {
         self.endpoints.iter().find_map(|e| e.matches(request))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tailcall_valid::{ValidationError, Validator};
+
+    use super::*;
+
+    #[test]
+    fn test_param_segments_collide() {
+        let set = EndpointSet::try_new(
+            r#"
+            query user_by_id($id: Int!) @rest(method: GET, path: "/user/$id") {
+              user(id: $id) { name }
+            }
+
+            query user_by_slug($slug: String!) @rest(method: GET, path: "/user/$slug") {
+              user(slug: $slug) { name }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let actual = validate_collisions(set.get_endpoints()).to_result();
+        let expected = Err(ValidationError::new(
+            "`GET /user/$id` collides with `GET /user/$slug`".to_string(),
+        ));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_distinct_endpoints() {
+        let set = EndpointSet::try_new(
+            r#"
+            query user($id: Int!) @rest(method: GET, path: "/user/$id") {
+              user(id: $id) { name }
+            }
+
+            query posts($id: Int!) @rest(method: GET, path: "/user/$id/posts") {
+              user(id: $id) { posts { title } }
+            }
+
+            query me @rest(method: GET, path: "/user/me/profile") {
+              me { name }
+            }
+
+            query profile($id: Int!) @rest(method: GET, path: "/user/$id/profile") {
+              user(id: $id) { name }
+            }
+
+            query current @rest(method: GET, path: "/user/me") {
+              me { name }
+            }
+
+            mutation update($id: Int!) @rest(method: POST, path: "/user/$id") {
+              updateUser(id: $id) { name }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let actual = validate_collisions(set.get_endpoints()).to_result();
+
+        assert_eq!(actual, Ok(()));
+    }
+}
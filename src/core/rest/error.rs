@@ -25,6 +25,10 @@ pub enum Error {
     #[error("Undefined query param: {}", _0)]
     UndefinedQueryParam(String),
 
+    #[error("Invalid Body: {}", _0)]
+    #[from(ignore)]
+    InvalidBody(String),
+
     #[error("Parse Integer Error: {}", _0)]
     ParseInteger(ParseIntError),
 
@@ -55,6 +59,9 @@ pub enum Error {
 
     #[error("Async Graphql Server Error: {}", _0)]
     GraphQLServer(ServerError),
+
+    #[error("Multipart Error: {}", _0)]
+    Multipart(anyhow::Error),
 }
 
 pub type Result<A> = std::result::Result<A, Error>;
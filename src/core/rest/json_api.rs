@@ -0,0 +1,375 @@
+use std::collections::{BTreeMap, HashSet};
+
+use async_graphql::parser::types::{
+    DocumentOperations, ExecutableDocument, Field, Selection, SelectionSet,
+};
+use async_graphql::{Name, Pos, Positioned};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+const TYPENAME: &str = "__typename";
+
+/// Output format of a REST endpoint, set with `@rest(format: jsonapi)`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// The flattened response data.
+    #[default]
+    Json,
+    /// A [JSON:API](https://jsonapi.org) document.
+    JsonApi,
+}
+
+/// Identifies the objects of a GraphQL type as JSON:API resources, set per
+/// type with `@rest(format: jsonapi, resources: {Post: {type: "posts"}})`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Resource {
+    /// `type` of the resources, the name of the GraphQL type when unset.
+    #[serde(rename = "type")]
+    pub type_name: Option<String>,
+    /// field holding the `id` of the resources.
+    pub id: String,
+}
+
+impl Default for Resource {
+    fn default() -> Self {
+        Self { type_name: None, id: "id".to_string() }
+    }
+}
+
+/// Reshapes the response data of a REST endpoint into a JSON:API document.
+/// Every object that has an id becomes a resource: its scalar fields are the
+/// `attributes`, the nested resources are `relationships` and are listed once
+/// in `included`. The objects without an id are kept as attributes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonApi {
+    resources: BTreeMap<String, Resource>,
+}
+
+/// What a resource is identified with.
+struct Identity {
+    type_name: String,
+    id: String,
+    /// field the `id` is read from, left out of the attributes.
+    id_field: String,
+}
+
+/// The resources referenced by the primary data, deduplicated by type and id.
+#[derive(Default)]
+struct Included {
+    keys: HashSet<(String, String)>,
+    resources: Vec<Value>,
+}
+
+impl JsonApi {
+    pub fn new(resources: BTreeMap<String, Resource>) -> Self {
+        Self { resources }
+    }
+
+    /// Selects the `__typename` of every object in the operation, as the
+    /// resources are identified by their GraphQL type.
+    pub fn select_typename(doc: &mut ExecutableDocument) {
+        match &mut doc.operations {
+            DocumentOperations::Single(operation) => {
+                select_typename(&mut operation.node.selection_set.node)
+            }
+            DocumentOperations::Multiple(operations) => {
+                for operation in operations.values_mut() {
+                    select_typename(&mut operation.node.selection_set.node);
+                }
+            }
+        }
+        for fragment in doc.fragments.values_mut() {
+            select_typename(&mut fragment.node.selection_set.node);
+        }
+    }
+
+    /// Builds the document for a single resource or a list of resources.
+    pub fn to_document(&self, data: Value) -> Value {
+        let mut included = Included::default();
+        let data = self.primary(data, &mut included);
+
+        let mut document = Map::new();
+        document.insert("data".to_string(), data);
+        if !included.resources.is_empty() {
+            document.insert("included".to_string(), Value::Array(included.resources));
+        }
+        Value::Object(document)
+    }
+
+    fn primary(&self, value: Value, included: &mut Included) -> Value {
+        match value {
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.primary(item, included))
+                    .collect(),
+            ),
+            Value::Object(object) => match self.identify(&object) {
+                Some(identity) => self.resource(identity, object, included),
+                None => strip_typename(Value::Object(object)),
+            },
+            value => value,
+        }
+    }
+
+    /// Returns the identity of the object when it's a resource.
+    fn identify(&self, object: &Map<String, Value>) -> Option<Identity> {
+        let type_name = object.get(TYPENAME)?.as_str()?;
+        let resource = self.resources.get(type_name);
+        let id_field = resource.map_or("id", |resource| resource.id.as_str());
+        let id = match object.get(id_field)? {
+            Value::String(id) => id.clone(),
+            Value::Number(id) => id.to_string(),
+            _ => return None,
+        };
+        let type_name = resource
+            .and_then(|resource| resource.type_name.clone())
+            .unwrap_or_else(|| type_name.to_string());
+
+        Some(Identity { type_name, id, id_field: id_field.to_string() })
+    }
+
+    fn resource(
+        &self,
+        identity: Identity,
+        mut object: Map<String, Value>,
+        included: &mut Included,
+    ) -> Value {
+        object.remove(TYPENAME);
+        object.remove(&identity.id_field);
+
+        let mut attributes = Map::new();
+        let mut relationships = Map::new();
+        for (key, value) in object {
+            match self.relationship(value, included) {
+                Ok(data) => {
+                    let mut relationship = Map::new();
+                    relationship.insert("data".to_string(), data);
+                    relationships.insert(key, Value::Object(relationship));
+                }
+                Err(value) => {
+                    attributes.insert(key, strip_typename(value));
+                }
+            }
+        }
+
+        let mut resource = Map::new();
+        resource.insert("type".to_string(), Value::String(identity.type_name));
+        resource.insert("id".to_string(), Value::String(identity.id));
+        if !attributes.is_empty() {
+            resource.insert("attributes".to_string(), Value::Object(attributes));
+        }
+        if !relationships.is_empty() {
+            resource.insert("relationships".to_string(), Value::Object(relationships));
+        }
+        Value::Object(resource)
+    }
+
+    /// Replaces a resource, or a non-empty list of them, with their resource
+    /// identifiers and includes them. Any other value is given back.
+    fn relationship(&self, value: Value, included: &mut Included) -> Result<Value, Value> {
+        match value {
+            Value::Object(object) => match self.identify(&object) {
+                Some(identity) => Ok(self.include(identity, object, included)),
+                None => Err(Value::Object(object)),
+            },
+            Value::Array(items)
+                if !items.is_empty()
+                    && items.iter().all(|item| {
+                        item.as_object()
+                            .and_then(|object| self.identify(object))
+                            .is_some()
+                    }) =>
+            {
+                Ok(Value::Array(
+                    items
+                        .into_iter()
+                        .map(|item| self.relationship(item, included).unwrap_or_default())
+                        .collect(),
+                ))
+            }
+            value => Err(value),
+        }
+    }
+
+    /// Includes the resource, unless it already is, and returns its resource
+    /// identifier.
+    fn include(
+        &self,
+        identity: Identity,
+        object: Map<String, Value>,
+        included: &mut Included,
+    ) -> Value {
+        let mut identifier = Map::new();
+        identifier.insert(
+            "type".to_string(),
+            Value::String(identity.type_name.clone()),
+        );
+        identifier.insert("id".to_string(), Value::String(identity.id.clone()));
+
+        let key = (identity.type_name.clone(), identity.id.clone());
+        if included.keys.insert(key) {
+            let resource = self.resource(identity, object, included);
+            included.resources.push(resource);
+        }
+
+        Value::Object(identifier)
+    }
+}
+
+fn select_typename(selection_set: &mut SelectionSet) {
+    for selection in selection_set.items.iter_mut() {
+        match &mut selection.node {
+            Selection::Field(field) => {
+                let selection_set = &mut field.node.selection_set.node;
+                if !selection_set.items.is_empty() {
+                    select_typename(selection_set);
+                    selection_set.items.push(typename_selection());
+                }
+            }
+            Selection::InlineFragment(fragment) => {
+                select_typename(&mut fragment.node.selection_set.node)
+            }
+            Selection::FragmentSpread(_) => {}
+        }
+    }
+}
+
+fn typename_selection() -> Positioned<Selection> {
+    let field = Field {
+        alias: None,
+        name: Positioned::new(Name::new(TYPENAME), Pos::default()),
+        arguments: Vec::new(),
+        directives: Vec::new(),
+        selection_set: Positioned::new(SelectionSet::default(), Pos::default()),
+    };
+    Positioned::new(
+        Selection::Field(Positioned::new(field, Pos::default())),
+        Pos::default(),
+    )
+}
+
+fn strip_typename(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .filter(|(key, _)| key != TYPENAME)
+                .map(|(key, value)| (key, strip_typename(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(strip_typename).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_post_with_authors() {
+        let json_api = JsonApi::new(BTreeMap::from([(
+            "User".to_string(),
+            Resource { type_name: Some("people".to_string()), ..Default::default() },
+        )]));
+        let author = |id: u64, name: &str| json!({"__typename": "User", "id": id, "name": name});
+        let data = json!([
+            {"__typename": "Post", "id": 1, "title": "First", "author": author(7, "Ada")},
+            {"__typename": "Post", "id": 2, "title": "Second", "author": author(7, "Ada")},
+            {"__typename": "Post", "id": 3, "title": "Third", "author": author(8, "Alan")},
+        ]);
+
+        let post = |id: &str, title: &str, author: &str| {
+            json!({
+                "type": "Post",
+                "id": id,
+                "attributes": {"title": title},
+                "relationships": {"author": {"data": {"type": "people", "id": author}}}
+            })
+        };
+        assert_eq!(
+            json_api.to_document(data),
+            json!({
+                "data": [post("1", "First", "7"), post("2", "Second", "7"), post("3", "Third", "8")],
+                "included": [
+                    {"type": "people", "id": "7", "attributes": {"name": "Ada"}},
+                    {"type": "people", "id": "8", "attributes": {"name": "Alan"}}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_single_resource() {
+        let json_api = JsonApi::default();
+        let data = json!({
+            "__typename": "Post",
+            "id": "1",
+            "tags": [],
+            "meta": {"__typename": "Meta", "views": 10}
+        });
+
+        assert_eq!(
+            json_api.to_document(data),
+            json!({
+                "data": {
+                    "type": "Post",
+                    "id": "1",
+                    "attributes": {"tags": [], "meta": {"views": 10}}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_select_typename() {
+        let mut doc = async_graphql::parser::parse_query(
+            "query { posts { id user { ...user } ... on Post { tags { name } } } } fragment user on User { name address { city } }",
+        )
+        .unwrap();
+        JsonApi::select_typename(&mut doc);
+
+        let operation = doc.operations.iter().next().unwrap().1;
+        let fragment = &doc.fragments[&Name::new("user")];
+        assert_eq!(
+            render(&operation.node.selection_set.node),
+            "posts { id user { ...user __typename } ...on Post { tags { name __typename } } __typename }"
+        );
+        assert_eq!(
+            render(&fragment.node.selection_set.node),
+            "name address { city __typename }"
+        );
+    }
+
+    fn render(selection_set: &SelectionSet) -> String {
+        selection_set
+            .items
+            .iter()
+            .map(|selection| match &selection.node {
+                Selection::Field(field) if field.node.selection_set.node.items.is_empty() => {
+                    field.node.name.node.to_string()
+                }
+                Selection::Field(field) => format!(
+                    "{} {{ {} }}",
+                    field.node.name.node,
+                    render(&field.node.selection_set.node)
+                ),
+                Selection::InlineFragment(fragment) => format!(
+                    "...on {} {{ {} }}",
+                    fragment.node.type_condition.as_ref().unwrap().node.on.node,
+                    render(&fragment.node.selection_set.node)
+                ),
+                Selection::FragmentSpread(spread) => {
+                    format!("...{}", spread.node.fragment_name.node)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
@@ -2,6 +2,7 @@ mod directive;
 mod endpoint;
 mod endpoint_set;
 pub mod error;
+mod json_api;
 mod operation;
 mod partial_request;
 mod path;
@@ -9,7 +10,9 @@ mod query_params;
 mod type_map;
 mod typed_variables;
 
+pub use directive::Envelope;
 pub use endpoint_set::{Checked, EndpointSet, Unchecked};
+pub use json_api::JsonApi;
 
 type Request = http::Request<hyper::Body>;
 pub use error::{Error, Result};
@@ -1,26 +1,89 @@
 use async_graphql::parser::types::ExecutableDocument;
 use async_graphql::{Name, Variables};
 use async_graphql_value::ConstValue;
+use indexmap::IndexMap;
+use tailcall_valid::Validator;
 
+use super::directive::Envelope;
+use super::json_api::JsonApi;
 use super::path::Path;
-use super::{Request, Result};
+use super::{Error, Request, Result};
 use crate::core::async_graphql_hyper::GraphQLRequest;
+use crate::core::http::{multipart, RequestContext};
+use crate::core::json::JsonSchema;
 
 /// A partial GraphQLRequest that contains a parsed executable GraphQL document.
 #[derive(Debug)]
 pub struct PartialRequest<'a> {
     pub body: Option<&'a String>,
+    pub envelope: Option<&'a Envelope>,
+    pub json_api: Option<&'a JsonApi>,
+    pub body_schema: Option<&'a JsonSchema>,
+    /// The argument the body is streamed to, see
+    /// [Endpoint::to_streamed_arg](super::endpoint::Endpoint::to_streamed_arg).
+    pub streamed_arg: Option<&'a String>,
     pub doc: &'a ExecutableDocument,
     pub variables: Variables,
     pub path: &'a Path,
 }
 
 impl PartialRequest<'_> {
-    pub async fn into_request(self, request: Request) -> Result<GraphQLRequest> {
+    pub async fn into_request(
+        self,
+        request: Request,
+        req_ctx: &RequestContext,
+    ) -> Result<GraphQLRequest> {
         let mut variables = self.variables;
         if let Some(key) = self.body {
-            let bytes = hyper::body::to_bytes(request.into_body()).await?;
-            let body: ConstValue = serde_json::from_slice(&bytes)?;
+            let content_type = request
+                .headers()
+                .get(hyper::header::CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok())
+                .map(|content_type| content_type.to_string());
+            let boundary = content_type.as_deref().and_then(multipart::boundary);
+            let body: ConstValue = match (boundary, self.streamed_arg) {
+                // An upload sent as the whole body is left unread, to be streamed
+                // to the upstream by the `@http` that forwards it
+                (None, Some(arg)) if cfg!(not(target_arch = "wasm32")) => {
+                    *req_ctx.upload.lock().unwrap() = Some((arg.clone(), request.into_body()));
+                    let mut upload = IndexMap::new();
+                    if let Some(content_type) = content_type {
+                        upload.insert(
+                            Name::new(multipart::CONTENT_TYPE),
+                            ConstValue::String(content_type),
+                        );
+                    }
+                    upload.insert(
+                        Name::new(multipart::CONTENT),
+                        ConstValue::Binary(Default::default()),
+                    );
+                    ConstValue::Object(upload)
+                }
+                // Each part becomes a field of the body, files as `Upload` values
+                (Some(boundary), _) => {
+                    let bytes = hyper::body::to_bytes(request.into_body()).await?;
+                    ConstValue::Object(
+                        multipart::decode(&boundary, &bytes)?
+                            .into_iter()
+                            .map(|part| (Name::new(&part.name), part.into_value()))
+                            .collect(),
+                    )
+                }
+                (None, _) => {
+                    let bytes = hyper::body::to_bytes(request.into_body()).await?;
+                    // An empty body leaves a nullable body variable as null
+                    let body = if bytes.is_empty() {
+                        ConstValue::Null
+                    } else {
+                        serde_json::from_slice(&bytes)
+                            .map_err(|error| Error::InvalidBody(error.to_string()))?
+                    };
+                    if let Some(schema) = self.body_schema {
+                        validate_body(schema, &body)?;
+                    }
+                    body
+                }
+            };
             variables.insert(Name::new(key), body);
         }
 
@@ -30,3 +93,22 @@ impl PartialRequest<'_> {
         Ok(GraphQLRequest(req))
     }
 }
+
+/// Fails with every mismatch between the body and the schema of the body
+/// variable, along with its path in the body.
+fn validate_body(schema: &JsonSchema, body: &ConstValue) -> Result<()> {
+    schema.validate(body).to_result().map_err(|error| {
+        let causes = error
+            .as_vec()
+            .iter()
+            .map(|cause| {
+                format!(
+                    "{} [{}]",
+                    cause.message,
+                    Vec::from(cause.trace.clone()).join(", ")
+                )
+            })
+            .collect::<Vec<_>>();
+        Error::InvalidBody(causes.join(", "))
+    })
+}
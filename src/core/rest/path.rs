@@ -50,6 +50,31 @@ impl Path {
         Ok(Self { segments, pattern: input.to_string() })
     }
 
+    /// Checks if the paths match the same requests, with the same literals
+    /// and params at the same positions. A literal and a param don't overlap,
+    /// as the path with the literal is matched first.
+    pub fn overlaps(&self, other: &Path) -> bool {
+        self.segments.len() == other.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(other.segments.iter())
+                .all(|segments| match segments {
+                    (Segment::Literal(a), Segment::Literal(b)) => a == b,
+                    (Segment::Param(_), Segment::Param(_)) => true,
+                    _ => false,
+                })
+    }
+
+    /// Positions of the params of the path, which sort the paths with a
+    /// literal before those with a param at the same position.
+    pub fn params(&self) -> Vec<bool> {
+        self.segments
+            .iter()
+            .map(|segment| matches!(segment, Segment::Param(_)))
+            .collect()
+    }
+
     pub fn matches(&self, path: &str) -> Option<Variables> {
         let mut variables = Variables::default();
         let mut req_segments = path.split('/').filter(|s| !s.is_empty());
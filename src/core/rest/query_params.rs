@@ -32,11 +32,13 @@ impl QueryParams {
         Ok(Self { params })
     }
 
-    pub fn matches(&self, query_params: BTreeMap<String, String>) -> Option<Variables> {
+    /// Matches the parameters of the request, with the values of every key
+    /// in the order they appear in.
+    pub fn matches(&self, query_params: BTreeMap<String, Vec<String>>) -> Option<Variables> {
         let mut variables = Variables::default();
         for (key, t_var) in &self.params {
-            if let Some(query_param) = query_params.get(key) {
-                let value = t_var.to_value(query_param).ok()?;
+            if let Some(values) = query_params.get(key) {
+                let value = t_var.to_values(values).ok()?;
                 variables.insert(Name::new(t_var.name()), value);
             }
         }
@@ -19,8 +19,17 @@ pub enum N {
 impl N {
     fn to_value(&self, value: &str) -> Result<ConstValue> {
         Ok(match self {
-            Self::Int => ConstValue::from(value.parse::<i64>()?),
-            Self::Float => ConstValue::from(value.parse::<f64>()?),
+            // values above i64::MAX are kept as u64 instead of failing
+            Self::Int => match value.parse::<u64>() {
+                Ok(n) if n > i64::MAX as u64 => ConstValue::from(n),
+                _ => ConstValue::from(value.parse::<i64>()?),
+            },
+            // integral values are kept as integers so they don't lose precision
+            // by going through f64
+            Self::Float => match value.parse::<i64>() {
+                Ok(n) => ConstValue::from(n),
+                Err(_) => ConstValue::from(value.parse::<f64>()?),
+            },
         })
     }
 }
@@ -44,9 +53,10 @@ impl TryFrom<&Type> for UrlParamType {
                 "Int" => Ok(Self::Number(N::Int)),
                 "Boolean" => Ok(Self::Boolean),
                 "Float" => Ok(Self::Number(N::Float)),
+                // kept as a string, so that it isn't bound to the range of u64
+                "BigInt" => Ok(Self::String),
                 _ => Err(Error::UnexpectedNamedType(name.to_owned())),
             },
-            // TODO: support for list types
             _ => Err(Error::UnexpectedType(value.to_owned())),
         }
     }
@@ -57,20 +67,54 @@ pub struct TypedVariable {
     name: String,
     // TODO: validate types for query
     nullable: bool,
+    /// Set for the variables of a list type, that take every value of a
+    /// repeated parameter
+    is_list: bool,
 }
 
 impl TypedVariable {
     fn new(tpe: UrlParamType, name: &str) -> Self {
-        Self { type_of: tpe, name: name.to_string(), nullable: false }
+        Self {
+            type_of: tpe,
+            name: name.to_string(),
+            nullable: false,
+            is_list: false,
+        }
     }
 
     pub fn try_from(type_of: &Type, name: &str) -> Result<Self> {
-        let tpe = UrlParamType::try_from(type_of)?;
-        Ok(Self::new(tpe, name))
+        match &type_of.base {
+            BaseType::List(item) => {
+                let tpe = UrlParamType::try_from(item.as_ref())?;
+                Ok(Self { is_list: true, ..Self::new(tpe, name) })
+            }
+            BaseType::Named(_) => {
+                let tpe = UrlParamType::try_from(type_of)?;
+                Ok(Self::new(tpe, name))
+            }
+        }
     }
 
     pub fn to_value(&self, value: &str) -> Result<ConstValue> {
-        self.type_of.to_value(value)
+        self.to_values(&[value])
+    }
+
+    /// Converts the values of a parameter that is repeated in the request. A
+    /// list takes all of them, even if there is a single one, while the other
+    /// types take the last one.
+    pub fn to_values<T: AsRef<str>>(&self, values: &[T]) -> Result<ConstValue> {
+        if self.is_list {
+            let values = values
+                .iter()
+                .map(|value| self.type_of.to_value(value.as_ref()))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(ConstValue::List(values))
+        } else {
+            match values.last() {
+                Some(value) => self.type_of.to_value(value.as_ref()),
+                None => Ok(ConstValue::Null),
+            }
+        }
     }
 
     pub fn name(&self) -> &str {
@@ -79,6 +123,9 @@ impl TypedVariable {
     pub fn nullable(&self) -> bool {
         self.nullable
     }
+    pub fn is_list(&self) -> bool {
+        self.is_list
+    }
     pub fn ty(&self) -> UrlParamType {
         self.type_of.clone()
     }
@@ -105,4 +152,29 @@ mod tests {
             Self::new(UrlParamType::Number(N::Int), name)
         }
     }
+
+    #[test]
+    fn test_large_int_precision() {
+        let value = "9007199254740993";
+
+        let int = TypedVariable::int("id").to_value(value).unwrap();
+        assert_eq!(int.to_string(), value);
+
+        let float = TypedVariable::float("id").to_value(value).unwrap();
+        assert_eq!(float.to_string(), value);
+
+        let max = u64::MAX.to_string();
+        let int = TypedVariable::int("id").to_value(&max).unwrap();
+        assert_eq!(int.to_string(), max);
+
+        let above_max = format!("{}0", u64::MAX);
+        assert!(TypedVariable::int("id").to_value(&above_max).is_err());
+
+        let big_int = Type::new("BigInt!").unwrap();
+        let big_int = TypedVariable::try_from(&big_int, "id").unwrap();
+        assert_eq!(
+            big_int.to_value(&above_max).unwrap(),
+            ConstValue::String(above_max)
+        );
+    }
 }
@@ -5,7 +5,7 @@ use async_graphql_value::ConstValue;
 use super::ir::model::IoId;
 use crate::core::schema_extension::SchemaExtension;
 use crate::core::worker::{Command, Event};
-use crate::core::{Cache, EnvIO, FileIO, HttpIO, WorkerIO};
+use crate::core::{Cache, EnvIO, FileIO, HttpIO, SecretIO, WorkerIO};
 
 /// The TargetRuntime struct unifies the available runtime-specific
 /// IO implementations. This is used to reduce piping IO structs all
@@ -19,6 +19,9 @@ pub struct TargetRuntime {
     /// Interface for accessing environment variables specific to the target
     /// environment.
     pub env: Arc<dyn EnvIO>,
+    /// Provider of the secrets used in templates, whose values are redacted
+    /// from the logs.
+    pub secret: Arc<dyn SecretIO>,
     /// Interface for file operations, tailored to the target environment's
     /// capabilities.
     pub file: Arc<dyn FileIO>,
@@ -61,6 +64,7 @@ pub mod test {
     use crate::core::cache::InMemoryCache;
     use crate::core::http::Response;
     use crate::core::runtime::TargetRuntime;
+    use crate::core::secret::EnvSecretIO;
     use crate::core::worker::{Command, Event};
     use crate::core::{blueprint, EnvIO, FileIO, HttpIO};
 
@@ -179,12 +183,13 @@ pub mod test {
         let http2 = TestHttp::init(&Upstream::default().http2_only(true));
 
         let file = TestFileIO::init();
-        let env = TestEnvIO::init();
+        let env: Arc<dyn EnvIO> = Arc::new(TestEnvIO::init());
 
         TargetRuntime {
             http,
             http2_only: http2,
-            env: Arc::new(env),
+            env: env.clone(),
+            secret: Arc::new(EnvSecretIO::new(env)),
             file: Arc::new(file),
             cache: Arc::new(InMemoryCache::default()),
             extensions: Arc::new(vec![]),
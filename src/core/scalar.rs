@@ -6,10 +6,19 @@ use schemars::schema::{InstanceType, Schema, SchemaObject};
 use strum::IntoEnumIterator;
 use tailcall_macros::{gen_doc, Doc};
 
+use crate::core::http::multipart;
 use crate::core::json::JsonLike;
 
 const PREDEFINED_SCALARS: &[&str] = &["Boolean", "Float", "ID", "Int", "String"];
 
+/// A value converted to the type of a scalar by [`Scalar::coerce`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Coerced {
+    Number(serde_json::Number),
+    String(String),
+    Boolean(bool),
+}
+
 lazy_static! {
     static ref CUSTOM_SCALARS: HashMap<String, Scalar> =
         Scalar::iter().map(|v| (v.name(), v)).collect();
@@ -73,6 +82,17 @@ pub enum Scalar {
     /// Field whose value is a sequence of bytes.
     #[gen_doc(ty = "String")]
     Bytes,
+    /// Field whose value is an integer of any size. It's serialized as a string
+    /// so that JavaScript clients don't lose precision on values above 2^53.
+    /// Numbers beyond the 64-bit range lose precision as soon as they're
+    /// parsed, so larger values have to be sent as strings.
+    #[gen_doc(ty = "String")]
+    BigInt,
+    /// Field whose value is a file uploaded with a multipart/form-data request.
+    /// It's an object with the `filename`, the `contentType` and the `content`
+    /// of the file.
+    #[gen_doc(ty = "Object")]
+    Upload,
 }
 
 fn eval_str<'a, Value: JsonLike<'a>, F: Fn(&str) -> bool>(val: &'a Value, fxn: F) -> bool {
@@ -103,6 +123,64 @@ fn eval_unsigned<
     val.as_u64().map_or(false, |n| fxn(n).is_ok())
 }
 
+fn is_integer(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Reads an integer from a numeric string, or from a float without a
+/// fractional part.
+fn to_integer<'a, Value: JsonLike<'a>>(value: &'a Value) -> Option<serde_json::Number> {
+    if let Some(n) = value.as_i64() {
+        return Some(n.into());
+    }
+    if let Some(n) = value.as_u64() {
+        return Some(n.into());
+    }
+    if let Some(s) = value.as_str() {
+        let s = s.trim();
+        return s
+            .parse::<i64>()
+            .map(Into::into)
+            .or_else(|_| s.parse::<u64>().map(Into::into))
+            .ok();
+    }
+
+    value
+        .as_f64()
+        .filter(|n| n.fract() == 0.0 && *n >= i64::MIN as f64 && *n <= i64::MAX as f64)
+        .map(|n| (n as i64).into())
+}
+
+/// Parses a date written in RFC 3339, RFC 2822, `YYYY-MM-DD HH:MM:SS` or
+/// `YYYY-MM-DD`, or given as a unix timestamp in seconds. Dates without an
+/// offset are in UTC.
+fn parse_date_time<'a, Value: JsonLike<'a>>(
+    value: &'a Value,
+) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    let timestamp = value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()));
+    if let Some(timestamp) = timestamp {
+        return chrono::DateTime::from_timestamp(timestamp, 0).map(|date| date.fixed_offset());
+    }
+
+    let s = value.as_str()?.trim();
+    chrono::DateTime::parse_from_rfc3339(s)
+        .or_else(|_| chrono::DateTime::parse_from_rfc2822(s))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                .or_else(|_| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S"))
+                .or_else(|_| {
+                    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                        .map(|date| date.and_time(chrono::NaiveTime::MIN))
+                })
+                .ok()
+                .map(|date| date.and_utc().fixed_offset())
+        })
+}
+
 impl Scalar {
     ///
     /// Check if the type is a predefined scalar
@@ -128,6 +206,10 @@ impl Scalar {
             }
             Scalar::Url => eval_str(value, |s| url::Url::parse(s).is_ok()),
             Scalar::Bytes => value.as_str().is_some(),
+            Scalar::BigInt => {
+                value.as_i64().is_some() || value.as_u64().is_some() || eval_str(value, is_integer)
+            }
+            Scalar::Upload => value.get_key(multipart::CONTENT).is_some(),
 
             Scalar::Int64 => eval_str(value, |s| s.parse::<i64>().is_ok()),
             Scalar::UInt64 => eval_str(value, |s| s.parse::<u64>().is_ok()),
@@ -143,6 +225,94 @@ impl Scalar {
             Scalar::UInt32 => eval_unsigned(value, u32::try_from),
         }
     }
+
+    /// Returns the string representation of the value if the scalar is
+    /// serialized as a string, e.g. the integers of a `BigInt`.
+    pub fn stringify<'a, Value: JsonLike<'a>>(&self, value: &'a Value) -> Option<String> {
+        match self {
+            Scalar::BigInt => value
+                .as_i64()
+                .map(|n| n.to_string())
+                .or_else(|| value.as_u64().map(|n| n.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Parses the values of a `Date` or a `DateTime` written in other common
+    /// formats than RFC 3339 and re-emits them in RFC 3339. The values of the
+    /// other scalars aren't normalized.
+    pub fn normalize<'a, Value: JsonLike<'a>>(&self, value: &'a Value) -> Option<String> {
+        match self {
+            Scalar::Date | Scalar::DateTime => parse_date_time(value)
+                .map(|date_time| date_time.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)),
+            _ => None,
+        }
+    }
+
+    /// Converts a value that isn't valid for the scalar into a valid one,
+    /// when it can be done without losing information: numeric strings and
+    /// whole floats become integers, the integers of the scalars written as
+    /// strings become strings, and dates are normalized. Returns `None` when
+    /// the value can't be coerced.
+    pub fn coerce<'a, Value: JsonLike<'a>>(&self, value: &'a Value) -> Option<Coerced> {
+        let coerced = match self {
+            Scalar::Int8
+            | Scalar::Int16
+            | Scalar::Int32
+            | Scalar::UInt8
+            | Scalar::UInt16
+            | Scalar::UInt32 => Coerced::Number(to_integer(value)?),
+            Scalar::Int64 | Scalar::UInt64 | Scalar::Int128 | Scalar::UInt128 => {
+                Coerced::String(to_integer(value)?.to_string())
+            }
+            Scalar::Date | Scalar::DateTime => Coerced::String(self.normalize(value)?),
+            _ => return None,
+        };
+
+        let valid = match &coerced {
+            Coerced::Number(n) => self.validate(&serde_json::Value::Number(n.clone())),
+            Coerced::String(s) => self.validate(&serde_json::Value::String(s.clone())),
+            Coerced::Boolean(b) => self.validate(&serde_json::Value::Bool(*b)),
+        };
+        valid.then_some(coerced)
+    }
+
+    /// Converts a value of the built-in scalar `type_name` that doesn't have
+    /// the type of the scalar, when it can be done without losing
+    /// information: numeric strings and whole floats become an `Int`, numeric
+    /// strings become a `Float`, `"true"` and `"false"` become a `Boolean`,
+    /// and numbers become a `String` or an `ID`. Returns `None` when the value
+    /// already has the type of the scalar or can't be coerced.
+    pub fn coerce_builtin<'a, Value: JsonLike<'a>>(
+        type_name: &str,
+        value: &'a Value,
+    ) -> Option<Coerced> {
+        match type_name {
+            "Int" if value.as_i64().is_none() => {
+                let n = to_integer(value)?;
+                i32::try_from(n.as_i64()?).ok()?;
+                Some(Coerced::Number(n))
+            }
+            "Float" if value.as_f64().is_none() => value
+                .as_str()
+                .and_then(|s| s.trim().parse::<f64>().ok())
+                .and_then(serde_json::Number::from_f64)
+                .map(Coerced::Number),
+            "Boolean" if value.as_bool().is_none() => match value.as_str()?.trim() {
+                "true" => Some(Coerced::Boolean(true)),
+                "false" => Some(Coerced::Boolean(false)),
+                _ => None,
+            },
+            "String" | "ID" if value.as_str().is_none() => value
+                .as_i64()
+                .map(|n| n.to_string())
+                .or_else(|| value.as_u64().map(|n| n.to_string()))
+                .or_else(|| value.as_f64().map(|n| n.to_string()))
+                .map(Coerced::String),
+            _ => None,
+        }
+    }
+
     pub fn find(name: &str) -> Option<&Scalar> {
         CUSTOM_SCALARS.get(name)
     }
@@ -212,6 +382,44 @@ mod test {
     };
 }
 
+    mod big_int {
+        use serde_json::Number;
+
+        use super::{ConstValue, Scalar};
+
+        test_scalar_valid! {
+            Scalar::BigInt,
+            ConstValue::Number(Number::from(9007199254740993u64)),
+            ConstValue::Number(Number::from(-15)),
+            ConstValue::String("-9007199254740993".to_string()),
+            ConstValue::String(format!("{}0", u128::MAX))
+        }
+
+        test_scalar_invalid! {
+            Scalar::BigInt,
+            ConstValue::Null,
+            ConstValue::Number(Number::from_f64(1.25).unwrap()),
+            ConstValue::Number(Number::from_f64(1e20).unwrap()),
+            ConstValue::String("-".to_string()),
+            ConstValue::String("12a".to_string())
+        }
+
+        #[test]
+        fn test_stringify() {
+            let value = ConstValue::Number(Number::from(9007199254740993u64));
+            assert_eq!(
+                Scalar::BigInt.stringify(&value),
+                Some("9007199254740993".to_string())
+            );
+
+            let value = ConstValue::Number(Number::from(u64::MAX));
+            assert_eq!(Scalar::BigInt.stringify(&value), Some(u64::MAX.to_string()));
+
+            let value = ConstValue::Number(Number::from(15));
+            assert_eq!(Scalar::Int32.stringify(&value), None);
+        }
+    }
+
     mod bytes {
         use serde_json::Number;
 
@@ -228,6 +436,95 @@ mod test {
         }
     }
 
+    mod coerce {
+        use serde_json::Number;
+
+        use super::{ConstValue, Scalar};
+        use crate::core::scalar::Coerced;
+
+        #[test]
+        fn test_coerce() {
+            let coerce = |scalar: Scalar, value: ConstValue| scalar.coerce(&value);
+
+            assert_eq!(
+                coerce(Scalar::Int32, ConstValue::String(" 42".to_string())),
+                Some(Coerced::Number(Number::from(42)))
+            );
+            assert_eq!(
+                coerce(
+                    Scalar::UInt8,
+                    ConstValue::Number(Number::from_f64(7.0).unwrap())
+                ),
+                Some(Coerced::Number(Number::from(7)))
+            );
+            assert_eq!(
+                coerce(Scalar::Int64, ConstValue::Number(Number::from(-15))),
+                Some(Coerced::String("-15".to_string()))
+            );
+            assert_eq!(
+                coerce(Scalar::Date, ConstValue::String("2023-03-08".to_string())),
+                Some(Coerced::String("2023-03-08T00:00:00Z".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_coerce_impossible() {
+            let coerce = |scalar: Scalar, value: ConstValue| scalar.coerce(&value);
+
+            assert_eq!(
+                coerce(Scalar::Int32, ConstValue::String("12a".to_string())),
+                None
+            );
+            assert_eq!(
+                coerce(Scalar::Int8, ConstValue::String("300".to_string())),
+                None
+            );
+            assert_eq!(
+                coerce(
+                    Scalar::UInt32,
+                    ConstValue::Number(Number::from_f64(1.25).unwrap())
+                ),
+                None
+            );
+            assert_eq!(
+                coerce(Scalar::Email, ConstValue::Number(Number::from(1))),
+                None
+            );
+        }
+
+        #[test]
+        fn test_coerce_builtin() {
+            let coerce =
+                |type_name: &str, value: ConstValue| Scalar::coerce_builtin(type_name, &value);
+
+            assert_eq!(
+                coerce("Int", ConstValue::String("42".to_string())),
+                Some(Coerced::Number(Number::from(42)))
+            );
+            assert_eq!(
+                coerce("Float", ConstValue::String("1.5".to_string())),
+                Some(Coerced::Number(Number::from_f64(1.5).unwrap()))
+            );
+            assert_eq!(
+                coerce("Boolean", ConstValue::String("true".to_string())),
+                Some(Coerced::Boolean(true))
+            );
+            assert_eq!(
+                coerce("ID", ConstValue::Number(Number::from(7))),
+                Some(Coerced::String("7".to_string()))
+            );
+            assert_eq!(coerce("Int", ConstValue::Number(Number::from(42))), None);
+            assert_eq!(
+                coerce("Int", ConstValue::String("3000000000".to_string())),
+                None
+            );
+            assert_eq!(
+                coerce("Boolean", ConstValue::String("yes".to_string())),
+                None
+            );
+        }
+    }
+
     mod date {
         use super::{ConstValue, Scalar};
         test_scalar_valid! {
@@ -240,6 +537,38 @@ mod test {
             ConstValue::Null
         }
     }
+
+    mod date_normalize {
+        use serde_json::Number;
+
+        use super::{ConstValue, Scalar};
+
+        #[test]
+        fn test_normalize() {
+            let normalize = |value: ConstValue| Scalar::DateTime.normalize(&value);
+
+            assert_eq!(
+                normalize(ConstValue::String(
+                    "Tue, 1 Jul 2003 10:52:37 +0200".to_string()
+                )),
+                Some("2003-07-01T10:52:37+02:00".to_string())
+            );
+            assert_eq!(
+                normalize(ConstValue::Number(Number::from(1700000000))),
+                Some("2023-11-14T22:13:20Z".to_string())
+            );
+            assert_eq!(
+                normalize(ConstValue::String("2023-03-08 12:45:26".to_string())),
+                Some("2023-03-08T12:45:26Z".to_string())
+            );
+            assert_eq!(normalize(ConstValue::String("yesterday".to_string())), None);
+            assert_eq!(
+                Scalar::Email.normalize(&ConstValue::String("1700000000".to_string())),
+                None
+            );
+        }
+    }
+
     mod email {
         use super::{ConstValue, Scalar};
         test_scalar_valid! {
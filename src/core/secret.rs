@@ -0,0 +1,23 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::core::{EnvIO, SecretIO};
+
+/// Reads the secrets from the environment variables, which is the default
+/// when no other secret provider is set up.
+#[derive(Clone)]
+pub struct EnvSecretIO {
+    env: Arc<dyn EnvIO>,
+}
+
+impl EnvSecretIO {
+    pub fn new(env: Arc<dyn EnvIO>) -> Self {
+        Self { env }
+    }
+}
+
+impl SecretIO for EnvSecretIO {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.env.get(key)
+    }
+}
@@ -0,0 +1,74 @@
+//! A small facade to embed tailcall in another application, without starting
+//! the HTTP server.
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use tailcall::{Tailcall, Variables};
+//!
+//! let tailcall = Tailcall::from_files(&["examples/jsonplaceholder.graphql"]).await?;
+//! let response = tailcall.execute("{ greet }", Variables::default()).await;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Arc;
+
+pub use async_graphql::{Response, Variables};
+use tailcall_valid::Validator;
+
+use crate::cli::runtime::init;
+use crate::core::app_context::AppContext;
+use crate::core::blueprint::Blueprint;
+use crate::core::config::reader::ConfigReader;
+use crate::core::config::{Config, ConfigModule};
+use crate::core::http::RequestContext;
+
+/// An executable instance of a tailcall configuration.
+#[derive(Clone)]
+pub struct Tailcall {
+    app_ctx: Arc<AppContext>,
+}
+
+impl Tailcall {
+    /// Creates an instance from a configuration written in GraphQL SDL.
+    /// Relative links are resolved against the current directory.
+    pub async fn from_sdl(sdl: &str) -> anyhow::Result<Self> {
+        let config = Config::from_sdl(sdl).to_result()?;
+        let config_module = Self::config_reader().resolve(config, None).await?;
+        Self::try_from_module(config_module).await
+    }
+
+    /// Creates an instance by reading and merging the configuration files.
+    pub async fn from_files(paths: &[&str]) -> anyhow::Result<Self> {
+        let config_module = Self::config_reader().read_all(paths).await?;
+        Self::try_from_module(config_module).await
+    }
+
+    /// Executes a GraphQL query against the configuration.
+    pub async fn execute(&self, query: &str, variables: Variables) -> Response {
+        let req_ctx = Arc::new(RequestContext::from(self.app_ctx.as_ref()));
+        let request = async_graphql::Request::new(query)
+            .variables(variables)
+            .data(req_ctx);
+
+        self.app_ctx.execute(request).await
+    }
+
+    fn config_reader() -> ConfigReader {
+        ConfigReader::init(init(&Blueprint::default()))
+    }
+
+    async fn try_from_module(config_module: ConfigModule) -> anyhow::Result<Self> {
+        let blueprint = Blueprint::try_from(&config_module)?;
+        let runtime = init(&blueprint);
+        let endpoints = config_module
+            .extensions()
+            .endpoint_set
+            .clone()
+            .into_checked(&blueprint, runtime.clone())
+            .await?;
+        let app_ctx = Arc::new(AppContext::new(blueprint, runtime, endpoints));
+
+        Ok(Self { app_ctx })
+    }
+}
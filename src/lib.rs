@@ -4,3 +4,11 @@ pub mod core;
 
 #[cfg(feature = "cli")]
 pub mod cli;
+
+#[cfg(feature = "cli")]
+pub mod embed;
+#[cfg(feature = "cli")]
+pub use embed::{Response, Tailcall, Variables};
+
+#[cfg(all(feature = "cli", any(test, feature = "testing")))]
+pub mod testing;
@@ -0,0 +1,244 @@
+//! Utilities to test a configuration against static data, without calling any
+//! upstream. Requires the `testing` feature.
+//!
+//! The data is mocked on the executor, so every test builds its own. Fields
+//! that aren't mocked fail instead of calling their upstream.
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use serde_json::json;
+//! use tailcall::testing::MockExecutor;
+//!
+//! let sdl = std::fs::read_to_string("examples/jsonplaceholder.graphql")?;
+//! let executor = MockExecutor::try_new(&sdl)?
+//!     .data("posts", json!([{"id": 1, "userId": 1, "title": "Hello"}]))?
+//!     .data("posts.user", json!({"id": 1, "name": "Leanne"}))?;
+//!
+//! let response = executor.execute("{ posts { title user { name } } }").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Arc;
+
+use async_graphql_value::ConstValue;
+use hyper::body::Bytes;
+use tailcall_valid::Validator;
+
+use crate::cli::runtime::init;
+use crate::core::app_context::AppContext;
+use crate::core::blueprint::{Blueprint, Definition, DynamicValue, FieldDefinition};
+use crate::core::config::{Config, ConfigModule};
+use crate::core::http::{RequestContext, Response};
+use crate::core::ir::model::IR;
+use crate::core::jit::{ConstValueExecutor, Request};
+use crate::core::rest::EndpointSet;
+use crate::core::runtime::TargetRuntime;
+use crate::core::HttpIO;
+
+/// Fails every upstream request, so that the fields that haven't been mocked
+/// don't reach the network.
+struct UnmockedHttp;
+
+#[async_trait::async_trait]
+impl HttpIO for UnmockedHttp {
+    async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+        Err(anyhow::anyhow!(
+            "No data mocked for {} {}",
+            request.method(),
+            request.url()
+        ))
+    }
+}
+
+/// Executes queries through the JIT pipeline, resolving the fields that have
+/// been mocked with static data instead of their configured resolvers.
+pub struct MockExecutor {
+    blueprint: Blueprint,
+}
+
+impl MockExecutor {
+    /// Creates an executor from a configuration written in GraphQL SDL.
+    pub fn try_new(sdl: &str) -> anyhow::Result<Self> {
+        let config = Config::from_sdl(sdl).to_result()?;
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+
+        Ok(Self { blueprint })
+    }
+
+    /// Resolves the field at `path` to `value`. The path is a dot separated
+    /// list of fields starting from the query type, e.g. `posts.user`. Nested
+    /// fields resolve to the same value for every parent.
+    pub fn data(mut self, path: &str, value: serde_json::Value) -> anyhow::Result<Self> {
+        let field = find_field(&mut self.blueprint, path)
+            .ok_or(anyhow::anyhow!("Field '{path}' is not defined"))?;
+        let value = ConstValue::from_json(value)?;
+        field.resolver = Some(IR::Dynamic(DynamicValue::Value(value)));
+
+        Ok(self)
+    }
+
+    /// Executes the query and returns the GraphQL response.
+    pub async fn execute(&self, query: &str) -> anyhow::Result<serde_json::Value> {
+        self.execute_request(Request::new(query)).await
+    }
+
+    /// Executes the request and returns the GraphQL response.
+    pub async fn execute_request(
+        &self,
+        request: Request<ConstValue>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let runtime = self.runtime();
+        let app_ctx = Arc::new(AppContext::new(
+            self.blueprint.clone(),
+            runtime,
+            EndpointSet::default(),
+        ));
+        let req_ctx = RequestContext::from(app_ctx.as_ref());
+
+        let executor = ConstValueExecutor::try_new(&request, &app_ctx)?;
+        let response = executor.execute(&app_ctx, &req_ctx, request).await;
+
+        Ok(serde_json::from_slice(response.body.as_slice())?)
+    }
+
+    /// Creates the runtime of a request, with its own caches and without
+    /// access to the upstreams.
+    fn runtime(&self) -> TargetRuntime {
+        let http: Arc<dyn HttpIO> = Arc::new(UnmockedHttp);
+        TargetRuntime {
+            http: http.clone(),
+            http2_only: http,
+            ..init(&self.blueprint)
+        }
+    }
+}
+
+fn find_field<'a>(blueprint: &'a mut Blueprint, path: &str) -> Option<&'a mut FieldDefinition> {
+    let (parents, name) = match path.rsplit_once('.') {
+        Some((parents, name)) => (Some(parents), name),
+        None => (None, path),
+    };
+
+    let mut type_name = blueprint.schema.query.clone();
+    for parent in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        type_name = fields(blueprint, &type_name)?
+            .iter()
+            .find(|field| field.name == parent)?
+            .of_type
+            .name()
+            .clone();
+    }
+
+    blueprint
+        .definitions
+        .iter_mut()
+        .find_map(|definition| match definition {
+            Definition::Object(def) if def.name == type_name => Some(&mut def.fields),
+            Definition::Interface(def) if def.name == type_name => Some(&mut def.fields),
+            _ => None,
+        })?
+        .iter_mut()
+        .find(|field| field.name == name)
+}
+
+fn fields<'a>(blueprint: &'a Blueprint, type_name: &str) -> Option<&'a Vec<FieldDefinition>> {
+    blueprint
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::Object(def) if def.name == type_name => Some(&def.fields),
+            Definition::Interface(def) if def.name == type_name => Some(&def.fields),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::MockExecutor;
+
+    const CONFIG: &str = include_str!("../examples/jsonplaceholder.graphql");
+
+    #[tokio::test]
+    async fn test_two_level_query() {
+        let executor = MockExecutor::try_new(CONFIG)
+            .unwrap()
+            .data(
+                "posts",
+                json!([
+                    {"id": 1, "userId": 1, "title": "First", "body": ""},
+                    {"id": 2, "userId": 1, "title": "Second", "body": ""}
+                ]),
+            )
+            .unwrap()
+            .data("posts.user", json!({"id": 1, "name": "Leanne"}))
+            .unwrap();
+
+        let actual = executor
+            .execute("{ posts { title user { name } } }")
+            .await
+            .unwrap();
+        let expected = json!({
+            "data": {
+                "posts": [
+                    {"title": "First", "user": {"name": "Leanne"}},
+                    {"title": "Second", "user": {"name": "Leanne"}}
+                ]
+            }
+        });
+
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_unmocked_field() {
+        let executor = MockExecutor::try_new(CONFIG)
+            .unwrap()
+            .data(
+                "posts",
+                json!([{"id": 1, "userId": 1, "title": "First", "body": ""}]),
+            )
+            .unwrap();
+
+        let actual = executor
+            .execute("{ posts { title user { name } } }")
+            .await
+            .unwrap();
+
+        assert_eq!(actual["data"]["posts"][0]["user"], json!(null));
+        let message = actual["errors"][0]["message"].as_str().unwrap();
+        assert!(message.contains("No data mocked for GET"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn test_mocks_per_executor() {
+        let mocked = MockExecutor::try_new(CONFIG)
+            .unwrap()
+            .data("users", json!([{"id": 1, "name": "Leanne"}]))
+            .unwrap();
+        let unmocked = MockExecutor::try_new(CONFIG).unwrap();
+
+        let actual = mocked.execute("{ users { name } }").await.unwrap();
+        assert_eq!(actual, json!({"data": {"users": [{"name": "Leanne"}]}}));
+
+        let actual = unmocked.execute("{ users { name } }").await.unwrap();
+        assert!(actual["errors"].is_array());
+    }
+
+    #[test]
+    fn test_undefined_field() {
+        let actual = MockExecutor::try_new(CONFIG)
+            .unwrap()
+            .data("posts.author", json!({}))
+            .err()
+            .map(|e| e.to_string());
+
+        assert_eq!(
+            actual,
+            Some("Field 'posts.author' is not defined".to_string())
+        );
+    }
+}
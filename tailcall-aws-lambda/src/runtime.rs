@@ -4,6 +4,7 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::runtime::TargetRuntime;
+use tailcall::core::secret::EnvSecretIO;
 use tailcall::core::{EntityCache, EnvIO, FileIO};
 use tokio::io::AsyncReadExt;
 
@@ -53,11 +54,13 @@ pub fn init_cache() -> Arc<EntityCache> {
 
 pub fn init_runtime() -> TargetRuntime {
     let http = init_http();
+    let env = init_env();
     TargetRuntime {
         http: http.clone(),
         http2_only: http,
         file: init_file(),
-        env: init_env(),
+        env: env.clone(),
+        secret: Arc::new(EnvSecretIO::new(env)),
         cache: init_cache(),
         extensions: Arc::new(vec![]),
         cmd_worker: None,
@@ -5,6 +5,7 @@ use anyhow::anyhow;
 use async_graphql_value::ConstValue;
 use tailcall::core::ir::model::IoId;
 use tailcall::core::runtime::TargetRuntime;
+use tailcall::core::secret::EnvSecretIO;
 use tailcall::core::{EnvIO, FileIO, HttpIO};
 
 use crate::{cache, env, file, http};
@@ -38,6 +39,7 @@ pub fn init(env: Rc<worker::Env>) -> anyhow::Result<TargetRuntime> {
         http: http.clone(),
         http2_only: http.clone(),
         env: init_env(env.clone()),
+        secret: Arc::new(EnvSecretIO::new(init_env(env.clone()))),
         file: init_file(env.clone(), &bucket_id)?,
         cache: init_cache(env),
         extensions: Arc::new(vec![]),
@@ -1,4 +1,6 @@
+use std::collections::hash_map::RandomState;
 use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
 use std::process::Output;
 
 use chrono::{DateTime, Utc};
@@ -34,6 +36,7 @@ pub struct Tracker {
     collectors: Vec<Box<dyn Collect>>,
     can_track: bool,
     start_time: DateTime<Utc>,
+    session_id: String,
     email: Mutex<Option<Vec<String>>>,
 }
 
@@ -50,6 +53,7 @@ impl Default for Tracker {
             collectors: vec![ga_tracker, posthog_tracker],
             can_track,
             start_time,
+            session_id: session_id(),
             email: Mutex::new(None),
         }
     }
@@ -68,21 +72,7 @@ impl Tracker {
 
     pub async fn dispatch(&'static self, event_kind: EventKind) -> Result<()> {
         if self.can_track {
-            // Create a new event
-            let event = Event {
-                event_name: event_kind.name(),
-                start_time: self.start_time,
-                cores: cores(),
-                client_id: client_id(),
-                os_name: os_name(),
-                up_time: up_time(self.start_time),
-                args: args(),
-                path: path(),
-                cwd: cwd(),
-                user: user(),
-                version: version(),
-                email: self.email().await.clone(),
-            };
+            let event = self.event(event_kind).await;
 
             // Dispatch the event to all collectors
             for collector in &self.collectors {
@@ -95,6 +85,25 @@ impl Tracker {
         Ok(())
     }
 
+    // Create a new event
+    async fn event(&'static self, event_kind: EventKind) -> Event {
+        Event {
+            event_name: event_kind.name(),
+            session_id: self.session_id.clone(),
+            start_time: self.start_time,
+            cores: cores(),
+            client_id: client_id(),
+            os_name: os_name(),
+            up_time: up_time(self.start_time),
+            args: args(),
+            path: path(),
+            cwd: cwd(),
+            user: user(),
+            version: version(),
+            email: self.email().await.clone(),
+        }
+    }
+
     async fn email(&'static self) -> Vec<String> {
         let mut guard = self.email.lock().await;
         if guard.is_none() {
@@ -164,6 +173,14 @@ fn client_id() -> String {
         .unwrap_or(DEFAULT_CLIENT_ID.to_string())
 }
 
+// Generates a random ID for the events of the running process
+fn session_id() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    hasher.write_i64(Utc::now().timestamp_micros());
+    format!("{:016x}", hasher.finish())
+}
+
 // Get the number of CPU cores
 fn cores() -> usize {
     let sys = System::new_all();
@@ -236,4 +253,13 @@ mod tests {
             panic!("Tracker dispatch error: {:?}", e);
         }
     }
+
+    #[tokio::test]
+    async fn test_events_share_session_id() {
+        let first = TRACKER.event(EventKind::Ping).await;
+        let second = TRACKER.event(EventKind::Command("check".to_string())).await;
+
+        assert!(!first.session_id.is_empty());
+        assert_eq!(first.session_id, second.session_id);
+    }
 }
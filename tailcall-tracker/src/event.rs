@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Event {
     pub event_name: Name,
+    /// Shared by all the events of a run of the CLI
+    pub session_id: String,
     pub start_time: DateTime<Utc>,
     pub cores: usize,
     pub client_id: String,
@@ -4,6 +4,7 @@ use async_graphql_value::ConstValue;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::ir::model::IoId;
 use tailcall::core::runtime::TargetRuntime;
+use tailcall::core::secret::EnvSecretIO;
 use tailcall::core::{EnvIO, FileIO, HttpIO};
 
 use crate::env::WasmEnv;
@@ -31,11 +32,13 @@ pub fn init_rt() -> TargetRuntime {
     let http2_only = init_http();
     let file = init_file();
     let env = init_env();
+    let secret = Arc::new(EnvSecretIO::new(env.clone()));
     let cache = init_cache();
     TargetRuntime {
         http,
         http2_only,
         env,
+        secret,
         file,
         cache,
         extensions: Arc::new(vec![]),
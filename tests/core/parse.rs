@@ -18,6 +18,7 @@ use tailcall::core::cache::InMemoryCache;
 use tailcall::core::config::{ConfigModule, Link, RuntimeConfig, Source};
 use tailcall::core::merge_right::MergeRight;
 use tailcall::core::runtime::TargetRuntime;
+use tailcall::core::secret::EnvSecretIO;
 use tailcall::core::worker::{Command, Event};
 use tailcall::core::{EnvIO, WorkerIO};
 
@@ -303,11 +304,13 @@ impl ExecutionSpec {
             None
         };
 
+        let env: Arc<dyn EnvIO> = Arc::new(Env::init(env));
         let runtime = TargetRuntime {
             http,
             http2_only,
             file: Arc::new(File::new(self.clone())),
-            env: Arc::new(Env::init(env)),
+            env: env.clone(),
+            secret: Arc::new(EnvSecretIO::new(env)),
             cache: Arc::new(InMemoryCache::default()),
             extensions: Arc::new(vec![]),
             cmd_worker: http_worker,
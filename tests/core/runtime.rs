@@ -12,7 +12,9 @@ use tailcall::core::blueprint::Script;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::config::RuntimeConfig;
 use tailcall::core::runtime::TargetRuntime;
+use tailcall::core::secret::EnvSecretIO;
 use tailcall::core::worker::{Command, Event};
+use tailcall::core::EnvIO;
 
 use super::env::Env;
 use super::file::TestFileIO;
@@ -72,12 +74,13 @@ pub fn create_runtime(
     let http2 = http_client.clone();
 
     let file = TestFileIO::init();
-    let env = Env::init(env);
+    let env: Arc<dyn EnvIO> = Arc::new(Env::init(env));
 
     TargetRuntime {
         http,
         http2_only: http2,
-        env: Arc::new(env),
+        env: env.clone(),
+        secret: Arc::new(EnvSecretIO::new(env)),
         file: Arc::new(file),
         cache: Arc::new(InMemoryCache::default()),
         extensions: Arc::new(vec![]),
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql_value::{ConstValue, Name};
+    use serde_json::json;
+    use tailcall::{Tailcall, Variables};
+
+    const JSONPLACEHOLDER: &str = "examples/jsonplaceholder.graphql";
+
+    #[tokio::test]
+    async fn test_execute_from_files() -> anyhow::Result<()> {
+        let tailcall = Tailcall::from_files(&[JSONPLACEHOLDER]).await?;
+        let response = tailcall.execute("{ greet }", Variables::default()).await;
+
+        assert!(response.errors.is_empty());
+        assert_eq!(response.data.into_json()?, json!({"greet": "Hello World!"}));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_from_sdl_with_variables() -> anyhow::Result<()> {
+        let sdl = tokio::fs::read_to_string(JSONPLACEHOLDER).await?;
+        let tailcall = Tailcall::from_sdl(&sdl).await?;
+        let variables = Variables::from_value(ConstValue::Object(
+            [(Name::new("skip"), ConstValue::Boolean(true))]
+                .into_iter()
+                .collect(),
+        ));
+        let response = tailcall
+            .execute(
+                "query ($skip: Boolean!) { greet @skip(if: $skip) }",
+                variables,
+            )
+            .await;
+
+        assert!(response.errors.is_empty());
+        assert_eq!(response.data.into_json()?, json!({}));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_sdl() {
+        let result = Tailcall::from_sdl("type Query { greet: Unknown }").await;
+
+        assert!(result.is_err());
+    }
+}
@@ -16,6 +16,7 @@ pub mod test {
     use tailcall::core::cache::InMemoryCache;
     use tailcall::core::http::Response;
     use tailcall::core::runtime::TargetRuntime;
+    use tailcall::core::secret::EnvSecretIO;
     use tailcall::core::worker::{Command, Event};
     use tailcall::core::{EnvIO, FileIO, HttpIO};
     use tailcall_http_cache::HttpCacheManager;
@@ -136,12 +137,13 @@ pub mod test {
         let http2 = TestHttp::init(&Upstream::default().http2_only(true));
 
         let file = TestFileIO::init();
-        let env = TestEnvIO::init();
+        let env: Arc<dyn EnvIO> = Arc::new(TestEnvIO::init());
 
         TargetRuntime {
             http,
             http2_only: http2,
-            env: Arc::new(env),
+            env: env.clone(),
+            secret: Arc::new(EnvSecretIO::new(env)),
             file: Arc::new(file),
             cache: Arc::new(InMemoryCache::default()),
             extensions: Arc::new(vec![]),